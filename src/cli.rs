@@ -1,8 +1,9 @@
 //! Command-line parsing and validation.
 
 use crate::data::{
-    self, CalculationAlgorithm, Command, InputPath, LocationSource, OutputFormat, Parameters,
-    Predicate, Step, TimezoneOverride,
+    self, CalculationAlgorithm, Command, DstPolicy, GridArea, GridSpacing, InputPath,
+    LocationSource, OutputFormat, Parameters, ParquetCompression, ParquetTimestamps, Predicate,
+    Step, Surface, TimeFormat, TimezoneOverride, Tracker,
 };
 use crate::error::{CliError, predicate_error};
 use crate::parsed::{ParsedCommand, ParsedInput, ParsedOptionUsage, ParsedTimeSource};
@@ -10,13 +11,14 @@ use std::path::PathBuf;
 
 const DELTAT_MULTIPLE_ERROR: &str = "Option --deltat cannot be used multiple times";
 const PREDICATE_MULTIPLE_ERROR: &str = "Predicate options cannot be used multiple times";
+const GRID_INPUT_ERROR: &str = "Option --grid-km requires start:end latitude and longitude bounds or a feature file (e.g. @area.geojson)";
 
 type CliResult<T> = Result<T, CliError>;
 
 pub fn parse_cli(args: Vec<String>) -> CliResult<ParsedCommand> {
     if args.len() < 2 {
         return Err(CliError::Exit(
-            "Usage: sunce [OPTIONS] <lat> <lon> <dateTime> <position|sunrise>".to_string(),
+            "Usage: sunce [OPTIONS] <lat> <lon> <dateTime> <command>".to_string(),
         ));
     }
 
@@ -55,7 +57,11 @@ pub fn parse_cli(args: Vec<String>) -> CliResult<ParsedCommand> {
         return Err(CliError::Exit(message));
     }
 
-    let (command, input) = parse_positional_args(&positional)?;
+    if option_usage.grid_cos_lat && params.grid.is_none() {
+        return Err("Option --grid-cos-lat requires --grid-km".into());
+    }
+
+    let (command, input) = parse_positional_args(&positional, params.grid)?;
     Ok(ParsedCommand {
         command,
         input,
@@ -77,6 +83,41 @@ fn parse_f64(label: &str, value: &str) -> CliResult<f64> {
     }
 }
 
+fn parse_positive_usize(label: &str, value: &str) -> CliResult<usize> {
+    match value.parse::<usize>() {
+        Ok(parsed) if parsed > 0 => Ok(parsed),
+        _ => Err(format!(
+            "Invalid {} value: {} (expected a positive integer)",
+            label.replace('-', " "),
+            value
+        )
+        .into()),
+    }
+}
+
+/// Parses a byte count with an optional binary `K`, `M`, or `G` suffix.
+fn parse_byte_size(label: &str, value: &str) -> CliResult<u64> {
+    let (digits, multiplier) = match value.char_indices().last() {
+        Some((index, 'K' | 'k')) => (&value[..index], 1 << 10),
+        Some((index, 'M' | 'm')) => (&value[..index], 1 << 20),
+        Some((index, 'G' | 'g')) => (&value[..index], 1 << 30),
+        _ => (value, 1),
+    };
+    match digits
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(multiplier))
+    {
+        Some(bytes) if bytes > 0 => Ok(bytes),
+        _ => Err(format!(
+            "Invalid {} value: {} (expected a positive byte count with optional K, M, or G suffix)",
+            label.replace('-', " "),
+            value
+        )
+        .into()),
+    }
+}
+
 fn ensure_flag(opt: &str, value: Option<&str>) -> CliResult<()> {
     if value.is_some() {
         Err(format!("Option --{} does not take a value", opt).into())
@@ -115,6 +156,14 @@ fn parse_predicate_option(name: &str, value: Option<&str>) -> CliResult<Option<P
             ensure_flag(name, value)?;
             Some(Predicate::IsAstronomicalNight)
         }
+        "is-golden-hour" => {
+            ensure_flag(name, value)?;
+            Some(Predicate::IsGoldenHour)
+        }
+        "is-blue-hour" => {
+            ensure_flag(name, value)?;
+            Some(Predicate::IsBlueHour)
+        }
         "after-sunset" => {
             ensure_flag(name, value)?;
             Some(Predicate::AfterSunset)
@@ -133,6 +182,13 @@ fn parse_predicate_option(name: &str, value: Option<&str>) -> CliResult<Option<P
             )
             .map_err(|err| predicate_error(err.to_string()))?,
         )),
+        "moon-below" => Some(Predicate::MoonBelow(
+            parse_f64(
+                "moon-below",
+                value.ok_or_else(|| predicate_error("Option --moon-below requires a value"))?,
+            )
+            .map_err(|err| predicate_error(err.to_string()))?,
+        )),
         _ => None,
     };
     Ok(parsed)
@@ -156,6 +212,87 @@ fn apply_option(
             params.output.format = v.parse::<OutputFormat>().map_err(CliError::from)?;
             option_usage.format = true;
         }
+        "time-format" => {
+            let v = required_value("time-format", value)?;
+            params.output.time_format = v.parse::<TimeFormat>().map_err(CliError::from)?;
+            option_usage.time_format = true;
+        }
+        "parquet-timestamps" => {
+            let v = required_value("parquet-timestamps", value)?;
+            params.output.parquet.timestamps =
+                v.parse::<ParquetTimestamps>().map_err(CliError::from)?;
+            option_usage.parquet_timestamps = true;
+        }
+        "parquet-compression" => {
+            let v = required_value("parquet-compression", value)?;
+            params.output.parquet.compression =
+                v.parse::<ParquetCompression>().map_err(CliError::from)?;
+            option_usage.parquet_compression = true;
+        }
+        "parquet-row-group-size" => {
+            let v = required_value("parquet-row-group-size", value)?;
+            params.output.parquet.row_group_size =
+                Some(parse_positive_usize("parquet-row-group-size", v)?);
+            option_usage.parquet_row_group_size = true;
+        }
+        "parquet-dictionary" => {
+            ensure_flag("parquet-dictionary", value)?;
+            params.output.parquet.dictionary = true;
+            option_usage.parquet_dictionary = true;
+        }
+        "no-parquet-dictionary" => {
+            ensure_flag("no-parquet-dictionary", value)?;
+            params.output.parquet.dictionary = false;
+            option_usage.parquet_dictionary = true;
+        }
+        "output-dir" => {
+            let v = required_value("output-dir", value)?;
+            params.output.parquet.dataset.dir = Some(PathBuf::from(v));
+            option_usage.output_dir = true;
+        }
+        "partition-lat-band" => {
+            let v = required_value("partition-lat-band", value)?;
+            let band = parse_f64("partition-lat-band", v)?;
+            if band <= 0.0 {
+                return Err(format!(
+                    "Invalid partition lat band value: {} (expected positive degrees)",
+                    v
+                )
+                .into());
+            }
+            params.output.parquet.dataset.lat_band = Some(band);
+            option_usage.partition_lat_band = true;
+        }
+        "max-file-rows" => {
+            let v = required_value("max-file-rows", value)?;
+            params.output.parquet.dataset.max_file_rows =
+                Some(parse_positive_usize("max-file-rows", v)?);
+            option_usage.max_file_rows = true;
+        }
+        "max-file-size" => {
+            let v = required_value("max-file-size", value)?;
+            params.output.parquet.dataset.max_file_bytes =
+                Some(parse_byte_size("max-file-size", v)?);
+            option_usage.max_file_size = true;
+        }
+        "output" => {
+            let v = required_value("output", value)?;
+            params.output.sqlite.path = Some(PathBuf::from(v));
+            option_usage.output = true;
+        }
+        "table" => {
+            let v = required_value("table", value)?;
+            if v.is_empty() {
+                return Err("Invalid table value: name must not be empty".into());
+            }
+            params.output.sqlite.table = Some(v.to_string());
+            option_usage.table = true;
+        }
+        "upsert" => {
+            ensure_flag("upsert", value)?;
+            params.output.sqlite.upsert = true;
+            option_usage.upsert = true;
+        }
         "deltat" => {
             if *deltat_seen {
                 return Err(DELTAT_MULTIPLE_ERROR.into());
@@ -168,7 +305,34 @@ fn apply_option(
         }
         "timezone" => {
             let v = required_value("timezone", value)?;
+            if v.eq_ignore_ascii_case("auto") {
+                if !cfg!(feature = "tz-lookup") {
+                    return Err(
+                        "Option --timezone=auto not available in this build (enable feature 'tz-lookup')"
+                            .into(),
+                    );
+                }
+                params.timezone_lookup = true;
+            } else {
+                let timezone = v.parse::<TimezoneOverride>()?;
+                params.output_timezone = Some(timezone.clone());
+                params.timezone = Some(timezone);
+            }
+            option_usage.timezone = true;
+        }
+        "input-timezone" => {
+            let v = required_value("input-timezone", value)?;
             params.timezone = Some(v.parse::<TimezoneOverride>()?);
+            option_usage.input_timezone = true;
+        }
+        "output-timezone" => {
+            let v = required_value("output-timezone", value)?;
+            params.output_timezone = Some(v.parse::<TimezoneOverride>()?);
+            option_usage.output_timezone = true;
+        }
+        "dst-policy" => {
+            let v = required_value("dst-policy", value)?;
+            params.dst_policy = Some(v.parse::<DstPolicy>().map_err(CliError::from)?);
         }
         "algorithm" => {
             let v = required_value("algorithm", value)?;
@@ -196,11 +360,108 @@ fn apply_option(
             params.environment.pressure = parse_f64("pressure", v)?;
             option_usage.pressure = true;
         }
+        "linke-turbidity" => {
+            let v = required_value("linke-turbidity", value)?;
+            let turbidity = parse_f64("linke-turbidity", v)?;
+            if !(1.0..=10.0).contains(&turbidity) {
+                return Err(
+                    format!("Invalid linke turbidity value: {} (expected 1 to 10)", v).into(),
+                );
+            }
+            params.environment.linke_turbidity = turbidity;
+            option_usage.linke_turbidity = true;
+        }
+        "surface-tilt" => {
+            let v = required_value("surface-tilt", value)?;
+            let tilt = parse_f64("surface-tilt", v)?;
+            if !(0.0..=180.0).contains(&tilt) {
+                return Err(
+                    format!("Invalid surface tilt value: {} (expected 0 to 180)", v).into(),
+                );
+            }
+            params.calculation.surface.get_or_insert_default().tilt = tilt;
+            option_usage.surface_tilt = true;
+        }
+        "surface-azimuth" => {
+            let v = required_value("surface-azimuth", value)?;
+            let azimuth = parse_f64("surface-azimuth", v)?;
+            if !(0.0..=360.0).contains(&azimuth) {
+                return Err(
+                    format!("Invalid surface azimuth value: {} (expected 0 to 360)", v).into(),
+                );
+            }
+            params.calculation.surface.get_or_insert_default().azimuth = azimuth;
+            option_usage.surface_azimuth = true;
+        }
+        "shadow-height" => {
+            let v = required_value("shadow-height", value)?;
+            let height = parse_f64("shadow-height", v)?;
+            if height <= 0.0 {
+                return Err(format!(
+                    "Invalid shadow height value: {} (expected a positive number of meters)",
+                    v
+                )
+                .into());
+            }
+            params.calculation.shadow_height = Some(height);
+            option_usage.shadow_height = true;
+        }
+        "grid-km" => {
+            let v = required_value("grid-km", value)?;
+            let km = parse_f64("grid-km", v)?;
+            if km <= 0.0 {
+                return Err(format!(
+                    "Invalid grid spacing value: {} (expected a positive number of kilometers)",
+                    v
+                )
+                .into());
+            }
+            params.grid = Some(GridSpacing {
+                km,
+                cos_lat: option_usage.grid_cos_lat,
+            });
+            option_usage.grid_km = true;
+        }
+        "grid-cos-lat" => {
+            ensure_flag("grid-cos-lat", value)?;
+            if let Some(grid) = params.grid.as_mut() {
+                grid.cos_lat = true;
+            }
+            option_usage.grid_cos_lat = true;
+        }
+        "tracker" => {
+            let v = required_value("tracker", value)?;
+            params.calculation.surface.get_or_insert_default().tracker =
+                v.parse::<Tracker>().map_err(CliError::from)?;
+            option_usage.tracker = true;
+        }
         "horizon" => {
             let v = required_value("horizon", value)?;
             params.calculation.horizon = Some(parse_f64("horizon", v)?);
             option_usage.horizon = true;
         }
+        "horizons" => {
+            let v = required_value("horizons", value)?;
+            params.calculation.horizons = data::parse_custom_horizons(v).map_err(CliError::from)?;
+            option_usage.horizons = true;
+        }
+        "horizon-profile" => {
+            let v = required_value("horizon-profile", value)?;
+            let path = match parse_file_arg(v) {
+                Ok(InputPath::File(path)) => InputPath::File(path),
+                Ok(InputPath::Stdin) => {
+                    return Err("Option --horizon-profile cannot read from stdin".into());
+                }
+                Err(_) => {
+                    return Err(
+                        "Option --horizon-profile requires a file argument (e.g. @skyline.csv)"
+                            .into(),
+                    );
+                }
+            };
+            params.calculation.horizon_profile_file = Some(path);
+            option_usage.horizon_profile = true;
+        }
         "headers" => {
             ensure_flag("headers", value)?;
             params.output.headers = true;
@@ -221,11 +482,34 @@ fn apply_option(
             params.output.show_inputs = Some(false);
             option_usage.show_inputs = true;
         }
+        "columns" => {
+            let v = required_value("columns", value)?;
+            let columns: Vec<String> = v.split(',').map(|name| name.trim().to_string()).collect();
+            if columns.iter().any(String::is_empty) {
+                return Err(format!(
+                    "Invalid columns value: {} (expected comma-separated names)",
+                    v
+                )
+                .into());
+            }
+            params.output.columns = Some(columns);
+            option_usage.columns = true;
+        }
+        "list-columns" => {
+            ensure_flag("list-columns", value)?;
+            params.output.list_columns = true;
+            option_usage.list_columns = true;
+        }
         "perf" => {
             ensure_flag("perf", value)?;
             params.perf = true;
             option_usage.perf = true;
         }
+        "threads" => {
+            let v = required_value("threads", value)?;
+            params.threads = parse_positive_usize("threads", v)?;
+            option_usage.threads = true;
+        }
         "wait" => {
             ensure_flag("wait", value)?;
             params.wait = true;
@@ -240,11 +524,31 @@ fn apply_option(
             params.output.elevation_angle = true;
             option_usage.elevation_angle = true;
         }
+        "irradiance" => {
+            ensure_flag("irradiance", value)?;
+            params.calculation.irradiance = true;
+            option_usage.irradiance = true;
+        }
+        "geometry" => {
+            ensure_flag("geometry", value)?;
+            params.calculation.geometry = true;
+            option_usage.geometry = true;
+        }
+        "day-stats" => {
+            ensure_flag("day-stats", value)?;
+            params.calculation.day_stats = true;
+            option_usage.day_stats = true;
+        }
         "twilight" => {
             ensure_flag("twilight", value)?;
             params.calculation.twilight = true;
             option_usage.twilight = true;
         }
+        "photo-hours" => {
+            ensure_flag("photo-hours", value)?;
+            params.calculation.photo_hours = true;
+            option_usage.photo_hours = true;
+        }
         "help" => {
             ensure_flag("help", value)?;
             return Err(CliError::Exit(get_help_text()));
@@ -275,7 +579,48 @@ fn parse_file_arg(arg: &str) -> CliResult<InputPath> {
     Ok(InputPath::File(PathBuf::from(stripped)))
 }
 
-fn parse_location_args(lat_str: &str, lon_str: &str) -> CliResult<LocationSource> {
+/// Returns whether `path` names a GeoJSON or KML feature file.
+fn is_feature_file(path: &InputPath) -> bool {
+    let InputPath::File(path) = path else {
+        return false;
+    };
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            ["geojson", "json", "kml"]
+                .iter()
+                .any(|known| ext.eq_ignore_ascii_case(known))
+        })
+}
+
+/// Parses `start:end` grid bounds; a single value bounds a one-row or one-column grid.
+fn parse_grid_bounds(
+    value: &str,
+    label: &str,
+    validate: fn(f64) -> Result<f64, String>,
+) -> CliResult<(f64, f64)> {
+    let (start, end) = value.split_once(':').unwrap_or((value, value));
+    let parse = |part: &str| {
+        part.parse::<f64>()
+            .map_err(|_| CliError::from(format!("Invalid {} bounds: {}", label, value)))
+            .and_then(|parsed| validate(parsed).map_err(CliError::from))
+    };
+    let (start, end) = (parse(start)?, parse(end)?);
+    if start > end {
+        return Err(format!(
+            "Grid {} bounds must be ascending (start:end), got: {}",
+            label, value
+        )
+        .into());
+    }
+    Ok((start, end))
+}
+
+fn parse_location_args(
+    lat_str: &str,
+    lon_str: &str,
+    grid: Option<GridSpacing>,
+) -> CliResult<LocationSource> {
     if lat_str.starts_with('@') || lon_str.starts_with('@') {
         return Err(
             "Coordinate files must be provided as a single @file argument (use @coords.txt <dateTime>)"
@@ -283,6 +628,16 @@ fn parse_location_args(lat_str: &str, lon_str: &str) -> CliResult<LocationSource
         );
     }
 
+    if let Some(spacing) = grid {
+        return Ok(LocationSource::Grid {
+            area: GridArea::Bounds {
+                lat: parse_grid_bounds(lat_str, "latitude", data::validate_latitude)?,
+                lon: parse_grid_bounds(lon_str, "longitude", data::validate_longitude)?,
+            },
+            spacing,
+        });
+    }
+
     fn parse_latitude(value: &str) -> CliResult<f64> {
         value
             .parse::<f64>()
@@ -321,10 +676,18 @@ fn parse_location_args(lat_str: &str, lon_str: &str) -> CliResult<LocationSource
     }
 }
 
-fn parse_positional_args(positional_args: &[String]) -> CliResult<(Command, ParsedInput)> {
+fn parse_positional_args(
+    positional_args: &[String],
+    grid: Option<GridSpacing>,
+) -> CliResult<(Command, ParsedInput)> {
     let command_index = positional_args
         .iter()
-        .position(|arg| arg == "position" || arg == "sunrise")
+        .position(|arg| {
+            matches!(
+                arg.as_str(),
+                "position" | "sunrise" | "events" | "moon-position" | "moonrise"
+            )
+        })
         .ok_or("No command found".to_string())?;
     if command_index == 0 {
         return Err("Need at least command and one argument".into());
@@ -333,19 +696,31 @@ fn parse_positional_args(positional_args: &[String]) -> CliResult<(Command, Pars
     let command = match positional_args[command_index].as_str() {
         "position" => Command::Position,
         "sunrise" => Command::Sunrise,
+        "events" => Command::Events,
+        "moon-position" => Command::MoonPosition,
+        "moonrise" => Command::Moonrise,
         _ => unreachable!("filtered above"),
     };
     Ok((
         command,
-        parse_data_source(&positional_args[..command_index])?,
+        parse_data_source(&positional_args[..command_index], grid)?,
     ))
 }
 
-fn parse_data_source(args: &[String]) -> CliResult<ParsedInput> {
+fn parse_data_source(args: &[String], grid: Option<GridSpacing>) -> CliResult<ParsedInput> {
     match args.len() {
         1 => {
-            if args[0].starts_with('@') {
-                parse_file_arg(&args[0]).map(ParsedInput::Paired)
+            if grid.is_some() {
+                Err(GRID_INPUT_ERROR.into())
+            } else if args[0].starts_with('@') {
+                let path = parse_file_arg(&args[0])?;
+                if is_feature_file(&path) {
+                    return Err(
+                        "Feature files provide locations only; add a datetime (use @sites.geojson <dateTime>)"
+                            .into(),
+                    );
+                }
+                Ok(ParsedInput::Paired(path))
             } else {
                 Err("Single argument must be a file (@file or @-)".into())
             }
@@ -354,8 +729,17 @@ fn parse_data_source(args: &[String]) -> CliResult<ParsedInput> {
             if !args[0].starts_with('@') {
                 Err("Two arguments: Use @coords.txt @times.txt, @coords.txt datetime, or three arguments (lat lon datetime)".into())
             } else {
+                let path = parse_file_arg(&args[0])?;
                 Ok(ParsedInput::Separate(
-                    LocationSource::File(parse_file_arg(&args[0])?),
+                    match (is_feature_file(&path), grid) {
+                        (true, Some(spacing)) => LocationSource::Grid {
+                            area: GridArea::Polygons(path),
+                            spacing,
+                        },
+                        (true, None) => LocationSource::Features(path),
+                        (false, Some(_)) => return Err(GRID_INPUT_ERROR.into()),
+                        (false, None) => LocationSource::File(path),
+                    },
                     if args[1].starts_with('@') {
                         ParsedTimeSource::File(parse_file_arg(&args[1])?)
                     } else {
@@ -365,7 +749,7 @@ fn parse_data_source(args: &[String]) -> CliResult<ParsedInput> {
             }
         }
         3 => Ok(ParsedInput::Separate(
-            parse_location_args(&args[0], &args[1])?,
+            parse_location_args(&args[0], &args[1], grid)?,
             parse_time_arg(&args[2])?,
         )),
         _ => Err("Too many arguments".into()),
@@ -433,17 +817,18 @@ fn get_help_text() -> String {
     let formats = OutputFormat::all().join(", ");
     format!(
         r#"sunce {}
-Calculates topocentric solar coordinates or sunrise/sunset times.
+Calculates topocentric solar and lunar coordinates or rise/set times.
 
 Usage:
-  sunce [OPTIONS] <latitude> <longitude> <dateTime> <position|sunrise>
-  sunce [OPTIONS] @data.txt <position|sunrise>
-  sunce [OPTIONS] @coords.txt @times.txt <position|sunrise>
-  sunce [OPTIONS] @coords.txt <dateTime> <position|sunrise>
+  sunce [OPTIONS] <latitude> <longitude> <dateTime> <command>
+  sunce [OPTIONS] @data.txt <command>
+  sunce [OPTIONS] @coords.txt @times.txt <command>
+  sunce [OPTIONS] @coords.txt <dateTime> <command>
 
 Examples:
   sunce 52.0 13.4 2024-01-01 position
   sunce 52:53:0.1 13:14:0.1 2024 position --format=csv
+  sunce --grid-km=25 46:48 10:13 2024-06-21 sunrise --format=geojson
   sunce @coords.txt @times.txt position
   sunce @data.txt position
   echo "52.0 13.4 2024-01-01T12:00:00" | sunce @- position
@@ -468,16 +853,26 @@ Arguments:
                        2024-01-01T12:00:00  date and time
                        "2024-01-01 12:00"   date and time (space separator; quote it)
                        2024                 entire year (daily by default)
-                       2024-06              entire month (position: hourly, sunrise: daily)
+                       2024-06              entire month (position: hourly,
+                                              sunrise/events: daily)
                        now                  current time (position repeats with --step
                                               for a single lat/lon only)
                        1704067200           unix timestamp (seconds)
                        @times.txt           file with times (or @- for stdin)
 
   File inputs:
-    - Coordinates files contain lat lon per line.
+    - Coordinates files contain lat lon per line, optionally followed by an
+      IANA timezone that dates and naive times of that row are read in.
+      Either every row has the column or none does (with --timezone=auto,
+      rows without it are looked up); with it, output is ordered by
+      location rather than by time.
+    - Coordinate files ending in .geojson, .json, or .kml are read as
+      GeoJSON or KML points (lon/lat order); feature id and name are
+      added as leading id and name columns. With --grid-km, their
+      polygons are sampled as well.
     - Time files contain one datetime per line.
-    - Paired data files contain lat lon datetime per line.
+    - Paired data files contain lat lon datetime per line, optionally
+      followed by an IANA timezone for that row.
     - Files accept comma- or whitespace-separated fields.
     - Blank lines and lines starting with # are ignored.
     - Stdin (@-) can be used for only one input parameter.
@@ -488,13 +883,61 @@ Options:
                         --deltat (no value) to estimate from the date
                         (falls back to 0 if unavailable).
   --format=<format>     Output format: {}. Default: {}
+  --time-format=<fmt>   Date/time rendering: rfc3339 (default), rfc3339-millis,
+                        unix, unix-ms, utc, or a strftime pattern such as
+                        '%Y-%m-%d %H:%M'.
+  --parquet-timestamps=<mode>
+                        Parquet date/time columns: native (UTC timestamps,
+                        each followed by its UTC offset in seconds:
+                        utc_offset for dateTime, <column>_utc_offset for
+                        the others) or string (RFC 3339 text). Default: {}
+  --parquet-compression=<codec>
+                        Parquet compression: none, snappy, lz4, gzip[:level]
+                        (0-9) or zstd[:level] (1-22). Default: {}
+  --parquet-row-group-size=<rows>
+                        Maximum rows per Parquet row group. Default: 1048576
+  --[no-]parquet-dictionary
+                        Dictionary-encode Parquet columns. Default: {}
+                        Parquet files also record the sunce version and
+                        calculation settings as key-value metadata.
+  --output-dir=<path>   With --format=parquet, write a Hive-partitioned dataset
+                        (year=YYYY/month=MM/part-NNNN.parquet) instead of stdout.
+  --partition-lat-band=<degrees>
+                        With --output-dir, add a lat_band=<start> partition level.
+  --max-file-rows=<n>   With --output-dir, start a new part file after n rows.
+  --max-file-size=<bytes>
+                        With --output-dir, start a new part file after about this
+                        many bytes (K, M, or G suffix allowed).
+  --output=<file>       With --format=sqlite, the database file to write.
+  --table=<name>        With --format=sqlite, the table to create or append to.
+                        Default: the command name.
+  --upsert              With --format=sqlite, replace rows with the same
+                        latitude, longitude and dateTime instant (kept in a
+                        dateTime_epoch column) instead of adding duplicates.
   --timezone=<tz>       Timezone offset (+01:00) or IANA name (Europe/Berlin).
-                        Overrides timezone for parsing and output.
+                        Overrides timezone for parsing and output. auto looks
+                        up each location's zone from its coordinates.
+  --input-timezone=<tz> Timezone for parsing inputs and local days only.
+  --output-timezone=<tz>
+                        Timezone that output times are converted to.
+  --dst-policy=<policy> Local times repeated or skipped by DST: earlier (default),
+                        later, reject, or shift-forward. Shown with --show-inputs.
   --[no-]headers        Include headers in CSV output. Default: {}
   --[no-]show-inputs    Include inputs in output. Auto-enabled for ranges,
                         files, and position date-only inputs unless
                         --no-show-inputs is used.
+  --columns=<names>     Comma-separated output columns, in order, for all formats.
+                        Input columns are always available.
+  --list-columns        Print every column of the command, with the option that
+                        enables those the current options leave out, then exit.
   --perf                Print performance statistics to stderr.
+  --threads=<n>         Compute on n worker threads. Output order is unchanged.
+                        Ignored for stdin and 'now' streams. Default: {}
+  --grid-km=<km>        Sample locations every km kilometers: pass latitude
+                        and longitude as start:end bounds, or a feature file
+                        whose polygons are sampled (points are kept).
+  --grid-cos-lat        With --grid-km, widen longitude steps by 1/cos(lat)
+                        so east-west spacing is in km as well.
   Predicate mode (automation via exit status):
     Works only with one explicit lat/lon pair and one explicit instant.
     Sunrise predicates:
@@ -503,10 +946,14 @@ Options:
       --is-nautical-twilight     Exit 0 if the instant is in nautical twilight.
       --is-astronomical-twilight Exit 0 if the instant is in astronomical twilight.
       --is-astronomical-night    Exit 0 if the instant is outside astronomical twilight.
+      --is-golden-hour           Exit 0 if the sun is between -4 and 6 degrees.
+      --is-blue-hour             Exit 0 if the sun is between -6 and -4 degrees.
       --after-sunset             Exit 0 from sunset until the next sunrise.
     Position predicates:
       --sun-above=<degrees>      Exit 0 if the elevation angle is above the threshold.
       --sun-below=<degrees>      Exit 0 if the elevation angle is below the threshold.
+    Moon position predicates:
+      --moon-below=<degrees>     Exit 0 if the moon's altitude is below the threshold.
     Shared:
       --wait                     With `now`, wait until the predicate becomes true.
                                  Completion is usually within seconds, not
@@ -517,13 +964,20 @@ Options:
 Commands:
   position              Calculate topocentric solar coordinates.
   sunrise               Calculate sunrise, transit, sunset, and optional twilight.
+  events                List solar-state transitions (sunrise, sunset, twilight).
+  moon-position         Calculate topocentric lunar coordinates and phase.
+  moonrise              Calculate moonrise, moonset, and the moon's phase.
 
 Run 'sunce help <command>' for command-specific options.
 "#,
         env!("CARGO_PKG_VERSION"),
         formats,
         defaults.output.format,
-        defaults.output.headers
+        defaults.output.parquet.timestamps,
+        defaults.output.parquet.compression,
+        defaults.output.parquet.dictionary,
+        defaults.output.headers,
+        defaults.threads
     )
 }
 
@@ -543,9 +997,37 @@ Options:
   --algorithm=<alg>         Algorithm: spa, grena3. Default: {}
   --elevation=<meters>      Elevation above sea level in meters. Default: {}
   --elevation-angle         Output elevation angle instead of zenith angle.
+  --horizon-profile=@file   Add an 'obstructed' column: true while the sun's
+                            upper limb is below the local skyline, as for
+                            skyline sunrise/sunset. The file has one
+                            'azimuth elevation' pair per line (degrees).
   --no-refraction           Disable refraction correction.
   --pressure=<hPa>          Air pressure in hPa (refraction). Default: {}
   --temperature=<celsius>   Air temperature in C (refraction). Default: {}
+  --irradiance              Add clear-sky irradiance columns (Ineichen-Perez):
+                            extraterrestrial, air_mass, ghi, dni, dhi in W/m².
+                            Uses --elevation and --pressure.
+  --linke-turbidity=<TL>    Linke turbidity for --irradiance, 1 to 10. Default: {}
+  --surface-tilt=<degrees>  Add incidence-angle columns for a surface with this
+                            tilt from horizontal (0 to 180). Default: {}
+  --surface-azimuth=<deg>   Surface azimuth, clockwise from north. Default: {}
+  --tracker=<mode>          Surface tracking: fixed, single-axis, dual-axis.
+                            single-axis rotates about an axis with the surface
+                            tilt and azimuth and adds tracker_rotation
+                            (positive when the surface faces the surface
+                            azimuth plus 90, e.g. west for azimuth 180);
+                            dual-axis faces the sun and adds tracker_tilt and
+                            tracker_azimuth. Tracker columns are empty while
+                            the sun is down. Default: fixed
+  --shadow-height=<meters>  Add shadow_length, shadow_azimuth, shadow_east and
+                            shadow_north (meters) for a vertical object of
+                            this height on level ground. Empty while the sun
+                            is down.
+  --geometry                Add declination, right_ascension, hour_angle
+                            (degrees, negative before transit),
+                            equation_of_time (minutes), earth_sun_distance
+                            (AU), julian_day, and sidereal_time (apparent,
+                            Greenwich, degrees).
   --step=<interval>         Time step for ranges and date-only inputs.
                             Examples: 30s, 15m, 2h, 1d
   --sun-above=<degrees>     Predicate mode: exit 0 if elevation angle is above
@@ -561,11 +1043,19 @@ Examples:
   sunce 52.0 13.4 2024-06-21T12:00:00 position
   sunce 52.0 13.4 2024-06-21 position --step=10m
   sunce 50:55:0.5 10:15:0.5 2024-06-21T12:00:00 position --algorithm=grena3
+  sunce 47.3 11.4 2024-06-21 position --irradiance --elevation=570 --format=csv
+  sunce 47.3 11.4 2024-06-21 position --surface-tilt=35 --surface-azimuth=180
+  sunce 47.3 11.4 2024-06-21 position --tracker=single-axis
+  sunce 52.0 13.4 2024-12-21 position --step=1h --shadow-height=25
+  sunce 52.0 13.4 2024-06-21 position --step=1h --geometry --format=csv
 "#,
             defaults.calculation.algorithm,
             defaults.environment.elevation,
             defaults.environment.pressure,
-            defaults.environment.temperature
+            defaults.environment.temperature,
+            defaults.environment.linke_turbidity,
+            Surface::default().tilt,
+            Surface::default().azimuth
         ),
         "sunrise" => r#"Usage:
   sunce [OPTIONS] <latitude> <longitude> <dateTime> sunrise
@@ -578,12 +1068,29 @@ Calculates sunrise, transit, sunset and (optionally) twilight times.
 Options:
   --twilight                Include civil, nautical, and astronomical twilight times.
   --horizon=<degrees>       Custom horizon angle in degrees (ignored with --twilight).
+  --horizons=<list>         Add <label>_start/<label>_end columns for each
+                            comma-separated [label=]degrees entry, e.g.
+                            --horizons=legal=-0.833,-6. Unlabelled entries are
+                            named after the angle (-6 -> horizon_m6).
+  --day-stats               Add day length, twilight durations (seconds), solar
+                            noon elevation, and day length change from the
+                            previous day.
+  --photo-hours             Add morning and evening start/end times of the
+                            golden hour (sun from -4 to 6 degrees) and blue
+                            hour (-6 to -4 degrees).
+  --horizon-profile=@file   Report when the sun clears the local skyline, one
+                            record per visible period. The file has one
+                            'azimuth elevation' pair per line (degrees).
+                            Periods running past midnight have an empty
+                            sunrise or sunset.
   --is-daylight             Predicate mode: exit 0 if the instant is daylight.
   --is-civil-twilight       Predicate mode: exit 0 if the instant is in civil twilight.
   --is-nautical-twilight    Predicate mode: exit 0 if the instant is in nautical twilight.
   --is-astronomical-twilight Predicate mode: exit 0 if the instant is in astronomical twilight.
   --is-astronomical-night   Predicate mode: exit 0 if the instant is outside
                             astronomical twilight.
+  --is-golden-hour          Predicate mode: exit 0 during the golden hour.
+  --is-blue-hour            Predicate mode: exit 0 during the blue hour.
   --after-sunset            Predicate mode: exit 0 from sunset until the next
                             sunrise.
   --wait                    Predicate mode: with `now`, wait until the
@@ -595,6 +1102,79 @@ Examples:
   sunce 52.0 13.4 2024-06-21 sunrise
   sunce 52.0 13.4 2024-06 sunrise --twilight
   sunce 52.0 13.4 2024-06-21 sunrise --horizon=-6.0
+  sunce 52.0 13.4 2024 sunrise --day-stats --format=csv
+  sunce 52.0 13.4 2024-06-21 sunrise --photo-hours
+  sunce 52.0 13.4 2024-06 sunrise --horizons=flight=-6,hunt=-9.5
+  sunce 47.3 11.4 2024-12 sunrise --horizon-profile=@skyline.csv
+"#
+        .to_string(),
+        "events" => r#"Usage:
+  sunce [OPTIONS] <latitude> <longitude> <dateTime> events
+  sunce [OPTIONS] @data.txt events
+  sunce [OPTIONS] @coords.txt @times.txt events
+  sunce [OPTIONS] @coords.txt <dateTime> events
+
+Lists every transition between daylight, civil, nautical, and astronomical
+twilight, and night during the local day of each input. Each record has the
+transition time, the states before and after, and the horizon crossed in
+degrees. Days without transitions (polar day or night) produce no records.
+
+Examples:
+  sunce 52.5 13.4 2026-03-21 events
+  sunce 52.5 13.4 2026-03 events --format=csv
+  sunce 69.6 18.9 2026 events --format=parquet > tromso.parquet
+"#
+        .to_string(),
+        "moon-position" => format!(
+            r#"Usage:
+  sunce [OPTIONS] <latitude> <longitude> <dateTime> moon-position
+  sunce [OPTIONS] @data.txt moon-position
+  sunce [OPTIONS] @coords.txt @times.txt moon-position
+  sunce [OPTIONS] @coords.txt <dateTime> moon-position
+
+Calculates the topocentric position of the moon's centre: azimuth, altitude,
+and distance (km, Earth centre to moon centre), with the illuminated fraction,
+phase angle (degrees), and phase name (NEW_MOON, WAXING_CRESCENT,
+FIRST_QUARTER, WAXING_GIBBOUS, FULL_MOON, WANING_GIBBOUS, LAST_QUARTER,
+WANING_CRESCENT).
+
+Options:
+  --elevation=<meters>      Elevation above sea level in meters. Default: {}
+  --no-refraction           Disable refraction correction.
+  --pressure=<hPa>          Air pressure in hPa (refraction). Default: {}
+  --temperature=<celsius>   Air temperature in C (refraction). Default: {}
+  --step=<interval>         Time step for ranges and date-only inputs.
+                            Examples: 30s, 15m, 2h, 1d
+  --moon-below=<degrees>    Predicate mode: exit 0 if the moon's altitude is
+                            below the threshold, 1 if not.
+  --wait                    Predicate mode: with `now`, wait until the
+                            predicate becomes true.
+
+Examples:
+  sunce 52.0 13.4 2024-06-21T22:00:00 moon-position
+  sunce 52.0 13.4 2024-06-21 moon-position --step=10m --format=csv
+  sunce --moon-below=0 52.0 13.4 now moon-position
+"#,
+            defaults.environment.elevation,
+            defaults.environment.pressure,
+            defaults.environment.temperature
+        ),
+        "moonrise" => r#"Usage:
+  sunce [OPTIONS] <latitude> <longitude> <dateTime> moonrise
+  sunce [OPTIONS] @data.txt moonrise
+  sunce [OPTIONS] @coords.txt @times.txt moonrise
+  sunce [OPTIONS] @coords.txt <dateTime> moonrise
+
+Calculates the first moonrise and moonset during the local day of each input,
+when the moon's upper limb crosses the horizon under standard refraction.
+About once a month one of them falls on the next day and is left empty. The
+type is NORMAL, or ALWAYS_UP/ALWAYS_DOWN when the moon neither rises nor sets.
+The illuminated fraction, phase angle, and phase are for the middle of the day.
+
+Examples:
+  sunce 52.0 13.4 2024-06-21 moonrise
+  sunce 52.0 13.4 2024-06 moonrise --format=csv
+  sunce 78.2 15.6 2024 moonrise --timezone=Arctic/Longyearbyen
 "#
         .to_string(),
         _ => format!(
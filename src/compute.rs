@@ -1,13 +1,37 @@
 //! Stream orchestration and shared calculation result types.
 
-use crate::data::{CalculationAlgorithm, Command, CoordTimeStream, Parameters};
-use crate::position::{SpaCache, TIME_CACHE_CAPACITY, refraction_correction, time_cache_get};
-use crate::sunrise::calculate_sunrise as calculate_sunrise_impl;
+use crate::data::time_utils::{TimezoneInfo, convert_datetime_to_timezone, parse_timezone_spec};
+use crate::data::{
+    CalculationAlgorithm, Command, CoordTimeResult, CoordTimeStream, Parameters, Site,
+    load_horizon_profile,
+};
+use crate::error::Error;
+use crate::geometry::SolarGeometry;
+use crate::incidence::SurfaceIncidence;
+use crate::irradiance::ClearSkyIrradiance;
+use crate::moon::{
+    MoonIllumination, MoonPosition, calculate_moonrise as calculate_moonrise_impl, moon_position,
+};
+use crate::position::{
+    SpaCache, TIME_CACHE_CAPACITY, position_result, refraction_correction, time_cache_get,
+};
+use crate::shadow::Shadow;
+use crate::sunrise::{
+    DayStats, PhotoHours, calculate_skyline_sunrise as calculate_skyline_sunrise_impl,
+    calculate_sunrise as calculate_sunrise_impl,
+    calculate_transitions as calculate_transitions_impl,
+};
 use chrono::{DateTime, FixedOffset};
-use solar_positioning::SolarPosition;
-use std::collections::VecDeque;
+use solar_positioning::{RefractionCorrection, SolarPosition, SunriseResult};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex, mpsc};
+use std::thread::JoinHandle;
 
-type CalculationStream = Box<dyn Iterator<Item = Result<CalculationResult, String>>>;
+/// Records per work item handed to a parallel worker.
+const PARALLEL_CHUNK_SIZE: usize = 1024;
+
+/// Lazy stream of calculation results, in input order.
+pub type CalculationStream = Box<dyn Iterator<Item = Result<CalculationResult, Error>>>;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SolarState {
@@ -18,6 +42,20 @@ pub enum SolarState {
     Night,
 }
 
+impl SolarState {
+    /// Returns the label used for this state in output records.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SolarState::Daylight => "DAYLIGHT",
+            SolarState::CivilTwilight => "CIVIL_TWILIGHT",
+            SolarState::NauticalTwilight => "NAUTICAL_TWILIGHT",
+            SolarState::AstronomicalTwilight => "ASTRONOMICAL_TWILIGHT",
+            SolarState::Night => "NIGHT",
+        }
+    }
+}
+
+/// One calculated record, carrying the inputs alongside the result.
 #[derive(Debug, Clone)]
 pub enum CalculationResult {
     Position {
@@ -26,6 +64,17 @@ pub enum CalculationResult {
         datetime: DateTime<FixedOffset>,
         position: SolarPosition,
         deltat: f64,
+        /// Whether the sun is behind the horizon profile; `None` without a profile.
+        obstructed: Option<bool>,
+        /// Clear-sky irradiance; `None` unless requested.
+        irradiance: Option<ClearSkyIrradiance>,
+        /// Incidence on the configured surface; `None` without a surface.
+        incidence: Option<SurfaceIncidence>,
+        /// Shadow of the configured object; `None` without a height or with the sun down.
+        shadow: Option<Shadow>,
+        /// Equatorial and time quantities; `None` unless requested.
+        geometry: Option<SolarGeometry>,
+        site: Option<Arc<Site>>,
     },
     Sunrise {
         lat: f64,
@@ -33,6 +82,7 @@ pub enum CalculationResult {
         date: DateTime<FixedOffset>,
         result: solar_positioning::SunriseResult<DateTime<FixedOffset>>,
         deltat: f64,
+        site: Option<Arc<Site>>,
     },
     SunriseWithTwilight {
         lat: f64,
@@ -43,94 +93,692 @@ pub enum CalculationResult {
         nautical: solar_positioning::SunriseResult<DateTime<FixedOffset>>,
         astronomical: solar_positioning::SunriseResult<DateTime<FixedOffset>>,
         deltat: f64,
+        /// Derived daily quantities; `None` unless requested.
+        stats: Option<DayStats>,
+        /// Golden-hour and blue-hour periods; `None` unless requested.
+        photo_hours: Option<Box<PhotoHours>>,
+        /// Results for each `--horizons` entry, in option order.
+        horizons: Vec<solar_positioning::SunriseResult<DateTime<FixedOffset>>>,
+        site: Option<Arc<Site>>,
+    },
+    /// One period with the sun clear of the horizon profile during the day of `date`.
+    ///
+    /// `sunrise`/`sunset` are `None` where the period continues past the start or end of
+    /// the day. A day without any such period has a single result with `visible` false.
+    SkylineSunrise {
+        lat: f64,
+        lon: f64,
+        date: DateTime<FixedOffset>,
+        sunrise: Option<DateTime<FixedOffset>>,
+        transit: DateTime<FixedOffset>,
+        sunset: Option<DateTime<FixedOffset>>,
+        visible: bool,
+        deltat: f64,
+        site: Option<Arc<Site>>,
+    },
+    /// A change of [`SolarState`] at `datetime`, caused by the sun crossing `horizon` degrees.
+    Transition {
+        lat: f64,
+        lon: f64,
+        datetime: DateTime<FixedOffset>,
+        before: SolarState,
+        after: SolarState,
+        horizon: f64,
+        deltat: f64,
+        site: Option<Arc<Site>>,
     },
+    MoonPosition {
+        lat: f64,
+        lon: f64,
+        datetime: DateTime<FixedOffset>,
+        position: MoonPosition,
+        deltat: f64,
+        site: Option<Arc<Site>>,
+    },
+    /// First moonrise and moonset during the day of `date`; either is `None` when it falls on
+    /// another day.
+    Moonrise {
+        lat: f64,
+        lon: f64,
+        date: DateTime<FixedOffset>,
+        moonrise: Option<DateTime<FixedOffset>>,
+        moonset: Option<DateTime<FixedOffset>>,
+        /// Whether the moon is up at the start of the day.
+        above_horizon: bool,
+        /// Illumination at the middle of the day.
+        illumination: MoonIllumination,
+        deltat: f64,
+        site: Option<Arc<Site>>,
+    },
+}
+
+impl CalculationResult {
+    /// Returns the properties of the input location: the id and name of a feature file
+    /// entry, and the timezone of a coordinate or paired file column or of `--timezone=auto`.
+    /// `None` for locations without any, such as command-line coordinates, ranges, and bounds
+    /// grids without a zone lookup.
+    pub fn site(&self) -> Option<&Site> {
+        match self {
+            CalculationResult::Position { site, .. }
+            | CalculationResult::Sunrise { site, .. }
+            | CalculationResult::SunriseWithTwilight { site, .. }
+            | CalculationResult::SkylineSunrise { site, .. }
+            | CalculationResult::Transition { site, .. }
+            | CalculationResult::MoonPosition { site, .. }
+            | CalculationResult::Moonrise { site, .. } => site.as_deref(),
+        }
+    }
+
+    /// Converts every date/time of the result to `tz`, keeping the instants.
+    fn in_timezone(self, tz: &TimezoneInfo) -> Self {
+        let convert = |dt: DateTime<FixedOffset>| convert_datetime_to_timezone(dt, tz);
+        match self {
+            CalculationResult::Position {
+                lat,
+                lon,
+                datetime,
+                position,
+                deltat,
+                obstructed,
+                irradiance,
+                incidence,
+                shadow,
+                geometry,
+                site,
+            } => CalculationResult::Position {
+                lat,
+                lon,
+                datetime: convert(datetime),
+                position,
+                deltat,
+                obstructed,
+                irradiance,
+                incidence,
+                shadow,
+                geometry,
+                site,
+            },
+            CalculationResult::Sunrise {
+                lat,
+                lon,
+                date,
+                result,
+                deltat,
+                site,
+            } => CalculationResult::Sunrise {
+                lat,
+                lon,
+                date: convert(date),
+                result: sunrise_result_in_timezone(result, convert),
+                deltat,
+                site,
+            },
+            CalculationResult::SunriseWithTwilight {
+                lat,
+                lon,
+                date,
+                sunrise_sunset,
+                civil,
+                nautical,
+                astronomical,
+                deltat,
+                stats,
+                photo_hours,
+                horizons,
+                site,
+            } => CalculationResult::SunriseWithTwilight {
+                lat,
+                lon,
+                date: convert(date),
+                sunrise_sunset: sunrise_result_in_timezone(sunrise_sunset, convert),
+                civil: sunrise_result_in_timezone(civil, convert),
+                nautical: sunrise_result_in_timezone(nautical, convert),
+                astronomical: sunrise_result_in_timezone(astronomical, convert),
+                deltat,
+                stats,
+                photo_hours: photo_hours
+                    .map(|hours| Box::new(photo_hours_in_timezone(*hours, convert))),
+                horizons: horizons
+                    .into_iter()
+                    .map(|result| sunrise_result_in_timezone(result, convert))
+                    .collect(),
+                site,
+            },
+            CalculationResult::SkylineSunrise {
+                lat,
+                lon,
+                date,
+                sunrise,
+                transit,
+                sunset,
+                visible,
+                deltat,
+                site,
+            } => CalculationResult::SkylineSunrise {
+                lat,
+                lon,
+                date: convert(date),
+                sunrise: sunrise.map(convert),
+                transit: convert(transit),
+                sunset: sunset.map(convert),
+                visible,
+                deltat,
+                site,
+            },
+            CalculationResult::Transition {
+                lat,
+                lon,
+                datetime,
+                before,
+                after,
+                horizon,
+                deltat,
+                site,
+            } => CalculationResult::Transition {
+                lat,
+                lon,
+                datetime: convert(datetime),
+                before,
+                after,
+                horizon,
+                deltat,
+                site,
+            },
+            CalculationResult::MoonPosition {
+                lat,
+                lon,
+                datetime,
+                position,
+                deltat,
+                site,
+            } => CalculationResult::MoonPosition {
+                lat,
+                lon,
+                datetime: convert(datetime),
+                position,
+                deltat,
+                site,
+            },
+            CalculationResult::Moonrise {
+                lat,
+                lon,
+                date,
+                moonrise,
+                moonset,
+                above_horizon,
+                illumination,
+                deltat,
+                site,
+            } => CalculationResult::Moonrise {
+                lat,
+                lon,
+                date: convert(date),
+                moonrise: moonrise.map(convert),
+                moonset: moonset.map(convert),
+                above_horizon,
+                illumination,
+                deltat,
+                site,
+            },
+        }
+    }
+
+    fn with_site(mut self, site: Option<Arc<Site>>) -> Self {
+        match &mut self {
+            CalculationResult::Position { site: slot, .. }
+            | CalculationResult::Sunrise { site: slot, .. }
+            | CalculationResult::SunriseWithTwilight { site: slot, .. }
+            | CalculationResult::SkylineSunrise { site: slot, .. }
+            | CalculationResult::Transition { site: slot, .. }
+            | CalculationResult::MoonPosition { site: slot, .. }
+            | CalculationResult::Moonrise { site: slot, .. } => *slot = site,
+        }
+        self
+    }
+}
+
+fn sunrise_result_in_timezone(
+    result: SunriseResult<DateTime<FixedOffset>>,
+    convert: impl Fn(DateTime<FixedOffset>) -> DateTime<FixedOffset>,
+) -> SunriseResult<DateTime<FixedOffset>> {
+    match result {
+        SunriseResult::RegularDay {
+            sunrise,
+            transit,
+            sunset,
+        } => SunriseResult::RegularDay {
+            sunrise: convert(sunrise),
+            transit: convert(transit),
+            sunset: convert(sunset),
+        },
+        SunriseResult::AllDay { transit } => SunriseResult::AllDay {
+            transit: convert(transit),
+        },
+        SunriseResult::AllNight { transit } => SunriseResult::AllNight {
+            transit: convert(transit),
+        },
+    }
+}
+
+fn photo_hours_in_timezone(
+    hours: PhotoHours,
+    convert: impl Fn(DateTime<FixedOffset>) -> DateTime<FixedOffset>,
+) -> PhotoHours {
+    let convert = |dt: Option<DateTime<FixedOffset>>| dt.map(&convert);
+    PhotoHours {
+        golden_morning_start: convert(hours.golden_morning_start),
+        golden_morning_end: convert(hours.golden_morning_end),
+        golden_evening_start: convert(hours.golden_evening_start),
+        golden_evening_end: convert(hours.golden_evening_end),
+        blue_morning_start: convert(hours.blue_morning_start),
+        blue_morning_end: convert(hours.blue_morning_end),
+        blue_evening_start: convert(hours.blue_evening_start),
+        blue_evening_end: convert(hours.blue_evening_end),
+    }
+}
+
+/// Per-record calculator that owns the refraction settings and, for SPA, its own time cache.
+///
+/// Each parallel worker holds one instance, so cache hits are local to that worker.
+struct RecordCalculator {
+    command: Command,
+    params: Parameters,
+    refraction: Option<RefractionCorrection>,
+    time_cache: Option<(SpaCache, VecDeque<DateTime<FixedOffset>>)>,
+    output_timezone: Option<TimezoneInfo>,
+    /// `params` with the timezone of the most recent site that had one.
+    site_params: Option<Parameters>,
+}
+
+impl RecordCalculator {
+    fn new(command: Command, params: Parameters, allow_time_cache: bool) -> Result<Self, Error> {
+        let refraction = match command {
+            Command::Position | Command::MoonPosition => refraction_correction(&params)?,
+            Command::Sunrise | Command::Events | Command::Moonrise => None,
+        };
+        let time_cache = (command == Command::Position
+            && params.calculation.algorithm == CalculationAlgorithm::Spa
+            && allow_time_cache)
+            .then(|| (SpaCache::default(), VecDeque::new()));
+        let output_timezone = params
+            .output_timezone
+            .as_ref()
+            .and_then(|tz| parse_timezone_spec(tz.as_str()));
+
+        Ok(Self {
+            command,
+            params,
+            refraction,
+            time_cache,
+            output_timezone,
+            site_params: None,
+        })
+    }
+
+    /// Returns the parameters for a record, using its site's timezone for local days.
+    fn params_for(&mut self, site: Option<&Site>) -> &Parameters {
+        let Some(tz) = site.and_then(|site| site.timezone.as_ref()) else {
+            return &self.params;
+        };
+        if !self
+            .site_params
+            .as_ref()
+            .is_some_and(|params| params.timezone.as_ref() == Some(tz))
+        {
+            let mut params = self.params.clone();
+            params.timezone = Some(tz.clone());
+            self.site_params = Some(params);
+        }
+        self.site_params.as_ref().unwrap_or(&self.params)
+    }
+
+    /// Calculates the single result of a one-record-per-input command.
+    fn calculate(&mut self, item: CoordTimeResult) -> Result<CalculationResult, Error> {
+        let (lat, lon, dt, site) = item?;
+        let result = match self.command {
+            Command::Position => self.calculate_position(lat, lon, dt),
+            Command::Sunrise => {
+                calculate_sunrise_impl(lat, lon, dt, self.params_for(site.as_deref()))
+            }
+            Command::Events => unreachable!("events are expanded by calculate_into"),
+            Command::MoonPosition => self.calculate_moon_position(lat, lon, dt),
+            Command::Moonrise => {
+                calculate_moonrise_impl(lat, lon, dt, self.params_for(site.as_deref()))
+            }
+        }?;
+        Ok(self.finish(result, site))
+    }
+
+    /// Attaches the input's site labels and converts the result to the output timezone.
+    fn finish(&self, result: CalculationResult, site: Option<Arc<Site>>) -> CalculationResult {
+        let result = result.with_site(site);
+        match &self.output_timezone {
+            Some(tz) => result.in_timezone(tz),
+            None => result,
+        }
+    }
+
+    /// Returns whether one input record can yield any number of results.
+    fn yields_many(&self) -> bool {
+        match self.command {
+            Command::Position | Command::MoonPosition | Command::Moonrise => false,
+            Command::Sunrise => self.params.calculation.horizon_profile.is_some(),
+            Command::Events => true,
+        }
+    }
+
+    /// Appends every result for one input record to `out`.
+    fn calculate_into(&mut self, item: CoordTimeResult, out: &mut ChunkResults) {
+        if !self.yields_many() {
+            out.push(self.calculate(item));
+            return;
+        }
+        let command = self.command;
+        let results = item.and_then(|(lat, lon, dt, site)| {
+            let params = self.params_for(site.as_deref());
+            match &params.calculation.horizon_profile {
+                Some(profile) if command == Command::Sunrise => {
+                    calculate_skyline_sunrise_impl(lat, lon, dt, params, profile)
+                }
+                _ => calculate_transitions_impl(lat, lon, dt, params),
+            }
+            .map(|results| (results, site))
+        });
+        match results {
+            Ok((results, site)) => out.extend(
+                results
+                    .into_iter()
+                    .map(|result| Ok(self.finish(result, site.clone()))),
+            ),
+            Err(err) => out.push(Err(err)),
+        }
+    }
+
+    fn calculate_position(
+        &mut self,
+        lat: f64,
+        lon: f64,
+        dt: DateTime<FixedOffset>,
+    ) -> Result<CalculationResult, Error> {
+        let params = &self.params;
+        let (position, deltat, time_parts) = if let Some((cache, order)) = self.time_cache.as_mut()
+        {
+            let (time_parts, deltat) =
+                time_cache_get(cache, order, TIME_CACHE_CAPACITY, dt, params)
+                    .map_err(Error::calculation)?;
+            let position = solar_positioning::spa::spa_with_time_dependent_parts(
+                lat,
+                lon,
+                params.environment.elevation,
+                self.refraction,
+                time_parts.as_ref(),
+            )
+            .map_err(|e| {
+                Error::calculation(format!("Failed to calculate solar position: {}", e))
+            })?;
+            (position, deltat, Some(time_parts))
+        } else {
+            let deltat = crate::position::resolve_deltat(dt, params);
+            let position = if params.calculation.algorithm == CalculationAlgorithm::Grena3 {
+                solar_positioning::grena3::solar_position(dt, lat, lon, deltat, self.refraction)
+                    .map_err(|e| {
+                        Error::calculation(format!("Failed to calculate solar position: {}", e))
+                    })?
+            } else {
+                solar_positioning::spa::solar_position(
+                    dt,
+                    lat,
+                    lon,
+                    params.environment.elevation,
+                    deltat,
+                    self.refraction,
+                )
+                .map_err(|e| {
+                    Error::calculation(format!("Failed to calculate solar position: {}", e))
+                })?
+            };
+            (position, deltat, None)
+        };
+
+        position_result(
+            lat,
+            lon,
+            dt,
+            position,
+            deltat,
+            time_parts.as_deref(),
+            params,
+        )
+        .map_err(Error::calculation)
+    }
+
+    fn calculate_moon_position(
+        &self,
+        lat: f64,
+        lon: f64,
+        dt: DateTime<FixedOffset>,
+    ) -> Result<CalculationResult, Error> {
+        let deltat = crate::position::resolve_deltat(dt, &self.params);
+        let position = moon_position(
+            lat,
+            lon,
+            dt,
+            self.params.environment.elevation,
+            deltat,
+            self.refraction,
+        )
+        .map_err(Error::calculation)?;
+        Ok(CalculationResult::MoonPosition {
+            lat,
+            lon,
+            datetime: dt,
+            position,
+            deltat,
+            site: None,
+        })
+    }
+}
+
+/// Loads `horizon_profile_file` into `horizon_profile` unless a profile is already set.
+pub(crate) fn resolve_horizon_profile(params: &mut Parameters) -> Result<(), Error> {
+    let calculation = &mut params.calculation;
+    if let (None, Some(path)) = (
+        &calculation.horizon_profile,
+        &calculation.horizon_profile_file,
+    ) {
+        calculation.horizon_profile = Some(Arc::new(load_horizon_profile(path)?));
+    }
+    Ok(())
 }
 
+/// Maps an input stream to calculation results for `command`.
+///
+/// `allow_time_cache` enables the SPA time-dependent cache; disable it for unbounded live streams.
 pub fn calculate_stream(
     data: CoordTimeStream,
     command: Command,
-    params: Parameters,
+    mut params: Parameters,
     allow_time_cache: bool,
 ) -> CalculationStream {
-    match command {
-        Command::Position => {
-            let refraction = match refraction_correction(&params) {
-                Ok(value) => value,
-                Err(err) => return Box::new(std::iter::once(Err(err))),
-            };
+    if let Err(err) = resolve_horizon_profile(&mut params) {
+        return Box::new(std::iter::once(Err(err)));
+    }
+    let mut calculator = match RecordCalculator::new(command, params, allow_time_cache) {
+        Ok(calculator) => calculator,
+        Err(err) => return Box::new(std::iter::once(Err(err))),
+    };
+    if calculator.yields_many() {
+        Box::new(data.flat_map(move |item| {
+            let mut out = Vec::new();
+            calculator.calculate_into(item, &mut out);
+            out
+        }))
+    } else {
+        Box::new(data.map(move |item| calculator.calculate(item)))
+    }
+}
 
-            if params.calculation.algorithm == CalculationAlgorithm::Spa && allow_time_cache {
-                use solar_positioning::spa;
-
-                let mut time_cache: SpaCache = SpaCache::default();
-                let mut time_cache_order: VecDeque<DateTime<FixedOffset>> = VecDeque::new();
-
-                Box::new(data.map(move |item| {
-                    item.and_then(|(lat, lon, dt)| {
-                        let (time_parts, deltat) = match time_cache_get(
-                            &mut time_cache,
-                            &mut time_cache_order,
-                            TIME_CACHE_CAPACITY,
-                            dt,
-                            &params,
-                        ) {
-                            Ok(value) => value,
-                            Err(err) => return Err(err),
-                        };
-
-                        let position = spa::spa_with_time_dependent_parts(
-                            lat,
-                            lon,
-                            params.environment.elevation,
-                            refraction,
-                            time_parts.as_ref(),
-                        )
-                        .map_err(|e| format!("Failed to calculate solar position: {}", e))?;
-
-                        Ok(CalculationResult::Position {
-                            lat,
-                            lon,
-                            datetime: dt,
-                            position,
-                            deltat,
-                        })
-                    })
-                }))
-            } else {
-                Box::new(data.map(move |item| {
-                    item.and_then(|(lat, lon, dt)| {
-                        let deltat = crate::position::resolve_deltat(dt, &params);
-                        let position =
-                            if params.calculation.algorithm == CalculationAlgorithm::Grena3 {
-                                solar_positioning::grena3::solar_position(
-                                    dt, lat, lon, deltat, refraction,
-                                )
-                                .map_err(|e| format!("Failed to calculate solar position: {}", e))?
-                            } else {
-                                solar_positioning::spa::solar_position(
-                                    dt,
-                                    lat,
-                                    lon,
-                                    params.environment.elevation,
-                                    deltat,
-                                    refraction,
-                                )
-                                .map_err(|e| format!("Failed to calculate solar position: {}", e))?
-                            };
-
-                        Ok(CalculationResult::Position {
-                            lat,
-                            lon,
-                            datetime: dt,
-                            position,
-                            deltat,
-                        })
-                    })
-                }))
+/// Like [`calculate_stream`], but computes chunks of the input on `threads` worker threads.
+///
+/// Input is still read lazily on the calling thread and results are yielded in input order.
+/// Each worker keeps its own SPA time cache. With `threads <= 1` this is `calculate_stream`.
+pub fn calculate_stream_parallel(
+    data: CoordTimeStream,
+    command: Command,
+    mut params: Parameters,
+    allow_time_cache: bool,
+    threads: usize,
+) -> CalculationStream {
+    if threads <= 1 {
+        return calculate_stream(data, command, params, allow_time_cache);
+    }
+    if let Err(err) = resolve_horizon_profile(&mut params) {
+        return Box::new(std::iter::once(Err(err)));
+    }
+    if let Err(err) = refraction_correction(&params) {
+        return Box::new(std::iter::once(Err(err.into())));
+    }
+
+    let (job_tx, job_rx) = mpsc::channel::<(usize, Vec<CoordTimeResult>)>();
+    let (result_tx, result_rx) = mpsc::channel();
+    let job_rx = Arc::new(Mutex::new(job_rx));
+    let workers = (0..threads)
+        .map(|_| {
+            let job_rx = Arc::clone(&job_rx);
+            let result_tx = result_tx.clone();
+            let params = params.clone();
+            std::thread::spawn(move || {
+                let Ok(mut calculator) = RecordCalculator::new(command, params, allow_time_cache)
+                else {
+                    return;
+                };
+                loop {
+                    let job = job_rx.lock().map(|rx| rx.recv());
+                    let Ok(Ok((seq, chunk))) = job else {
+                        break;
+                    };
+                    let mut results = Vec::with_capacity(chunk.len());
+                    for item in chunk {
+                        calculator.calculate_into(item, &mut results);
+                    }
+                    if result_tx.send((seq, results)).is_err() {
+                        break;
+                    }
+                }
+            })
+        })
+        .collect();
+
+    Box::new(
+        ParallelResults {
+            data: Some(data),
+            job_tx: Some(job_tx),
+            result_rx,
+            workers,
+            max_in_flight: threads * 2,
+            next_job: 0,
+            next_output: 0,
+            pending: BTreeMap::new(),
+        }
+        .flatten(),
+    )
+}
+
+type ChunkResults = Vec<Result<CalculationResult, Error>>;
+
+/// Ordered reassembly of chunk results produced by the worker pool.
+struct ParallelResults {
+    data: Option<CoordTimeStream>,
+    job_tx: Option<mpsc::Sender<(usize, Vec<CoordTimeResult>)>>,
+    result_rx: mpsc::Receiver<(usize, ChunkResults)>,
+    workers: Vec<JoinHandle<()>>,
+    max_in_flight: usize,
+    next_job: usize,
+    next_output: usize,
+    pending: BTreeMap<usize, ChunkResults>,
+}
+
+impl ParallelResults {
+    fn dispatch_jobs(&mut self) {
+        while self.next_job - self.next_output < self.max_in_flight {
+            let (Some(data), Some(job_tx)) = (self.data.as_mut(), self.job_tx.as_ref()) else {
+                return;
+            };
+            let chunk = data.by_ref().take(PARALLEL_CHUNK_SIZE).collect::<Vec<_>>();
+            if chunk.len() < PARALLEL_CHUNK_SIZE {
+                self.data = None;
+            }
+            if chunk.is_empty() || job_tx.send((self.next_job, chunk)).is_err() {
+                self.job_tx = None;
+                return;
             }
+            self.next_job += 1;
+        }
+    }
+}
+
+impl Iterator for ParallelResults {
+    type Item = ChunkResults;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.dispatch_jobs();
+        if self.next_output == self.next_job {
+            return None;
+        }
+
+        while !self.pending.contains_key(&self.next_output) {
+            let Ok((seq, results)) = self.result_rx.recv() else {
+                // Every worker has exited: report it once, then end the stream.
+                self.data = None;
+                self.job_tx = None;
+                self.pending.clear();
+                self.next_output = self.next_job;
+                return Some(vec![Err(Error::calculation(
+                    "Calculation worker terminated unexpectedly",
+                ))]);
+            };
+            self.pending.insert(seq, results);
         }
-        Command::Sunrise => Box::new(data.map(move |item| {
-            item.and_then(|(lat, lon, dt)| calculate_sunrise_impl(lat, lon, dt, &params))
-        })),
+
+        let results = self.pending.remove(&self.next_output);
+        self.next_output += 1;
+        results
+    }
+}
+
+impl Drop for ParallelResults {
+    fn drop(&mut self) {
+        self.job_tx = None;
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parallel_results_end_after_workers_exit() {
+        let (_, result_rx) = mpsc::channel();
+        let mut results = ParallelResults {
+            data: None,
+            job_tx: None,
+            result_rx,
+            workers: Vec::new(),
+            max_in_flight: 2,
+            next_job: 1,
+            next_output: 0,
+            pending: BTreeMap::new(),
+        };
+
+        let error = results.next().unwrap();
+        assert_eq!(error.len(), 1);
+        assert_eq!(
+            error[0].as_ref().unwrap_err().kind(),
+            crate::ErrorKind::Calculation
+        );
+        assert!(results.next().is_none());
     }
 }
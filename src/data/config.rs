@@ -1,26 +1,60 @@
 //! Configuration types for commands, calculation options, and output settings.
 
+use super::{GridSpacing, HorizonProfile, InputPath};
 use chrono::Duration;
+use chrono::format::{Item, StrftimeItems};
 use std::fmt;
+use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::Arc;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum OutputFormat {
     Text,
     Csv,
     Json,
+    /// A GeoJSON FeatureCollection with one point feature per record.
+    GeoJson,
+    /// An iCalendar file with one event per sunrise, transit and sunset.
+    Ics,
     #[cfg(feature = "parquet")]
     Parquet,
+    /// An Arrow IPC stream with the same schema as Parquet output.
+    #[cfg(feature = "parquet")]
+    Arrow,
+    /// Rows inserted into a table of a SQLite database file.
+    #[cfg(feature = "sqlite")]
+    Sqlite,
 }
 
 impl OutputFormat {
+    pub(crate) fn is_parquet(self) -> bool {
+        #[cfg(feature = "parquet")]
+        return self == OutputFormat::Parquet;
+        #[cfg(not(feature = "parquet"))]
+        false
+    }
+
+    pub(crate) fn is_sqlite(self) -> bool {
+        #[cfg(feature = "sqlite")]
+        return self == OutputFormat::Sqlite;
+        #[cfg(not(feature = "sqlite"))]
+        false
+    }
+
     pub(crate) const fn all() -> &'static [&'static str] {
         &[
             "text",
             "csv",
             "json",
+            "geojson",
+            "ics",
             #[cfg(feature = "parquet")]
             "parquet",
+            #[cfg(feature = "parquet")]
+            "arrow",
+            #[cfg(feature = "sqlite")]
+            "sqlite",
         ]
     }
 }
@@ -31,8 +65,14 @@ impl fmt::Display for OutputFormat {
             OutputFormat::Text => "text",
             OutputFormat::Csv => "csv",
             OutputFormat::Json => "json",
+            OutputFormat::GeoJson => "geojson",
+            OutputFormat::Ics => "ics",
             #[cfg(feature = "parquet")]
             OutputFormat::Parquet => "parquet",
+            #[cfg(feature = "parquet")]
+            OutputFormat::Arrow => "arrow",
+            #[cfg(feature = "sqlite")]
+            OutputFormat::Sqlite => "sqlite",
         };
         f.write_str(s)
     }
@@ -46,12 +86,26 @@ impl FromStr for OutputFormat {
             "text" => Ok(OutputFormat::Text),
             "csv" => Ok(OutputFormat::Csv),
             "json" => Ok(OutputFormat::Json),
+            "geojson" => Ok(OutputFormat::GeoJson),
+            "ics" => Ok(OutputFormat::Ics),
             #[cfg(feature = "parquet")]
             "parquet" => Ok(OutputFormat::Parquet),
+            #[cfg(feature = "parquet")]
+            "arrow" => Ok(OutputFormat::Arrow),
+            #[cfg(feature = "sqlite")]
+            "sqlite" => Ok(OutputFormat::Sqlite),
             #[cfg(not(feature = "parquet"))]
             "parquet" => Err(
                 "PARQUET format not available in this build (enable feature 'parquet')".to_string(),
             ),
+            #[cfg(not(feature = "parquet"))]
+            "arrow" => Err(
+                "ARROW format not available in this build (enable feature 'parquet')".to_string(),
+            ),
+            #[cfg(not(feature = "sqlite"))]
+            "sqlite" => Err(
+                "SQLITE format not available in this build (enable feature 'sqlite')".to_string(),
+            ),
             _ => Err(format!(
                 "Invalid format: '{}'. Supported formats: {}",
                 s,
@@ -61,12 +115,284 @@ impl FromStr for OutputFormat {
     }
 }
 
+/// How date/time values are rendered in text, CSV, JSON, SQLite, and string Parquet columns.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum TimeFormat {
+    /// RFC 3339 with whole seconds and the value's own offset.
+    #[default]
+    Rfc3339,
+    /// RFC 3339 with milliseconds.
+    Rfc3339Millis,
+    /// Seconds since the Unix epoch.
+    Unix,
+    /// Milliseconds since the Unix epoch.
+    UnixMs,
+    /// RFC 3339 normalised to UTC, with a `Z` suffix.
+    Utc,
+    /// A strftime pattern, rendered in the value's own offset.
+    Custom(String),
+}
+
+impl TimeFormat {
+    /// Returns whether values are epoch numbers rather than text.
+    pub fn is_epoch(&self) -> bool {
+        matches!(self, TimeFormat::Unix | TimeFormat::UnixMs)
+    }
+}
+
+impl fmt::Display for TimeFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            TimeFormat::Rfc3339 => "rfc3339",
+            TimeFormat::Rfc3339Millis => "rfc3339-millis",
+            TimeFormat::Unix => "unix",
+            TimeFormat::UnixMs => "unix-ms",
+            TimeFormat::Utc => "utc",
+            TimeFormat::Custom(pattern) => pattern,
+        };
+        f.write_str(s)
+    }
+}
+
+impl FromStr for TimeFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "rfc3339" => Ok(TimeFormat::Rfc3339),
+            "rfc3339-millis" => Ok(TimeFormat::Rfc3339Millis),
+            "unix" => Ok(TimeFormat::Unix),
+            "unix-ms" => Ok(TimeFormat::UnixMs),
+            "utc" => Ok(TimeFormat::Utc),
+            _ if s.contains('%') => {
+                if StrftimeItems::new(s).any(|item| item == Item::Error) {
+                    Err(format!("Invalid strftime pattern in time format: '{}'", s))
+                } else {
+                    Ok(TimeFormat::Custom(s.to_string()))
+                }
+            }
+            _ => Err(format!(
+                "Invalid time format: '{}'. Supported values: rfc3339, rfc3339-millis, unix, unix-ms, utc, or a strftime pattern",
+                s
+            )),
+        }
+    }
+}
+
+/// How local times that occur twice or not at all around DST transitions are resolved.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DstPolicy {
+    /// Repeated times use the earlier instant; skipped times are rejected.
+    #[default]
+    Earlier,
+    /// Repeated times use the later instant; skipped times are rejected.
+    Later,
+    /// Both repeated and skipped times are rejected.
+    Reject,
+    /// Skipped times move forward by the length of the gap; repeated times use the earlier
+    /// instant.
+    ShiftForward,
+}
+
+impl DstPolicy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DstPolicy::Earlier => "earlier",
+            DstPolicy::Later => "later",
+            DstPolicy::Reject => "reject",
+            DstPolicy::ShiftForward => "shift-forward",
+        }
+    }
+}
+
+impl fmt::Display for DstPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for DstPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "earlier" => Ok(DstPolicy::Earlier),
+            "later" => Ok(DstPolicy::Later),
+            "reject" => Ok(DstPolicy::Reject),
+            "shift-forward" => Ok(DstPolicy::ShiftForward),
+            _ => Err(format!(
+                "Invalid DST policy: '{}'. Supported values: earlier, later, reject, shift-forward",
+                s
+            )),
+        }
+    }
+}
+
+/// How Parquet output stores date/time columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParquetTimestamps {
+    /// UTC-normalised Arrow timestamps, each followed by an offset column (`utc_offset` for
+    /// `dateTime`, `<column>_utc_offset` for the others).
+    Native,
+    /// RFC 3339 strings with their offsets, as in text output.
+    String,
+}
+
+impl fmt::Display for ParquetTimestamps {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            ParquetTimestamps::Native => "native",
+            ParquetTimestamps::String => "string",
+        };
+        f.write_str(s)
+    }
+}
+
+impl FromStr for ParquetTimestamps {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "native" => Ok(ParquetTimestamps::Native),
+            "string" => Ok(ParquetTimestamps::String),
+            _ => Err(format!(
+                "Invalid Parquet timestamps: '{}'. Supported values: native, string",
+                s
+            )),
+        }
+    }
+}
+
+/// Parquet compression codec with an optional level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParquetCompression {
+    None,
+    Snappy,
+    Lz4,
+    /// Level 0 to 9.
+    Gzip(Option<u32>),
+    /// Level 1 to 22.
+    Zstd(Option<i32>),
+}
+
+impl fmt::Display for ParquetCompression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParquetCompression::None => f.write_str("none"),
+            ParquetCompression::Snappy => f.write_str("snappy"),
+            ParquetCompression::Lz4 => f.write_str("lz4"),
+            ParquetCompression::Gzip(None) => f.write_str("gzip"),
+            ParquetCompression::Gzip(Some(level)) => write!(f, "gzip:{}", level),
+            ParquetCompression::Zstd(None) => f.write_str("zstd"),
+            ParquetCompression::Zstd(Some(level)) => write!(f, "zstd:{}", level),
+        }
+    }
+}
+
+impl FromStr for ParquetCompression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.to_ascii_lowercase();
+        let (codec, level) = match lower.split_once(':') {
+            Some((codec, level)) => (codec, Some(level)),
+            None => (lower.as_str(), None),
+        };
+        let invalid_level = || {
+            format!(
+                "Invalid compression level in '{}': gzip accepts 0 to 9, zstd 1 to 22",
+                s
+            )
+        };
+        match (codec, level) {
+            ("none", None) => Ok(ParquetCompression::None),
+            ("snappy", None) => Ok(ParquetCompression::Snappy),
+            ("lz4", None) => Ok(ParquetCompression::Lz4),
+            ("gzip", None) => Ok(ParquetCompression::Gzip(None)),
+            ("zstd", None) => Ok(ParquetCompression::Zstd(None)),
+            ("gzip", Some(level)) => match level.parse::<u32>() {
+                Ok(level) if level <= 9 => Ok(ParquetCompression::Gzip(Some(level))),
+                _ => Err(invalid_level()),
+            },
+            ("zstd", Some(level)) => match level.parse::<i32>() {
+                Ok(level) if (1..=22).contains(&level) => Ok(ParquetCompression::Zstd(Some(level))),
+                _ => Err(invalid_level()),
+            },
+            ("none" | "snappy" | "lz4", Some(_)) => Err(format!(
+                "Compression '{}' does not take a level (only gzip and zstd do)",
+                codec
+            )),
+            _ => Err(format!(
+                "Invalid compression: '{}'. Supported codecs: none, snappy, lz4, gzip[:level], zstd[:level]",
+                s
+            )),
+        }
+    }
+}
+
+/// Encoding and writer settings for Parquet output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParquetOptions {
+    pub timestamps: ParquetTimestamps,
+    pub compression: ParquetCompression,
+    /// Maximum rows per row group; `None` keeps the writer's default.
+    pub row_group_size: Option<usize>,
+    pub dictionary: bool,
+    pub dataset: ParquetDataset,
+}
+
+/// Layout of a `--output-dir` Parquet dataset.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParquetDataset {
+    /// Writes a partitioned dataset below this directory instead of one stream to stdout.
+    pub dir: Option<PathBuf>,
+    /// Width in degrees of the optional `lat_band=` partition level.
+    pub lat_band: Option<f64>,
+    /// Rolls to a new part file after this many rows.
+    pub max_file_rows: Option<usize>,
+    /// Rolls to a new part file once this many bytes have been written.
+    pub max_file_bytes: Option<u64>,
+}
+
+impl Default for ParquetOptions {
+    fn default() -> Self {
+        Self {
+            timestamps: ParquetTimestamps::Native,
+            compression: ParquetCompression::Snappy,
+            row_group_size: None,
+            dictionary: true,
+            dataset: ParquetDataset::default(),
+        }
+    }
+}
+
+/// Destination table and write mode for SQLite output.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SqliteOptions {
+    /// Database file, created if it does not exist.
+    pub path: Option<PathBuf>,
+    /// Table name; defaults to the command name.
+    pub table: Option<String>,
+    /// Replaces rows with the same `latitude`, `longitude` and `dateTime` instant instead of
+    /// adding duplicates.
+    pub upsert: bool,
+}
+
 #[derive(Debug, Clone)]
 pub struct OutputOptions {
     pub format: OutputFormat,
     pub headers: bool,
     pub show_inputs: Option<bool>,
     pub elevation_angle: bool,
+    /// Adds `id` and `name` columns for locations read from a feature file.
+    pub site_columns: bool,
+    /// Output columns in order; `None` emits the command's default columns.
+    pub columns: Option<Vec<String>>,
+    /// Prints the available columns instead of computing results.
+    pub list_columns: bool,
+    pub time_format: TimeFormat,
+    pub parquet: ParquetOptions,
+    pub sqlite: SqliteOptions,
 }
 
 impl OutputOptions {
@@ -83,6 +409,12 @@ impl Default for OutputOptions {
             headers: true,
             show_inputs: None,
             elevation_angle: false,
+            site_columns: false,
+            columns: None,
+            list_columns: false,
+            time_format: TimeFormat::default(),
+            parquet: ParquetOptions::default(),
+            sqlite: SqliteOptions::default(),
         }
     }
 }
@@ -118,12 +450,146 @@ impl FromStr for CalculationAlgorithm {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tracker {
+    Fixed,
+    SingleAxis,
+    DualAxis,
+}
+
+impl fmt::Display for Tracker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Tracker::Fixed => "fixed",
+            Tracker::SingleAxis => "single-axis",
+            Tracker::DualAxis => "dual-axis",
+        };
+        f.write_str(s)
+    }
+}
+
+impl FromStr for Tracker {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "fixed" => Ok(Tracker::Fixed),
+            "single-axis" => Ok(Tracker::SingleAxis),
+            "dual-axis" => Ok(Tracker::DualAxis),
+            _ => Err(format!(
+                "Invalid tracker: '{}'. Supported trackers: fixed, single-axis, dual-axis",
+                s
+            )),
+        }
+    }
+}
+
+/// Receiving surface for incidence-angle output.
+///
+/// For a fixed surface, `tilt` and `azimuth` orient the plane; for a single-axis tracker they
+/// orient the rotation axis. Dual-axis trackers ignore both.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Surface {
+    pub tracker: Tracker,
+    pub tilt: f64,
+    pub azimuth: f64,
+}
+
+impl Default for Surface {
+    fn default() -> Self {
+        Self {
+            tracker: Tracker::Fixed,
+            tilt: 0.0,
+            azimuth: 180.0,
+        }
+    }
+}
+
+/// A horizon from `--horizons`, reported as a `<label>_start`/`<label>_end` column pair.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomHorizon {
+    degrees: f64,
+    label: String,
+    columns: [String; 2],
+}
+
+impl CustomHorizon {
+    pub fn new(label: impl Into<String>, degrees: f64) -> Self {
+        let label = label.into();
+        let columns = [format!("{label}_start"), format!("{label}_end")];
+        Self {
+            degrees,
+            label,
+            columns,
+        }
+    }
+
+    pub fn degrees(&self) -> f64 {
+        self.degrees
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Names of the rising and setting time columns.
+    pub fn columns(&self) -> [&str; 2] {
+        [&self.columns[0], &self.columns[1]]
+    }
+}
+
+/// Parses `--horizons`: comma-separated `[label=]degrees` entries.
+///
+/// Unlabelled entries are named after their angle, so `-0.833` becomes `horizon_m0_833`.
+pub fn parse_custom_horizons(s: &str) -> Result<Arc<[CustomHorizon]>, String> {
+    let mut horizons: Vec<CustomHorizon> = Vec::new();
+    for entry in s.split(',').map(str::trim) {
+        let (label, degrees) = match entry.split_once('=') {
+            Some((label, degrees)) => (Some(label.trim()), degrees.trim()),
+            None => (None, entry),
+        };
+        let degrees = degrees
+            .parse::<f64>()
+            .ok()
+            .filter(|degrees| degrees.is_finite())
+            .ok_or_else(|| {
+                format!(
+                    "Invalid horizons value: '{}' (expected comma-separated [label=]degrees)",
+                    entry
+                )
+            })?;
+        let label = match label {
+            Some(label) => {
+                if label.is_empty() || !label.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+                {
+                    return Err(format!(
+                        "Invalid horizon label: '{}' (expected letters, digits, and underscores)",
+                        label
+                    ));
+                }
+                label.to_string()
+            }
+            None => format!(
+                "horizon_{}",
+                degrees.to_string().replace('-', "m").replace('.', "_")
+            ),
+        };
+        if horizons.iter().any(|horizon| horizon.label == label) {
+            return Err(format!("Duplicate horizon label: '{}'", label));
+        }
+        horizons.push(CustomHorizon::new(label, degrees));
+    }
+    Ok(horizons.into())
+}
+
 #[derive(Debug, Clone)]
 pub struct Environment {
     pub refraction: bool,
     pub elevation: f64,
     pub temperature: f64,
     pub pressure: f64,
+    /// Linke turbidity factor for clear-sky irradiance.
+    pub linke_turbidity: f64,
 }
 
 impl Default for Environment {
@@ -133,6 +599,7 @@ impl Default for Environment {
             elevation: 0.0,
             temperature: 15.0,
             pressure: 1013.0,
+            linke_turbidity: 3.0,
         }
     }
 }
@@ -142,6 +609,31 @@ pub struct CalculationOptions {
     pub algorithm: CalculationAlgorithm,
     pub horizon: Option<f64>,
     pub twilight: bool,
+    /// Adds derived daily quantities to sunrise results.
+    pub day_stats: bool,
+    /// Adds golden-hour and blue-hour times to sunrise results.
+    pub photo_hours: bool,
+    /// Adds rising and setting times for each of these horizons to sunrise results.
+    pub horizons: Arc<[CustomHorizon]>,
+    /// Adds clear-sky irradiance to position results.
+    pub irradiance: bool,
+    /// Local skyline for obstructed sunrise/sunset and the position `obstructed` column.
+    pub horizon_profile: Option<Arc<HorizonProfile>>,
+    /// Skyline file, loaded into `horizon_profile` when a calculation stream starts.
+    pub horizon_profile_file: Option<InputPath>,
+    /// Adds incidence-angle output for this surface to position results.
+    pub surface: Option<Surface>,
+    /// Adds the shadow of a vertical object this many metres tall to position results.
+    pub shadow_height: Option<f64>,
+    /// Adds declination, hour angle, equation of time, and related columns to position results.
+    pub geometry: bool,
+}
+
+impl CalculationOptions {
+    /// Returns whether a skyline is set, loaded or not.
+    pub fn has_horizon_profile(&self) -> bool {
+        self.horizon_profile.is_some() || self.horizon_profile_file.is_some()
+    }
 }
 
 impl Default for CalculationOptions {
@@ -150,6 +642,15 @@ impl Default for CalculationOptions {
             algorithm: CalculationAlgorithm::Spa,
             horizon: None,
             twilight: false,
+            day_stats: false,
+            photo_hours: false,
+            horizons: Arc::default(),
+            irradiance: false,
+            horizon_profile: None,
+            horizon_profile_file: None,
+            surface: None,
+            shadow_height: None,
+            geometry: false,
         }
     }
 }
@@ -162,8 +663,21 @@ pub struct Parameters {
     pub calculation: CalculationOptions,
     pub wait: bool,
     pub perf: bool,
+    /// Worker threads for stream calculations; `1` computes on the calling thread.
+    pub threads: usize,
     pub step: Option<Step>,
+    /// Spacing for `--grid-km` location grids over bounds or polygons.
+    pub grid: Option<GridSpacing>,
+    /// Zone for interpreting naive input times; other inputs are converted into it.
     pub timezone: Option<TimezoneOverride>,
+    /// Zone that result times are converted to for output; `None` keeps them as computed.
+    pub output_timezone: Option<TimezoneOverride>,
+    /// Looks up each location's zone from its coordinates (`--timezone=auto`) and uses it like
+    /// a timezone column of a coordinate file; needs the `tz-lookup` feature.
+    pub timezone_lookup: bool,
+    /// Resolution of repeated and skipped local times; `None` uses the default policy and
+    /// leaves it out of `--show-inputs` output.
+    pub dst_policy: Option<DstPolicy>,
 }
 
 impl Default for Parameters {
@@ -175,8 +689,13 @@ impl Default for Parameters {
             calculation: CalculationOptions::default(),
             wait: false,
             perf: false,
+            threads: 1,
             step: None,
+            grid: None,
             timezone: None,
+            output_timezone: None,
+            timezone_lookup: false,
+            dst_policy: None,
         }
     }
 }
@@ -185,6 +704,29 @@ impl Default for Parameters {
 pub enum Command {
     Position,
     Sunrise,
+    Events,
+    MoonPosition,
+    Moonrise,
+}
+
+impl Command {
+    /// Returns whether partial-date inputs expand to one record per day by default.
+    pub(crate) fn is_daily(self) -> bool {
+        matches!(self, Command::Sunrise | Command::Events | Command::Moonrise)
+    }
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Command::Position => "position",
+            Command::Sunrise => "sunrise",
+            Command::Events => "events",
+            Command::MoonPosition => "moon-position",
+            Command::Moonrise => "moonrise",
+        };
+        f.write_str(s)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -194,9 +736,12 @@ pub enum Predicate {
     IsNauticalTwilight,
     IsAstronomicalTwilight,
     IsAstronomicalNight,
+    IsGoldenHour,
+    IsBlueHour,
     AfterSunset,
     SunAbove(f64),
     SunBelow(f64),
+    MoonBelow(f64),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -252,6 +797,11 @@ mod tests {
             "parquet".parse::<OutputFormat>().unwrap(),
             OutputFormat::Parquet
         );
+        #[cfg(feature = "parquet")]
+        assert_eq!(
+            "Arrow".parse::<OutputFormat>().unwrap(),
+            OutputFormat::Arrow
+        );
         assert!("wat".parse::<OutputFormat>().is_err());
 
         let defaults = OutputOptions::default();
@@ -273,6 +823,11 @@ mod tests {
             CalculationAlgorithm::Grena3
         );
         assert!("bad".parse::<CalculationAlgorithm>().is_err());
+        assert_eq!(
+            "Single-Axis".parse::<Tracker>().unwrap(),
+            Tracker::SingleAxis
+        );
+        assert!("polar".parse::<Tracker>().is_err());
 
         let step = "1h".parse::<Step>().unwrap();
         assert_eq!(Duration::from(step), Duration::hours(1));
@@ -287,6 +842,28 @@ mod tests {
         assert!("Not/AZone".parse::<TimezoneOverride>().is_err());
     }
 
+    #[test]
+    fn custom_horizons_parse_labels_and_defaults() {
+        let horizons = parse_custom_horizons("-0.833, hunting=-6,10").unwrap();
+        let columns: Vec<_> = horizons
+            .iter()
+            .map(|horizon| (horizon.degrees(), horizon.columns()))
+            .collect();
+        assert_eq!(
+            columns,
+            [
+                (-0.833, ["horizon_m0_833_start", "horizon_m0_833_end"]),
+                (-6.0, ["hunting_start", "hunting_end"]),
+                (10.0, ["horizon_10_start", "horizon_10_end"]),
+            ]
+        );
+        assert!(parse_custom_horizons("-3,,-6").is_err());
+        assert!(parse_custom_horizons("bad label=-6").is_err());
+        assert!(parse_custom_horizons("=-6").is_err());
+        assert!(parse_custom_horizons("a=-6,a=-9").is_err());
+        assert!(parse_custom_horizons("-6,-6").is_err());
+    }
+
     #[test]
     fn parameter_defaults_are_sensible() {
         let defaults = Parameters::default();
@@ -295,14 +872,18 @@ mod tests {
         assert!(!defaults.perf);
         assert!(defaults.step.is_none());
         assert!(defaults.timezone.is_none());
+        assert!(!defaults.timezone_lookup);
         assert_eq!(defaults.output.format, OutputFormat::Text);
         assert!(defaults.output.headers);
         assert_eq!(defaults.calculation.algorithm, CalculationAlgorithm::Spa);
         assert!(defaults.calculation.horizon.is_none());
         assert!(!defaults.calculation.twilight);
+        assert!(defaults.calculation.horizon_profile.is_none());
         assert!(defaults.environment.refraction);
         assert_eq!(defaults.environment.elevation, 0.0);
         assert_eq!(defaults.environment.temperature, 15.0);
         assert_eq!(defaults.environment.pressure, 1013.0);
+        assert_eq!(defaults.environment.linke_turbidity, 3.0);
+        assert!(!defaults.calculation.irradiance);
     }
 }
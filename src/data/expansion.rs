@@ -1,18 +1,26 @@
 //! Input expansion for ranges, files, and cartesian products.
 
+use super::features::{FeaturePoint, parse_features};
+use super::grid::bounds_points;
 use super::time_utils::{
-    TimezoneInfo, convert_datetime_to_timezone, get_timezone_info, parse_datetime_string,
+    TimezoneInfo, convert_datetime_to_timezone, get_timezone_info, lookup_timezone,
+    parse_datetime_with_policy,
 };
 use super::types::{
-    CoordTimeResult, CoordTimeStream, InputPath, LocationSource, LocationStream, TimeSource,
+    CoordTimeResult, CoordTimeStream, DataSource, GridArea, GridSpacing, InputPath, LocationSource,
+    LocationStream, Site, TimeSource,
 };
-use super::{Command, Step, TimezoneOverride, validate_latitude, validate_longitude};
+use super::{
+    Command, DstPolicy, HorizonProfile, Parameters, Step, TimezoneOverride, validate_latitude,
+    validate_longitude,
+};
+use crate::error::Error;
 use chrono::{DateTime, Duration, FixedOffset, NaiveDate, NaiveDateTime, Utc};
 use std::fs::File;
-use std::io::{self, BufRead, BufReader};
+use std::io::{self, BufRead, BufReader, Read};
 use std::sync::Arc;
 
-type TimeIter = Box<dyn Iterator<Item = Result<DateTime<FixedOffset>, String>>>;
+type TimeIter = Box<dyn Iterator<Item = Result<DateTime<FixedOffset>, Error>>>;
 
 struct CoordRangeIter {
     next: Option<f64>,
@@ -121,6 +129,57 @@ fn parse_lat_lon(
     Ok((lat, lon))
 }
 
+/// Parses an optional trailing IANA timezone column, e.g. `Europe/Berlin`.
+///
+/// Fields starting with a letter are zone names and must be known; others, such as the time
+/// of a paired file row, are not a timezone column.
+fn parse_site_timezone(
+    field: &str,
+    ctx: &str,
+    line: usize,
+) -> Result<Option<TimezoneOverride>, String> {
+    let name = field.trim();
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return Ok(None);
+    }
+    name.parse::<chrono_tz::Tz>()
+        .map_err(|_| format!("{}:{}: unknown timezone '{}'", ctx, line, name))?;
+    name.parse().map(Some)
+}
+
+fn site_with_timezone(timezone: Option<TimezoneOverride>) -> Option<Arc<Site>> {
+    timezone.map(|timezone| {
+        Arc::new(Site {
+            timezone: Some(timezone),
+            ..Site::default()
+        })
+    })
+}
+
+/// Returns whether a coordinate file has a timezone column, judging by its first data row.
+fn has_site_timezones(input_path: &InputPath) -> Result<bool, Error> {
+    let Some(line) = read_non_comment_lines(input_path)?.next() else {
+        return Ok(false);
+    };
+    let line = line?;
+    let parts = parse_delimited_line(&line.content);
+    Ok(parts.len() == 3 && parse_site_timezone(parts[2], &line.ctx, line.number)?.is_some())
+}
+
+/// Adds the zone found for its coordinates to a location without a timezone column.
+fn with_looked_up_timezone(
+    (lat, lon, site): (f64, f64, Option<Arc<Site>>),
+) -> Result<(f64, f64, Option<Arc<Site>>), Error> {
+    if site.as_ref().is_some_and(|site| site.timezone.is_some()) {
+        return Ok((lat, lon, site));
+    }
+    let site = Site {
+        timezone: Some(lookup_timezone(lat, lon)?),
+        ..site.as_deref().cloned().unwrap_or_default()
+    };
+    Ok((lat, lon, Some(Arc::new(site))))
+}
+
 fn open_input(input_path: &InputPath) -> io::Result<Box<dyn BufRead>> {
     match input_path {
         InputPath::Stdin => Ok(Box::new(BufReader::new(io::stdin()))),
@@ -146,10 +205,11 @@ fn input_context(input_path: &InputPath) -> String {
 
 fn read_non_comment_lines(
     input_path: &InputPath,
-) -> Result<Box<dyn Iterator<Item = Result<Line, String>>>, String> {
+) -> Result<Box<dyn Iterator<Item = Result<Line, Error>>>, Error> {
     let ctx = input_context(input_path);
 
-    let reader = open_input(input_path).map_err(|e| format!("Error opening {}: {}", ctx, e))?;
+    let reader =
+        open_input(input_path).map_err(|e| Error::io(format!("Error opening {}: {}", ctx, e)))?;
     let mut lines = reader.lines().enumerate();
 
     Ok(Box::new(std::iter::from_fn(move || {
@@ -158,10 +218,10 @@ fn read_non_comment_lines(
             let line = match line_result {
                 Ok(value) => value,
                 Err(err) => {
-                    return Some(Err(format!(
+                    return Some(Err(Error::io(format!(
                         "{}:{}: failed to read line: {}",
                         ctx, number, err
-                    )));
+                    ))));
                 }
             };
             let trimmed = line.trim();
@@ -178,6 +238,57 @@ fn read_non_comment_lines(
     })))
 }
 
+/// Reads a horizon profile file of `azimuth elevation` lines (degrees, comma or whitespace separated).
+pub fn load_horizon_profile(input_path: &InputPath) -> Result<HorizonProfile, Error> {
+    let mut points = Vec::new();
+    for line in read_non_comment_lines(input_path)? {
+        let line = line?;
+        let (ctx, number) = (&line.ctx, line.number);
+        let fields = parse_delimited_line(&line.content);
+        let [azimuth, elevation] = fields.as_slice() else {
+            return Err(format!("{}:{}: expected 'azimuth elevation'", ctx, number).into());
+        };
+        let azimuth: f64 = azimuth
+            .trim()
+            .parse()
+            .map_err(|_| format!("{}:{}: invalid azimuth '{}'", ctx, number, azimuth))?;
+        let elevation: f64 = elevation
+            .trim()
+            .parse()
+            .map_err(|_| format!("{}:{}: invalid elevation '{}'", ctx, number, elevation))?;
+        if !(0.0..=360.0).contains(&azimuth) {
+            return Err(format!(
+                "{}:{}: azimuth must be between 0 and 360 degrees, got {}",
+                ctx, number, azimuth
+            )
+            .into());
+        }
+        if !(-90.0..=90.0).contains(&elevation) {
+            return Err(format!(
+                "{}:{}: elevation must be between -90 and 90 degrees, got {}",
+                ctx, number, elevation
+            )
+            .into());
+        }
+        points.push((azimuth, elevation));
+    }
+    HorizonProfile::new(points)
+        .map_err(|err| Error::from(format!("{}: {}", input_context(input_path), err)))
+}
+
+/// Reads all points of a GeoJSON or KML feature file, sampling polygons on `grid`.
+fn load_features(
+    input_path: &InputPath,
+    grid: Option<GridSpacing>,
+) -> Result<Vec<FeaturePoint>, Error> {
+    let ctx = input_context(input_path);
+    let mut content = String::new();
+    open_input(input_path)
+        .and_then(|mut reader| reader.read_to_string(&mut content))
+        .map_err(|e| Error::io(format!("Error reading {}: {}", ctx, e)))?;
+    parse_features(&content, &ctx, grid)
+}
+
 fn coord_range_iter(start: f64, end: f64, step: f64) -> CoordRangeIter {
     CoordRangeIter::new(start, end, step)
 }
@@ -192,18 +303,71 @@ fn range_point_count(start: f64, end: f64, step: f64) -> usize {
     }
 }
 
-fn shared_values(values: Arc<[f64]>) -> impl Iterator<Item = f64> {
+fn shared_values<T: Clone>(values: Arc<[T]>) -> impl Iterator<Item = T> {
     let mut index = 0usize;
     std::iter::from_fn(move || {
-        let value = values.get(index).copied();
+        let value = values.get(index).cloned();
         index += 1;
         value
     })
 }
 
-pub fn expand_location_source(source: LocationSource) -> Result<LocationStream, String> {
+/// Reads a coordinate file of `lat lon [timezone]` rows.
+///
+/// The first row decides whether the file has a timezone column, and other rows must match it
+/// unless `mixed_zones` is set because rows without a zone get one looked up.
+fn coordinate_file_locations(
+    input_path: &InputPath,
+    mixed_zones: bool,
+) -> Result<LocationStream, Error> {
+    // Whether the first row has a timezone column, which decides the record order.
+    let mut zoned = None;
+    let iter = read_non_comment_lines(input_path)?.map(move |line_res| {
+        let line = line_res?;
+        let parts = parse_delimited_line(&line.content);
+        if parts.len() < 2 {
+            return Err(format!(
+                "{}:{}: expected 2 fields (lat lon), found {}",
+                line.ctx,
+                line.number,
+                parts.len()
+            )
+            .into());
+        }
+        let timezone = match parts.get(2) {
+            Some(field) if parts.len() == 3 => {
+                parse_site_timezone(field, &line.ctx, line.number)?
+            }
+            _ => None,
+        };
+        if parts.len() > 2 && timezone.is_none() {
+            return Err(format!(
+                "{}:{}: expected 2 fields (lat lon), found {}. File appears to be a paired data file (lat lon datetime), which cannot be used with a separate time source.",
+                line.ctx,
+                line.number,
+                parts.len()
+            )
+            .into());
+        }
+
+        if !mixed_zones && *zoned.get_or_insert(timezone.is_some()) != timezone.is_some() {
+            return Err(format!(
+                "{}:{}: timezone column on some rows only; give every row a zone or none, or use --timezone=auto",
+                line.ctx, line.number
+            )
+            .into());
+        }
+
+        let (lat, lon) = parse_lat_lon(parts[0], parts[1], &line.ctx, line.number)?;
+        Ok((lat, lon, site_with_timezone(timezone)))
+    });
+
+    Ok(Box::new(iter))
+}
+
+pub fn expand_location_source(source: LocationSource) -> Result<LocationStream, Error> {
     match source {
-        LocationSource::Single(lat, lon) => Ok(Box::new(std::iter::once(Ok((lat, lon))))),
+        LocationSource::Single(lat, lon) => Ok(Box::new(std::iter::once(Ok((lat, lon, None))))),
         LocationSource::Range { lat, lon } => {
             let (lon_start, lon_end, lon_step) = lon;
             let lat_count = range_point_count(lat.0, lat.1, lat.2);
@@ -215,8 +379,7 @@ pub fn expand_location_source(source: LocationSource) -> Result<LocationStream,
                 Ok(Box::new(
                     coord_range_iter(lon_start, lon_end, lon_step).flat_map(move |lon| {
                         let lat_coords = Arc::clone(&lat_coords);
-                        shared_values(lat_coords)
-                            .map(move |lat| Ok::<(f64, f64), String>((lat, lon)))
+                        shared_values(lat_coords).map(move |lat| Ok((lat, lon, None)))
                     }),
                 ))
             } else {
@@ -225,61 +388,105 @@ pub fn expand_location_source(source: LocationSource) -> Result<LocationStream,
                 Ok(Box::new(coord_range_iter(lat.0, lat.1, lat.2).flat_map(
                     move |lat| {
                         let lon_coords = Arc::clone(&lon_coords);
-                        shared_values(lon_coords)
-                            .map(move |lon| Ok::<(f64, f64), String>((lat, lon)))
+                        shared_values(lon_coords).map(move |lon| Ok((lat, lon, None)))
                     },
                 )))
             }
         }
-        LocationSource::File(input_path) => {
-            let iter = read_non_comment_lines(&input_path)?.map(|line_res| {
-                let line = line_res?;
-                let parts = parse_delimited_line(&line.content);
-                if parts.len() < 2 {
-                    return Err(format!(
-                        "{}:{}: expected 2 fields (lat lon), found {}",
-                        line.ctx,
-                        line.number,
-                        parts.len()
-                    ));
-                }
-                if parts.len() > 2 {
-                    return Err(format!(
-                        "{}:{}: expected 2 fields (lat lon), found {}. File appears to be a paired data file (lat lon datetime), which cannot be used with a separate time source.",
-                        line.ctx,
-                        line.number,
-                        parts.len()
-                    ));
-                }
+        LocationSource::File(input_path) => coordinate_file_locations(&input_path, false),
+        LocationSource::Features(input_path) => Ok(Box::new(
+            load_features(&input_path, None)?.into_iter().map(Ok),
+        )),
+        LocationSource::Grid {
+            area: GridArea::Bounds { lat, lon },
+            spacing,
+        } => Ok(Box::new(
+            bounds_points(lat, lon, spacing).map(|(lat, lon)| Ok((lat, lon, None))),
+        )),
+        LocationSource::Grid {
+            area: GridArea::Polygons(input_path),
+            spacing,
+        } => Ok(Box::new(
+            load_features(&input_path, Some(spacing))?
+                .into_iter()
+                .map(Ok),
+        )),
+    }
+}
 
-                let (lat, lon) = parse_lat_lon(parts[0], parts[1], &line.ctx, line.number)?;
-                Ok((lat, lon))
-            });
+/// Expands `source`, looking up the zone of each location without one if `zone_lookup` is set.
+fn expand_locations(source: LocationSource, zone_lookup: bool) -> Result<LocationStream, Error> {
+    if !zone_lookup {
+        return expand_location_source(source);
+    }
+    let locations = match source {
+        LocationSource::File(input_path) => coordinate_file_locations(&input_path, true)?,
+        source => expand_location_source(source)?,
+    };
+    Ok(Box::new(locations.map(|location| {
+        location.and_then(with_looked_up_timezone)
+    })))
+}
 
-            Ok(Box::new(iter))
+/// Returns a factory for repeated passes over `source`, reading feature files only once.
+fn replayable_locations(
+    source: LocationSource,
+    zone_lookup: bool,
+) -> Result<Box<dyn Fn() -> Result<LocationStream, Error>>, Error> {
+    let features = match &source {
+        LocationSource::Features(input_path) => Some((input_path, None)),
+        LocationSource::Grid {
+            area: GridArea::Polygons(input_path),
+            spacing,
+        } => Some((input_path, Some(*spacing))),
+        _ => None,
+    };
+    if let Some((input_path, grid)) = features {
+        let mut points = load_features(input_path, grid)?;
+        if zone_lookup {
+            points = points
+                .into_iter()
+                .map(with_looked_up_timezone)
+                .collect::<Result<_, _>>()?;
         }
+        let points: Arc<[FeaturePoint]> = Arc::from(points);
+        return Ok(Box::new(move || {
+            Ok(Box::new(shared_values(Arc::clone(&points)).map(Ok)) as LocationStream)
+        }));
     }
+    Ok(Box::new(move || {
+        expand_locations(source.clone(), zone_lookup)
+    }))
 }
 
 pub fn expand_time_source(
     source: TimeSource,
     step_override: Option<Step>,
     override_tz: Option<TimezoneOverride>,
+    dst_policy: DstPolicy,
     command: Command,
-) -> Result<TimeIter, String> {
+) -> Result<TimeIter, Error> {
     match source {
         TimeSource::Single(dt) => Ok(Box::new(std::iter::once(Ok(dt)))),
+        TimeSource::Local(text) => {
+            let dt = parse_datetime_with_policy(
+                &text,
+                override_tz.as_ref().map(|tz| tz.as_str()),
+                dst_policy,
+            )?;
+            Ok(Box::new(std::iter::once(Ok(dt))))
+        }
         TimeSource::Range(partial_date) => {
             let step = step_override.unwrap_or_else(|| {
-                if command == Command::Sunrise || partial_date.len() == 4 {
+                if command.is_daily() || partial_date.len() == 4 {
                     Step(chrono::Duration::days(1))
                 } else {
                     Step(chrono::Duration::hours(1))
                 }
             });
-            expand_partial_date(partial_date, step, override_tz)
+            expand_partial_date(partial_date, step, override_tz, dst_policy)
         }
-        TimeSource::File(path) => read_times_file(path, override_tz),
+        TimeSource::File(path) => read_times_file(path, override_tz, dst_policy),
         TimeSource::Now => {
             let tz_info = get_timezone_info(override_tz.as_ref().map(|tz| tz.as_str()));
             if let Some(step_str) = step_override {
@@ -400,28 +607,27 @@ fn naive_bounds_from_partial(date_str: &str) -> Result<NaiveBounds, String> {
 fn to_local_datetime(
     tz_info: &TimezoneInfo,
     naive: NaiveDateTime,
+    dst_policy: DstPolicy,
     label: &str,
     original: &str,
 ) -> Result<DateTime<FixedOffset>, String> {
-    tz_info.to_datetime_from_local(&naive).ok_or_else(|| {
-        format!(
-            "{} time does not exist in timezone (likely DST gap): {}",
-            label, original
-        )
-    })
+    tz_info
+        .to_datetime_from_local(&naive, dst_policy)
+        .map_err(|reason| format!("{} time {}: {}", label, reason, original))
 }
 
 fn expand_partial_date(
     date_str: String,
     step: Step,
     override_tz: Option<TimezoneOverride>,
-) -> Result<TimeIter, String> {
+    dst_policy: DstPolicy,
+) -> Result<TimeIter, Error> {
     let step_duration: chrono::Duration = step.into();
     let tz_info = get_timezone_info(override_tz.as_ref().map(|tz| tz.as_str()));
     let bounds = naive_bounds_from_partial(&date_str)?;
 
-    let start_dt = to_local_datetime(&tz_info, bounds.start, "Start", &date_str)?;
-    let end_dt = to_local_datetime(&tz_info, bounds.end, "End", &date_str)?;
+    let start_dt = to_local_datetime(&tz_info, bounds.start, dst_policy, "Start", &date_str)?;
+    let end_dt = to_local_datetime(&tz_info, bounds.end, dst_policy, "End", &date_str)?;
 
     let iter = TimeStepIter::new(start_dt, end_dt, step_duration, tz_info).map(Ok);
 
@@ -431,13 +637,15 @@ fn expand_partial_date(
 fn read_times_file(
     input_path: InputPath,
     override_tz: Option<TimezoneOverride>,
-) -> Result<TimeIter, String> {
+    dst_policy: DstPolicy,
+) -> Result<TimeIter, Error> {
     let tz_override = override_tz.clone();
 
     let iter = read_non_comment_lines(&input_path)?.map(move |line_res| {
         let line = line_res?;
-        parse_datetime_string(&line.content, tz_override.as_ref().map(|tz| tz.as_str()))
-            .map_err(|err| format!("{}:{}: {}", line.ctx, line.number, err))
+        let tz = tz_override.as_ref().map(|tz| tz.as_str());
+        parse_datetime_with_policy(&line.content, tz, dst_policy)
+            .map_err(|err| Error::from(format!("{}:{}: {}", line.ctx, line.number, err)))
     });
 
     Ok(Box::new(iter))
@@ -448,19 +656,22 @@ fn expand_time_outer(
     time_source: TimeSource,
     step: Option<Step>,
     override_tz: Option<TimezoneOverride>,
+    dst_policy: DstPolicy,
+    zone_lookup: bool,
     command: Command,
-) -> Result<CoordTimeStream, String> {
-    let iter = expand_time_source(time_source, step, override_tz.clone(), command)?.flat_map(
-        move |time_res| match time_res {
-            Ok(dt) => match expand_location_source(loc_source.clone()) {
-                Ok(locations) => Box::new(
-                    locations.map(move |coord_res| coord_res.map(|(lat, lon)| (lat, lon, dt))),
-                ) as Box<dyn Iterator<Item = CoordTimeResult>>,
-                Err(err) => Box::new(std::iter::once(Err(err))),
-            },
+) -> Result<CoordTimeStream, Error> {
+    let locations = replayable_locations(loc_source, zone_lookup)?;
+    let times = expand_time_source(time_source, step, override_tz, dst_policy, command)?;
+    let iter = times.flat_map(move |time_res| match time_res {
+        Ok(dt) => match locations() {
+            Ok(locations) => Box::new(
+                locations
+                    .map(move |coord_res| coord_res.map(|(lat, lon, site)| (lat, lon, dt, site))),
+            ) as Box<dyn Iterator<Item = CoordTimeResult>>,
             Err(err) => Box::new(std::iter::once(Err(err))),
         },
-    );
+        Err(err) => Box::new(std::iter::once(Err(err))),
+    });
     Ok(Box::new(iter))
 }
 
@@ -469,13 +680,21 @@ fn expand_location_outer(
     time_source: TimeSource,
     step: Option<Step>,
     override_tz: Option<TimezoneOverride>,
+    dst_policy: DstPolicy,
+    zone_lookup: bool,
     command: Command,
-) -> Result<CoordTimeStream, String> {
-    let iter = expand_location_source(loc_source)?.flat_map(move |coord_res| match coord_res {
-        Ok((lat, lon)) => {
-            match expand_time_source(time_source.clone(), step, override_tz.clone(), command) {
-                Ok(times) => Box::new(times.map(move |time_res| time_res.map(|dt| (lat, lon, dt))))
-                    as Box<dyn Iterator<Item = CoordTimeResult>>,
+) -> Result<CoordTimeStream, Error> {
+    let locations = expand_locations(loc_source, zone_lookup)?;
+    let iter = locations.flat_map(move |coord_res| match coord_res {
+        Ok((lat, lon, site)) => {
+            let tz = site
+                .as_ref()
+                .and_then(|site| site.timezone.clone())
+                .or_else(|| override_tz.clone());
+            match expand_time_source(time_source.clone(), step, tz, dst_policy, command) {
+                Ok(times) => Box::new(
+                    times.map(move |time_res| time_res.map(|dt| (lat, lon, dt, site.clone()))),
+                ) as Box<dyn Iterator<Item = CoordTimeResult>>,
                 Err(err) => Box::new(std::iter::once(Err(err))),
             }
         }
@@ -484,18 +703,73 @@ fn expand_location_outer(
     Ok(Box::new(iter))
 }
 
+/// Expands a data source into a lazy `(lat, lon, datetime)` stream using the step and
+/// timezone settings from `params`.
+pub fn expand_data_source(
+    source: &DataSource,
+    params: &Parameters,
+    command: Command,
+) -> Result<CoordTimeStream, Error> {
+    match source {
+        DataSource::Separate(loc_source, time_source) => expand_cartesian_product(
+            loc_source.clone(),
+            time_source.clone(),
+            params.step,
+            params.timezone.clone(),
+            params.dst_policy.unwrap_or_default(),
+            params.timezone_lookup,
+            command,
+        ),
+        DataSource::Paired(path) => expand_paired_file(
+            path.clone(),
+            params.timezone.clone(),
+            params.dst_policy.unwrap_or_default(),
+            params.timezone_lookup,
+        ),
+    }
+}
+
 pub fn expand_cartesian_product(
     loc_source: LocationSource,
     time_source: TimeSource,
     step: Option<Step>,
     override_tz: Option<TimezoneOverride>,
+    dst_policy: DstPolicy,
+    zone_lookup: bool,
     command: Command,
-) -> Result<CoordTimeStream, String> {
+) -> Result<CoordTimeStream, Error> {
     let loc_is_single = matches!(loc_source, LocationSource::Single(_, _));
     let loc_replayable = !matches!(loc_source, LocationSource::File(InputPath::Stdin));
 
+    // Times given without an offset are read in each location's zone if the coordinate file
+    // has one or zones are looked up. All times of a location are then expanded together, so
+    // records are ordered by location rather than by time.
+    let site_zones = match (&loc_source, &time_source) {
+        (_, TimeSource::Local(_) | TimeSource::Range(_) | TimeSource::File(InputPath::File(_)))
+            if zone_lookup =>
+        {
+            true
+        }
+        (
+            LocationSource::File(path),
+            TimeSource::Local(_) | TimeSource::Range(_) | TimeSource::File(InputPath::File(_)),
+        ) => !loc_replayable || has_site_timezones(path)?,
+        _ => false,
+    };
+    if site_zones {
+        return expand_location_outer(
+            loc_source,
+            time_source,
+            step,
+            override_tz,
+            dst_policy,
+            zone_lookup,
+            command,
+        );
+    }
+
     let time_is_single = match &time_source {
-        TimeSource::Single(_) => true,
+        TimeSource::Single(_) | TimeSource::Local(_) => true,
         TimeSource::Now => step.is_none(),
         _ => false,
     };
@@ -506,49 +780,84 @@ pub fn expand_cartesian_product(
     if !time_bounded && !loc_is_single {
         return Err(
             "Cannot use an unbounded time stream with multiple locations. Drop --step or choose a single location when combining 'now' with streaming."
-                .to_string(),
+                .into(),
         );
     }
 
     if time_is_single {
-        let mut time_iter =
-            expand_time_source(time_source.clone(), step, override_tz.clone(), command)?;
+        let mut time_iter = expand_time_source(
+            time_source.clone(),
+            step,
+            override_tz.clone(),
+            dst_policy,
+            command,
+        )?;
         let Some(first_time) = time_iter.next() else {
             return Ok(Box::new(std::iter::empty()));
         };
         let dt = first_time?;
-        let iter = expand_location_source(loc_source.clone())?
-            .map(move |coord_res| coord_res.map(|(lat, lon)| (lat, lon, dt)));
+        let iter = expand_locations(loc_source.clone(), zone_lookup)?
+            .map(move |coord_res| coord_res.map(|(lat, lon, site)| (lat, lon, dt, site)));
         return Ok(Box::new(iter));
     }
 
     if time_replayable {
         if loc_replayable {
-            return expand_time_outer(loc_source, time_source, step, override_tz, command);
+            return expand_time_outer(
+                loc_source,
+                time_source,
+                step,
+                override_tz,
+                dst_policy,
+                zone_lookup,
+                command,
+            );
         }
-        return expand_location_outer(loc_source, time_source, step, override_tz, command);
+        return expand_location_outer(
+            loc_source,
+            time_source,
+            step,
+            override_tz,
+            dst_policy,
+            zone_lookup,
+            command,
+        );
     }
 
     if loc_replayable {
-        return expand_time_outer(loc_source, time_source, step, override_tz, command);
+        return expand_time_outer(
+            loc_source,
+            time_source,
+            step,
+            override_tz,
+            dst_policy,
+            zone_lookup,
+            command,
+        );
     }
 
     if loc_is_single {
-        let (lat, lon) = expand_location_source(loc_source)?
+        let (lat, lon, site) = expand_locations(loc_source, zone_lookup)?
             .next()
-            .unwrap_or(Err("No location provided".to_string()))?;
-        let iter = expand_time_source(time_source, step, override_tz, command)?
-            .map(move |time_res| time_res.map(|dt| (lat, lon, dt)));
+            .unwrap_or(Err(Error::from("No location provided")))?;
+        let tz = site
+            .as_ref()
+            .and_then(|site| site.timezone.clone())
+            .or(override_tz);
+        let iter = expand_time_source(time_source, step, tz, dst_policy, command)?
+            .map(move |time_res| time_res.map(|dt| (lat, lon, dt, site.clone())));
         return Ok(Box::new(iter));
     }
 
-    Err("Cannot combine non-replayable streams for both locations and times. Use files instead of stdin or provide bounded values.".to_string())
+    Err("Cannot combine non-replayable streams for both locations and times. Use files instead of stdin or provide bounded values.".into())
 }
 
 pub fn expand_paired_file(
     input_path: InputPath,
     override_tz: Option<TimezoneOverride>,
-) -> Result<CoordTimeStream, String> {
+    dst_policy: DstPolicy,
+    zone_lookup: bool,
+) -> Result<CoordTimeStream, Error> {
     let tz_override = override_tz.clone();
 
     let iter = read_non_comment_lines(&input_path)?.map(move |line_res| {
@@ -560,15 +869,29 @@ pub fn expand_paired_file(
                 line.ctx,
                 line.number,
                 parts.len()
-            ));
+            )
+            .into());
         }
 
         let (lat, lon) = parse_lat_lon(parts[0], parts[1], &line.ctx, line.number)?;
 
-        let dt_str = parts[2..].join(" ");
-        let dt = parse_datetime_string(dt_str.trim(), tz_override.as_ref().map(|tz| tz.as_str()))
+        let mut dt_parts = &parts[2..];
+        let mut timezone = match dt_parts.split_last() {
+            Some((last, rest)) if !rest.is_empty() => {
+                parse_site_timezone(last, &line.ctx, line.number)?
+            }
+            _ => None,
+        };
+        if timezone.is_some() {
+            dt_parts = &dt_parts[..dt_parts.len() - 1];
+        } else if zone_lookup {
+            timezone = Some(lookup_timezone(lat, lon)?);
+        }
+        let dt_str = dt_parts.join(" ");
+        let tz = timezone.as_ref().or(tz_override.as_ref());
+        let dt = parse_datetime_with_policy(dt_str.trim(), tz.map(|tz| tz.as_str()), dst_policy)
             .map_err(|err| format!("{}:{}: {}", line.ctx, line.number, err))?;
-        Ok((lat, lon, dt))
+        Ok((lat, lon, dt, site_with_timezone(timezone)))
     });
 
     Ok(Box::new(iter))
@@ -577,6 +900,7 @@ pub fn expand_paired_file(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::data::parse_datetime_string;
 
     #[test]
     fn fixed_offset_timezone_accepts_dst_gap() {
@@ -601,7 +925,7 @@ mod tests {
         assert!(
             collected
                 .iter()
-                .all(|(_, lon)| (*lon - 13.4).abs() < f64::EPSILON)
+                .all(|(_, lon, _)| (*lon - 13.4).abs() < f64::EPSILON)
         );
     }
 
@@ -618,7 +942,7 @@ mod tests {
             .expect("collect coords");
 
         assert_eq!(collected.len(), 6);
-        assert_eq!(collected.first().copied(), Some((53.0, 13.0)));
-        assert_eq!(collected.last().copied(), Some((52.0, 11.0)));
+        assert_eq!(collected.first().cloned(), Some((53.0, 13.0, None)));
+        assert_eq!(collected.last().cloned(), Some((52.0, 11.0, None)));
     }
 }
@@ -0,0 +1,505 @@
+//! GeoJSON and KML point features as location input.
+
+use super::grid::{Polygon, polygon_points};
+use super::types::{GridSpacing, Site};
+use super::{validate_latitude, validate_longitude};
+use crate::error::Error;
+use serde_json::Value;
+use std::sync::Arc;
+
+/// One feature point: latitude, longitude, and the feature's labels.
+pub(crate) type FeaturePoint = (f64, f64, Option<Arc<Site>>);
+
+/// Reads every point of a GeoJSON or KML document, in document order.
+///
+/// With a `grid`, polygons are sampled at its spacing; without one, they are rejected.
+/// KML is recognised by its leading `<`; anything else is parsed as GeoJSON.
+pub(crate) fn parse_features(
+    content: &str,
+    ctx: &str,
+    grid: Option<GridSpacing>,
+) -> Result<Vec<FeaturePoint>, Error> {
+    let points = if content.trim_start().starts_with('<') {
+        parse_kml(content, grid)
+    } else {
+        parse_geojson(content, grid)
+    }
+    .map_err(|err| Error::from(format!("{}: {}", ctx, err)))?;
+    if points.is_empty() {
+        return Err(match grid {
+            Some(_) => format!("{}: no grid points inside the features", ctx),
+            None => format!("{}: no point features found", ctx),
+        }
+        .into());
+    }
+    Ok(points)
+}
+
+fn checked_lat_lon(lon: f64, lat: f64) -> Result<(f64, f64), String> {
+    Ok((validate_latitude(lat)?, validate_longitude(lon)?))
+}
+
+/// Appends the grid points of `polygon`, or rejects it without a grid.
+fn push_polygon(
+    polygon: &Polygon,
+    kind: &str,
+    site: &Arc<Site>,
+    grid: Option<GridSpacing>,
+    points: &mut Vec<FeaturePoint>,
+) -> Result<(), String> {
+    let Some(spacing) = grid else {
+        return Err(format!(
+            "unsupported geometry type '{}' without --grid-km (expected Point or MultiPoint)",
+            kind
+        ));
+    };
+    if polygon.first().is_none_or(|outer| outer.len() < 3) {
+        return Err(format!("{} ring with fewer than 3 positions", kind));
+    }
+    points.extend(
+        polygon_points(polygon, spacing)
+            .into_iter()
+            .map(|(lat, lon)| (lat, lon, Some(Arc::clone(site)))),
+    );
+    Ok(())
+}
+
+fn parse_geojson(content: &str, grid: Option<GridSpacing>) -> Result<Vec<FeaturePoint>, String> {
+    let root: Value =
+        serde_json::from_str(content).map_err(|err| format!("invalid GeoJSON: {}", err))?;
+    let mut points = Vec::new();
+    match root.get("type").and_then(Value::as_str) {
+        Some("FeatureCollection") => {
+            let features = root
+                .get("features")
+                .and_then(Value::as_array)
+                .ok_or("FeatureCollection without a 'features' array")?;
+            for (idx, feature) in features.iter().enumerate() {
+                push_geojson_feature(feature, grid, &mut points)
+                    .map_err(|err| format!("feature {}: {}", idx + 1, err))?;
+            }
+        }
+        Some("Feature") => push_geojson_feature(&root, grid, &mut points)?,
+        Some(_) => push_geojson_geometry(&root, &Arc::new(Site::default()), grid, &mut points)?,
+        None => return Err("GeoJSON object without a 'type'".to_string()),
+    }
+    Ok(points)
+}
+
+fn push_geojson_feature(
+    feature: &Value,
+    grid: Option<GridSpacing>,
+    points: &mut Vec<FeaturePoint>,
+) -> Result<(), String> {
+    let properties = feature.get("properties");
+    let property = |name: &str| properties.and_then(|props| props.get(name));
+    let site = Arc::new(Site {
+        id: feature.get("id").or(property("id")).and_then(label),
+        name: property("name").and_then(label),
+        timezone: None,
+    });
+    let geometry = feature
+        .get("geometry")
+        .filter(|geometry| !geometry.is_null())
+        .ok_or("feature without geometry")?;
+    push_geojson_geometry(geometry, &site, grid, points)
+}
+
+/// Returns a string or number property as text.
+fn label(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text.clone()),
+        Value::Number(number) => Some(number.to_string()),
+        _ => None,
+    }
+}
+
+fn push_geojson_geometry(
+    geometry: &Value,
+    site: &Arc<Site>,
+    grid: Option<GridSpacing>,
+    points: &mut Vec<FeaturePoint>,
+) -> Result<(), String> {
+    let coordinates = geometry.get("coordinates");
+    let kind = geometry.get("type").and_then(Value::as_str);
+    match kind {
+        Some("Point") => {
+            let (lat, lon) = geojson_position(coordinates)?;
+            points.push((lat, lon, Some(Arc::clone(site))));
+        }
+        Some("MultiPoint") => {
+            for position in geojson_array(coordinates, "MultiPoint")? {
+                let (lat, lon) = geojson_position(Some(position))?;
+                points.push((lat, lon, Some(Arc::clone(site))));
+            }
+        }
+        Some("Polygon") => {
+            let polygon = geojson_polygon(coordinates)?;
+            push_polygon(&polygon, "Polygon", site, grid, points)?;
+        }
+        Some("MultiPolygon") => {
+            for polygon in geojson_array(coordinates, "MultiPolygon")? {
+                let polygon = geojson_polygon(Some(polygon))?;
+                push_polygon(&polygon, "MultiPolygon", site, grid, points)?;
+            }
+        }
+        Some(other) => {
+            return Err(format!(
+                "unsupported geometry type '{}' (expected Point, MultiPoint, Polygon, or MultiPolygon)",
+                other
+            ));
+        }
+        None => return Err("geometry without a 'type'".to_string()),
+    }
+    Ok(())
+}
+
+fn geojson_array<'a>(value: Option<&'a Value>, kind: &str) -> Result<&'a Vec<Value>, String> {
+    value
+        .and_then(Value::as_array)
+        .ok_or_else(|| format!("{} without a coordinates array", kind))
+}
+
+/// Reads GeoJSON polygon rings, outer boundary first.
+fn geojson_polygon(rings: Option<&Value>) -> Result<Polygon, String> {
+    geojson_array(rings, "Polygon")?
+        .iter()
+        .map(|ring| {
+            geojson_array(Some(ring), "Polygon ring")?
+                .iter()
+                .map(|position| geojson_position(Some(position)))
+                .collect()
+        })
+        .collect()
+}
+
+/// Reads a GeoJSON `[longitude, latitude, ...]` position as `(latitude, longitude)`.
+fn geojson_position(position: Option<&Value>) -> Result<(f64, f64), String> {
+    let values = position
+        .and_then(Value::as_array)
+        .filter(|values| values.len() >= 2)
+        .ok_or("expected a [longitude, latitude] position")?;
+    match (values[0].as_f64(), values[1].as_f64()) {
+        (Some(lon), Some(lat)) => checked_lat_lon(lon, lat),
+        _ => Err("expected a [longitude, latitude] position".to_string()),
+    }
+}
+
+fn parse_kml(content: &str, grid: Option<GridSpacing>) -> Result<Vec<FeaturePoint>, String> {
+    let content = strip_xml_comments(content);
+    let mut points = Vec::new();
+    let mut rest = content.as_str();
+    let mut index = 0;
+    while let Some((attributes, body, after)) = next_element(rest, "Placemark") {
+        index += 1;
+        rest = after;
+        push_kml_placemark(attributes, body, grid, &mut points)
+            .map_err(|err| format!("placemark {}: {}", index, err))?;
+    }
+    Ok(points)
+}
+
+fn push_kml_placemark(
+    attributes: &str,
+    body: &str,
+    grid: Option<GridSpacing>,
+    points: &mut Vec<FeaturePoint>,
+) -> Result<(), String> {
+    let site = Arc::new(Site {
+        id: xml_attribute(attributes, "id"),
+        name: next_element(body, "name").map(|(_, name, _)| xml_text(name)),
+        timezone: None,
+    });
+
+    let mut found = false;
+    let mut rest = body;
+    while let Some((_, point, after)) = next_element(rest, "Point") {
+        rest = after;
+        let (_, coordinates, _) =
+            next_element(point, "coordinates").ok_or("Point without coordinates")?;
+        let (lat, lon) = kml_coordinates(coordinates)?;
+        points.push((lat, lon, Some(Arc::clone(&site))));
+        found = true;
+    }
+    let mut rest = body;
+    while let Some((_, polygon, after)) = next_element(rest, "Polygon") {
+        rest = after;
+        push_polygon(&kml_polygon(polygon)?, "Polygon", &site, grid, points)?;
+        found = true;
+    }
+    if found {
+        return Ok(());
+    }
+    match ["LineString", "LinearRing", "Model", "Track"]
+        .into_iter()
+        .find(|kind| next_element(body, kind).is_some())
+    {
+        Some(kind) => Err(format!(
+            "unsupported geometry type '{}' (expected Point or Polygon)",
+            kind
+        )),
+        None => Err("placemark without geometry".to_string()),
+    }
+}
+
+/// Reads KML polygon boundaries, outer boundary first.
+fn kml_polygon(body: &str) -> Result<Polygon, String> {
+    let mut rings = Vec::new();
+    let mut rest = body;
+    while let Some((_, coordinates, after)) = next_element(rest, "coordinates") {
+        rest = after;
+        rings.push(
+            coordinates
+                .split_whitespace()
+                .map(kml_coordinates)
+                .collect::<Result<Vec<_>, _>>()?,
+        );
+    }
+    Ok(rings)
+}
+
+/// Reads a KML `longitude,latitude[,altitude]` tuple as `(latitude, longitude)`.
+fn kml_coordinates(text: &str) -> Result<(f64, f64), String> {
+    let text = text.trim();
+    let mut parts = text.split(',').map(str::trim);
+    match (
+        parts.next().and_then(|lon| lon.parse::<f64>().ok()),
+        parts.next().and_then(|lat| lat.parse::<f64>().ok()),
+    ) {
+        (Some(lon), Some(lat)) => checked_lat_lon(lon, lat),
+        _ => Err(format!("invalid coordinates '{}'", text)),
+    }
+}
+
+fn strip_xml_comments(content: &str) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut rest = content;
+    while let Some(start) = rest.find("<!--") {
+        out.push_str(&rest[..start]);
+        rest = rest[start..]
+            .find("-->")
+            .map_or("", |end| &rest[start + end + 3..]);
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Finds the next `<tag ...>body</tag>` element, returning its attributes, body, and the rest.
+///
+/// Elements with a namespace prefix are matched too; self-closing elements have an empty body.
+fn next_element<'a>(content: &'a str, tag: &str) -> Option<(&'a str, &'a str, &'a str)> {
+    let mut offset = 0;
+    while let Some(pos) = content[offset..].find('<') {
+        let start = offset + pos + 1;
+        let name_end = content[start..]
+            .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+            .map_or(content.len(), |end| start + end);
+        let name = &content[start..name_end];
+        offset = name_end;
+        if local_name(name) != tag {
+            continue;
+        }
+        let open_end = start + content[start..].find('>')?;
+        let attributes = &content[name_end..open_end];
+        if let Some(attributes) = attributes.strip_suffix('/') {
+            return Some((attributes, "", &content[open_end + 1..]));
+        }
+        let close = format!("</{}>", name);
+        let body_start = open_end + 1;
+        let body_end = body_start + content[body_start..].find(&close)?;
+        return Some((
+            attributes,
+            &content[body_start..body_end],
+            &content[body_end + close.len()..],
+        ));
+    }
+    None
+}
+
+fn local_name(name: &str) -> &str {
+    name.rsplit_once(':').map_or(name, |(_, local)| local)
+}
+
+fn xml_attribute(attributes: &str, name: &str) -> Option<String> {
+    let mut rest = attributes;
+    while let Some(eq) = rest.find('=') {
+        let key = rest[..eq].trim();
+        let value = rest[eq + 1..].trim_start();
+        let quote = value.chars().next()?;
+        let end = value[1..].find(quote)? + 1;
+        if local_name(key) == name {
+            return Some(decode_entities(&value[1..end]));
+        }
+        rest = &value[end + 1..];
+    }
+    None
+}
+
+/// Returns element text with CDATA unwrapped and entities decoded.
+fn xml_text(text: &str) -> String {
+    let text = text.trim();
+    match text
+        .strip_prefix("<![CDATA[")
+        .and_then(|inner| inner.strip_suffix("]]>"))
+    {
+        Some(inner) => inner.to_string(),
+        None => decode_entities(text),
+    }
+}
+
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest.find(';').and_then(|semi| {
+            let ch = match &rest[1..semi] {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                entity => entity
+                    .strip_prefix("#x")
+                    .map(|hex| u32::from_str_radix(hex, 16))
+                    .or_else(|| entity.strip_prefix('#').map(str::parse))
+                    .and_then(Result::ok)
+                    .and_then(char::from_u32),
+            }?;
+            Some((ch, semi + 1))
+        });
+        match decoded {
+            Some((ch, len)) => {
+                out.push(ch);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(point: &FeaturePoint) -> (Option<&str>, Option<&str>) {
+        let site = point.2.as_deref().expect("site");
+        (site.id.as_deref(), site.name.as_deref())
+    }
+
+    #[test]
+    fn geojson_points_and_multipoints() {
+        let content = r#"{
+            "type": "FeatureCollection",
+            "features": [
+                {"type": "Feature", "id": 7, "properties": {"name": "Roof"},
+                 "geometry": {"type": "Point", "coordinates": [13.4, 52.5, 34.0]}},
+                {"type": "Feature", "properties": {"id": "B-2"},
+                 "geometry": {"type": "MultiPoint", "coordinates": [[11.4, 47.3], [11.5, 47.2]]}}
+            ]
+        }"#;
+        let points = parse_features(content, "sites.geojson", None).unwrap();
+        assert_eq!(points.len(), 3);
+        assert_eq!((points[0].0, points[0].1), (52.5, 13.4));
+        assert_eq!(labels(&points[0]), (Some("7"), Some("Roof")));
+        assert_eq!(labels(&points[2]), (Some("B-2"), None));
+        assert!(Arc::ptr_eq(
+            points[1].2.as_ref().unwrap(),
+            points[2].2.as_ref().unwrap()
+        ));
+    }
+
+    #[test]
+    fn geojson_errors_name_the_feature() {
+        let polygon = r#"{"type": "FeatureCollection", "features": [
+            {"type": "Feature", "properties": {}, "geometry": {"type": "Point", "coordinates": [0, 0]}},
+            {"type": "Feature", "properties": {}, "geometry": {"type": "Polygon", "coordinates": []}}
+        ]}"#;
+        let err = parse_features(polygon, "f.geojson", None)
+            .unwrap_err()
+            .to_string();
+        assert!(err.starts_with("f.geojson: feature 2: unsupported geometry type 'Polygon'"));
+
+        let swapped = r#"{"type": "Point", "coordinates": [13.4, 95.0]}"#;
+        assert!(parse_features(swapped, "f.geojson", None).is_err());
+    }
+
+    #[test]
+    fn kml_placemarks() {
+        let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <kml xmlns="http://www.opengis.net/kml/2.2"><Document>
+              <!-- <Placemark><Point><coordinates>0,0</coordinates></Point></Placemark> -->
+              <Placemark id="p1">
+                <name>Smith &amp; Sons</name>
+                <Point><coordinates> 13.4,52.5,0 </coordinates></Point>
+              </Placemark>
+              <Placemark>
+                <name><![CDATA[Hut <B>]]></name>
+                <MultiGeometry>
+                  <Point><coordinates>11.4,47.3</coordinates></Point>
+                  <Point><coordinates>11.5,47.2</coordinates></Point>
+                </MultiGeometry>
+              </Placemark>
+            </Document></kml>"#;
+        let points = parse_features(content, "sites.kml", None).unwrap();
+        assert_eq!(points.len(), 3);
+        assert_eq!((points[0].0, points[0].1), (52.5, 13.4));
+        assert_eq!(labels(&points[0]), (Some("p1"), Some("Smith & Sons")));
+        assert_eq!(labels(&points[1]), (None, Some("Hut <B>")));
+    }
+
+    #[test]
+    fn polygons_are_sampled_with_a_grid() {
+        let grid = Some(GridSpacing {
+            km: 111.19508,
+            cos_lat: false,
+        });
+        let geojson = r#"{"type": "Feature", "properties": {"name": "Field"},
+            "geometry": {"type": "Polygon",
+                         "coordinates": [[[10, 50], [13, 50], [13, 53], [10, 53], [10, 50]]]}}"#;
+        let points = parse_features(geojson, "area.geojson", grid).unwrap();
+        assert_eq!(points.len(), 9);
+        assert_eq!((points[0].0, points[0].1), (50.0, 10.0));
+        assert_eq!(labels(&points[8]), (None, Some("Field")));
+
+        let kml = "<kml><Placemark><Polygon>
+            <outerBoundaryIs><LinearRing><coordinates>
+              10,50 13,50 13,53 10,53 10,50
+            </coordinates></LinearRing></outerBoundaryIs>
+            <innerBoundaryIs><LinearRing><coordinates>
+              10.5,50.5 11.5,50.5 11.5,51.5 10.5,51.5 10.5,50.5
+            </coordinates></LinearRing></innerBoundaryIs>
+            </Polygon></Placemark></kml>";
+        let points = parse_features(kml, "area.kml", grid).unwrap();
+        assert_eq!(points.len(), 8);
+        assert!(
+            !points
+                .iter()
+                .any(|point| (point.0, point.1) == (51.0, 11.0))
+        );
+
+        let tiny =
+            r#"{"type": "Polygon", "coordinates": [[[10.2, 50.1], [10.2, 50.2], [10.1, 50.2]]]}"#;
+        let err = parse_features(tiny, "tiny.geojson", grid).unwrap_err();
+        assert!(err.to_string().contains("no grid points"));
+    }
+
+    #[test]
+    fn kml_rejects_lines() {
+        let content = "<kml><Placemark><LineString><coordinates>0,0 1,1</coordinates></LineString></Placemark></kml>";
+        let err = parse_features(content, "f.kml", None)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("placemark 1: unsupported geometry type 'LineString'"));
+        assert!(parse_features("<kml></kml>", "f.kml", None).is_err());
+    }
+}
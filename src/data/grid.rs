@@ -0,0 +1,144 @@
+//! Kilometre-spaced sampling of bounding boxes and polygons.
+
+use super::types::GridSpacing;
+
+/// Length of one degree of latitude on the mean Earth sphere.
+const KM_PER_DEGREE: f64 = 6371.0088 * std::f64::consts::PI / 180.0;
+
+/// Polygon rings as `(latitude, longitude)` vertices; the first ring is the outer boundary
+/// and any further rings are holes.
+pub(crate) type Polygon = Vec<Vec<(f64, f64)>>;
+
+impl GridSpacing {
+    fn lat_step(&self) -> f64 {
+        self.km / KM_PER_DEGREE
+    }
+
+    fn lon_step(&self, lat: f64) -> f64 {
+        if self.cos_lat {
+            (self.lat_step() / lat.to_radians().cos()).min(360.0)
+        } else {
+            self.lat_step()
+        }
+    }
+}
+
+/// Number of whole steps from `start` to `end`, tolerating rounding at the far edge.
+fn step_count(start: f64, end: f64, step: f64) -> usize {
+    ((end - start) / step + 1e-9).floor() as usize
+}
+
+/// Rounds to six decimals (about 0.1 m) so grid coordinates print cleanly.
+fn round_coordinate(value: f64) -> f64 {
+    (value * 1e6).round() / 1e6
+}
+
+/// Returns grid points from the south-west corner of the inclusive bounds, row by row.
+pub(crate) fn bounds_points(
+    lat: (f64, f64),
+    lon: (f64, f64),
+    spacing: GridSpacing,
+) -> impl Iterator<Item = (f64, f64)> {
+    let lat_step = spacing.lat_step();
+    (0..=step_count(lat.0, lat.1, lat_step)).flat_map(move |row| {
+        let row_lat = round_coordinate(lat.0 + row as f64 * lat_step);
+        let lon_step = spacing.lon_step(row_lat);
+        (0..=step_count(lon.0, lon.1, lon_step))
+            .map(move |col| (row_lat, round_coordinate(lon.0 + col as f64 * lon_step)))
+    })
+}
+
+/// Returns the grid points of the polygon's bounding box that fall inside the polygon.
+pub(crate) fn polygon_points(polygon: &Polygon, spacing: GridSpacing) -> Vec<(f64, f64)> {
+    let Some(outer) = polygon.first().filter(|ring| !ring.is_empty()) else {
+        return Vec::new();
+    };
+    let (mut south, mut west) = outer[0];
+    let (mut north, mut east) = outer[0];
+    for &(lat, lon) in outer {
+        south = south.min(lat);
+        north = north.max(lat);
+        west = west.min(lon);
+        east = east.max(lon);
+    }
+    bounds_points((south, north), (west, east), spacing)
+        .filter(|&point| polygon_contains(polygon, point))
+        .collect()
+}
+
+/// Even-odd containment test treating coordinates as planar.
+fn polygon_contains(polygon: &Polygon, (lat, lon): (f64, f64)) -> bool {
+    let mut inside = false;
+    for ring in polygon {
+        let mut previous = match ring.last() {
+            Some(&vertex) => vertex,
+            None => continue,
+        };
+        for &vertex in ring {
+            let ((lat_a, lon_a), (lat_b, lon_b)) = (previous, vertex);
+            if (lat_a > lat) != (lat_b > lat)
+                && lon < lon_a + (lat - lat_a) / (lat_b - lat_a) * (lon_b - lon_a)
+            {
+                inside = !inside;
+            }
+            previous = vertex;
+        }
+    }
+    inside
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAIN: GridSpacing = GridSpacing {
+        km: 111.19508,
+        cos_lat: false,
+    };
+
+    #[test]
+    fn bounds_are_sampled_row_by_row() {
+        let points: Vec<_> = bounds_points((50.0, 51.0), (10.0, 12.0), PLAIN).collect();
+        assert_eq!(
+            points,
+            [
+                (50.0, 10.0),
+                (50.0, 11.0),
+                (50.0, 12.0),
+                (51.0, 10.0),
+                (51.0, 11.0),
+                (51.0, 12.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn cos_lat_widens_longitude_steps() {
+        let spacing = GridSpacing {
+            cos_lat: true,
+            ..PLAIN
+        };
+        let points: Vec<_> = bounds_points((60.0, 60.0), (0.0, 4.0), spacing).collect();
+        assert_eq!(points, [(60.0, 0.0), (60.0, 2.0), (60.0, 4.0)]);
+
+        let pole: Vec<_> = bounds_points((90.0, 90.0), (-180.0, 180.0), spacing).collect();
+        assert_eq!(pole, [(90.0, -180.0), (90.0, 180.0)]);
+    }
+
+    #[test]
+    fn polygons_keep_inner_points_and_skip_holes() {
+        let square = |min: f64, max: f64| vec![(min, min), (min, max), (max, max), (max, min)];
+        let polygon = vec![square(0.0, 4.0), square(0.5, 1.5)];
+        let points = polygon_points(&polygon, PLAIN);
+        assert_eq!(points.len(), 15);
+        assert!(!points.contains(&(1.0, 1.0)));
+        assert!(points.contains(&(2.0, 2.0)));
+
+        let triangle = vec![vec![(0.0, 0.0), (0.0, 4.0), (4.0, 0.0)]];
+        assert!(
+            polygon_points(&triangle, PLAIN)
+                .iter()
+                .all(|(lat, lon)| lat + lon <= 4.0)
+        );
+    }
+}
@@ -0,0 +1,93 @@
+//! Local skyline profiles for obstructed sunrise, sunset, and visibility.
+
+/// Skyline elevation angles by azimuth, in degrees.
+///
+/// Azimuths run clockwise from north like solar azimuths. Elevations between points are
+/// interpolated linearly, wrapping around through north.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HorizonProfile {
+    points: Vec<(f64, f64)>,
+}
+
+impl HorizonProfile {
+    /// Builds a profile from `(azimuth, elevation)` points in any order.
+    ///
+    /// An azimuth of 360 is the same direction as 0.
+    pub fn new(points: impl IntoIterator<Item = (f64, f64)>) -> Result<Self, String> {
+        let mut points: Vec<(f64, f64)> = points
+            .into_iter()
+            .map(|(azimuth, elevation)| (azimuth.rem_euclid(360.0), elevation))
+            .collect();
+        if points.is_empty() {
+            return Err("Horizon profile has no points".to_string());
+        }
+        if let Some((azimuth, _)) = points
+            .iter()
+            .find(|(azimuth, elevation)| !azimuth.is_finite() || !elevation.is_finite())
+        {
+            return Err(format!(
+                "Horizon profile has a non-finite point at {azimuth}"
+            ));
+        }
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        if let Some(pair) = points.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+            return Err(format!(
+                "Horizon profile has more than one point at azimuth {}",
+                pair[0].0
+            ));
+        }
+        Ok(Self { points })
+    }
+
+    /// Returns the skyline elevation angle towards `azimuth`.
+    pub fn elevation_at(&self, azimuth: f64) -> f64 {
+        let azimuth = azimuth.rem_euclid(360.0);
+        let idx = self.points.partition_point(|(az, _)| *az <= azimuth);
+        let before = self.points[(idx + self.points.len() - 1) % self.points.len()];
+        let after = self.points[idx % self.points.len()];
+
+        let span = (after.0 - before.0).rem_euclid(360.0);
+        if span == 0.0 {
+            return before.1;
+        }
+        let offset = (azimuth - before.0).rem_euclid(360.0);
+        before.1 + (after.1 - before.1) * offset / span
+    }
+
+    /// Returns the lowest skyline elevation angle in any direction.
+    pub fn min_elevation(&self) -> f64 {
+        self.points
+            .iter()
+            .map(|(_, elevation)| *elevation)
+            .fold(f64::INFINITY, f64::min)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interpolates_between_points_and_across_north() {
+        let profile =
+            HorizonProfile::new([(90.0, 10.0), (270.0, 2.0), (0.0, 4.0), (180.0, 6.0)]).unwrap();
+
+        assert_eq!(profile.elevation_at(90.0), 10.0);
+        assert_eq!(profile.elevation_at(135.0), 8.0);
+        assert_eq!(profile.elevation_at(315.0), 3.0);
+        assert_eq!(profile.elevation_at(360.0), 4.0);
+        assert_eq!(profile.elevation_at(-45.0), 3.0);
+        assert_eq!(profile.min_elevation(), 2.0);
+    }
+
+    #[test]
+    fn single_point_is_flat_and_duplicates_are_rejected() {
+        let flat = HorizonProfile::new([(120.0, 1.5)]).unwrap();
+        assert_eq!(flat.elevation_at(0.0), 1.5);
+        assert_eq!(flat.elevation_at(300.0), 1.5);
+
+        assert!(HorizonProfile::new([]).is_err());
+        assert!(HorizonProfile::new([(0.0, 1.0), (360.0, 2.0)]).is_err());
+        assert!(HorizonProfile::new([(f64::NAN, 1.0)]).is_err());
+    }
+}
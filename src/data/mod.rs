@@ -2,14 +2,21 @@
 
 pub mod config;
 pub mod expansion;
+pub mod features;
+pub mod grid;
+pub mod horizon_profile;
 pub mod time_utils;
 pub mod types;
 pub mod validation;
 
 pub use config::{
-    CalculationAlgorithm, Command, OutputFormat, Parameters, Predicate, Step, TimezoneOverride,
+    CalculationAlgorithm, CalculationOptions, Command, CustomHorizon, DstPolicy, Environment,
+    OutputFormat, OutputOptions, Parameters, ParquetCompression, ParquetDataset, ParquetOptions,
+    ParquetTimestamps, Predicate, SqliteOptions, Step, Surface, TimeFormat, TimezoneOverride,
+    Tracker, parse_custom_horizons,
 };
 pub use expansion::*;
+pub use horizon_profile::HorizonProfile;
 pub use time_utils::parse_datetime_string;
 pub use types::*;
 pub use validation::*;
@@ -1,5 +1,6 @@
 //! Date, time, duration, and timezone parsing utilities.
 
+use super::{DstPolicy, TimezoneOverride};
 use chrono::{
     DateTime, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, Offset, TimeZone, Utc,
 };
@@ -9,15 +10,10 @@ use std::env;
 use std::sync::OnceLock;
 
 static SYSTEM_TIMEZONE: OnceLock<TimezoneInfo> = OnceLock::new();
+#[cfg(feature = "tz-lookup")]
+static TIMEZONE_FINDER: OnceLock<tzf_rs::DefaultFinder> = OnceLock::new();
 const SYSTEM_TZ_OVERRIDE_ENV: &str = "SUNCE_SYSTEM_TIMEZONE";
 
-fn timezone_gap_error(dt_str: &str) -> String {
-    format!(
-        "Datetime does not exist in timezone (likely DST gap): {}",
-        dt_str
-    )
-}
-
 enum ParsedDateTime {
     Now,
     Fixed(DateTime<FixedOffset>),
@@ -63,6 +59,7 @@ fn parse_datetime_input(dt_str: &str) -> Result<ParsedDateTime, String> {
 fn resolve_datetime(
     parsed: ParsedDateTime,
     override_tz: Option<&str>,
+    policy: DstPolicy,
     original: &str,
 ) -> Result<DateTime<FixedOffset>, String> {
     let tz_info = get_timezone_info(override_tz);
@@ -76,15 +73,15 @@ fn resolve_datetime(
             }
         }
         ParsedDateTime::Naive(naive_dt) => tz_info
-            .to_datetime_from_local(&naive_dt)
-            .ok_or_else(|| timezone_gap_error(original)),
+            .to_datetime_from_local(&naive_dt, policy)
+            .map_err(|reason| format!("Datetime {}: {}", reason, original)),
         ParsedDateTime::DateOnly(date) => {
             let naive_dt = date
                 .and_hms_opt(0, 0, 0)
                 .expect("Midnight time creation cannot fail");
             tz_info
-                .to_datetime_from_local(&naive_dt)
-                .ok_or_else(|| timezone_gap_error(original))
+                .to_datetime_from_local(&naive_dt, policy)
+                .map_err(|reason| format!("Datetime {}: {}", reason, original))
         }
         ParsedDateTime::UnixTimestamp(ts) => {
             let utc_dt = DateTime::<Utc>::from_timestamp(ts, 0)
@@ -116,26 +113,51 @@ impl TimezoneInfo {
         }
     }
 
-    pub fn to_datetime_from_local(&self, dt: &NaiveDateTime) -> Option<DateTime<FixedOffset>> {
+    /// Resolves a local time, applying `policy` to times repeated or skipped by a DST
+    /// transition. Errors describe why the time was rejected.
+    pub fn to_datetime_from_local(
+        &self,
+        dt: &NaiveDateTime,
+        policy: DstPolicy,
+    ) -> Result<DateTime<FixedOffset>, &'static str> {
         match self {
-            TimezoneInfo::Fixed(offset) => match offset.from_local_datetime(dt) {
-                chrono::LocalResult::Single(dt) => Some(dt),
-                chrono::LocalResult::Ambiguous(dt1, _dt2) => Some(dt1),
-                chrono::LocalResult::None => {
-                    let seconds = offset.local_minus_utc() as i64;
-                    let utc_naive = *dt - Duration::seconds(seconds);
-                    Some(offset.from_utc_datetime(&utc_naive))
+            // A fixed offset maps every local time to exactly one instant.
+            TimezoneInfo::Fixed(offset) => {
+                let seconds = offset.local_minus_utc() as i64;
+                Ok(offset.from_utc_datetime(&(*dt - Duration::seconds(seconds))))
+            }
+            TimezoneInfo::Named(tz) => match (tz.from_local_datetime(dt), policy) {
+                (chrono::LocalResult::Single(dt), _) => Ok(dt.fixed_offset()),
+                (chrono::LocalResult::Ambiguous(_, _), DstPolicy::Reject) => {
+                    Err("is ambiguous in timezone (DST overlap)")
+                }
+                (chrono::LocalResult::Ambiguous(_, later), DstPolicy::Later) => {
+                    Ok(later.fixed_offset())
+                }
+                (chrono::LocalResult::Ambiguous(earlier, _), _) => Ok(earlier.fixed_offset()),
+                (chrono::LocalResult::None, DstPolicy::ShiftForward) => {
+                    shift_past_gap(tz, dt).ok_or("does not exist in timezone (likely DST gap)")
+                }
+                (chrono::LocalResult::None, _) => {
+                    Err("does not exist in timezone (likely DST gap)")
                 }
-            },
-            TimezoneInfo::Named(tz) => match tz.from_local_datetime(dt) {
-                chrono::LocalResult::None => None,
-                chrono::LocalResult::Single(dt) => Some(dt.fixed_offset()),
-                chrono::LocalResult::Ambiguous(dt1, _dt2) => Some(dt1.fixed_offset()),
             },
         }
     }
 }
 
+/// Reads a local time skipped by a DST gap with the offset in effect before the gap, which
+/// moves it forward by the gap's length (02:30 becomes 03:30 for a one-hour gap).
+fn shift_past_gap(tz: &Tz, dt: &NaiveDateTime) -> Option<DateTime<FixedOffset>> {
+    let before = tz
+        .from_local_datetime(&(*dt - Duration::days(1)))
+        .earliest()?
+        .offset()
+        .fix();
+    let utc = *dt - Duration::seconds(before.local_minus_utc() as i64);
+    Some(tz.from_utc_datetime(&utc).fixed_offset())
+}
+
 pub fn convert_datetime_to_timezone<Tz: TimeZone>(
     dt: DateTime<Tz>,
     tz_info: &TimezoneInfo,
@@ -146,9 +168,18 @@ pub fn convert_datetime_to_timezone<Tz: TimeZone>(
 pub fn parse_datetime_string(
     dt_str: &str,
     override_tz: Option<&str>,
+) -> Result<DateTime<FixedOffset>, String> {
+    parse_datetime_with_policy(dt_str, override_tz, DstPolicy::default())
+}
+
+/// Like [`parse_datetime_string`], resolving DST-repeated or skipped local times by `policy`.
+pub fn parse_datetime_with_policy(
+    dt_str: &str,
+    override_tz: Option<&str>,
+    policy: DstPolicy,
 ) -> Result<DateTime<FixedOffset>, String> {
     let parsed = parse_datetime_input(dt_str)?;
-    resolve_datetime(parsed, override_tz, dt_str)
+    resolve_datetime(parsed, override_tz, policy, dt_str)
 }
 
 pub fn parse_duration_positive(s: &str) -> Result<Duration, String> {
@@ -197,6 +228,15 @@ pub fn parse_duration_positive(s: &str) -> Result<Duration, String> {
     Ok(duration)
 }
 
+/// Returns whether `s` is a date or date/time without an offset, so its instant depends on
+/// the timezone it is read in.
+pub fn is_local_datetime(s: &str) -> bool {
+    matches!(
+        parse_datetime_input(s),
+        Ok(ParsedDateTime::Naive(_) | ParsedDateTime::DateOnly(_))
+    )
+}
+
 pub fn is_date_without_time(s: &str) -> bool {
     s.len() == 10
         && s.matches('-').count() == 2
@@ -227,6 +267,22 @@ pub fn parse_timezone_spec(spec: &str) -> Option<TimezoneInfo> {
         .or_else(|| spec.parse::<Tz>().ok().map(TimezoneInfo::Named))
 }
 
+/// Returns the IANA zone at a location from the embedded timezone boundaries; at sea this is
+/// the `Etc/GMT` zone of the nautical time band.
+#[cfg(feature = "tz-lookup")]
+pub fn lookup_timezone(lat: f64, lon: f64) -> Result<TimezoneOverride, String> {
+    let name = TIMEZONE_FINDER
+        .get_or_init(tzf_rs::DefaultFinder::new)
+        .get_tz_name(lon, lat);
+    name.parse()
+        .map_err(|_| format!("No timezone found for {}, {}", lat, lon))
+}
+
+#[cfg(not(feature = "tz-lookup"))]
+pub fn lookup_timezone(_lat: f64, _lon: f64) -> Result<TimezoneOverride, String> {
+    Err("Timezone lookup not available in this build (enable feature 'tz-lookup')".to_string())
+}
+
 fn detect_system_timezone() -> TimezoneInfo {
     parse_timezone_env(env::var(SYSTEM_TZ_OVERRIDE_ENV).ok())
         .or_else(|| parse_timezone_env(get_timezone().ok()))
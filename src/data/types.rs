@@ -1,32 +1,77 @@
 //! Core input and stream data types used across the program.
 
+use super::TimezoneOverride;
+use crate::error::Error;
 use chrono::{DateTime, FixedOffset};
 use std::path::PathBuf;
+use std::sync::Arc;
 
+/// A file path or standard input.
 #[derive(Debug, Clone, PartialEq)]
 pub enum InputPath {
     Stdin,
     File(PathBuf),
 }
 
+/// Where latitude/longitude pairs come from.
 #[derive(Debug, Clone)]
 pub enum LocationSource {
     Single(f64, f64),
+    /// Inclusive `(start, end, step)` ranges in degrees.
     Range {
         lat: (f64, f64, f64),
         lon: (f64, f64, f64),
     },
     File(InputPath),
+    /// GeoJSON or KML points, each labelled with its feature's [`Site`].
+    Features(InputPath),
+    /// Points at a fixed distance from each other inside an area.
+    Grid {
+        area: GridArea,
+        spacing: GridSpacing,
+    },
+}
+
+/// Area sampled by a [`LocationSource::Grid`].
+#[derive(Debug, Clone)]
+pub enum GridArea {
+    /// Inclusive `(start, end)` latitude and longitude bounds in degrees.
+    Bounds { lat: (f64, f64), lon: (f64, f64) },
+    /// Polygons of a GeoJSON or KML feature file; point features are kept as they are.
+    Polygons(InputPath),
+}
+
+/// Distance between neighbouring grid points.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GridSpacing {
+    pub km: f64,
+    /// Widens longitude steps by `1 / cos(latitude)` so east-west spacing stays `km` too.
+    pub cos_lat: bool,
+}
+
+/// Properties of a location: feature labels and its own timezone.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Site {
+    pub id: Option<String>,
+    pub name: Option<String>,
+    /// IANA timezone from the optional last column of a coordinate or paired file, or looked up
+    /// from the coordinates with `--timezone=auto`.
+    pub timezone: Option<TimezoneOverride>,
 }
 
+/// Where datetimes come from.
 #[derive(Debug, Clone)]
 pub enum TimeSource {
     Single(DateTime<FixedOffset>),
+    /// Partial date (`YYYY`, `YYYY-MM`, or `YYYY-MM-DD`) expanded into a series.
     Range(String),
+    /// A date or date/time without offset, placed in each location's timezone.
+    Local(String),
     File(InputPath),
     Now,
 }
 
+/// Combined input: a cartesian product of locations and times, or a paired file.
 #[derive(Debug, Clone)]
 pub enum DataSource {
     Separate(LocationSource, TimeSource),
@@ -37,7 +82,15 @@ impl DataSource {
     pub fn uses_stdin(&self) -> bool {
         match self {
             DataSource::Separate(loc, time) => {
-                let loc_stdin = matches!(loc, LocationSource::File(InputPath::Stdin));
+                let loc_stdin = matches!(
+                    loc,
+                    LocationSource::File(InputPath::Stdin)
+                        | LocationSource::Features(InputPath::Stdin)
+                        | LocationSource::Grid {
+                            area: GridArea::Polygons(InputPath::Stdin),
+                            ..
+                        }
+                );
                 let time_stdin = matches!(time, TimeSource::File(InputPath::Stdin));
                 loc_stdin || time_stdin
             }
@@ -49,11 +102,31 @@ impl DataSource {
     pub fn is_watch_mode(&self, step: &Option<crate::data::config::Step>) -> bool {
         matches!(self, DataSource::Separate(_, TimeSource::Now)) && step.is_some()
     }
+
+    /// Returns whether locations carry [`Site`] labels.
+    pub fn has_sites(&self) -> bool {
+        matches!(
+            self,
+            DataSource::Separate(
+                LocationSource::Features(_)
+                    | LocationSource::Grid {
+                        area: GridArea::Polygons(_),
+                        ..
+                    },
+                _
+            )
+        )
+    }
 }
 
-pub type CoordTime = (f64, f64, DateTime<FixedOffset>);
-pub type CoordTimeResult = Result<CoordTime, String>;
+/// One `(latitude, longitude, datetime, site)` input record.
+///
+/// `site` is `None` unless the record comes from a file with feature labels or a timezone
+/// column, or zones are looked up with `--timezone=auto`. It was added with feature-file input; earlier versions used a three-element tuple.
+pub type CoordTime = (f64, f64, DateTime<FixedOffset>, Option<Arc<Site>>);
+pub type CoordTimeResult = Result<CoordTime, Error>;
+/// Lazy stream of input records produced by expansion.
 pub type CoordTimeStream = Box<dyn Iterator<Item = CoordTimeResult>>;
 
-pub type LocationResult = Result<(f64, f64), String>;
+pub type LocationResult = Result<(f64, f64, Option<Arc<Site>>), Error>;
 pub type LocationStream = Box<dyn Iterator<Item = LocationResult>>;
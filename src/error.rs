@@ -45,32 +45,97 @@ impl fmt::Display for CliError {
     }
 }
 
-#[derive(Debug)]
-pub struct StringError(pub String);
+/// Pipeline stage an [`Error`] comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// Invalid parameters or unparseable input data.
+    Input,
+    /// A calculation failed for a record.
+    Calculation,
+    /// Results could not be converted to the output format.
+    Output,
+    /// Reading or writing a file or stream failed.
+    Io,
+}
+
+/// Error returned by input expansion, calculation, and output writing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    kind: ErrorKind,
+    message: String,
+}
+
+impl Error {
+    /// Creates an error of `kind` with a human-readable message.
+    pub fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+        }
+    }
+
+    pub(crate) fn calculation(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Calculation, message)
+    }
 
-impl From<String> for StringError {
+    pub(crate) fn output(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Output, message)
+    }
+
+    pub(crate) fn io(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Io, message)
+    }
+
+    /// Returns the stage the error comes from.
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// Returns the human-readable error message.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+/// Plain messages are input errors; the calculation and output stages tag their own.
+impl From<String> for Error {
     fn from(value: String) -> Self {
-        Self(value)
+        Self::new(ErrorKind::Input, value)
     }
 }
 
-impl From<&str> for StringError {
+impl From<&str> for Error {
     fn from(value: &str) -> Self {
-        Self(value.to_string())
+        Self::new(ErrorKind::Input, value)
     }
 }
 
-impl From<io::Error> for StringError {
+/// I/O errors carrying an [`Error`] (as the Arrow writers produce) keep its kind.
+impl From<io::Error> for Error {
     fn from(value: io::Error) -> Self {
-        Self(value.to_string())
+        match value
+            .get_ref()
+            .and_then(|inner| inner.downcast_ref::<Error>())
+        {
+            Some(inner) => inner.clone(),
+            None => Self::io(value.to_string()),
+        }
     }
 }
 
-impl fmt::Display for StringError {
+impl From<Error> for String {
+    fn from(value: Error) -> Self {
+        value.message
+    }
+}
+
+impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_str(&self.0)
+        f.write_str(&self.message)
     }
 }
 
-pub type PlannerError = StringError;
-pub type OutputError = StringError;
+impl std::error::Error for Error {}
+
+pub type PlannerError = Error;
+pub type OutputError = Error;
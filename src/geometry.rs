@@ -0,0 +1,161 @@
+//! Extended solar geometry: equatorial coordinates, hour angle, and time quantities.
+
+use crate::error::Error;
+use chrono::{DateTime, FixedOffset};
+use solar_positioning::spa::SpaTimeDependent;
+use solar_positioning::time::JulianDate;
+
+/// Nutation in longitude and the true obliquity of the ecliptic, in degrees.
+pub(crate) struct Nutation {
+    pub longitude: f64,
+    pub obliquity: f64,
+}
+
+/// Nutation to about half an arcsecond (Meeus chapter 22), for `t` Julian ephemeris centuries
+/// since J2000.0.
+pub(crate) fn nutation(t: f64) -> Nutation {
+    let sin = |degrees: f64| degrees.to_radians().sin();
+    let cos = |degrees: f64| degrees.to_radians().cos();
+    let node = 125.04452 - 1934.136261 * t;
+    let sun_longitude = 280.4665 + 36000.7698 * t;
+    let moon_longitude = 218.3165 + 481267.8813 * t;
+    let longitude =
+        (-17.20 * sin(node) - 1.32 * sin(2.0 * sun_longitude) - 0.23 * sin(2.0 * moon_longitude)
+            + 0.21 * sin(2.0 * node))
+            / 3600.0;
+    let obliquity =
+        (9.20 * cos(node) + 0.57 * cos(2.0 * sun_longitude) + 0.10 * cos(2.0 * moon_longitude)
+            - 0.09 * cos(2.0 * node))
+            / 3600.0;
+    let mean_obliquity = 84381.448 + t * (-46.8150 + t * (-0.00059 + t * 0.001813));
+    Nutation {
+        longitude,
+        obliquity: mean_obliquity / 3600.0 + obliquity,
+    }
+}
+
+/// Apparent sidereal time at Greenwich in degrees (Meeus 12.4 plus the equation of the
+/// equinoxes).
+pub(crate) fn apparent_sidereal_time(jd: &JulianDate, nutation: &Nutation) -> f64 {
+    let t = jd.julian_century();
+    let mean = 280.46061837
+        + 360.98564736629 * (jd.julian_date() - 2451545.0)
+        + t * t * (0.000387933 - t / 38710000.0);
+    (mean + nutation.longitude * nutation.obliquity.to_radians().cos()).rem_euclid(360.0)
+}
+
+/// Equatorial and time quantities behind a solar position.
+///
+/// Declination, right ascension, and the Earth-Sun distance come from SPA. The hour angle,
+/// sidereal time, and equation of time use the truncated Meeus nutation above rather than SPA's
+/// full series, so they can disagree with SPA-derived columns at the arcsecond level.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SolarGeometry {
+    /// Apparent geocentric declination, in degrees.
+    pub declination: f64,
+    /// Apparent geocentric right ascension, in degrees.
+    pub right_ascension: f64,
+    /// Local hour angle in degrees, negative before transit.
+    pub hour_angle: f64,
+    /// Apparent minus mean solar time, in minutes.
+    pub equation_of_time: f64,
+    /// Earth-Sun distance in astronomical units.
+    pub earth_sun_distance: f64,
+    /// Julian day (UT).
+    pub julian_day: f64,
+    /// Apparent sidereal time at Greenwich, in degrees.
+    pub sidereal_time: f64,
+}
+
+/// Calculates the solar geometry at `dt` for longitude `lon`.
+pub fn solar_geometry(
+    dt: DateTime<FixedOffset>,
+    lon: f64,
+    deltat: f64,
+) -> Result<SolarGeometry, Error> {
+    let parts = solar_positioning::spa::spa_time_dependent_parts(dt, deltat).map_err(|err| {
+        Error::calculation(format!("Failed to calculate time-dependent parts: {}", err))
+    })?;
+    solar_geometry_from_parts(dt, lon, deltat, &parts)
+}
+
+/// Calculates the solar geometry from already computed SPA time-dependent parts.
+pub(crate) fn solar_geometry_from_parts(
+    dt: DateTime<FixedOffset>,
+    lon: f64,
+    deltat: f64,
+    parts: &SpaTimeDependent,
+) -> Result<SolarGeometry, Error> {
+    let jd = JulianDate::from_datetime(&dt, deltat)
+        .map_err(|err| Error::calculation(format!("Failed to calculate Julian day: {}", err)))?;
+    let nutation = nutation(jd.julian_ephemeris_century());
+    let sidereal_time = apparent_sidereal_time(&jd, &nutation);
+    let right_ascension = parts.right_ascension();
+
+    // Sun's mean longitude and the equation of time from SPA appendix A.1.
+    let tau = jd.julian_ephemeris_millennium();
+    let mean_longitude = 280.4664567
+        + tau
+            * (360007.6982779
+                + tau * (0.03032028 + tau * (1.0 / 49931.0 - tau * (1.0 / 15300.0 + tau / 2e6))));
+    let equation_of_time = mean_longitude - 0.0057183 - right_ascension
+        + nutation.longitude * nutation.obliquity.to_radians().cos();
+
+    Ok(SolarGeometry {
+        declination: parts.declination(),
+        right_ascension,
+        hour_angle: signed_degrees(sidereal_time + lon - right_ascension),
+        equation_of_time: signed_degrees(equation_of_time) * 4.0,
+        earth_sun_distance: parts.earth_radius_vector(),
+        julian_day: jd.julian_date(),
+        sidereal_time,
+    })
+}
+
+/// Normalises `degrees` to the range [-180, 180).
+fn signed_degrees(degrees: f64) -> f64 {
+    (degrees + 180.0).rem_euclid(360.0) - 180.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn utc(year: i32, month: u32, day: u32) -> DateTime<FixedOffset> {
+        FixedOffset::east_opt(0)
+            .unwrap()
+            .with_ymd_and_hms(year, month, day, 0, 0, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn sidereal_time_matches_meeus_example() {
+        // Meeus example 12.a: 1987 April 10, 0h UT, apparent sidereal time 13h10m46.1351s.
+        let geometry = solar_geometry(utc(1987, 4, 10), 0.0, 0.0).unwrap();
+        let expected = (13.0 + 10.0 / 60.0 + 46.1351 / 3600.0) * 15.0;
+        assert!((geometry.sidereal_time - expected).abs() < 1e-3);
+        assert_eq!(geometry.julian_day, 2446895.5);
+    }
+
+    #[test]
+    fn equation_of_time_matches_meeus_example() {
+        // Meeus examples 25.b and 28.a: 1992 October 13, 0h TD, R = 0.99760853 AU,
+        // declination -7°47'01.74", E = 13m42.6s.
+        let geometry = solar_geometry(utc(1992, 10, 13), 0.0, 0.0).unwrap();
+        assert!((geometry.equation_of_time - 13.71).abs() < 0.01);
+        assert!((geometry.earth_sun_distance - 0.99760853).abs() < 1e-6);
+        assert!((geometry.declination + 7.78382).abs() < 1e-4);
+    }
+
+    #[test]
+    fn hour_angle_is_negative_before_transit() {
+        let morning =
+            solar_geometry(utc(2024, 6, 21) + chrono::Duration::hours(8), 0.0, 69.0).unwrap();
+        let evening =
+            solar_geometry(utc(2024, 6, 21) + chrono::Duration::hours(16), 0.0, 69.0).unwrap();
+        assert!((morning.hour_angle + 60.0).abs() < 1.0);
+        assert!((evening.hour_angle - 60.0).abs() < 1.0);
+        assert!((morning.declination - 23.44).abs() < 0.01);
+    }
+}
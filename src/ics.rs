@@ -0,0 +1,450 @@
+//! iCalendar (RFC 5545) output for sunrise results.
+
+use crate::compute::CalculationStream;
+use crate::data::Parameters;
+use crate::data::time_utils::{TimezoneInfo, parse_timezone_spec};
+use crate::error::OutputError;
+use crate::output::{SunriseLayout, SunriseRow, row_from_result};
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveDateTime, Offset, TimeZone, Utc,
+};
+use chrono_tz::{OffsetComponents, OffsetName, Tz};
+use std::io::{BufWriter, Write};
+
+/// Maximum content line length in octets before folding.
+const LINE_LIMIT: usize = 75;
+
+/// Writes sunrise results as a VCALENDAR with one VEVENT per sunrise, transit and sunset,
+/// plus twilight begin/end with `--twilight`.
+///
+/// Days without sunrise or sunset become all-day events. Times carry the TZID of a named
+/// output timezone and are written in UTC otherwise. The VTIMEZONE for a named zone follows
+/// the events so that it can cover every year seen in the stream. Returns the number of
+/// records written.
+pub(crate) fn write_ics<W: Write>(
+    results: CalculationStream,
+    params: &Parameters,
+    writer: W,
+    flush_each_record: bool,
+) -> Result<usize, OutputError> {
+    let mut writer = BufWriter::new(writer);
+    let tz =
+        params
+            .output_timezone
+            .as_ref()
+            .and_then(|tz| match parse_timezone_spec(tz.as_str()) {
+                Some(TimezoneInfo::Named(tz)) => Some(tz),
+                _ => None,
+            });
+    let include_twilight = SunriseLayout::from_params(params).include_twilight;
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let mut count = 0;
+    let mut years: Option<(i32, i32)> = None;
+
+    write_line(&mut writer, "BEGIN:VCALENDAR")?;
+    write_line(&mut writer, "VERSION:2.0")?;
+    write_line(
+        &mut writer,
+        &format!("PRODID:-//sunce//sunce {}//EN", env!("CARGO_PKG_VERSION")),
+    )?;
+    write_line(&mut writer, "CALSCALE:GREGORIAN")?;
+
+    for result in results {
+        let row = row_from_result::<SunriseRow>(result)?;
+        let year = row.date_time.year();
+        years = Some(years.map_or((year, year), |(first, last)| {
+            (first.min(year), last.max(year))
+        }));
+        write_row_events(&mut writer, &row, tz, include_twilight, &stamp)?;
+        count += 1;
+        if flush_each_record {
+            writer.flush().map_err(OutputError::from)?;
+        }
+    }
+
+    if let (Some(tz), Some((first, last))) = (tz, years) {
+        for line in vtimezone_lines(tz, first, last) {
+            write_line(&mut writer, &line)?;
+        }
+    }
+    write_line(&mut writer, "END:VCALENDAR")?;
+    writer.flush().map_err(OutputError::from)?;
+    Ok(count)
+}
+
+fn write_row_events<W: Write>(
+    writer: &mut W,
+    row: &SunriseRow,
+    tz: Option<Tz>,
+    include_twilight: bool,
+    stamp: &str,
+) -> Result<(), OutputError> {
+    let all_day = match row.type_label {
+        "ALL_DAY" => Some(("all-day", "Sun up all day")),
+        "ALL_NIGHT" => Some(("all-night", "Sun down all day")),
+        _ => None,
+    };
+    if let Some((kind, summary)) = all_day {
+        let date = row.date_time.date_naive();
+        let next = date.succ_opt().unwrap_or(date);
+        write_event(
+            writer,
+            row,
+            &format!("{kind}-{}", date.format("%Y%m%d")),
+            summary,
+            stamp,
+            &[
+                format!("DTSTART;VALUE=DATE:{}", date.format("%Y%m%d")),
+                format!("DTEND;VALUE=DATE:{}", next.format("%Y%m%d")),
+            ],
+        )?;
+    }
+
+    let twilight = |time: Option<DateTime<FixedOffset>>| time.filter(|_| include_twilight);
+    let instants = [
+        (
+            "astronomical-dawn",
+            "Astronomical dawn",
+            twilight(row.astro_start),
+        ),
+        (
+            "nautical-dawn",
+            "Nautical dawn",
+            twilight(row.nautical_start),
+        ),
+        ("civil-dawn", "Civil dawn", twilight(row.civil_start)),
+        ("sunrise", "Sunrise", row.sunrise),
+        ("transit", "Solar noon", Some(row.transit)),
+        ("sunset", "Sunset", row.sunset),
+        ("civil-dusk", "Civil dusk", twilight(row.civil_end)),
+        ("nautical-dusk", "Nautical dusk", twilight(row.nautical_end)),
+        (
+            "astronomical-dusk",
+            "Astronomical dusk",
+            twilight(row.astro_end),
+        ),
+    ];
+    for (kind, summary, time) in instants {
+        let Some(time) = time else {
+            continue;
+        };
+        let uid = format!(
+            "{kind}-{}",
+            time.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ")
+        );
+        write_event(
+            writer,
+            row,
+            &uid,
+            summary,
+            stamp,
+            &[datetime_property("DTSTART", &time, tz)],
+        )?;
+    }
+    Ok(())
+}
+
+fn write_event<W: Write>(
+    writer: &mut W,
+    row: &SunriseRow,
+    uid: &str,
+    summary: &str,
+    stamp: &str,
+    times: &[String],
+) -> Result<(), OutputError> {
+    write_line(writer, "BEGIN:VEVENT")?;
+    write_line(writer, &format!("UID:{uid}-{}-{}@sunce", row.lat, row.lon))?;
+    write_line(writer, &format!("DTSTAMP:{stamp}"))?;
+    for time in times {
+        write_line(writer, time)?;
+    }
+    write_line(writer, &format!("SUMMARY:{summary}"))?;
+    let location = row
+        .site
+        .as_ref()
+        .and_then(|site| site.name.as_deref().or(site.id.as_deref()))
+        .map_or_else(|| format!("{}, {}", row.lat, row.lon), str::to_string);
+    write_line(writer, &format!("LOCATION:{}", escape_text(&location)))?;
+    write_line(writer, &format!("GEO:{};{}", row.lat, row.lon))?;
+    write_line(writer, "TRANSP:TRANSPARENT")?;
+    write_line(writer, "END:VEVENT")
+}
+
+fn datetime_property(name: &str, time: &DateTime<FixedOffset>, tz: Option<Tz>) -> String {
+    match tz {
+        Some(tz) => format!(
+            "{name};TZID={}:{}",
+            tz.name(),
+            time.with_timezone(&tz).format("%Y%m%dT%H%M%S")
+        ),
+        None => format!(
+            "{name}:{}",
+            time.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ")
+        ),
+    }
+}
+
+/// Escapes a TEXT property value.
+fn escape_text(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Writes one content line, folded at [`LINE_LIMIT`] octets and terminated by CRLF.
+fn write_line<W: Write>(writer: &mut W, line: &str) -> Result<(), OutputError> {
+    let mut rest = line;
+    let mut limit = LINE_LIMIT;
+    while rest.len() > limit {
+        let mut split = limit;
+        while !rest.is_char_boundary(split) {
+            split -= 1;
+        }
+        writer
+            .write_all(&rest.as_bytes()[..split])
+            .and_then(|()| writer.write_all(b"\r\n "))
+            .map_err(OutputError::from)?;
+        rest = &rest[split..];
+        // Continuation lines start with a space, which counts towards the limit.
+        limit = LINE_LIMIT - 1;
+    }
+    writer
+        .write_all(rest.as_bytes())
+        .and_then(|()| writer.write_all(b"\r\n"))
+        .map_err(OutputError::from)
+}
+
+/// Returns a VTIMEZONE for `tz` covering the years `first` through `last`.
+///
+/// The offset in effect at the start of `first` becomes the initial observance. Transitions
+/// with the same offsets and name are grouped into one STANDARD or DAYLIGHT observance: a
+/// yearly RRULE on the same weekday of the month when that reproduces every one of them, as
+/// for most zones that observe DST, and RDATEs for each transition otherwise.
+fn vtimezone_lines(tz: Tz, first: i32, last: i32) -> Vec<String> {
+    let mut lines = vec!["BEGIN:VTIMEZONE".to_string(), format!("TZID:{}", tz.name())];
+    let transitions: Vec<_> = (first..=last)
+        .flat_map(|year| year_transitions(tz, year))
+        .collect();
+    let initial = tz.offset_from_utc_datetime(&start_of_year(first));
+    let start = if transitions.is_empty() {
+        "19700101T000000".to_string()
+    } else {
+        start_of_year(first).format("%Y%m%dT%H%M%S").to_string()
+    };
+    push_observance(
+        &mut lines,
+        observance_kind(&initial),
+        start,
+        Vec::new(),
+        initial.fix(),
+        initial.fix(),
+        initial.abbreviation(),
+    );
+
+    let mut groups: Vec<Observance> = Vec::new();
+    for (instant, from) in transitions {
+        let to = tz.offset_from_utc_datetime(&instant.naive_utc());
+        let local = instant.with_timezone(&from).naive_local();
+        let key = (
+            observance_kind(&to),
+            from,
+            to.fix(),
+            to.abbreviation().map(str::to_string),
+        );
+        match groups.iter_mut().find(|group| group.key == key) {
+            Some(group) => group.starts.push(local),
+            None => groups.push(Observance {
+                key,
+                starts: vec![local],
+            }),
+        }
+    }
+    for Observance {
+        key: (kind, from, to, name),
+        starts,
+    } in groups
+    {
+        let rule = yearly_rule(starts[0].date());
+        let recurs = starts.len() as i32 == last - first + 1
+            && starts
+                .iter()
+                .all(|start| start.time() == starts[0].time() && yearly_rule(start.date()) == rule);
+        let recurrence = if recurs {
+            vec![rule]
+        } else {
+            starts[1..]
+                .iter()
+                .map(|start| format!("RDATE:{}", start.format("%Y%m%dT%H%M%S")))
+                .collect()
+        };
+        push_observance(
+            &mut lines,
+            kind,
+            starts[0].format("%Y%m%dT%H%M%S").to_string(),
+            recurrence,
+            from,
+            to,
+            name.as_deref(),
+        );
+    }
+    lines.push("END:VTIMEZONE".to_string());
+    lines
+}
+
+/// Transitions sharing kind, offsets, and name, with their local onset times.
+struct Observance {
+    key: (&'static str, FixedOffset, FixedOffset, Option<String>),
+    starts: Vec<NaiveDateTime>,
+}
+
+fn observance_kind(offset: &<Tz as TimeZone>::Offset) -> &'static str {
+    if offset.dst_offset().is_zero() {
+        "STANDARD"
+    } else {
+        "DAYLIGHT"
+    }
+}
+
+fn push_observance(
+    lines: &mut Vec<String>,
+    kind: &str,
+    start: String,
+    recurrence: Vec<String>,
+    from: FixedOffset,
+    to: FixedOffset,
+    name: Option<&str>,
+) {
+    lines.push(format!("BEGIN:{kind}"));
+    lines.push(format!("DTSTART:{start}"));
+    lines.extend(recurrence);
+    lines.push(format!("TZOFFSETFROM:{}", utc_offset(from)));
+    lines.push(format!("TZOFFSETTO:{}", utc_offset(to)));
+    lines.extend(name.map(|name| format!("TZNAME:{}", escape_text(name))));
+    lines.push(format!("END:{kind}"));
+}
+
+fn start_of_year(year: i32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(year, 1, 1)
+        .unwrap_or_default()
+        .and_time(chrono::NaiveTime::MIN)
+}
+
+/// Returns the UTC instants in `year` at which the offset of `tz` changes, with the offset
+/// in effect before each change.
+fn year_transitions(tz: Tz, year: i32) -> Vec<(DateTime<Utc>, FixedOffset)> {
+    let offset_at = |time: DateTime<Utc>| tz.offset_from_utc_datetime(&time.naive_utc()).fix();
+    let end = Utc.from_utc_datetime(&start_of_year(year + 1));
+    let mut time = Utc.from_utc_datetime(&start_of_year(year));
+    let mut offset = offset_at(time);
+    let mut transitions = Vec::new();
+    while time < end {
+        let next = time + Duration::days(1);
+        if offset_at(next) != offset {
+            // Narrow the change down to the first second with the new offset.
+            let (mut before, mut after) = (time, next);
+            while after - before > Duration::seconds(1) {
+                let middle = before + (after - before) / 2;
+                if offset_at(middle) == offset {
+                    before = middle;
+                } else {
+                    after = middle;
+                }
+            }
+            transitions.push((after, offset));
+            offset = offset_at(after);
+        }
+        time = next;
+    }
+    transitions
+}
+
+/// Returns an RRULE repeating on the same weekday of the month as `date`, counted from the
+/// end of the month when it falls in the last week.
+fn yearly_rule(date: NaiveDate) -> String {
+    let weekday = match date.weekday() {
+        chrono::Weekday::Mon => "MO",
+        chrono::Weekday::Tue => "TU",
+        chrono::Weekday::Wed => "WE",
+        chrono::Weekday::Thu => "TH",
+        chrono::Weekday::Fri => "FR",
+        chrono::Weekday::Sat => "SA",
+        chrono::Weekday::Sun => "SU",
+    };
+    let in_last_week = (date + Duration::days(7)).month() != date.month();
+    let ordinal = if in_last_week {
+        -1
+    } else {
+        (date.day() as i32 - 1) / 7 + 1
+    };
+    format!(
+        "RRULE:FREQ=YEARLY;BYMONTH={};BYDAY={ordinal}{weekday}",
+        date.month()
+    )
+}
+
+fn utc_offset(offset: FixedOffset) -> String {
+    let seconds = offset.local_minus_utc();
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let seconds = seconds.abs();
+    format!("{sign}{:02}{:02}", seconds / 3600, seconds % 3600 / 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn long_lines_are_folded_at_75_octets() {
+        let mut out = Vec::new();
+        write_line(&mut out, &format!("SUMMARY:{}", "ä".repeat(60))).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = text.split_terminator("\r\n").collect();
+        assert!(lines.len() > 1);
+        assert!(lines.iter().all(|line| line.len() <= LINE_LIMIT));
+        assert!(lines[1..].iter().all(|line| line.starts_with(' ')));
+        let unfolded: String = lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| if i == 0 { *line } else { &line[1..] })
+            .collect();
+        assert_eq!(unfolded, format!("SUMMARY:{}", "ä".repeat(60)));
+    }
+
+    #[test]
+    fn vtimezone_rules_follow_dst_transitions() {
+        let lines = vtimezone_lines(chrono_tz::Europe::Berlin, 2024, 2024);
+        let text = lines.join("\n");
+        assert!(text.contains(
+            "BEGIN:DAYLIGHT\nDTSTART:20240331T020000\nRRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU\nTZOFFSETFROM:+0100\nTZOFFSETTO:+0200\nTZNAME:CEST"
+        ));
+        assert!(text.contains(
+            "BEGIN:STANDARD\nDTSTART:20241027T030000\nRRULE:FREQ=YEARLY;BYMONTH=10;BYDAY=-1SU\nTZOFFSETFROM:+0200\nTZOFFSETTO:+0100\nTZNAME:CET"
+        ));
+
+        let years = vtimezone_lines(chrono_tz::Europe::Berlin, 2023, 2025).join("\n");
+        assert_eq!(years.matches("RRULE:").count(), 2);
+        assert!(!years.contains("RDATE"));
+
+        let fixed = vtimezone_lines(chrono_tz::Asia::Kolkata, 2024, 2024).join("\n");
+        assert!(fixed.contains("DTSTART:19700101T000000\nTZOFFSETFROM:+0530\nTZOFFSETTO:+0530"));
+        assert!(yearly_rule(NaiveDate::from_ymd_opt(2024, 3, 10).unwrap()).ends_with("BYDAY=2SU"));
+    }
+
+    #[test]
+    fn text_values_are_escaped() {
+        assert_eq!(
+            escape_text("Hut; north, a\\b\nc"),
+            "Hut\\; north\\, a\\\\b\\nc"
+        );
+    }
+}
@@ -0,0 +1,135 @@
+//! Angle of incidence on fixed and sun-tracking surfaces.
+
+use crate::data::{Surface, Tracker};
+
+/// Sun-to-surface geometry for one solar position, in degrees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SurfaceIncidence {
+    /// Angle between the sun and the surface normal; `None` for trackers with the sun down.
+    pub incidence: Option<f64>,
+    /// Single-axis rotation from level, positive when the surface turns towards the axis
+    /// azimuth plus 90 degrees (west for an axis azimuth of 180, east for 0).
+    pub rotation: Option<f64>,
+    /// Dual-axis surface tilt from horizontal.
+    pub tilt: Option<f64>,
+    /// Dual-axis surface azimuth, clockwise from north.
+    pub azimuth: Option<f64>,
+}
+
+impl SurfaceIncidence {
+    /// Returns the cosine of the incidence angle.
+    pub fn cos_incidence(&self) -> Option<f64> {
+        self.incidence.map(|incidence| incidence.to_radians().cos())
+    }
+}
+
+/// Calculates the incidence on `surface` of the sun at `zenith` and `azimuth` degrees.
+///
+/// Fixed surfaces report the incidence even with the sun behind them or below the horizon.
+/// Trackers report their ideal orientation while the sun is up: dual-axis trackers face the
+/// sun, and single-axis trackers use the rotation of Marion and Dobos (2013) about an axis
+/// with the surface tilt and azimuth, without rotation limits or backtracking.
+pub fn surface_incidence(surface: &Surface, zenith: f64, azimuth: f64) -> SurfaceIncidence {
+    let (sin_zenith, cos_zenith) = zenith.to_radians().sin_cos();
+    let (sin_azimuth, cos_azimuth) = azimuth.to_radians().sin_cos();
+    let sun_up = zenith < 90.0;
+    let mut result = SurfaceIncidence {
+        incidence: None,
+        rotation: None,
+        tilt: None,
+        azimuth: None,
+    };
+
+    match surface.tracker {
+        Tracker::Fixed => {
+            let (sin_tilt, cos_tilt) = surface.tilt.to_radians().sin_cos();
+            let cos_incidence = cos_zenith * cos_tilt
+                + sin_zenith * sin_tilt * (azimuth - surface.azimuth).to_radians().cos();
+            result.incidence = Some(cos_incidence.clamp(-1.0, 1.0).acos().to_degrees());
+        }
+        Tracker::SingleAxis if sun_up => {
+            let (east, north) = (sin_zenith * sin_azimuth, sin_zenith * cos_azimuth);
+            let (sin_axis_tilt, cos_axis_tilt) = surface.tilt.to_radians().sin_cos();
+            let (sin_axis_azimuth, cos_axis_azimuth) = surface.azimuth.to_radians().sin_cos();
+            // Sun vector across and normal to the axis, in the tracker's frame.
+            let across = east * cos_axis_azimuth - north * sin_axis_azimuth;
+            let normal = (east * sin_axis_azimuth + north * cos_axis_azimuth) * sin_axis_tilt
+                + cos_zenith * cos_axis_tilt;
+            let cos_incidence = across.hypot(normal).min(1.0);
+            result.rotation = Some(across.atan2(normal).to_degrees());
+            result.incidence = Some(cos_incidence.acos().to_degrees());
+        }
+        Tracker::DualAxis if sun_up => {
+            result.tilt = Some(zenith);
+            result.azimuth = Some(azimuth);
+            result.incidence = Some(0.0);
+        }
+        Tracker::SingleAxis | Tracker::DualAxis => {}
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn surface(tracker: Tracker, tilt: f64, azimuth: f64) -> Surface {
+        Surface {
+            tracker,
+            tilt,
+            azimuth,
+        }
+    }
+
+    fn assert_close(actual: Option<f64>, expected: f64) {
+        let actual = actual.expect("value expected");
+        assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn fixed_surface_incidence() {
+        let south_30 = surface(Tracker::Fixed, 30.0, 180.0);
+        assert_close(surface_incidence(&south_30, 30.0, 180.0).incidence, 0.0);
+        assert_close(surface_incidence(&south_30, 60.0, 180.0).incidence, 30.0);
+        assert_close(surface_incidence(&south_30, 30.0, 0.0).incidence, 60.0);
+
+        let flat = surface(Tracker::Fixed, 0.0, 180.0);
+        assert_close(surface_incidence(&flat, 40.0, 95.0).incidence, 40.0);
+
+        // Sun behind a south-facing facade.
+        let facade = surface(Tracker::Fixed, 90.0, 180.0);
+        let behind = surface_incidence(&facade, 60.0, 0.0);
+        assert!(behind.incidence.unwrap() > 90.0);
+        assert!(behind.cos_incidence().unwrap() < 0.0);
+    }
+
+    #[test]
+    fn single_axis_tracker_rotates_towards_sun() {
+        let north_south = surface(Tracker::SingleAxis, 0.0, 180.0);
+
+        let morning = surface_incidence(&north_south, 60.0, 90.0);
+        assert_close(morning.rotation, -60.0);
+        assert_close(morning.incidence, 0.0);
+
+        let afternoon = surface_incidence(&north_south, 45.0, 270.0);
+        assert_close(afternoon.rotation, 45.0);
+
+        // A sun due south is out of the rotation plane and cannot be tracked.
+        let noon = surface_incidence(&north_south, 30.0, 180.0);
+        assert_close(noon.rotation, 0.0);
+        assert_close(noon.incidence, 30.0);
+
+        assert_eq!(surface_incidence(&north_south, 95.0, 300.0).rotation, None);
+    }
+
+    #[test]
+    fn dual_axis_tracker_faces_sun() {
+        let dual = surface(Tracker::DualAxis, 0.0, 180.0);
+        let up = surface_incidence(&dual, 35.0, 120.0);
+        assert_close(up.tilt, 35.0);
+        assert_close(up.azimuth, 120.0);
+        assert_close(up.cos_incidence(), 1.0);
+
+        assert_eq!(surface_incidence(&dual, 100.0, 0.0).incidence, None);
+    }
+}
@@ -0,0 +1,141 @@
+//! Clear-sky irradiance estimates for solar positions.
+
+use chrono::{DateTime, Datelike, FixedOffset, Utc};
+use std::f64::consts::PI;
+
+/// Solar constant in W/m² used for extraterrestrial irradiance.
+const SOLAR_CONSTANT: f64 = 1366.1;
+/// Standard sea-level pressure in hPa.
+const STANDARD_PRESSURE: f64 = 1013.25;
+
+/// Clear-sky irradiance for one solar position, in W/m².
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClearSkyIrradiance {
+    /// Extraterrestrial irradiance normal to the sun's rays.
+    pub extraterrestrial: f64,
+    /// Relative optical air mass; `None` with the sun below the horizon.
+    pub air_mass: Option<f64>,
+    /// Global horizontal irradiance.
+    pub ghi: f64,
+    /// Direct normal irradiance.
+    pub dni: f64,
+    /// Diffuse horizontal irradiance.
+    pub dhi: f64,
+}
+
+/// Extraterrestrial normal irradiance on the UTC day of `dt` (Spencer 1971).
+fn extraterrestrial_irradiance(dt: DateTime<FixedOffset>) -> f64 {
+    let day_angle = 2.0 * PI * f64::from(dt.with_timezone(&Utc).ordinal0()) / 365.0;
+    SOLAR_CONSTANT
+        * (1.00011
+            + 0.034221 * day_angle.cos()
+            + 0.00128 * day_angle.sin()
+            + 0.000719 * (2.0 * day_angle).cos()
+            + 0.000077 * (2.0 * day_angle).sin())
+}
+
+/// Relative air mass for a zenith angle in degrees (Kasten and Young 1989).
+fn relative_air_mass(zenith: f64) -> Option<f64> {
+    (zenith < 90.0)
+        .then(|| 1.0 / (zenith.to_radians().cos() + 0.50572 * (96.07995 - zenith).powf(-1.6364)))
+}
+
+/// Estimates clear-sky irradiance with the Ineichen-Perez model (Ineichen and Perez 2002).
+///
+/// `zenith` is the (refracted) zenith angle in degrees, `elevation` the site altitude in
+/// metres, and `pressure` the air pressure in hPa used for the absolute air mass.
+pub fn clear_sky_irradiance(
+    dt: DateTime<FixedOffset>,
+    zenith: f64,
+    elevation: f64,
+    pressure: f64,
+    linke_turbidity: f64,
+) -> ClearSkyIrradiance {
+    let extraterrestrial = extraterrestrial_irradiance(dt);
+    let Some(air_mass) = relative_air_mass(zenith) else {
+        return ClearSkyIrradiance {
+            extraterrestrial,
+            air_mass: None,
+            ghi: 0.0,
+            dni: 0.0,
+            dhi: 0.0,
+        };
+    };
+
+    let cos_zenith = zenith.to_radians().cos().max(0.0);
+    let absolute_air_mass = air_mass * pressure / STANDARD_PRESSURE;
+    let tl = linke_turbidity;
+    let fh1 = (-elevation / 8000.0).exp();
+    let fh2 = (-elevation / 1250.0).exp();
+    let cg1 = 5.09e-5 * elevation + 0.868;
+    let cg2 = 3.92e-5 * elevation + 0.0387;
+
+    let ghi = cg1
+        * extraterrestrial
+        * cos_zenith
+        * (-cg2 * absolute_air_mass * (fh1 + fh2 * (tl - 1.0)))
+            .exp()
+            .max(0.0);
+
+    let b = 0.664 + 0.163 / fh1;
+    let beam = extraterrestrial * (b * (-0.09 * absolute_air_mass * (tl - 1.0)).exp()).max(0.0);
+    // Keeps the beam component consistent with the global irradiance near the horizon.
+    let beam_limit = if cos_zenith > 0.0 {
+        ghi * ((1.0 - (0.1 - 0.2 * (-tl).exp()) / (0.1 + 0.882 / fh1)) / cos_zenith).max(0.0)
+    } else {
+        0.0
+    };
+    let dni = beam.min(beam_limit);
+
+    ClearSkyIrradiance {
+        extraterrestrial,
+        air_mass: Some(air_mass),
+        ghi,
+        dni,
+        dhi: ghi - dni * cos_zenith,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn utc(year: i32, month: u32, day: u32) -> DateTime<FixedOffset> {
+        FixedOffset::east_opt(0)
+            .unwrap()
+            .with_ymd_and_hms(year, month, day, 12, 0, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn extraterrestrial_irradiance_peaks_at_perihelion() {
+        let january = extraterrestrial_irradiance(utc(2024, 1, 3));
+        let july = extraterrestrial_irradiance(utc(2024, 7, 4));
+        assert!((january - 1414.0).abs() < 3.0, "{january}");
+        assert!((july - 1320.5).abs() < 3.0, "{july}");
+    }
+
+    #[test]
+    fn air_mass_follows_kasten_young() {
+        assert!((relative_air_mass(0.0).unwrap() - 1.0).abs() < 1e-3);
+        assert!((relative_air_mass(60.0).unwrap() - 1.9943).abs() < 1e-3);
+        assert!(relative_air_mass(90.0).is_none());
+    }
+
+    #[test]
+    fn clear_sky_components_are_consistent() {
+        let sky = clear_sky_irradiance(utc(2024, 6, 21), 30.0, 0.0, 1013.25, 3.0);
+        assert!((sky.ghi - 868.8).abs() < 1.0, "{sky:?}");
+        assert!((sky.dni - 887.9).abs() < 1.0, "{sky:?}");
+        let cos_zenith = 30f64.to_radians().cos();
+        assert!((sky.ghi - sky.dni * cos_zenith - sky.dhi).abs() < 1e-9);
+
+        let hazy = clear_sky_irradiance(utc(2024, 6, 21), 30.0, 0.0, 1013.25, 6.0);
+        assert!(hazy.dni < sky.dni && hazy.dhi > sky.dhi);
+
+        let night = clear_sky_irradiance(utc(2024, 6, 21), 95.0, 0.0, 1013.25, 3.0);
+        assert_eq!((night.ghi, night.dni, night.dhi), (0.0, 0.0, 0.0));
+        assert_eq!(night.air_mass, None);
+    }
+}
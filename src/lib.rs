@@ -1,9 +1,34 @@
 //! Solar position calculator application pipeline.
+//!
+//! Besides the [`run`] entry point used by the `sunce` binary, the crate exposes the same
+//! expansion, calculation, and output stages as a library:
+//!
+//! ```
+//! use sunce::{Command, DataSource, LocationSource, Parameters, TimeSource};
+//!
+//! let params = Parameters::default();
+//! let source = DataSource::Separate(
+//!     LocationSource::Single(52.0, 13.4),
+//!     TimeSource::Single("2024-06-21T12:00:00Z".parse().unwrap()),
+//! );
+//! let data = sunce::expand_data_source(&source, &params, Command::Position)?;
+//! let results = sunce::calculate_stream(data, Command::Position, params.clone(), true);
+//!
+//! let mut out = Vec::new();
+//! let count = sunce::write_output(results, Command::Position, &params, &mut out, false)?;
+//! assert_eq!(count, 1);
+//! # Ok::<(), sunce::Error>(())
+//! ```
 
 mod cli;
 mod compute;
 mod data;
 mod error;
+mod geometry;
+mod ics;
+mod incidence;
+mod irradiance;
+mod moon;
 mod output;
 #[cfg(feature = "parquet")]
 mod parquet;
@@ -11,9 +36,35 @@ mod parsed;
 mod planner;
 mod position;
 mod predicate;
+mod shadow;
+#[cfg(feature = "sqlite")]
+mod sqlite;
 mod sunrise;
 mod validate;
 
+pub use compute::{
+    CalculationResult, CalculationStream, SolarState, calculate_stream, calculate_stream_parallel,
+};
+pub use data::{
+    CalculationAlgorithm, CalculationOptions, Command, CoordTime, CoordTimeResult, CoordTimeStream,
+    CustomHorizon, DataSource, DstPolicy, Environment, GridArea, GridSpacing, HorizonProfile,
+    InputPath, LocationSource, OutputFormat, OutputOptions, Parameters, ParquetCompression,
+    ParquetDataset, ParquetOptions, ParquetTimestamps, Site, SqliteOptions, Step, Surface,
+    TimeFormat, TimeSource, TimezoneOverride, Tracker, expand_data_source,
+};
+pub use error::{Error, ErrorKind};
+pub use geometry::{SolarGeometry, solar_geometry};
+pub use incidence::{SurfaceIncidence, surface_incidence};
+pub use irradiance::{ClearSkyIrradiance, clear_sky_irradiance};
+pub use moon::{
+    MoonIllumination, MoonPhase, MoonPosition, calculate_moon_position, calculate_moonrise,
+};
+pub use output::write_output;
+pub use position::calculate_position;
+pub use shadow::{Shadow, shadow};
+pub use solar_positioning::{SolarPosition, SunriseResult};
+pub use sunrise::{DayStats, PhotoHours, calculate_sunrise};
+
 fn cli_error_exit_code(err: crate::error::CliError) -> i32 {
     match err {
         crate::error::CliError::Exit(message) => {
@@ -57,13 +108,15 @@ pub fn run(args: Vec<String>) -> i32 {
                             params,
                             allow_time_cache,
                             flush_each_record,
+                            threads,
                         } = plan;
 
-                        let results = compute::calculate_stream(
+                        let results = compute::calculate_stream_parallel(
                             data_iter,
                             command,
                             params.clone(),
                             allow_time_cache,
+                            threads,
                         );
                         let record_count = match output::dispatch_output(
                             results,
@@ -0,0 +1,621 @@
+//! Lunar position, illumination, and moonrise/moonset calculations.
+//!
+//! Positions follow the truncated ELP-2000/82 series of Meeus, *Astronomical Algorithms*,
+//! chapter 47, which is accurate to about 10 arcseconds in longitude and 4 in latitude.
+
+use crate::compute::CalculationResult;
+use crate::data::Parameters;
+use crate::error::Error;
+use crate::geometry::{apparent_sidereal_time, nutation};
+use crate::position::{refraction_correction, resolve_deltat};
+use crate::sunrise::local_day_bounds;
+use chrono::{DateTime, Duration, FixedOffset};
+use solar_positioning::time::JulianDate;
+use solar_positioning::{Horizon, RefractionCorrection};
+
+/// Equatorial radius of the Earth in kilometres.
+const EARTH_RADIUS_KM: f64 = 6378.14;
+const ASTRONOMICAL_UNIT_KM: f64 = 149_597_870.7;
+
+/// Periodic terms for longitude (1e-6 degrees) and distance (1e-3 km), as multiples of
+/// D, M, M' and F (Meeus table 47.A).
+#[rustfmt::skip]
+const LONGITUDE_DISTANCE_TERMS: [[f64; 6]; 60] = [
+    [0.0, 0.0, 1.0, 0.0, 6288774.0, -20905355.0],
+    [2.0, 0.0, -1.0, 0.0, 1274027.0, -3699111.0],
+    [2.0, 0.0, 0.0, 0.0, 658314.0, -2955968.0],
+    [0.0, 0.0, 2.0, 0.0, 213618.0, -569925.0],
+    [0.0, 1.0, 0.0, 0.0, -185116.0, 48888.0],
+    [0.0, 0.0, 0.0, 2.0, -114332.0, -3149.0],
+    [2.0, 0.0, -2.0, 0.0, 58793.0, 246158.0],
+    [2.0, -1.0, -1.0, 0.0, 57066.0, -152138.0],
+    [2.0, 0.0, 1.0, 0.0, 53322.0, -170733.0],
+    [2.0, -1.0, 0.0, 0.0, 45758.0, -204586.0],
+    [0.0, 1.0, -1.0, 0.0, -40923.0, -129620.0],
+    [1.0, 0.0, 0.0, 0.0, -34720.0, 108743.0],
+    [0.0, 1.0, 1.0, 0.0, -30383.0, 104755.0],
+    [2.0, 0.0, 0.0, -2.0, 15327.0, 10321.0],
+    [0.0, 0.0, 1.0, 2.0, -12528.0, 0.0],
+    [0.0, 0.0, 1.0, -2.0, 10980.0, 79661.0],
+    [4.0, 0.0, -1.0, 0.0, 10675.0, -34782.0],
+    [0.0, 0.0, 3.0, 0.0, 10034.0, -23210.0],
+    [4.0, 0.0, -2.0, 0.0, 8548.0, -21636.0],
+    [2.0, 1.0, -1.0, 0.0, -7888.0, 24208.0],
+    [2.0, 1.0, 0.0, 0.0, -6766.0, 30824.0],
+    [1.0, 0.0, -1.0, 0.0, -5163.0, -8379.0],
+    [1.0, 1.0, 0.0, 0.0, 4987.0, -16675.0],
+    [2.0, -1.0, 1.0, 0.0, 4036.0, -12831.0],
+    [2.0, 0.0, 2.0, 0.0, 3994.0, -10445.0],
+    [4.0, 0.0, 0.0, 0.0, 3861.0, -11650.0],
+    [2.0, 0.0, -3.0, 0.0, 3665.0, 14403.0],
+    [0.0, 1.0, -2.0, 0.0, -2689.0, -7003.0],
+    [2.0, 0.0, -1.0, 2.0, -2602.0, 0.0],
+    [2.0, -1.0, -2.0, 0.0, 2390.0, 10056.0],
+    [1.0, 0.0, 1.0, 0.0, -2348.0, 6322.0],
+    [2.0, -2.0, 0.0, 0.0, 2236.0, -9884.0],
+    [0.0, 1.0, 2.0, 0.0, -2120.0, 5751.0],
+    [0.0, 2.0, 0.0, 0.0, -2069.0, 0.0],
+    [2.0, -2.0, -1.0, 0.0, 2048.0, -4950.0],
+    [2.0, 0.0, 1.0, -2.0, -1773.0, 4130.0],
+    [2.0, 0.0, 0.0, 2.0, -1595.0, 0.0],
+    [4.0, -1.0, -1.0, 0.0, 1215.0, -3958.0],
+    [0.0, 0.0, 2.0, 2.0, -1110.0, 0.0],
+    [3.0, 0.0, -1.0, 0.0, -892.0, 3258.0],
+    [2.0, 1.0, 1.0, 0.0, -810.0, 2616.0],
+    [4.0, -1.0, -2.0, 0.0, 759.0, -1897.0],
+    [0.0, 2.0, -1.0, 0.0, -713.0, -2117.0],
+    [2.0, 2.0, -1.0, 0.0, -700.0, 2354.0],
+    [2.0, 1.0, -2.0, 0.0, 691.0, 0.0],
+    [2.0, -1.0, 0.0, -2.0, 596.0, 0.0],
+    [4.0, 0.0, 1.0, 0.0, 549.0, -1423.0],
+    [0.0, 0.0, 4.0, 0.0, 537.0, -1117.0],
+    [4.0, -1.0, 0.0, 0.0, 520.0, -1571.0],
+    [1.0, 0.0, -2.0, 0.0, -487.0, -1739.0],
+    [2.0, 1.0, 0.0, -2.0, -399.0, 0.0],
+    [0.0, 0.0, 2.0, -2.0, -381.0, -4421.0],
+    [1.0, 1.0, 1.0, 0.0, 351.0, 0.0],
+    [3.0, 0.0, -2.0, 0.0, -340.0, 0.0],
+    [4.0, 0.0, -3.0, 0.0, 330.0, 0.0],
+    [2.0, -1.0, 2.0, 0.0, 327.0, 0.0],
+    [0.0, 2.0, 1.0, 0.0, -323.0, 1165.0],
+    [1.0, 1.0, -1.0, 0.0, 299.0, 0.0],
+    [2.0, 0.0, 3.0, 0.0, 294.0, 0.0],
+    [2.0, 0.0, -1.0, -2.0, 0.0, 8752.0],
+];
+
+/// Periodic terms for latitude (1e-6 degrees), as multiples of D, M, M' and F (Meeus
+/// table 47.B).
+#[rustfmt::skip]
+const LATITUDE_TERMS: [[f64; 5]; 60] = [
+    [0.0, 0.0, 0.0, 1.0, 5128122.0],
+    [0.0, 0.0, 1.0, 1.0, 280602.0],
+    [0.0, 0.0, 1.0, -1.0, 277693.0],
+    [2.0, 0.0, 0.0, -1.0, 173237.0],
+    [2.0, 0.0, -1.0, 1.0, 55413.0],
+    [2.0, 0.0, -1.0, -1.0, 46271.0],
+    [2.0, 0.0, 0.0, 1.0, 32573.0],
+    [0.0, 0.0, 2.0, 1.0, 17198.0],
+    [2.0, 0.0, 1.0, -1.0, 9266.0],
+    [0.0, 0.0, 2.0, -1.0, 8822.0],
+    [2.0, -1.0, 0.0, -1.0, 8216.0],
+    [2.0, 0.0, -2.0, -1.0, 4324.0],
+    [2.0, 0.0, 1.0, 1.0, 4200.0],
+    [2.0, 1.0, 0.0, -1.0, -3359.0],
+    [2.0, -1.0, -1.0, 1.0, 2463.0],
+    [2.0, -1.0, 0.0, 1.0, 2211.0],
+    [2.0, -1.0, -1.0, -1.0, 2065.0],
+    [0.0, 1.0, -1.0, -1.0, -1870.0],
+    [4.0, 0.0, -1.0, -1.0, 1828.0],
+    [0.0, 1.0, 0.0, 1.0, -1794.0],
+    [0.0, 0.0, 0.0, 3.0, -1749.0],
+    [0.0, 1.0, -1.0, 1.0, -1565.0],
+    [1.0, 0.0, 0.0, 1.0, -1491.0],
+    [0.0, 1.0, 1.0, 1.0, -1475.0],
+    [0.0, 1.0, 1.0, -1.0, -1410.0],
+    [0.0, 1.0, 0.0, -1.0, -1344.0],
+    [1.0, 0.0, 0.0, -1.0, -1335.0],
+    [0.0, 0.0, 3.0, 1.0, 1107.0],
+    [4.0, 0.0, 0.0, -1.0, 1021.0],
+    [4.0, 0.0, -1.0, 1.0, 833.0],
+    [0.0, 0.0, 1.0, -3.0, 777.0],
+    [4.0, 0.0, -2.0, 1.0, 671.0],
+    [2.0, 0.0, 0.0, -3.0, 607.0],
+    [2.0, 0.0, 2.0, -1.0, 596.0],
+    [2.0, -1.0, 1.0, -1.0, 491.0],
+    [2.0, 0.0, -2.0, 1.0, -451.0],
+    [0.0, 0.0, 3.0, -1.0, 439.0],
+    [2.0, 0.0, 2.0, 1.0, 422.0],
+    [2.0, 0.0, -3.0, -1.0, 421.0],
+    [2.0, 1.0, -1.0, 1.0, -366.0],
+    [2.0, 1.0, 0.0, 1.0, -351.0],
+    [4.0, 0.0, 0.0, 1.0, 331.0],
+    [2.0, -1.0, 1.0, 1.0, 315.0],
+    [2.0, -2.0, 0.0, -1.0, 302.0],
+    [0.0, 0.0, 1.0, 3.0, -283.0],
+    [2.0, 1.0, 1.0, -1.0, -229.0],
+    [1.0, 1.0, 0.0, -1.0, 223.0],
+    [1.0, 1.0, 0.0, 1.0, 223.0],
+    [0.0, 1.0, -2.0, -1.0, -220.0],
+    [2.0, 1.0, -1.0, -1.0, -220.0],
+    [1.0, 0.0, 1.0, 1.0, -185.0],
+    [2.0, -1.0, -2.0, -1.0, 181.0],
+    [0.0, 1.0, 2.0, 1.0, -177.0],
+    [4.0, 0.0, -2.0, -1.0, 176.0],
+    [4.0, -1.0, -1.0, -1.0, 166.0],
+    [1.0, 0.0, 1.0, -1.0, -164.0],
+    [4.0, 0.0, 1.0, -1.0, 132.0],
+    [1.0, 0.0, -1.0, -1.0, -119.0],
+    [4.0, -1.0, 0.0, -1.0, 115.0],
+    [2.0, -2.0, 0.0, 1.0, 107.0],
+];
+
+/// Named lunar phase, from the moon's elongation east of the sun in 45-degree sectors
+/// centred on new moon, first quarter, full moon and last quarter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoonPhase {
+    NewMoon,
+    WaxingCrescent,
+    FirstQuarter,
+    WaxingGibbous,
+    FullMoon,
+    WaningGibbous,
+    LastQuarter,
+    WaningCrescent,
+}
+
+impl MoonPhase {
+    /// Returns the label used for this phase in output records.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MoonPhase::NewMoon => "NEW_MOON",
+            MoonPhase::WaxingCrescent => "WAXING_CRESCENT",
+            MoonPhase::FirstQuarter => "FIRST_QUARTER",
+            MoonPhase::WaxingGibbous => "WAXING_GIBBOUS",
+            MoonPhase::FullMoon => "FULL_MOON",
+            MoonPhase::WaningGibbous => "WANING_GIBBOUS",
+            MoonPhase::LastQuarter => "LAST_QUARTER",
+            MoonPhase::WaningCrescent => "WANING_CRESCENT",
+        }
+    }
+
+    fn from_elongation(degrees: f64) -> Self {
+        const PHASES: [MoonPhase; 8] = [
+            MoonPhase::NewMoon,
+            MoonPhase::WaxingCrescent,
+            MoonPhase::FirstQuarter,
+            MoonPhase::WaxingGibbous,
+            MoonPhase::FullMoon,
+            MoonPhase::WaningGibbous,
+            MoonPhase::LastQuarter,
+            MoonPhase::WaningCrescent,
+        ];
+        PHASES[((degrees.rem_euclid(360.0) + 22.5) / 45.0) as usize % PHASES.len()]
+    }
+}
+
+/// Sunlit part of the lunar disk as seen from the centre of the Earth.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MoonIllumination {
+    /// Illuminated fraction of the disk, 0 to 1.
+    pub illuminated_fraction: f64,
+    /// Sun-moon-Earth angle in degrees; 0 at full moon and 180 at new moon.
+    pub phase_angle: f64,
+    pub phase: MoonPhase,
+}
+
+/// Topocentric position of the moon's centre.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MoonPosition {
+    /// Degrees clockwise from north.
+    pub azimuth: f64,
+    /// Degrees above the horizon, refracted when refraction is enabled.
+    pub altitude: f64,
+    /// Distance between the centres of the Earth and the moon, in kilometres.
+    pub distance: f64,
+    pub illumination: MoonIllumination,
+}
+
+/// Apparent geocentric coordinates of the moon at one instant, in degrees.
+struct Apparent {
+    longitude: f64,
+    latitude: f64,
+    /// Distance in kilometres.
+    distance: f64,
+    right_ascension: f64,
+    declination: f64,
+    /// Apparent sidereal time at Greenwich.
+    sidereal_time: f64,
+    /// Julian ephemeris centuries since J2000.0.
+    centuries: f64,
+}
+
+fn polynomial(t: f64, coefficients: &[f64]) -> f64 {
+    coefficients.iter().rev().fold(0.0, |acc, c| acc * t + c)
+}
+
+fn apparent(dt: DateTime<FixedOffset>, deltat: f64) -> Result<Apparent, String> {
+    let jd = JulianDate::from_datetime(&dt, deltat)
+        .map_err(|e| format!("Failed to calculate lunar position: {}", e))?;
+    let t = jd.julian_ephemeris_century();
+
+    let mean_longitude = polynomial(
+        t,
+        &[
+            218.3164477,
+            481267.88123421,
+            -0.0015786,
+            1.0 / 538841.0,
+            -1.0 / 65194000.0,
+        ],
+    );
+    let elongation = polynomial(
+        t,
+        &[
+            297.8501921,
+            445267.1114034,
+            -0.0018819,
+            1.0 / 545868.0,
+            -1.0 / 113065000.0,
+        ],
+    );
+    let sun_anomaly = polynomial(
+        t,
+        &[357.5291092, 35999.0502909, -0.0001536, 1.0 / 24490000.0],
+    );
+    let moon_anomaly = polynomial(
+        t,
+        &[
+            134.9633964,
+            477198.8675055,
+            0.0087414,
+            1.0 / 69699.0,
+            -1.0 / 14712000.0,
+        ],
+    );
+    let latitude_argument = polynomial(
+        t,
+        &[
+            93.2720950,
+            483202.0175233,
+            -0.0036539,
+            -1.0 / 3526000.0,
+            1.0 / 863310000.0,
+        ],
+    );
+    let a1 = 119.75 + 131.849 * t;
+    let a2 = 53.09 + 479264.290 * t;
+    let a3 = 313.45 + 481266.484 * t;
+    // Terms with the sun's anomaly shrink with the decreasing eccentricity of the Earth's orbit.
+    let eccentricity = 1.0 - 0.002516 * t - 0.0000074 * t * t;
+    let argument = |multiples: &[f64]| {
+        (multiples[0] * elongation
+            + multiples[1] * sun_anomaly
+            + multiples[2] * moon_anomaly
+            + multiples[3] * latitude_argument)
+            .to_radians()
+    };
+
+    let (mut sum_l, mut sum_r, mut sum_b) = (0.0, 0.0, 0.0);
+    for term in &LONGITUDE_DISTANCE_TERMS {
+        let factor = eccentricity.powi(term[1].abs() as i32);
+        let arg = argument(term);
+        sum_l += term[4] * factor * arg.sin();
+        sum_r += term[5] * factor * arg.cos();
+    }
+    for term in &LATITUDE_TERMS {
+        sum_b += term[4] * eccentricity.powi(term[1].abs() as i32) * argument(term).sin();
+    }
+    let sin = |degrees: f64| degrees.to_radians().sin();
+    sum_l += 3958.0 * sin(a1) + 1962.0 * sin(mean_longitude - latitude_argument) + 318.0 * sin(a2);
+    sum_b += -2235.0 * sin(mean_longitude)
+        + 382.0 * sin(a3)
+        + 175.0 * sin(a1 - latitude_argument)
+        + 175.0 * sin(a1 + latitude_argument)
+        + 127.0 * sin(mean_longitude - moon_anomaly)
+        - 115.0 * sin(mean_longitude + moon_anomaly);
+
+    let nutation = nutation(t);
+
+    let longitude = (mean_longitude + sum_l / 1e6 + nutation.longitude).rem_euclid(360.0);
+    let latitude = sum_b / 1e6;
+    let (lambda, beta, epsilon) = (
+        longitude.to_radians(),
+        latitude.to_radians(),
+        nutation.obliquity.to_radians(),
+    );
+    let right_ascension = (lambda.sin() * epsilon.cos() - beta.tan() * epsilon.sin())
+        .atan2(lambda.cos())
+        .to_degrees()
+        .rem_euclid(360.0);
+    let declination = (beta.sin() * epsilon.cos() + beta.cos() * epsilon.sin() * lambda.sin())
+        .asin()
+        .to_degrees();
+
+    Ok(Apparent {
+        longitude,
+        latitude,
+        distance: 385000.56 + sum_r / 1000.0,
+        right_ascension,
+        declination,
+        sidereal_time: apparent_sidereal_time(&jd, &nutation),
+        centuries: t,
+    })
+}
+
+/// Returns the sun's apparent longitude in degrees and its distance in kilometres, from the
+/// low-accuracy solar theory of Meeus chapter 25.
+fn sun_longitude_distance(t: f64) -> (f64, f64) {
+    let mean_longitude = polynomial(t, &[280.46646, 36000.76983, 0.0003032]);
+    let anomaly = polynomial(t, &[357.52911, 35999.05029, -0.0001537]);
+    let eccentricity = polynomial(t, &[0.016708634, -0.000042037, -0.0000001267]);
+    let m = anomaly.to_radians();
+    let centre = polynomial(t, &[1.914602, -0.004817, -0.000014]) * m.sin()
+        + (0.019993 - 0.000101 * t) * (2.0 * m).sin()
+        + 0.000289 * (3.0 * m).sin();
+    let true_anomaly = (anomaly + centre).to_radians();
+    let radius = 1.000001018 * (1.0 - eccentricity * eccentricity)
+        / (1.0 + eccentricity * true_anomaly.cos());
+    let node = (125.04 - 1934.136 * t).to_radians();
+    let longitude = mean_longitude + centre - 0.00569 - 0.00478 * node.sin();
+    (longitude, radius * ASTRONOMICAL_UNIT_KM)
+}
+
+fn illumination(moon: &Apparent) -> MoonIllumination {
+    let (sun_longitude, sun_distance) = sun_longitude_distance(moon.centuries);
+    let elongation = moon.longitude - sun_longitude;
+    let cos_elongation = moon.latitude.to_radians().cos() * elongation.to_radians().cos();
+    let elongation_angle = cos_elongation.clamp(-1.0, 1.0).acos();
+    let phase_angle = (sun_distance * elongation_angle.sin())
+        .atan2(moon.distance - sun_distance * cos_elongation);
+    MoonIllumination {
+        illuminated_fraction: (1.0 + phase_angle.cos()) / 2.0,
+        phase_angle: phase_angle.to_degrees(),
+        phase: MoonPhase::from_elongation(elongation),
+    }
+}
+
+/// Returns the moon's horizontal parallax in degrees.
+fn parallax(distance: f64) -> f64 {
+    (EARTH_RADIUS_KM / distance).asin().to_degrees()
+}
+
+/// Returns the azimuth (clockwise from north) and altitude for an hour angle and declination.
+fn horizontal(lat: f64, hour_angle: f64, declination: f64) -> (f64, f64) {
+    let (phi, h, d) = (
+        lat.to_radians(),
+        hour_angle.to_radians(),
+        declination.to_radians(),
+    );
+    let altitude = (phi.sin() * d.sin() + phi.cos() * d.cos() * h.cos()).asin();
+    let azimuth = h.sin().atan2(h.cos() * phi.sin() - d.tan() * phi.cos());
+    (
+        (azimuth.to_degrees() + 180.0).rem_euclid(360.0),
+        altitude.to_degrees(),
+    )
+}
+
+/// Moves the hour angle and declination from the Earth's centre to an observer at `lat` and
+/// `elevation` metres (Meeus chapter 40).
+fn topocentric(
+    lat: f64,
+    elevation: f64,
+    hour_angle: f64,
+    declination: f64,
+    distance: f64,
+) -> (f64, f64) {
+    const POLAR_RATIO: f64 = 0.99664719;
+    let phi = lat.to_radians();
+    let u = (POLAR_RATIO * phi.tan()).atan();
+    let height = elevation / (EARTH_RADIUS_KM * 1000.0);
+    let rho_sin = POLAR_RATIO * u.sin() + height * phi.sin();
+    let rho_cos = u.cos() + height * phi.cos();
+    let sin_parallax = EARTH_RADIUS_KM / distance;
+    let (h, d) = (hour_angle.to_radians(), declination.to_radians());
+
+    let denominator = d.cos() - rho_cos * sin_parallax * h.cos();
+    let shift = (-rho_cos * sin_parallax * h.sin()).atan2(denominator);
+    let declination = ((d.sin() - rho_sin * sin_parallax) * shift.cos()).atan2(denominator);
+    (hour_angle - shift.to_degrees(), declination.to_degrees())
+}
+
+/// Applies the refraction correction used for solar positions to an altitude in degrees.
+fn refracted(altitude: f64, refraction: Option<RefractionCorrection>) -> f64 {
+    match refraction {
+        Some(correction) if altitude > Horizon::SunriseSunset.elevation_angle() => {
+            altitude
+                + (correction.pressure() / 1010.0)
+                    * (283.0 / (273.0 + correction.temperature()))
+                    * 1.02
+                    / (60.0 * (altitude + 10.3 / (altitude + 5.11)).to_radians().tan())
+        }
+        _ => altitude,
+    }
+}
+
+/// Calculates the topocentric lunar position for an observer `elevation` metres above sea level.
+pub(crate) fn moon_position(
+    lat: f64,
+    lon: f64,
+    dt: DateTime<FixedOffset>,
+    elevation: f64,
+    deltat: f64,
+    refraction: Option<RefractionCorrection>,
+) -> Result<MoonPosition, String> {
+    let moon = apparent(dt, deltat)?;
+    let hour_angle = moon.sidereal_time + lon - moon.right_ascension;
+    let (hour_angle, declination) =
+        topocentric(lat, elevation, hour_angle, moon.declination, moon.distance);
+    let (azimuth, altitude) = horizontal(lat, hour_angle, declination);
+    Ok(MoonPosition {
+        azimuth,
+        altitude: refracted(altitude, refraction),
+        distance: moon.distance,
+        illumination: illumination(&moon),
+    })
+}
+
+pub fn moon_altitude_at(
+    lat: f64,
+    lon: f64,
+    dt: DateTime<FixedOffset>,
+    params: &Parameters,
+) -> Result<f64, String> {
+    let position = moon_position(
+        lat,
+        lon,
+        dt,
+        params.environment.elevation,
+        resolve_deltat(dt, params),
+        refraction_correction(params)?,
+    )?;
+    Ok(position.altitude)
+}
+
+/// Calculates the lunar position and illumination for a single location and instant.
+pub fn calculate_moon_position(
+    lat: f64,
+    lon: f64,
+    dt: DateTime<FixedOffset>,
+    params: &Parameters,
+) -> Result<CalculationResult, Error> {
+    let deltat = resolve_deltat(dt, params);
+    let position = moon_position(
+        lat,
+        lon,
+        dt,
+        params.environment.elevation,
+        deltat,
+        refraction_correction(params)?,
+    )
+    .map_err(Error::calculation)?;
+    Ok(CalculationResult::MoonPosition {
+        lat,
+        lon,
+        datetime: dt,
+        position,
+        deltat,
+        site: None,
+    })
+}
+
+/// Returns how far the moon's upper limb is above the horizon under standard refraction,
+/// in degrees of geocentric altitude (Meeus chapter 15).
+fn rise_clearance(
+    lat: f64,
+    lon: f64,
+    dt: DateTime<FixedOffset>,
+    deltat: f64,
+) -> Result<f64, String> {
+    let moon = apparent(dt, deltat)?;
+    let hour_angle = moon.sidereal_time + lon - moon.right_ascension;
+    let (_, altitude) = horizontal(lat, hour_angle, moon.declination);
+    Ok(altitude - (0.7275 * parallax(moon.distance) + Horizon::SunriseSunset.elevation_angle()))
+}
+
+/// Calculates the first moonrise and moonset during the local day of `dt`, with the moon's
+/// illumination at the middle of the day.
+///
+/// The day is sampled every 30 minutes and each crossing is located to within one second, so
+/// a rise and set less than 30 minutes apart may be missed. Days without a moonrise or
+/// moonset leave them empty; about once a month one of them falls on the next day.
+pub fn calculate_moonrise(
+    lat: f64,
+    lon: f64,
+    dt: DateTime<FixedOffset>,
+    params: &Parameters,
+) -> Result<CalculationResult, Error> {
+    const SAMPLE_STEP: Duration = Duration::minutes(30);
+
+    let deltat = resolve_deltat(dt, params);
+    let (day_start, day_end) = local_day_bounds(dt, params)?;
+    let is_up = |time| {
+        rise_clearance(lat, lon, time, deltat)
+            .map(|clearance| clearance >= 0.0)
+            .map_err(Error::calculation)
+    };
+
+    let mut previous = (day_start, is_up(day_start)?);
+    let above_horizon = previous.1;
+    let (mut moonrise, mut moonset) = (None, None);
+    while previous.0 < day_end {
+        let time = (previous.0 + SAMPLE_STEP).min(day_end);
+        let up = is_up(time)?;
+        if up != previous.1 {
+            let (mut low, mut high) = (previous.0, time);
+            while high - low > Duration::seconds(1) {
+                let mid = low + (high - low) / 2;
+                if is_up(mid)? == up {
+                    high = mid;
+                } else {
+                    low = mid;
+                }
+            }
+            if up {
+                moonrise.get_or_insert(high);
+            } else {
+                moonset.get_or_insert(high);
+            }
+        }
+        previous = (time, up);
+    }
+
+    let midday = day_start + (day_end - day_start) / 2;
+    let illumination = illumination(
+        &apparent(midday, resolve_deltat(midday, params)).map_err(Error::calculation)?,
+    );
+    Ok(CalculationResult::Moonrise {
+        lat,
+        lon,
+        date: dt,
+        moonrise,
+        moonset,
+        above_horizon,
+        illumination,
+        deltat,
+        site: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn meeus_example_time() -> DateTime<FixedOffset> {
+        FixedOffset::east_opt(0)
+            .unwrap()
+            .with_ymd_and_hms(1992, 4, 12, 0, 0, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn apparent_position_matches_meeus_example() {
+        // Example 47.a, 1992 April 12 at 0h TD.
+        let moon = apparent(meeus_example_time(), 0.0).unwrap();
+        assert!((moon.longitude - 133.167265).abs() < 0.001);
+        assert!((moon.latitude + 3.229126).abs() < 0.001);
+        assert!((moon.distance - 368409.7).abs() < 1.0);
+        assert!((moon.right_ascension - 134.688470).abs() < 0.001);
+        assert!((moon.declination - 13.768368).abs() < 0.001);
+    }
+
+    #[test]
+    fn illumination_matches_meeus_example() {
+        // Example 48.a, same instant as 47.a.
+        let illumination = illumination(&apparent(meeus_example_time(), 0.0).unwrap());
+        assert!((illumination.illuminated_fraction - 0.6786).abs() < 0.001);
+        assert!((illumination.phase_angle - 69.0756).abs() < 0.05);
+        // Elongation is about 111 degrees, inside the first-quarter sector.
+        assert_eq!(illumination.phase, MoonPhase::FirstQuarter);
+    }
+
+    #[test]
+    fn phase_sectors_are_centred_on_principal_phases() {
+        assert_eq!(MoonPhase::from_elongation(-10.0), MoonPhase::NewMoon);
+        assert_eq!(MoonPhase::from_elongation(22.6), MoonPhase::WaxingCrescent);
+        assert_eq!(MoonPhase::from_elongation(90.0), MoonPhase::FirstQuarter);
+        assert_eq!(MoonPhase::from_elongation(180.0), MoonPhase::FullMoon);
+        assert_eq!(MoonPhase::from_elongation(290.0), MoonPhase::LastQuarter);
+        assert_eq!(MoonPhase::from_elongation(350.0), MoonPhase::NewMoon);
+    }
+}
@@ -1,23 +1,83 @@
 //! Output formatting for CSV, JSON, and text table formats.
 
-use crate::compute::CalculationResult;
-use crate::data::{Command, OutputFormat, Parameters};
+use crate::compute::{CalculationResult, CalculationStream};
+use crate::data::{
+    Command, CustomHorizon, DstPolicy, InputPath, OutputFormat, Parameters, Site, Surface,
+    TimeFormat, Tracker,
+};
 use crate::error::OutputError;
+use crate::geometry::SolarGeometry;
+use crate::incidence::SurfaceIncidence;
+use crate::irradiance::ClearSkyIrradiance;
+use crate::moon::{MoonIllumination, MoonPosition};
+use crate::shadow::Shadow;
+use crate::sunrise::{DayStats, PhotoHours};
 use ahash::AHashMap;
-use chrono::{DateTime, FixedOffset};
+use chrono::{DateTime, FixedOffset, Utc};
 use serde::Serializer;
 use serde::ser::SerializeMap;
 use solar_positioning::SunriseResult;
+use std::sync::Arc;
 
 const RFC3339_NO_MILLIS: &str = "%Y-%m-%dT%H:%M:%S%:z";
+const RFC3339_MILLIS: &str = "%Y-%m-%dT%H:%M:%S%.3f%:z";
+const RFC3339_UTC: &str = "%Y-%m-%dT%H:%M:%SZ";
 
-type DateTimeCache = AHashMap<DateTime<FixedOffset>, String>;
-type FixedDecimalCache = AHashMap<(u64, u32), String>;
+pub(crate) type FixedDecimalCache = AHashMap<(u64, u32), String>;
 
 pub(crate) fn format_rfc3339(dt: &DateTime<FixedOffset>) -> String {
     dt.format(RFC3339_NO_MILLIS).to_string()
 }
 
+/// Renders a date/time in the selected `--time-format`.
+pub(crate) fn format_datetime(format: &TimeFormat, dt: &DateTime<FixedOffset>) -> String {
+    match format {
+        TimeFormat::Rfc3339 => format_rfc3339(dt),
+        TimeFormat::Rfc3339Millis => dt.format(RFC3339_MILLIS).to_string(),
+        TimeFormat::Unix => dt.timestamp().to_string(),
+        TimeFormat::UnixMs => dt.timestamp_millis().to_string(),
+        TimeFormat::Utc => dt.with_timezone(&Utc).format(RFC3339_UTC).to_string(),
+        TimeFormat::Custom(pattern) => dt.format(pattern).to_string(),
+    }
+}
+
+/// Formatted date/time strings for one `--time-format`, keyed by instant and offset.
+pub(crate) struct DateTimeCache {
+    format: TimeFormat,
+    entries: AHashMap<DateTime<FixedOffset>, String>,
+}
+
+impl DateTimeCache {
+    pub(crate) fn with_capacity(format: &TimeFormat, capacity: usize) -> Self {
+        Self {
+            format: format.clone(),
+            entries: AHashMap::with_capacity(capacity),
+        }
+    }
+
+    pub(crate) fn get(&mut self, dt: &DateTime<FixedOffset>) -> &str {
+        let format = &self.format;
+        self.entries
+            .entry(*dt)
+            .or_insert_with(|| format_datetime(format, dt))
+    }
+}
+
+/// A date/time JSON value: a number in the epoch formats, otherwise the formatted string.
+enum JsonTime<'a> {
+    Epoch(i64),
+    Text(&'a str),
+}
+
+impl serde::Serialize for JsonTime<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Epoch(value) => serializer.serialize_i64(*value),
+            Self::Text(text) => serializer.serialize_str(text),
+        }
+    }
+}
+
 fn round_f64(value: f64, decimals: u32) -> f64 {
     let factor = 10_f64.powi(decimals as i32);
     (value * factor).round() / factor
@@ -130,8 +190,7 @@ fn set_cached_datetime(
     cache: &mut DateTimeCache,
     dt: &DateTime<FixedOffset>,
 ) {
-    let formatted = cache.entry(*dt).or_insert_with(|| format_rfc3339(dt));
-    set_field(out, idx, formatted);
+    set_field(out, idx, cache.get(dt));
 }
 
 fn set_cached_optional_datetime(
@@ -151,18 +210,19 @@ fn set_formatted_f64(out: &mut Vec<String>, idx: usize, value: f64, decimals: u3
     format_f64_fixed_into(ensure_field(out, idx), value, decimals);
 }
 
-fn cached_datetime<'a>(cache: &'a mut DateTimeCache, dt: &DateTime<FixedOffset>) -> &'a str {
-    cache.entry(*dt).or_insert_with(|| format_rfc3339(dt))
+fn cached_datetime<'a>(cache: &'a mut DateTimeCache, dt: &DateTime<FixedOffset>) -> JsonTime<'a> {
+    match cache.format {
+        TimeFormat::Unix => JsonTime::Epoch(dt.timestamp()),
+        TimeFormat::UnixMs => JsonTime::Epoch(dt.timestamp_millis()),
+        _ => JsonTime::Text(cache.get(dt)),
+    }
 }
 
 fn cached_optional_datetime<'a>(
     cache: &'a mut DateTimeCache,
     dt: Option<&DateTime<FixedOffset>>,
-) -> Option<&'a str> {
-    match dt {
-        Some(dt) => Some(cached_datetime(cache, dt)),
-        None => None,
-    }
+) -> Option<JsonTime<'a>> {
+    dt.map(|dt| cached_datetime(cache, dt))
 }
 
 fn extract_sunrise_times<T>(result: &SunriseResult<T>) -> (Option<&T>, &T, Option<&T>) {
@@ -178,6 +238,215 @@ fn extract_sunrise_times<T>(result: &SunriseResult<T>) -> (Option<&T>, &T, Optio
     }
 }
 
+fn obstructed_str(obstructed: Option<bool>) -> &'static str {
+    match obstructed {
+        Some(true) => "true",
+        Some(false) => "false",
+        None => "",
+    }
+}
+
+/// Irradiance column names with their output decimals, in [`irradiance_values`] order.
+const IRRADIANCE_COLUMNS: [(&str, u32); 5] = [
+    ("extraterrestrial", 2),
+    ("air_mass", 4),
+    ("ghi", 2),
+    ("dni", 2),
+    ("dhi", 2),
+];
+
+pub(crate) fn irradiance_values(irradiance: Option<ClearSkyIrradiance>) -> [Option<f64>; 5] {
+    irradiance
+        .map(|irradiance| {
+            [
+                Some(irradiance.extraterrestrial),
+                irradiance.air_mass,
+                Some(irradiance.ghi),
+                Some(irradiance.dni),
+                Some(irradiance.dhi),
+            ]
+        })
+        .unwrap_or_default()
+}
+
+/// Incidence column names with their output decimals, in [`incidence_values`] order.
+pub(crate) fn incidence_columns(tracker: Tracker) -> &'static [(&'static str, u32)] {
+    match tracker {
+        Tracker::Fixed => &[("incidence", 4), ("cos_incidence", 6)],
+        Tracker::SingleAxis => &[
+            ("tracker_rotation", 4),
+            ("incidence", 4),
+            ("cos_incidence", 6),
+        ],
+        Tracker::DualAxis => &[
+            ("tracker_tilt", 4),
+            ("tracker_azimuth", 4),
+            ("incidence", 4),
+            ("cos_incidence", 6),
+        ],
+    }
+}
+
+pub(crate) fn incidence_values(
+    incidence: Option<SurfaceIncidence>,
+    tracker: Tracker,
+) -> Vec<Option<f64>> {
+    let Some(incidence) = incidence else {
+        return vec![None; incidence_columns(tracker).len()];
+    };
+    let mut values = match tracker {
+        Tracker::Fixed => Vec::with_capacity(2),
+        Tracker::SingleAxis => vec![incidence.rotation],
+        Tracker::DualAxis => vec![incidence.tilt, incidence.azimuth],
+    };
+    values.extend([incidence.incidence, incidence.cos_incidence()]);
+    values
+}
+
+/// Site label columns for locations read from a feature file.
+pub(crate) const SITE_COLUMNS: [&str; 2] = ["id", "name"];
+
+fn set_site_fields(out: &mut Vec<String>, idx: usize, site: Option<&Site>) {
+    set_field(
+        out,
+        idx,
+        site.and_then(|site| site.id.as_deref()).unwrap_or(""),
+    );
+    set_field(
+        out,
+        idx + 1,
+        site.and_then(|site| site.name.as_deref()).unwrap_or(""),
+    );
+}
+
+fn serialize_site<M: SerializeMap>(map: &mut M, site: Option<&Site>) -> Result<(), String> {
+    map.serialize_entry("id", &site.and_then(|site| site.id.as_deref()))
+        .map_err(|e| e.to_string())?;
+    map.serialize_entry("name", &site.and_then(|site| site.name.as_deref()))
+        .map_err(|e| e.to_string())
+}
+
+/// Shadow column names with their output decimals, in [`shadow_values`] order.
+const SHADOW_COLUMNS: [(&str, u32); 4] = [
+    ("shadow_length", 3),
+    ("shadow_azimuth", 4),
+    ("shadow_east", 3),
+    ("shadow_north", 3),
+];
+
+pub(crate) fn shadow_values(shadow: Option<Shadow>) -> [Option<f64>; 4] {
+    shadow
+        .map(|shadow| {
+            [
+                Some(shadow.length),
+                Some(shadow.azimuth),
+                Some(shadow.east),
+                Some(shadow.north),
+            ]
+        })
+        .unwrap_or_default()
+}
+
+/// Geometry column names with their output decimals, in [`geometry_values`] order.
+const GEOMETRY_COLUMNS: [(&str, u32); 7] = [
+    ("declination", 4),
+    ("right_ascension", 4),
+    ("hour_angle", 4),
+    ("equation_of_time", 4),
+    ("earth_sun_distance", 7),
+    ("julian_day", 6),
+    ("sidereal_time", 4),
+];
+
+pub(crate) fn geometry_values(geometry: Option<SolarGeometry>) -> [Option<f64>; 7] {
+    geometry
+        .map(|geometry| {
+            [
+                geometry.declination,
+                geometry.right_ascension,
+                geometry.hour_angle,
+                geometry.equation_of_time,
+                geometry.earth_sun_distance,
+                geometry.julian_day,
+                geometry.sidereal_time,
+            ]
+            .map(Some)
+        })
+        .unwrap_or_default()
+}
+
+/// Day statistics columns, in [`day_stats_values`] order with `noon_elevation` second to last.
+pub(crate) const DAY_STATS_COLUMNS: [&str; 6] = [
+    "day_length",
+    "civil_twilight",
+    "nautical_twilight",
+    "astronomical_twilight",
+    "noon_elevation",
+    "day_length_change",
+];
+
+/// Returns the duration columns (the last one being `day_length_change`) and `noon_elevation`.
+pub(crate) fn day_stats_values(stats: Option<DayStats>) -> ([Option<i64>; 5], Option<f64>) {
+    match stats {
+        Some(stats) => (
+            [
+                Some(stats.day_length),
+                Some(stats.civil_twilight),
+                Some(stats.nautical_twilight),
+                Some(stats.astronomical_twilight),
+                Some(stats.day_length_change),
+            ],
+            Some(stats.noon_elevation),
+        ),
+        None => ([None; 5], None),
+    }
+}
+
+/// Golden-hour and blue-hour columns, in [`photo_hours_values`] order.
+pub(crate) const PHOTO_HOURS_COLUMNS: [&str; 8] = [
+    "golden_morning_start",
+    "golden_morning_end",
+    "golden_evening_start",
+    "golden_evening_end",
+    "blue_morning_start",
+    "blue_morning_end",
+    "blue_evening_start",
+    "blue_evening_end",
+];
+
+pub(crate) fn photo_hours_values(hours: Option<PhotoHours>) -> [Option<DateTime<FixedOffset>>; 8] {
+    hours.map_or([None; 8], |hours| {
+        [
+            hours.golden_morning_start,
+            hours.golden_morning_end,
+            hours.golden_evening_start,
+            hours.golden_evening_end,
+            hours.blue_morning_start,
+            hours.blue_morning_end,
+            hours.blue_evening_start,
+            hours.blue_evening_end,
+        ]
+    })
+}
+
+/// `--horizons` column names in output order.
+pub(crate) fn horizon_columns(horizons: &[CustomHorizon]) -> impl Iterator<Item = &str> {
+    horizons.iter().flat_map(CustomHorizon::columns)
+}
+
+/// `--horizons` times in column order; rows without them yield empty values.
+pub(crate) fn horizon_values<'a>(
+    times: &'a [[Option<DateTime<FixedOffset>>; 2]],
+    horizon_count: usize,
+) -> impl Iterator<Item = Option<DateTime<FixedOffset>>> + 'a {
+    let padding = horizon_count.saturating_sub(times.len());
+    times
+        .iter()
+        .flatten()
+        .copied()
+        .chain(std::iter::repeat_n(None, padding * 2))
+}
+
 fn sunrise_type_str(result: &SunriseResult<impl std::any::Any>) -> &'static str {
     match result {
         SunriseResult::RegularDay { .. } => "NORMAL",
@@ -194,6 +463,12 @@ pub(crate) struct PositionRow {
     pub deltat: f64,
     pub azimuth: f64,
     pub zenith: f64,
+    pub obstructed: Option<bool>,
+    pub irradiance: Option<ClearSkyIrradiance>,
+    pub incidence: Option<SurfaceIncidence>,
+    pub shadow: Option<Shadow>,
+    pub geometry: Option<SolarGeometry>,
+    pub site: Option<Arc<Site>>,
 }
 
 impl PositionRow {
@@ -215,6 +490,11 @@ impl PositionRow {
     ) {
         let mut idx = 0;
 
+        if layout.include_site {
+            set_site_fields(out, idx, self.site.as_deref());
+            idx += SITE_COLUMNS.len();
+        }
+
         if layout.show_inputs {
             set_cached_f64_fixed(out, idx, fixed_decimal_cache, self.lat, 5);
             idx += 1;
@@ -252,6 +532,7 @@ impl PositionRow {
         idx += 1;
 
         if layout.show_inputs {
+            set_dst_policy_field(out, &mut idx, layout.dst_policy);
             set_cached_f64_fixed(out, idx, fixed_decimal_cache, self.deltat, 3);
             idx += 1;
         }
@@ -260,26 +541,69 @@ impl PositionRow {
         idx += 1;
         set_formatted_f64(out, idx, self.angle(layout.uses_elevation_angle()), 4);
         idx += 1;
+        if layout.include_obstructed {
+            set_field(out, idx, obstructed_str(self.obstructed));
+            idx += 1;
+        }
+        if layout.include_irradiance {
+            for (value, (_, decimals)) in irradiance_values(self.irradiance)
+                .into_iter()
+                .zip(IRRADIANCE_COLUMNS)
+            {
+                match value {
+                    Some(value) => set_formatted_f64(out, idx, value, decimals),
+                    None => set_field(out, idx, ""),
+                }
+                idx += 1;
+            }
+        }
+        if let Some(tracker) = layout.tracker {
+            for (value, (_, decimals)) in incidence_values(self.incidence, tracker)
+                .into_iter()
+                .zip(incidence_columns(tracker))
+            {
+                match value {
+                    Some(value) => set_formatted_f64(out, idx, value, *decimals),
+                    None => set_field(out, idx, ""),
+                }
+                idx += 1;
+            }
+        }
+        if layout.include_shadow {
+            for (value, (_, decimals)) in shadow_values(self.shadow).into_iter().zip(SHADOW_COLUMNS)
+            {
+                match value {
+                    Some(value) => set_formatted_f64(out, idx, value, decimals),
+                    None => set_field(out, idx, ""),
+                }
+                idx += 1;
+            }
+        }
+        if layout.include_geometry {
+            for (value, (_, decimals)) in geometry_values(self.geometry)
+                .into_iter()
+                .zip(GEOMETRY_COLUMNS)
+            {
+                match value {
+                    Some(value) => set_formatted_f64(out, idx, value, decimals),
+                    None => set_field(out, idx, ""),
+                }
+                idx += 1;
+            }
+        }
         out.truncate(idx);
     }
 
-    fn write_json_line(
+    fn serialize_json_fields<M: SerializeMap>(
         &self,
         params: &Parameters,
         layout: PositionLayout,
-        writer: &mut dyn std::io::Write,
+        map: &mut M,
         datetime_cache: &mut DateTimeCache,
     ) -> Result<(), String> {
-        let field_count = match (layout.show_inputs, layout.include_refraction) {
-            (true, true) => 9,
-            (true, false) => 7,
-            (false, _) => 3,
-        };
-
-        let mut serializer = serde_json::Serializer::new(&mut *writer);
-        let mut map = serializer
-            .serialize_map(Some(field_count))
-            .map_err(|e| e.to_string())?;
+        if layout.include_site {
+            serialize_site(map, self.site.as_deref())?;
+        }
 
         if layout.show_inputs {
             map.serialize_entry("latitude", &self.lat)
@@ -301,6 +625,7 @@ impl PositionRow {
             .map_err(|e| e.to_string())?;
 
         if layout.show_inputs {
+            serialize_dst_policy(map, layout.dst_policy)?;
             map.serialize_entry("deltaT", &self.deltat)
                 .map_err(|e| e.to_string())?;
         }
@@ -312,8 +637,46 @@ impl PositionRow {
             &round_f64(self.angle(layout.uses_elevation_angle()), 4),
         )
         .map_err(|e| e.to_string())?;
-        map.end().map_err(|e| e.to_string())?;
-        writeln!(writer).map_err(|e| e.to_string())
+        if layout.include_obstructed {
+            map.serialize_entry("obstructed", &self.obstructed)
+                .map_err(|e| e.to_string())?;
+        }
+        if layout.include_irradiance {
+            for (value, (name, decimals)) in irradiance_values(self.irradiance)
+                .into_iter()
+                .zip(IRRADIANCE_COLUMNS)
+            {
+                map.serialize_entry(name, &value.map(|value| round_f64(value, decimals)))
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+        if let Some(tracker) = layout.tracker {
+            for (value, (name, decimals)) in incidence_values(self.incidence, tracker)
+                .into_iter()
+                .zip(incidence_columns(tracker))
+            {
+                map.serialize_entry(name, &value.map(|value| round_f64(value, *decimals)))
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+        if layout.include_shadow {
+            for (value, (name, decimals)) in
+                shadow_values(self.shadow).into_iter().zip(SHADOW_COLUMNS)
+            {
+                map.serialize_entry(name, &value.map(|value| round_f64(value, decimals)))
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+        if layout.include_geometry {
+            for (value, (name, decimals)) in geometry_values(self.geometry)
+                .into_iter()
+                .zip(GEOMETRY_COLUMNS)
+            {
+                map.serialize_entry(name, &value.map(|value| round_f64(value, decimals)))
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+        Ok(())
     }
 }
 
@@ -333,6 +696,11 @@ pub(crate) struct SunriseRow {
     pub nautical_end: Option<DateTime<FixedOffset>>,
     pub astro_start: Option<DateTime<FixedOffset>>,
     pub astro_end: Option<DateTime<FixedOffset>>,
+    pub stats: Option<DayStats>,
+    pub photo_hours: Option<PhotoHours>,
+    /// Start and end times for each `--horizons` entry.
+    pub horizons: Vec<[Option<DateTime<FixedOffset>>; 2]>,
+    pub site: Option<Arc<Site>>,
 }
 
 impl SunriseRow {
@@ -346,6 +714,11 @@ impl SunriseRow {
     ) {
         let mut idx = 0;
 
+        if layout.include_site {
+            set_site_fields(out, idx, self.site.as_deref());
+            idx += SITE_COLUMNS.len();
+        }
+
         if layout.show_inputs {
             set_cached_f64_fixed(out, idx, fixed_decimal_cache, self.lat, 5);
             idx += 1;
@@ -353,6 +726,7 @@ impl SunriseRow {
             idx += 1;
             set_cached_datetime(out, idx, datetime_cache, &self.date_time);
             idx += 1;
+            set_dst_policy_field(out, &mut idx, layout.dst_policy);
             set_cached_f64_fixed(out, idx, fixed_decimal_cache, self.deltat, 3);
             idx += 1;
         } else {
@@ -383,23 +757,54 @@ impl SunriseRow {
             set_cached_optional_datetime(out, idx, datetime_cache, self.astro_end.as_ref());
             idx += 1;
         }
+
+        if layout.include_photo_hours {
+            for time in photo_hours_values(self.photo_hours) {
+                set_cached_optional_datetime(out, idx, datetime_cache, time.as_ref());
+                idx += 1;
+            }
+        }
+
+        for time in horizon_values(&self.horizons, layout.horizon_count) {
+            set_cached_optional_datetime(out, idx, datetime_cache, time.as_ref());
+            idx += 1;
+        }
+
+        if layout.include_stats {
+            let (durations, noon_elevation) = day_stats_values(self.stats);
+            for duration in &durations[..4] {
+                set_field(
+                    out,
+                    idx,
+                    &duration.map(|d| d.to_string()).unwrap_or_default(),
+                );
+                idx += 1;
+            }
+            match noon_elevation {
+                Some(value) => set_formatted_f64(out, idx, value, 4),
+                None => set_field(out, idx, ""),
+            }
+            idx += 1;
+            set_field(
+                out,
+                idx,
+                &durations[4].map(|d| d.to_string()).unwrap_or_default(),
+            );
+            idx += 1;
+        }
         out.truncate(idx);
     }
 
-    fn write_json_line(
+    fn serialize_json_fields<M: SerializeMap>(
         &self,
-        _params: &Parameters,
+        params: &Parameters,
         layout: SunriseLayout,
-        writer: &mut dyn std::io::Write,
+        map: &mut M,
         datetime_cache: &mut DateTimeCache,
     ) -> Result<(), String> {
-        let field_count =
-            if layout.show_inputs { 8 } else { 5 } + usize::from(layout.include_twilight) * 6;
-
-        let mut serializer = serde_json::Serializer::new(&mut *writer);
-        let mut map = serializer
-            .serialize_map(Some(field_count))
-            .map_err(|e| e.to_string())?;
+        if layout.include_site {
+            serialize_site(map, self.site.as_deref())?;
+        }
 
         if layout.show_inputs {
             map.serialize_entry("latitude", &self.lat)
@@ -409,6 +814,7 @@ impl SunriseRow {
             let date_time = cached_datetime(datetime_cache, &self.date_time);
             map.serialize_entry("dateTime", &date_time)
                 .map_err(|e| e.to_string())?;
+            serialize_dst_policy(map, layout.dst_policy)?;
             map.serialize_entry("deltaT", &self.deltat)
                 .map_err(|e| e.to_string())?;
         } else {
@@ -453,115 +859,813 @@ impl SunriseRow {
                 .map_err(|e| e.to_string())?;
         }
 
-        map.end().map_err(|e| e.to_string())?;
-        writeln!(writer).map_err(|e| e.to_string())
-    }
-}
+        if layout.include_photo_hours {
+            for (name, time) in PHOTO_HOURS_COLUMNS
+                .into_iter()
+                .zip(photo_hours_values(self.photo_hours))
+            {
+                let time = cached_optional_datetime(datetime_cache, time.as_ref());
+                map.serialize_entry(name, &time)
+                    .map_err(|e| e.to_string())?;
+            }
+        }
 
-pub(crate) fn position_angle_label(elevation_angle: bool) -> &'static str {
-    if elevation_angle {
-        "elevation-angle"
-    } else {
-        "zenith"
+        for (name, time) in horizon_columns(&params.calculation.horizons)
+            .zip(horizon_values(&self.horizons, layout.horizon_count))
+        {
+            let time = cached_optional_datetime(datetime_cache, time.as_ref());
+            map.serialize_entry(name, &time)
+                .map_err(|e| e.to_string())?;
+        }
+
+        if layout.include_stats {
+            let (durations, noon_elevation) = day_stats_values(self.stats);
+            for (name, duration) in DAY_STATS_COLUMNS[..4].iter().zip(&durations[..4]) {
+                map.serialize_entry(name, duration)
+                    .map_err(|e| e.to_string())?;
+            }
+            map.serialize_entry(
+                "noon_elevation",
+                &noon_elevation.map(|value| round_f64(value, 4)),
+            )
+            .map_err(|e| e.to_string())?;
+            map.serialize_entry("day_length_change", &durations[4])
+                .map_err(|e| e.to_string())?;
+        }
+
+        Ok(())
     }
 }
 
-#[derive(Copy, Clone)]
-pub(crate) struct PositionLayout {
-    pub show_inputs: bool,
-    pub include_refraction: bool,
-    elevation_angle: bool,
+#[derive(Clone)]
+pub(crate) struct TransitionRow {
+    pub lat: f64,
+    pub lon: f64,
+    pub datetime: DateTime<FixedOffset>,
+    pub deltat: f64,
+    pub before: &'static str,
+    pub after: &'static str,
+    pub horizon: f64,
+    pub site: Option<Arc<Site>>,
 }
 
-impl PositionLayout {
-    pub(crate) fn from_params(params: &Parameters) -> Self {
-        Self {
-            show_inputs: params.output.should_show_inputs(),
-            include_refraction: params.environment.refraction,
-            elevation_angle: params.output.elevation_angle,
+impl TransitionRow {
+    fn fill_csv_values(
+        &self,
+        _params: &Parameters,
+        layout: TransitionLayout,
+        datetime_cache: &mut DateTimeCache,
+        fixed_decimal_cache: &mut FixedDecimalCache,
+        out: &mut Vec<String>,
+    ) {
+        let mut idx = 0;
+
+        if layout.include_site {
+            set_site_fields(out, idx, self.site.as_deref());
+            idx += SITE_COLUMNS.len();
         }
-    }
 
-    pub(crate) fn uses_elevation_angle(self) -> bool {
-        self.elevation_angle
-    }
+        if layout.show_inputs {
+            set_cached_f64_fixed(out, idx, fixed_decimal_cache, self.lat, 5);
+            idx += 1;
+            set_cached_f64_fixed(out, idx, fixed_decimal_cache, self.lon, 5);
+            idx += 1;
+        }
 
-    pub(crate) fn angle_label(self) -> &'static str {
-        position_angle_label(self.elevation_angle)
+        set_cached_datetime(out, idx, datetime_cache, &self.datetime);
+        idx += 1;
+
+        if layout.show_inputs {
+            set_dst_policy_field(out, &mut idx, layout.dst_policy);
+            set_cached_f64_fixed(out, idx, fixed_decimal_cache, self.deltat, 3);
+            idx += 1;
+        }
+
+        set_field(out, idx, self.before);
+        idx += 1;
+        set_field(out, idx, self.after);
+        idx += 1;
+        set_cached_f64_fixed(out, idx, fixed_decimal_cache, self.horizon, 3);
+        idx += 1;
+        out.truncate(idx);
     }
 
-    pub(crate) fn csv_headers(self) -> Vec<&'static str> {
-        let mut headers = Vec::with_capacity(if self.show_inputs {
-            if self.include_refraction { 9 } else { 7 }
-        } else {
-            3
-        });
+    fn serialize_json_fields<M: SerializeMap>(
+        &self,
+        _params: &Parameters,
+        layout: TransitionLayout,
+        map: &mut M,
+        datetime_cache: &mut DateTimeCache,
+    ) -> Result<(), String> {
+        if layout.include_site {
+            serialize_site(map, self.site.as_deref())?;
+        }
 
-        if self.show_inputs {
-            headers.extend(["latitude", "longitude", "elevation"]);
-            if self.include_refraction {
-                headers.extend(["pressure", "temperature"]);
-            }
-            headers.extend(["dateTime", "deltaT"]);
-        } else {
-            headers.push("dateTime");
+        if layout.show_inputs {
+            map.serialize_entry("latitude", &self.lat)
+                .map_err(|e| e.to_string())?;
+            map.serialize_entry("longitude", &self.lon)
+                .map_err(|e| e.to_string())?;
         }
 
-        headers.push("azimuth");
-        headers.push(self.angle_label());
-        headers
+        let datetime = cached_datetime(datetime_cache, &self.datetime);
+        map.serialize_entry("dateTime", &datetime)
+            .map_err(|e| e.to_string())?;
+
+        if layout.show_inputs {
+            serialize_dst_policy(map, layout.dst_policy)?;
+            map.serialize_entry("deltaT", &self.deltat)
+                .map_err(|e| e.to_string())?;
+        }
+
+        map.serialize_entry("state_before", &self.before)
+            .map_err(|e| e.to_string())?;
+        map.serialize_entry("state_after", &self.after)
+            .map_err(|e| e.to_string())?;
+        map.serialize_entry("horizon", &round_f64(self.horizon, 3))
+            .map_err(|e| e.to_string())?;
+        Ok(())
     }
 }
 
-#[derive(Copy, Clone)]
-pub(crate) struct SunriseLayout {
-    pub show_inputs: bool,
-    pub include_twilight: bool,
+#[derive(Clone)]
+pub(crate) struct MoonPositionRow {
+    pub lat: f64,
+    pub lon: f64,
+    pub datetime: DateTime<FixedOffset>,
+    pub deltat: f64,
+    pub position: MoonPosition,
+    pub site: Option<Arc<Site>>,
 }
 
-impl SunriseLayout {
-    pub(crate) fn from_params(params: &Parameters) -> Self {
-        Self {
-            show_inputs: params.output.should_show_inputs(),
-            include_twilight: params.calculation.twilight,
+impl MoonPositionRow {
+    fn fill_csv_values(
+        &self,
+        params: &Parameters,
+        layout: MoonPositionLayout,
+        datetime_cache: &mut DateTimeCache,
+        fixed_decimal_cache: &mut FixedDecimalCache,
+        out: &mut Vec<String>,
+    ) {
+        let mut idx = 0;
+
+        if layout.include_site {
+            set_site_fields(out, idx, self.site.as_deref());
+            idx += SITE_COLUMNS.len();
         }
-    }
 
-    pub(crate) fn csv_headers(self) -> Vec<&'static str> {
-        let mut headers = Vec::with_capacity(if self.show_inputs {
-            if self.include_twilight { 14 } else { 8 }
-        } else if self.include_twilight {
+        if layout.show_inputs {
+            set_cached_f64_fixed(out, idx, fixed_decimal_cache, self.lat, 5);
+            idx += 1;
+            set_cached_f64_fixed(out, idx, fixed_decimal_cache, self.lon, 5);
+            idx += 1;
+            set_cached_f64_fixed(
+                out,
+                idx,
+                fixed_decimal_cache,
+                params.environment.elevation,
+                3,
+            );
+            idx += 1;
+            if layout.include_refraction {
+                set_cached_f64_fixed(
+                    out,
+                    idx,
+                    fixed_decimal_cache,
+                    params.environment.pressure,
+                    3,
+                );
+                idx += 1;
+                set_cached_f64_fixed(
+                    out,
+                    idx,
+                    fixed_decimal_cache,
+                    params.environment.temperature,
+                    3,
+                );
+                idx += 1;
+            }
+        }
+
+        set_cached_datetime(out, idx, datetime_cache, &self.datetime);
+        idx += 1;
+
+        if layout.show_inputs {
+            set_dst_policy_field(out, &mut idx, layout.dst_policy);
+            set_cached_f64_fixed(out, idx, fixed_decimal_cache, self.deltat, 3);
+            idx += 1;
+        }
+
+        set_formatted_f64(out, idx, self.position.azimuth, 4);
+        idx += 1;
+        set_formatted_f64(out, idx, self.position.altitude, 4);
+        idx += 1;
+        set_formatted_f64(out, idx, self.position.distance, 1);
+        idx += 1;
+        idx = set_illumination_fields(out, idx, &self.position.illumination);
+        out.truncate(idx);
+    }
+
+    fn serialize_json_fields<M: SerializeMap>(
+        &self,
+        params: &Parameters,
+        layout: MoonPositionLayout,
+        map: &mut M,
+        datetime_cache: &mut DateTimeCache,
+    ) -> Result<(), String> {
+        if layout.include_site {
+            serialize_site(map, self.site.as_deref())?;
+        }
+
+        if layout.show_inputs {
+            map.serialize_entry("latitude", &self.lat)
+                .map_err(|e| e.to_string())?;
+            map.serialize_entry("longitude", &self.lon)
+                .map_err(|e| e.to_string())?;
+            map.serialize_entry("elevation", &params.environment.elevation)
+                .map_err(|e| e.to_string())?;
+            if layout.include_refraction {
+                map.serialize_entry("pressure", &params.environment.pressure)
+                    .map_err(|e| e.to_string())?;
+                map.serialize_entry("temperature", &params.environment.temperature)
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+
+        let datetime = cached_datetime(datetime_cache, &self.datetime);
+        map.serialize_entry("dateTime", &datetime)
+            .map_err(|e| e.to_string())?;
+
+        if layout.show_inputs {
+            serialize_dst_policy(map, layout.dst_policy)?;
+            map.serialize_entry("deltaT", &self.deltat)
+                .map_err(|e| e.to_string())?;
+        }
+
+        map.serialize_entry("azimuth", &round_f64(self.position.azimuth, 4))
+            .map_err(|e| e.to_string())?;
+        map.serialize_entry("altitude", &round_f64(self.position.altitude, 4))
+            .map_err(|e| e.to_string())?;
+        map.serialize_entry("distance", &round_f64(self.position.distance, 1))
+            .map_err(|e| e.to_string())?;
+        serialize_illumination(map, &self.position.illumination)
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct MoonriseRow {
+    pub lat: f64,
+    pub lon: f64,
+    pub date_time: DateTime<FixedOffset>,
+    pub deltat: f64,
+    pub type_label: &'static str,
+    pub moonrise: Option<DateTime<FixedOffset>>,
+    pub moonset: Option<DateTime<FixedOffset>>,
+    pub illumination: MoonIllumination,
+    pub site: Option<Arc<Site>>,
+}
+
+impl MoonriseRow {
+    fn fill_csv_values(
+        &self,
+        _params: &Parameters,
+        layout: MoonriseLayout,
+        datetime_cache: &mut DateTimeCache,
+        fixed_decimal_cache: &mut FixedDecimalCache,
+        out: &mut Vec<String>,
+    ) {
+        let mut idx = 0;
+
+        if layout.include_site {
+            set_site_fields(out, idx, self.site.as_deref());
+            idx += SITE_COLUMNS.len();
+        }
+
+        if layout.show_inputs {
+            set_cached_f64_fixed(out, idx, fixed_decimal_cache, self.lat, 5);
+            idx += 1;
+            set_cached_f64_fixed(out, idx, fixed_decimal_cache, self.lon, 5);
+            idx += 1;
+        }
+
+        set_cached_datetime(out, idx, datetime_cache, &self.date_time);
+        idx += 1;
+
+        if layout.show_inputs {
+            set_dst_policy_field(out, &mut idx, layout.dst_policy);
+            set_cached_f64_fixed(out, idx, fixed_decimal_cache, self.deltat, 3);
+            idx += 1;
+        }
+
+        set_field(out, idx, self.type_label);
+        idx += 1;
+        set_cached_optional_datetime(out, idx, datetime_cache, self.moonrise.as_ref());
+        idx += 1;
+        set_cached_optional_datetime(out, idx, datetime_cache, self.moonset.as_ref());
+        idx += 1;
+        idx = set_illumination_fields(out, idx, &self.illumination);
+        out.truncate(idx);
+    }
+
+    fn serialize_json_fields<M: SerializeMap>(
+        &self,
+        _params: &Parameters,
+        layout: MoonriseLayout,
+        map: &mut M,
+        datetime_cache: &mut DateTimeCache,
+    ) -> Result<(), String> {
+        if layout.include_site {
+            serialize_site(map, self.site.as_deref())?;
+        }
+
+        if layout.show_inputs {
+            map.serialize_entry("latitude", &self.lat)
+                .map_err(|e| e.to_string())?;
+            map.serialize_entry("longitude", &self.lon)
+                .map_err(|e| e.to_string())?;
+        }
+
+        let date_time = cached_datetime(datetime_cache, &self.date_time);
+        map.serialize_entry("dateTime", &date_time)
+            .map_err(|e| e.to_string())?;
+
+        if layout.show_inputs {
+            serialize_dst_policy(map, layout.dst_policy)?;
+            map.serialize_entry("deltaT", &self.deltat)
+                .map_err(|e| e.to_string())?;
+        }
+
+        map.serialize_entry("type", &self.type_label)
+            .map_err(|e| e.to_string())?;
+        let moonrise = cached_optional_datetime(datetime_cache, self.moonrise.as_ref());
+        map.serialize_entry("moonrise", &moonrise)
+            .map_err(|e| e.to_string())?;
+        let moonset = cached_optional_datetime(datetime_cache, self.moonset.as_ref());
+        map.serialize_entry("moonset", &moonset)
+            .map_err(|e| e.to_string())?;
+        serialize_illumination(map, &self.illumination)
+    }
+}
+
+pub(crate) const MOON_ILLUMINATION_COLUMNS: [&str; 3] =
+    ["illuminated_fraction", "phase_angle", "phase"];
+
+fn set_illumination_fields(
+    out: &mut Vec<String>,
+    idx: usize,
+    illumination: &MoonIllumination,
+) -> usize {
+    set_formatted_f64(out, idx, illumination.illuminated_fraction, 4);
+    set_formatted_f64(out, idx + 1, illumination.phase_angle, 4);
+    set_field(out, idx + 2, illumination.phase.as_str());
+    idx + MOON_ILLUMINATION_COLUMNS.len()
+}
+
+fn serialize_illumination<M: SerializeMap>(
+    map: &mut M,
+    illumination: &MoonIllumination,
+) -> Result<(), String> {
+    map.serialize_entry(
+        "illuminated_fraction",
+        &round_f64(illumination.illuminated_fraction, 4),
+    )
+    .map_err(|e| e.to_string())?;
+    map.serialize_entry("phase_angle", &round_f64(illumination.phase_angle, 4))
+        .map_err(|e| e.to_string())?;
+    map.serialize_entry("phase", illumination.phase.as_str())
+        .map_err(|e| e.to_string())
+}
+
+/// Input column naming the `--dst-policy` that resolved local input times.
+pub(crate) const DST_POLICY_COLUMN: &str = "dstPolicy";
+
+/// Returns the explicitly chosen DST policy when inputs are shown.
+fn shown_dst_policy(params: &Parameters) -> Option<DstPolicy> {
+    params
+        .dst_policy
+        .filter(|_| params.output.should_show_inputs())
+}
+
+fn set_dst_policy_field(out: &mut Vec<String>, idx: &mut usize, policy: Option<DstPolicy>) {
+    if let Some(policy) = policy {
+        set_field(out, *idx, policy.as_str());
+        *idx += 1;
+    }
+}
+
+fn serialize_dst_policy<M: SerializeMap>(
+    map: &mut M,
+    policy: Option<DstPolicy>,
+) -> Result<(), String> {
+    match policy {
+        Some(policy) => map
+            .serialize_entry(DST_POLICY_COLUMN, policy.as_str())
+            .map_err(|e| e.to_string()),
+        None => Ok(()),
+    }
+}
+
+pub(crate) fn position_angle_label(elevation_angle: bool) -> &'static str {
+    if elevation_angle {
+        "elevation-angle"
+    } else {
+        "zenith"
+    }
+}
+
+#[derive(Copy, Clone)]
+pub(crate) struct PositionLayout {
+    pub show_inputs: bool,
+    pub include_refraction: bool,
+    pub include_obstructed: bool,
+    pub include_irradiance: bool,
+    pub tracker: Option<Tracker>,
+    pub include_shadow: bool,
+    pub include_geometry: bool,
+    pub include_site: bool,
+    pub dst_policy: Option<DstPolicy>,
+    elevation_angle: bool,
+}
+
+impl PositionLayout {
+    pub(crate) fn from_params(params: &Parameters) -> Self {
+        Self {
+            show_inputs: params.output.should_show_inputs(),
+            include_refraction: params.environment.refraction,
+            include_obstructed: params.calculation.has_horizon_profile(),
+            include_irradiance: params.calculation.irradiance,
+            tracker: params.calculation.surface.map(|surface| surface.tracker),
+            include_shadow: params.calculation.shadow_height.is_some(),
+            include_geometry: params.calculation.geometry,
+            include_site: params.output.site_columns,
+            dst_policy: shown_dst_policy(params),
+            elevation_angle: params.output.elevation_angle,
+        }
+    }
+
+    pub(crate) fn uses_elevation_angle(self) -> bool {
+        self.elevation_angle
+    }
+
+    pub(crate) fn angle_label(self) -> &'static str {
+        position_angle_label(self.elevation_angle)
+    }
+
+    pub(crate) fn csv_headers(self) -> Vec<&'static str> {
+        let mut headers = Vec::with_capacity(if self.show_inputs {
+            if self.include_refraction { 10 } else { 8 }
+        } else {
+            4
+        });
+
+        if self.include_site {
+            headers.extend(SITE_COLUMNS);
+        }
+        if self.show_inputs {
+            headers.extend(["latitude", "longitude", "elevation"]);
+            if self.include_refraction {
+                headers.extend(["pressure", "temperature"]);
+            }
+            headers.push("dateTime");
+            headers.extend(self.dst_policy.map(|_| DST_POLICY_COLUMN));
+            headers.push("deltaT");
+        } else {
+            headers.push("dateTime");
+        }
+
+        headers.push("azimuth");
+        headers.push(self.angle_label());
+        if self.include_obstructed {
+            headers.push("obstructed");
+        }
+        if self.include_irradiance {
+            headers.extend(IRRADIANCE_COLUMNS.map(|(name, _)| name));
+        }
+        if let Some(tracker) = self.tracker {
+            headers.extend(incidence_columns(tracker).iter().map(|(name, _)| *name));
+        }
+        if self.include_shadow {
+            headers.extend(SHADOW_COLUMNS.map(|(name, _)| name));
+        }
+        if self.include_geometry {
+            headers.extend(GEOMETRY_COLUMNS.map(|(name, _)| name));
+        }
+        headers
+    }
+}
+
+#[derive(Copy, Clone)]
+pub(crate) struct SunriseLayout {
+    pub show_inputs: bool,
+    pub include_twilight: bool,
+    pub include_photo_hours: bool,
+    pub horizon_count: usize,
+    pub include_stats: bool,
+    pub include_site: bool,
+    pub dst_policy: Option<DstPolicy>,
+}
+
+impl SunriseLayout {
+    pub(crate) fn from_params(params: &Parameters) -> Self {
+        Self {
+            show_inputs: params.output.should_show_inputs(),
+            include_twilight: params.calculation.twilight,
+            include_photo_hours: params.calculation.photo_hours,
+            horizon_count: params.calculation.horizons.len(),
+            include_stats: params.calculation.day_stats,
+            include_site: params.output.site_columns,
+            dst_policy: shown_dst_policy(params),
+        }
+    }
+
+    /// `horizons` are the `--horizons` entries the layout was built from.
+    pub(crate) fn csv_headers(self, horizons: &[CustomHorizon]) -> Vec<&str> {
+        let mut headers = Vec::with_capacity(if self.show_inputs {
+            if self.include_twilight { 14 } else { 8 }
+        } else if self.include_twilight {
             11
         } else {
             5
         });
 
-        if self.show_inputs {
-            headers.extend(["latitude", "longitude", "dateTime", "deltaT"]);
-        } else {
-            headers.push("dateTime");
-        }
+        if self.include_site {
+            headers.extend(SITE_COLUMNS);
+        }
+        if self.show_inputs {
+            headers.extend(["latitude", "longitude", "dateTime"]);
+            headers.extend(self.dst_policy.map(|_| DST_POLICY_COLUMN));
+            headers.push("deltaT");
+        } else {
+            headers.push("dateTime");
+        }
+
+        headers.extend(["type", "sunrise", "transit", "sunset"]);
+        if self.include_twilight {
+            headers.extend([
+                "civil_start",
+                "civil_end",
+                "nautical_start",
+                "nautical_end",
+                "astronomical_start",
+                "astronomical_end",
+            ]);
+        }
+        if self.include_photo_hours {
+            headers.extend(PHOTO_HOURS_COLUMNS);
+        }
+        headers.extend(horizon_columns(horizons));
+        if self.include_stats {
+            headers.extend(DAY_STATS_COLUMNS);
+        }
+        headers
+    }
+}
+
+#[derive(Copy, Clone)]
+pub(crate) struct TransitionLayout {
+    pub show_inputs: bool,
+    pub include_site: bool,
+    pub dst_policy: Option<DstPolicy>,
+}
+
+impl TransitionLayout {
+    pub(crate) fn from_params(params: &Parameters) -> Self {
+        Self {
+            show_inputs: params.output.should_show_inputs(),
+            include_site: params.output.site_columns,
+            dst_policy: shown_dst_policy(params),
+        }
+    }
+
+    pub(crate) fn csv_headers(self) -> Vec<&'static str> {
+        let mut headers = Vec::with_capacity(9);
+        if self.include_site {
+            headers.extend(SITE_COLUMNS);
+        }
+        if self.show_inputs {
+            headers.extend(["latitude", "longitude", "dateTime"]);
+            headers.extend(self.dst_policy.map(|_| DST_POLICY_COLUMN));
+            headers.extend(["deltaT", "state_before", "state_after", "horizon"]);
+        } else {
+            headers.extend(["dateTime", "state_before", "state_after", "horizon"]);
+        }
+        headers
+    }
+}
+
+#[derive(Copy, Clone)]
+pub(crate) struct MoonPositionLayout {
+    pub show_inputs: bool,
+    pub include_refraction: bool,
+    pub include_site: bool,
+    pub dst_policy: Option<DstPolicy>,
+}
+
+impl MoonPositionLayout {
+    pub(crate) fn from_params(params: &Parameters) -> Self {
+        Self {
+            show_inputs: params.output.should_show_inputs(),
+            include_refraction: params.environment.refraction,
+            include_site: params.output.site_columns,
+            dst_policy: shown_dst_policy(params),
+        }
+    }
+
+    pub(crate) fn csv_headers(self) -> Vec<&'static str> {
+        let mut headers = Vec::with_capacity(15);
+        if self.include_site {
+            headers.extend(SITE_COLUMNS);
+        }
+        if self.show_inputs {
+            headers.extend(["latitude", "longitude", "elevation"]);
+            if self.include_refraction {
+                headers.extend(["pressure", "temperature"]);
+            }
+            headers.push("dateTime");
+            headers.extend(self.dst_policy.map(|_| DST_POLICY_COLUMN));
+            headers.push("deltaT");
+        } else {
+            headers.push("dateTime");
+        }
+        headers.extend(["azimuth", "altitude", "distance"]);
+        headers.extend(MOON_ILLUMINATION_COLUMNS);
+        headers
+    }
+}
+
+#[derive(Copy, Clone)]
+pub(crate) struct MoonriseLayout {
+    pub show_inputs: bool,
+    pub include_site: bool,
+    pub dst_policy: Option<DstPolicy>,
+}
+
+impl MoonriseLayout {
+    pub(crate) fn from_params(params: &Parameters) -> Self {
+        Self {
+            show_inputs: params.output.should_show_inputs(),
+            include_site: params.output.site_columns,
+            dst_policy: shown_dst_policy(params),
+        }
+    }
+
+    pub(crate) fn csv_headers(self) -> Vec<&'static str> {
+        let mut headers = Vec::with_capacity(12);
+        if self.include_site {
+            headers.extend(SITE_COLUMNS);
+        }
+        if self.show_inputs {
+            headers.extend(["latitude", "longitude", "dateTime"]);
+            headers.extend(self.dst_policy.map(|_| DST_POLICY_COLUMN));
+            headers.push("deltaT");
+        } else {
+            headers.push("dateTime");
+        }
+        headers.extend(["type", "moonrise", "moonset"]);
+        headers.extend(MOON_ILLUMINATION_COLUMNS);
+        headers
+    }
+}
+
+pub(crate) trait OutputRowExt: Sized {
+    type Layout: Copy;
+
+    fn normalize(result: &CalculationResult) -> Option<Self>;
+    fn lat_lon(&self) -> (f64, f64);
+    /// Instant shown in the `dateTime` column.
+    #[cfg(feature = "sqlite")]
+    fn datetime(&self) -> DateTime<FixedOffset>;
+    fn headers(layout: Self::Layout, params: &Parameters) -> Vec<&str>;
+    fn csv_values(
+        &self,
+        params: &Parameters,
+        layout: Self::Layout,
+        datetime_cache: &mut DateTimeCache,
+        fixed_decimal_cache: &mut FixedDecimalCache,
+        out: &mut Vec<String>,
+    );
+    fn serialize_json<M: SerializeMap>(
+        &self,
+        params: &Parameters,
+        layout: Self::Layout,
+        map: &mut M,
+        datetime_cache: &mut DateTimeCache,
+    ) -> Result<(), String>;
+    fn unexpected_output_error() -> OutputError;
+}
+
+impl OutputRowExt for PositionRow {
+    type Layout = PositionLayout;
+
+    fn normalize(result: &CalculationResult) -> Option<Self> {
+        normalize_position_result(result)
+    }
+
+    fn lat_lon(&self) -> (f64, f64) {
+        (self.lat, self.lon)
+    }
+
+    #[cfg(feature = "sqlite")]
+    fn datetime(&self) -> DateTime<FixedOffset> {
+        self.datetime
+    }
+
+    fn headers(layout: Self::Layout, _params: &Parameters) -> Vec<&str> {
+        layout.csv_headers()
+    }
+
+    fn csv_values(
+        &self,
+        params: &Parameters,
+        layout: Self::Layout,
+        datetime_cache: &mut DateTimeCache,
+        fixed_decimal_cache: &mut FixedDecimalCache,
+        out: &mut Vec<String>,
+    ) {
+        self.fill_csv_values(params, layout, datetime_cache, fixed_decimal_cache, out);
+    }
+
+    fn serialize_json<M: SerializeMap>(
+        &self,
+        params: &Parameters,
+        layout: Self::Layout,
+        map: &mut M,
+        datetime_cache: &mut DateTimeCache,
+    ) -> Result<(), String> {
+        self.serialize_json_fields(params, layout, map, datetime_cache)
+    }
+
+    fn unexpected_output_error() -> OutputError {
+        OutputError::output("Unexpected calculation result for position output")
+    }
+}
+
+impl OutputRowExt for SunriseRow {
+    type Layout = SunriseLayout;
 
-        headers.extend(["type", "sunrise", "transit", "sunset"]);
-        if self.include_twilight {
-            headers.extend([
-                "civil_start",
-                "civil_end",
-                "nautical_start",
-                "nautical_end",
-                "astronomical_start",
-                "astronomical_end",
-            ]);
-        }
-        headers
+    fn normalize(result: &CalculationResult) -> Option<Self> {
+        normalize_sunrise_result(result)
+    }
+
+    fn lat_lon(&self) -> (f64, f64) {
+        (self.lat, self.lon)
+    }
+
+    #[cfg(feature = "sqlite")]
+    fn datetime(&self) -> DateTime<FixedOffset> {
+        self.date_time
+    }
+
+    fn headers(layout: Self::Layout, params: &Parameters) -> Vec<&str> {
+        layout.csv_headers(&params.calculation.horizons)
+    }
+
+    fn csv_values(
+        &self,
+        params: &Parameters,
+        layout: Self::Layout,
+        datetime_cache: &mut DateTimeCache,
+        fixed_decimal_cache: &mut FixedDecimalCache,
+        out: &mut Vec<String>,
+    ) {
+        self.fill_csv_values(params, layout, datetime_cache, fixed_decimal_cache, out);
+    }
+
+    fn serialize_json<M: SerializeMap>(
+        &self,
+        params: &Parameters,
+        layout: Self::Layout,
+        map: &mut M,
+        datetime_cache: &mut DateTimeCache,
+    ) -> Result<(), String> {
+        self.serialize_json_fields(params, layout, map, datetime_cache)
+    }
+
+    fn unexpected_output_error() -> OutputError {
+        OutputError::output("Unexpected calculation result for sunrise output")
     }
 }
 
-trait OutputRowExt: Sized {
-    type Layout: Copy;
+impl OutputRowExt for TransitionRow {
+    type Layout = TransitionLayout;
+
+    fn normalize(result: &CalculationResult) -> Option<Self> {
+        normalize_transition_result(result)
+    }
+
+    fn lat_lon(&self) -> (f64, f64) {
+        (self.lat, self.lon)
+    }
+
+    #[cfg(feature = "sqlite")]
+    fn datetime(&self) -> DateTime<FixedOffset> {
+        self.datetime
+    }
+
+    fn headers(layout: Self::Layout, _params: &Parameters) -> Vec<&str> {
+        layout.csv_headers()
+    }
 
-    fn normalize(result: &CalculationResult) -> Option<Self>;
-    fn headers(layout: Self::Layout) -> Vec<&'static str>;
     fn csv_values(
         &self,
         params: &Parameters,
@@ -569,25 +1673,42 @@ trait OutputRowExt: Sized {
         datetime_cache: &mut DateTimeCache,
         fixed_decimal_cache: &mut FixedDecimalCache,
         out: &mut Vec<String>,
-    );
-    fn write_json(
+    ) {
+        self.fill_csv_values(params, layout, datetime_cache, fixed_decimal_cache, out);
+    }
+
+    fn serialize_json<M: SerializeMap>(
         &self,
         params: &Parameters,
         layout: Self::Layout,
-        writer: &mut dyn std::io::Write,
+        map: &mut M,
         datetime_cache: &mut DateTimeCache,
-    ) -> Result<(), String>;
-    fn unexpected_output_error() -> OutputError;
+    ) -> Result<(), String> {
+        self.serialize_json_fields(params, layout, map, datetime_cache)
+    }
+
+    fn unexpected_output_error() -> OutputError {
+        OutputError::output("Unexpected calculation result for events output")
+    }
 }
 
-impl OutputRowExt for PositionRow {
-    type Layout = PositionLayout;
+impl OutputRowExt for MoonPositionRow {
+    type Layout = MoonPositionLayout;
 
     fn normalize(result: &CalculationResult) -> Option<Self> {
-        normalize_position_result(result)
+        normalize_moon_position_result(result)
+    }
+
+    fn lat_lon(&self) -> (f64, f64) {
+        (self.lat, self.lon)
+    }
+
+    #[cfg(feature = "sqlite")]
+    fn datetime(&self) -> DateTime<FixedOffset> {
+        self.datetime
     }
 
-    fn headers(layout: Self::Layout) -> Vec<&'static str> {
+    fn headers(layout: Self::Layout, _params: &Parameters) -> Vec<&str> {
         layout.csv_headers()
     }
 
@@ -602,29 +1723,38 @@ impl OutputRowExt for PositionRow {
         self.fill_csv_values(params, layout, datetime_cache, fixed_decimal_cache, out);
     }
 
-    fn write_json(
+    fn serialize_json<M: SerializeMap>(
         &self,
         params: &Parameters,
         layout: Self::Layout,
-        writer: &mut dyn std::io::Write,
+        map: &mut M,
         datetime_cache: &mut DateTimeCache,
     ) -> Result<(), String> {
-        self.write_json_line(params, layout, writer, datetime_cache)
+        self.serialize_json_fields(params, layout, map, datetime_cache)
     }
 
     fn unexpected_output_error() -> OutputError {
-        OutputError::from("Unexpected calculation result for position output")
+        OutputError::output("Unexpected calculation result for moon-position output")
     }
 }
 
-impl OutputRowExt for SunriseRow {
-    type Layout = SunriseLayout;
+impl OutputRowExt for MoonriseRow {
+    type Layout = MoonriseLayout;
 
     fn normalize(result: &CalculationResult) -> Option<Self> {
-        normalize_sunrise_result(result)
+        normalize_moonrise_result(result)
+    }
+
+    fn lat_lon(&self) -> (f64, f64) {
+        (self.lat, self.lon)
+    }
+
+    #[cfg(feature = "sqlite")]
+    fn datetime(&self) -> DateTime<FixedOffset> {
+        self.date_time
     }
 
-    fn headers(layout: Self::Layout) -> Vec<&'static str> {
+    fn headers(layout: Self::Layout, _params: &Parameters) -> Vec<&str> {
         layout.csv_headers()
     }
 
@@ -639,18 +1769,18 @@ impl OutputRowExt for SunriseRow {
         self.fill_csv_values(params, layout, datetime_cache, fixed_decimal_cache, out);
     }
 
-    fn write_json(
+    fn serialize_json<M: SerializeMap>(
         &self,
         params: &Parameters,
         layout: Self::Layout,
-        writer: &mut dyn std::io::Write,
+        map: &mut M,
         datetime_cache: &mut DateTimeCache,
     ) -> Result<(), String> {
-        self.write_json_line(params, layout, writer, datetime_cache)
+        self.serialize_json_fields(params, layout, map, datetime_cache)
     }
 
     fn unexpected_output_error() -> OutputError {
-        OutputError::from("Unexpected calculation result for sunrise output")
+        OutputError::output("Unexpected calculation result for moonrise output")
     }
 }
 
@@ -661,6 +1791,12 @@ pub(crate) fn normalize_position_result(result: &CalculationResult) -> Option<Po
         datetime,
         position,
         deltat,
+        obstructed,
+        irradiance,
+        incidence,
+        shadow,
+        geometry,
+        site,
     } = result
     {
         Some(PositionRow {
@@ -670,6 +1806,12 @@ pub(crate) fn normalize_position_result(result: &CalculationResult) -> Option<Po
             deltat: *deltat,
             azimuth: position.azimuth(),
             zenith: position.zenith_angle(),
+            obstructed: *obstructed,
+            irradiance: *irradiance,
+            incidence: *incidence,
+            shadow: *shadow,
+            geometry: *geometry,
+            site: site.clone(),
         })
     } else {
         None
@@ -684,6 +1826,7 @@ pub(crate) fn normalize_sunrise_result(result: &CalculationResult) -> Option<Sun
             date,
             result,
             deltat,
+            site,
         } => {
             let (sunrise, transit, sunset) = extract_sunrise_times(result);
             Some(SunriseRow {
@@ -701,6 +1844,10 @@ pub(crate) fn normalize_sunrise_result(result: &CalculationResult) -> Option<Sun
                 nautical_end: None,
                 astro_start: None,
                 astro_end: None,
+                stats: None,
+                photo_hours: None,
+                horizons: Vec::new(),
+                site: site.clone(),
             })
         }
         CalculationResult::SunriseWithTwilight {
@@ -712,6 +1859,10 @@ pub(crate) fn normalize_sunrise_result(result: &CalculationResult) -> Option<Sun
             nautical,
             astronomical,
             deltat,
+            stats,
+            photo_hours,
+            horizons,
+            site,
         } => {
             let (sunrise, transit, sunset) = extract_sunrise_times(sunrise_sunset);
             let (civil_start, _, civil_end) = extract_sunrise_times(civil);
@@ -733,15 +1884,147 @@ pub(crate) fn normalize_sunrise_result(result: &CalculationResult) -> Option<Sun
                 nautical_end: nautical_end.copied(),
                 astro_start: astro_start.copied(),
                 astro_end: astro_end.copied(),
+                stats: *stats,
+                photo_hours: photo_hours.as_deref().copied(),
+                horizons: horizons
+                    .iter()
+                    .map(|result| {
+                        let (start, _, end) = extract_sunrise_times(result);
+                        [start.copied(), end.copied()]
+                    })
+                    .collect(),
+                site: site.clone(),
             })
         }
+        CalculationResult::SkylineSunrise {
+            lat,
+            lon,
+            date,
+            sunrise,
+            transit,
+            sunset,
+            visible,
+            deltat,
+            site,
+        } => Some(SunriseRow {
+            lat: *lat,
+            lon: *lon,
+            date_time: *date,
+            deltat: *deltat,
+            type_label: match (visible, sunrise, sunset) {
+                (false, _, _) => "ALL_NIGHT",
+                (true, None, None) => "ALL_DAY",
+                _ => "NORMAL",
+            },
+            sunrise: *sunrise,
+            transit: *transit,
+            sunset: *sunset,
+            civil_start: None,
+            civil_end: None,
+            nautical_start: None,
+            nautical_end: None,
+            astro_start: None,
+            astro_end: None,
+            stats: None,
+            photo_hours: None,
+            horizons: Vec::new(),
+            site: site.clone(),
+        }),
         _ => None,
     }
 }
 
+pub(crate) fn normalize_transition_result(result: &CalculationResult) -> Option<TransitionRow> {
+    if let CalculationResult::Transition {
+        lat,
+        lon,
+        datetime,
+        before,
+        after,
+        horizon,
+        deltat,
+        site,
+    } = result
+    {
+        Some(TransitionRow {
+            lat: *lat,
+            lon: *lon,
+            datetime: *datetime,
+            deltat: *deltat,
+            before: before.as_str(),
+            after: after.as_str(),
+            horizon: *horizon,
+            site: site.clone(),
+        })
+    } else {
+        None
+    }
+}
+
+pub(crate) fn normalize_moon_position_result(
+    result: &CalculationResult,
+) -> Option<MoonPositionRow> {
+    if let CalculationResult::MoonPosition {
+        lat,
+        lon,
+        datetime,
+        position,
+        deltat,
+        site,
+    } = result
+    {
+        Some(MoonPositionRow {
+            lat: *lat,
+            lon: *lon,
+            datetime: *datetime,
+            deltat: *deltat,
+            position: *position,
+            site: site.clone(),
+        })
+    } else {
+        None
+    }
+}
+
+pub(crate) fn normalize_moonrise_result(result: &CalculationResult) -> Option<MoonriseRow> {
+    if let CalculationResult::Moonrise {
+        lat,
+        lon,
+        date,
+        moonrise,
+        moonset,
+        above_horizon,
+        illumination,
+        deltat,
+        site,
+    } = result
+    {
+        let type_label = if moonrise.is_some() || moonset.is_some() {
+            "NORMAL"
+        } else if *above_horizon {
+            "ALWAYS_UP"
+        } else {
+            "ALWAYS_DOWN"
+        };
+        Some(MoonriseRow {
+            lat: *lat,
+            lon: *lon,
+            date_time: *date,
+            deltat: *deltat,
+            type_label,
+            moonrise: *moonrise,
+            moonset: *moonset,
+            illumination: *illumination,
+            site: site.clone(),
+        })
+    } else {
+        None
+    }
+}
+
 #[cfg(feature = "parquet")]
 pub fn write_parquet_output<W: std::io::Write + Send>(
-    results: Box<dyn Iterator<Item = Result<CalculationResult, String>>>,
+    results: CalculationStream,
     command: Command,
     params: &Parameters,
     writer: W,
@@ -760,14 +2043,31 @@ where
             writer.write_all(b",").map_err(|e| e.to_string())?;
         }
         first = false;
-        writer
-            .write_all(field.as_ref().as_bytes())
-            .map_err(|e| e.to_string())?;
+        let field = field.as_ref();
+        // Only free-text fields such as site names can need quoting.
+        if field.contains([',', '"', '\n', '\r']) {
+            write!(writer, "\"{}\"", field.replace('"', "\"\"")).map_err(|e| e.to_string())?;
+        } else {
+            writer
+                .write_all(field.as_bytes())
+                .map_err(|e| e.to_string())?;
+        }
     }
     writer.write_all(b"\n").map_err(|e| e.to_string())
 }
 
-fn is_numeric_column(name: &str) -> bool {
+/// Whether `name` holds a time. Crossing columns, including user-labelled `--horizons` ones,
+/// end in `_start` or `_end`.
+pub(crate) fn is_datetime_column(name: &str) -> bool {
+    name.ends_with("_start")
+        || name.ends_with("_end")
+        || matches!(
+            name,
+            "dateTime" | "sunrise" | "transit" | "sunset" | "moonrise" | "moonset"
+        )
+}
+
+pub(crate) fn is_numeric_column(name: &str) -> bool {
     matches!(
         name,
         "latitude"
@@ -779,6 +2079,38 @@ fn is_numeric_column(name: &str) -> bool {
             | "azimuth"
             | "zenith"
             | "elevation-angle"
+            | "horizon"
+            | "extraterrestrial"
+            | "air_mass"
+            | "ghi"
+            | "dni"
+            | "dhi"
+            | "incidence"
+            | "cos_incidence"
+            | "tracker_rotation"
+            | "tracker_tilt"
+            | "tracker_azimuth"
+            | "shadow_length"
+            | "shadow_azimuth"
+            | "shadow_east"
+            | "shadow_north"
+            | "declination"
+            | "right_ascension"
+            | "hour_angle"
+            | "equation_of_time"
+            | "earth_sun_distance"
+            | "julian_day"
+            | "sidereal_time"
+            | "day_length"
+            | "civil_twilight"
+            | "nautical_twilight"
+            | "astronomical_twilight"
+            | "noon_elevation"
+            | "day_length_change"
+            | "altitude"
+            | "distance"
+            | "illuminated_fraction"
+            | "phase_angle"
     )
 }
 
@@ -787,11 +2119,21 @@ fn suggested_column_width(name: &str) -> usize {
         "latitude" | "longitude" => 10,
         "elevation" => 9,
         "pressure" | "temperature" | "deltaT" => 10,
-        "dateTime" => 25,
-        "azimuth" | "zenith" | "elevation-angle" => 10,
+        name if is_datetime_column(name) => 25,
+        "azimuth" | "zenith" | "elevation-angle" | "altitude" => 10,
+        "distance" => 12,
+        "phase" => 15,
         "type" => 8,
-        "sunrise" | "transit" | "sunset" | "civil_start" | "civil_end" | "nautical_start"
-        | "nautical_end" | "astronomical_start" | "astronomical_end" => 25,
+        "state_before" | "state_after" => 21,
+        "id" => 8,
+        "name" => 20,
+        "obstructed" => 10,
+        "extraterrestrial" => 16,
+        "air_mass" | "ghi" | "dni" | "dhi" => 8,
+        "incidence" | "cos_incidence" => 13,
+        "tracker_rotation" | "tracker_tilt" | "tracker_azimuth" => 16,
+        "shadow_length" | "shadow_azimuth" | "shadow_east" | "shadow_north" => 14,
+        "julian_day" => 14,
         _ => name.len(),
     }
 }
@@ -838,24 +2180,77 @@ fn write_pretty_row<W: std::io::Write>(
 }
 
 pub fn dispatch_output(
-    results: Box<dyn Iterator<Item = Result<CalculationResult, String>>>,
+    results: CalculationStream,
+    command: Command,
+    params: &Parameters,
+    flush_each_record: bool,
+) -> Result<usize, OutputError> {
+    #[cfg(feature = "parquet")]
+    if let Some(dir) = &params.output.parquet.dataset.dir {
+        return crate::parquet::write_parquet_dataset(results, command, params, dir)
+            .map_err(OutputError::from);
+    }
+    #[cfg(feature = "sqlite")]
+    if params.output.format == OutputFormat::Sqlite {
+        return crate::sqlite::write_sqlite(results, command, params, flush_each_record);
+    }
+    write_output(
+        results,
+        command,
+        params,
+        std::io::stdout(),
+        flush_each_record,
+    )
+}
+
+/// Writes a calculation stream to `writer` in the format selected by `params`.
+///
+/// Returns the number of records written.
+pub fn write_output<W: std::io::Write + Send>(
+    results: CalculationStream,
     command: Command,
     params: &Parameters,
+    writer: W,
     flush_each_record: bool,
 ) -> Result<usize, OutputError> {
     match params.output.format {
         #[cfg(feature = "parquet")]
         OutputFormat::Parquet => {
-            let stdout = std::io::stdout();
-            return write_parquet_output(results, command, params, stdout)
-                .map_err(|e| OutputError::from(e.to_string()));
+            return write_parquet_output(results, command, params, writer)
+                .map_err(OutputError::from);
+        }
+        #[cfg(feature = "parquet")]
+        OutputFormat::Arrow => {
+            return crate::parquet::write_arrow(
+                results,
+                command,
+                params,
+                writer,
+                flush_each_record,
+            )
+            .map_err(OutputError::from);
+        }
+        #[cfg(feature = "sqlite")]
+        OutputFormat::Sqlite => {
+            return Err(OutputError::from(
+                "SQLite output writes to a database file, not a stream",
+            ));
+        }
+        OutputFormat::Ics => {
+            return match command {
+                Command::Sunrise => {
+                    crate::ics::write_ics(results, params, writer, flush_each_record)
+                }
+                _ => Err(OutputError::from(
+                    "iCalendar output requires the sunrise command",
+                )),
+            };
         }
         _ => {}
     }
 
     use std::io::{BufWriter, Write};
-    let stdout = std::io::stdout();
-    let mut writer = BufWriter::new(stdout.lock());
+    let mut writer = BufWriter::new(writer);
     let result = match command {
         Command::Position => write_rows::<_, PositionRow>(
             results,
@@ -871,15 +2266,242 @@ pub fn dispatch_output(
             &mut writer,
             flush_each_record,
         ),
+        Command::Events => write_rows::<_, TransitionRow>(
+            results,
+            params,
+            TransitionLayout::from_params(params),
+            &mut writer,
+            flush_each_record,
+        ),
+        Command::MoonPosition => write_rows::<_, MoonPositionRow>(
+            results,
+            params,
+            MoonPositionLayout::from_params(params),
+            &mut writer,
+            flush_each_record,
+        ),
+        Command::Moonrise => write_rows::<_, MoonriseRow>(
+            results,
+            params,
+            MoonriseLayout::from_params(params),
+            &mut writer,
+            flush_each_record,
+        ),
     };
     let _ = writer.flush();
     result
 }
 
-fn row_from_result<R: OutputRowExt>(
-    result: Result<CalculationResult, String>,
+pub(crate) fn row_from_result<R: OutputRowExt>(
+    result: Result<CalculationResult, OutputError>,
 ) -> Result<R, OutputError> {
-    R::normalize(&result.map_err(OutputError::from)?).ok_or_else(R::unexpected_output_error)
+    R::normalize(&result?).ok_or_else(R::unexpected_output_error)
+}
+
+/// Full column list for `command` under the current options, in output order.
+pub(crate) fn column_catalogue(command: Command, params: &Parameters) -> Vec<&str> {
+    match command {
+        Command::Position => PositionLayout::from_params(params).csv_headers(),
+        Command::Sunrise => {
+            SunriseLayout::from_params(params).csv_headers(&params.calculation.horizons)
+        }
+        Command::Events => TransitionLayout::from_params(params).csv_headers(),
+        Command::MoonPosition => MoonPositionLayout::from_params(params).csv_headers(),
+        Command::Moonrise => MoonriseLayout::from_params(params).csv_headers(),
+    }
+}
+
+/// Turns on an option that adds columns.
+type EnableColumns = fn(&mut Parameters);
+
+/// Changes to the options that add columns to `command`, each with the option it stands for.
+fn column_options(command: Command) -> Vec<(&'static str, EnableColumns)> {
+    fn surface(params: &mut Parameters, tracker: Tracker) {
+        params.calculation.surface = Some(Surface {
+            tracker,
+            ..Surface::default()
+        });
+    }
+    let mut options: Vec<(&'static str, EnableColumns)> = Vec::new();
+    options.push(("a feature file", |params| params.output.site_columns = true));
+    options.push(("--dst-policy", |params| {
+        params.dst_policy = Some(DstPolicy::default())
+    }));
+    match command {
+        Command::Position => {
+            options.push(("refraction, without --no-refraction", |params| {
+                params.environment.refraction = true
+            }));
+            options.push(("--elevation-angle", |params| {
+                params.output.elevation_angle = true
+            }));
+            options.push(("--horizon-profile", |params| {
+                params.calculation.horizon_profile_file = Some(InputPath::Stdin)
+            }));
+            options.push(("--irradiance", |params| {
+                params.calculation.irradiance = true
+            }));
+            options.push(("--surface-tilt or --surface-azimuth", |params| {
+                surface(params, Tracker::Fixed)
+            }));
+            options.push(("--tracker=single-axis", |params| {
+                surface(params, Tracker::SingleAxis)
+            }));
+            options.push(("--tracker=dual-axis", |params| {
+                surface(params, Tracker::DualAxis)
+            }));
+            options.push(("--shadow-height", |params| {
+                params.calculation.shadow_height = Some(0.0)
+            }));
+            options.push(("--geometry", |params| params.calculation.geometry = true));
+        }
+        Command::Sunrise => {
+            options.push(("--twilight", |params| params.calculation.twilight = true));
+            options.push(("--photo-hours", |params| {
+                params.calculation.photo_hours = true
+            }));
+            options.push(("--horizons", |params| {
+                // A placeholder standing for the columns of any label.
+                if params.calculation.horizons.is_empty() {
+                    params.calculation.horizons = [CustomHorizon::new("<label>", 0.0)].into();
+                }
+            }));
+            options.push(("--day-stats", |params| params.calculation.day_stats = true));
+        }
+        Command::MoonPosition => {
+            options.push(("refraction, without --no-refraction", |params| {
+                params.environment.refraction = true
+            }));
+        }
+        Command::Events | Command::Moonrise => {}
+    }
+    options
+}
+
+/// Every column `command` can write: those of the current options first, then the ones
+/// other options add, each with the option that enables it.
+pub(crate) fn full_column_catalogue(
+    command: Command,
+    params: &Parameters,
+) -> Vec<(String, Option<&'static str>)> {
+    let mut catalogue: Vec<_> = column_catalogue(command, params)
+        .into_iter()
+        .map(|name| (name.to_string(), None))
+        .collect();
+    for (option, enable) in column_options(command) {
+        let mut enabled = params.clone();
+        enable(&mut enabled);
+        for name in column_catalogue(command, &enabled) {
+            if !catalogue.iter().any(|(listed, _)| listed == name) {
+                catalogue.push((name.to_string(), Some(option)));
+            }
+        }
+    }
+    catalogue
+}
+
+/// Positions of the `--columns` selection within `headers`; `None` keeps every column.
+pub(crate) fn column_indices(headers: &[&str], params: &Parameters) -> Option<Vec<usize>> {
+    params.output.columns.as_ref().map(|columns| {
+        columns
+            .iter()
+            .filter_map(|name| headers.iter().position(|header| header == name))
+            .collect()
+    })
+}
+
+pub(crate) fn project<T: Copy>(items: &[T], indices: Option<&[usize]>) -> Vec<T> {
+    match indices {
+        Some(indices) => indices.iter().map(|&index| items[index]).collect(),
+        None => items.to_vec(),
+    }
+}
+
+/// Reorders `values` in place to the selected columns, reusing `scratch` between rows.
+pub(crate) fn project_values(
+    values: &mut Vec<String>,
+    indices: Option<&[usize]>,
+    scratch: &mut Vec<String>,
+) {
+    let Some(indices) = indices else {
+        return;
+    };
+    scratch.clear();
+    scratch.extend(
+        indices
+            .iter()
+            .map(|&index| std::mem::take(&mut values[index])),
+    );
+    std::mem::swap(values, scratch);
+}
+
+/// Map serializer that keeps only the `--columns` entries of a row, in selection order.
+struct JsonColumns<'a> {
+    columns: &'a [String],
+    values: Vec<serde_json::Value>,
+    current: Option<usize>,
+}
+
+impl SerializeMap for JsonColumns<'_> {
+    type Ok = ();
+    type Error = serde_json::Error;
+
+    fn serialize_key<T: ?Sized + serde::Serialize>(&mut self, key: &T) -> Result<(), Self::Error> {
+        let key = serde_json::to_value(key)?;
+        self.current = key
+            .as_str()
+            .and_then(|key| self.columns.iter().position(|column| column == key));
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + serde::Serialize>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        if let Some(index) = self.current.take() {
+            self.values[index] = serde_json::to_value(value)?;
+        }
+        Ok(())
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Writes a row as one JSON object line, limited to the `--columns` selection if any.
+fn write_json_line<R: OutputRowExt>(
+    row: &R,
+    params: &Parameters,
+    layout: R::Layout,
+    writer: &mut dyn std::io::Write,
+    datetime_cache: &mut DateTimeCache,
+) -> Result<(), String> {
+    let mut serializer = serde_json::Serializer::new(&mut *writer);
+    match params.output.columns.as_deref() {
+        Some(columns) => {
+            let mut selected = JsonColumns {
+                columns,
+                values: vec![serde_json::Value::Null; columns.len()],
+                current: None,
+            };
+            row.serialize_json(params, layout, &mut selected, datetime_cache)?;
+            let mut map = serializer
+                .serialize_map(Some(columns.len()))
+                .map_err(|e| e.to_string())?;
+            for (name, value) in columns.iter().zip(&selected.values) {
+                map.serialize_entry(name, value)
+                    .map_err(|e| e.to_string())?;
+            }
+            map.end().map_err(|e| e.to_string())?;
+        }
+        None => {
+            let mut map = serializer.serialize_map(None).map_err(|e| e.to_string())?;
+            row.serialize_json(params, layout, &mut map, datetime_cache)?;
+            map.end().map_err(|e| e.to_string())?;
+        }
+    }
+    writeln!(writer).map_err(|e| e.to_string())
 }
 
 fn header_widths(headers: &[&str], values: &[String]) -> Vec<usize> {
@@ -896,18 +2518,23 @@ fn header_widths(headers: &[&str], values: &[String]) -> Vec<usize> {
 }
 
 fn write_rows<W: std::io::Write, R: OutputRowExt>(
-    results: Box<dyn Iterator<Item = Result<CalculationResult, String>>>,
+    results: CalculationStream,
     params: &Parameters,
     layout: R::Layout,
     writer: &mut W,
     flush_each: bool,
 ) -> Result<usize, OutputError> {
-    let headers = R::headers(layout);
+    let all_headers = R::headers(layout, params);
+    let selection = column_indices(&all_headers, params);
+    let selection = selection.as_deref();
+    let headers = project(&all_headers, selection);
     let mut count = 0;
     let mut header_written = false;
-    let mut datetime_cache = DateTimeCache::with_capacity(2048);
+    let mut datetime_cache = DateTimeCache::with_capacity(&params.output.time_format, 2048);
     let mut fixed_decimal_cache = FixedDecimalCache::with_capacity(256);
     let mut row_values = Vec::new();
+    let mut scratch = Vec::new();
+    let mut properties = Vec::new();
 
     if params.output.format == OutputFormat::Text {
         let mut iter = results;
@@ -923,6 +2550,7 @@ fn write_rows<W: std::io::Write, R: OutputRowExt>(
             &mut fixed_decimal_cache,
             &mut row_values,
         );
+        project_values(&mut row_values, selection, &mut scratch);
 
         let widths = header_widths(&headers, &row_values);
 
@@ -944,6 +2572,7 @@ fn write_rows<W: std::io::Write, R: OutputRowExt>(
                 &mut fixed_decimal_cache,
                 &mut row_values,
             );
+            project_values(&mut row_values, selection, &mut scratch);
             write_pretty_row(writer, &headers, &widths, &row_values)?;
             count += 1;
             if flush_each {
@@ -954,6 +2583,13 @@ fn write_rows<W: std::io::Write, R: OutputRowExt>(
         return Ok(count);
     }
 
+    let geojson = params.output.format == OutputFormat::GeoJson;
+    if geojson {
+        writer
+            .write_all(br#"{"type":"FeatureCollection","features":["#)
+            .map_err(OutputError::from)?;
+    }
+
     for result in results {
         let row = row_from_result::<R>(result)?;
 
@@ -970,14 +2606,28 @@ fn write_rows<W: std::io::Write, R: OutputRowExt>(
                     &mut fixed_decimal_cache,
                     &mut row_values,
                 );
+                project_values(&mut row_values, selection, &mut scratch);
                 write_csv_line(writer, row_values.iter()).map_err(OutputError::from)?;
             }
-            OutputFormat::Json => row
-                .write_json(params, layout, writer, &mut datetime_cache)
-                .map_err(OutputError::from)?,
+            OutputFormat::Json => {
+                write_json_line(&row, params, layout, writer, &mut datetime_cache)
+                    .map_err(OutputError::from)?
+            }
+            OutputFormat::GeoJson => {
+                properties.clear();
+                write_json_line(&row, params, layout, &mut properties, &mut datetime_cache)
+                    .map_err(OutputError::from)?;
+                write_geojson_feature(writer, row.lat_lon(), &properties, count == 0)
+                    .map_err(OutputError::from)?;
+            }
             OutputFormat::Text => unreachable!("handled above"),
+            OutputFormat::Ics => return Err(OutputError::from("Unsupported format")),
             #[cfg(feature = "parquet")]
-            OutputFormat::Parquet => return Err(OutputError::from("Unsupported format")),
+            OutputFormat::Parquet | OutputFormat::Arrow => {
+                return Err(OutputError::from("Unsupported format"));
+            }
+            #[cfg(feature = "sqlite")]
+            OutputFormat::Sqlite => return Err(OutputError::from("Unsupported format")),
         }
 
         count += 1;
@@ -986,9 +2636,37 @@ fn write_rows<W: std::io::Write, R: OutputRowExt>(
         }
     }
 
+    if geojson {
+        let end: &[u8] = if count == 0 { b"]}\n" } else { b"\n]}\n" };
+        writer.write_all(end).map_err(OutputError::from)?;
+    }
+
     Ok(count)
 }
 
+/// Writes a point feature on its own line, with `properties` holding a row's JSON object.
+fn write_geojson_feature<W: std::io::Write>(
+    writer: &mut W,
+    (lat, lon): (f64, f64),
+    properties: &[u8],
+    first: bool,
+) -> Result<(), String> {
+    writer
+        .write_all(if first { b"\n" } else { b",\n" })
+        .map_err(|e| e.to_string())?;
+    writer
+        .write_all(br#"{"type":"Feature","geometry":{"type":"Point","coordinates":"#)
+        .map_err(|e| e.to_string())?;
+    serde_json::to_writer(&mut *writer, &[lon, lat]).map_err(|e| e.to_string())?;
+    writer
+        .write_all(br#"},"properties":"#)
+        .map_err(|e| e.to_string())?;
+    writer
+        .write_all(properties.trim_ascii_end())
+        .map_err(|e| e.to_string())?;
+    writer.write_all(b"}").map_err(|e| e.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1029,8 +2707,7 @@ mod tests {
         };
 
         let calc = crate::position::calculate_position(52.0, 13.4, dt, &params).unwrap();
-        let results: Box<dyn Iterator<Item = Result<CalculationResult, String>>> =
-            Box::new(vec![Ok(calc)].into_iter());
+        let results: CalculationStream = Box::new(vec![Ok(calc)].into_iter());
 
         let mut writer = MockWriter::default();
         write_rows::<_, PositionRow>(
@@ -1074,10 +2751,16 @@ mod tests {
             deltat: 69.123,
             azimuth: 180.12345,
             zenith: 45.98765,
+            obstructed: None,
+            irradiance: None,
+            incidence: None,
+            shadow: None,
+            geometry: None,
+            site: None,
         };
 
         let mut values = Vec::new();
-        let mut datetime_cache = DateTimeCache::new();
+        let mut datetime_cache = DateTimeCache::with_capacity(&TimeFormat::Rfc3339, 0);
         let mut decimal_cache = FixedDecimalCache::new();
         row.csv_values(
             &params,
@@ -1135,10 +2818,14 @@ mod tests {
             nautical_end: Some(dt + chrono::Duration::hours(22)),
             astro_start: Some(dt + chrono::Duration::hours(1)),
             astro_end: Some(dt + chrono::Duration::hours(23)),
+            stats: None,
+            photo_hours: None,
+            horizons: Vec::new(),
+            site: None,
         };
 
         let mut values = Vec::new();
-        let mut datetime_cache = DateTimeCache::new();
+        let mut datetime_cache = DateTimeCache::with_capacity(&TimeFormat::Rfc3339, 0);
         let mut decimal_cache = FixedDecimalCache::new();
         row.csv_values(
             &params,
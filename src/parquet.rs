@@ -1,58 +1,238 @@
-//! Parquet output format support.
+//! Parquet and Arrow IPC output format support.
 
-use crate::compute::CalculationResult;
-use crate::data::{Command, Parameters};
+use crate::compute::{CalculationResult, CalculationStream};
+use crate::data::config::ParquetDataset;
+use crate::data::{
+    Command, DstPolicy, InputPath, Parameters, ParquetCompression, ParquetTimestamps, Site,
+};
+use crate::moon::MoonIllumination;
 use crate::output::{
-    PositionLayout, SunriseLayout, format_rfc3339, normalize_position_result,
-    normalize_sunrise_result,
+    DateTimeCache, MoonPositionLayout, MoonPositionRow, MoonriseLayout, MoonriseRow,
+    PositionLayout, PositionRow, SunriseLayout, SunriseRow, TransitionLayout, TransitionRow,
+    day_stats_values, geometry_values, horizon_values, incidence_columns, incidence_values,
+    irradiance_values, is_datetime_column, normalize_moon_position_result,
+    normalize_moonrise_result, normalize_position_result, normalize_sunrise_result,
+    normalize_transition_result, photo_hours_values, shadow_values,
 };
 use ahash::AHashMap;
-use arrow::array::{ArrayRef, Float64Builder, StringBuilder};
-use arrow::datatypes::{DataType, Field, Schema};
+use arrow::array::{
+    ArrayRef, BooleanBuilder, Float64Builder, Int32Builder, Int64Builder, StringBuilder,
+    TimestampSecondBuilder,
+};
+use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+use arrow::ipc::writer::StreamWriter;
 use arrow::record_batch::RecordBatch;
+use chrono::Datelike;
 use parquet::arrow::ArrowWriter;
-use parquet::basic::Compression;
+use parquet::basic::{Compression, GzipLevel, ZstdLevel};
+use parquet::file::metadata::KeyValue;
 use parquet::file::properties::WriterProperties;
-use std::io::{self, Write};
+use std::collections::hash_map::Entry;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 const BATCH_SIZE: usize = 8192;
-type DateTimeCache = AHashMap<chrono::DateTime<chrono::FixedOffset>, String>;
+const UTC: &str = "UTC";
+/// Companion column holding the UTC offset of `dateTime` with native timestamps.
+const UTC_OFFSET_COLUMN: &str = "utc_offset";
 
-fn cached_datetime<'a>(
-    cache: &'a mut DateTimeCache,
-    dt: &chrono::DateTime<chrono::FixedOffset>,
-) -> &'a str {
-    cache.entry(*dt).or_insert_with(|| format_rfc3339(dt))
+/// Name of the column holding the UTC offset of the native timestamp column `name`.
+fn utc_offset_column(name: &str) -> String {
+    if name == "dateTime" {
+        UTC_OFFSET_COLUMN.to_string()
+    } else {
+        format!("{name}_{UTC_OFFSET_COLUMN}")
+    }
 }
 
-fn append_time(
-    builder: &mut StringBuilder,
-    time: &chrono::DateTime<chrono::FixedOffset>,
-    datetime_cache: &mut DateTimeCache,
-) {
-    builder.append_value(cached_datetime(datetime_cache, time));
+/// Builder for one date/time column in the selected [`ParquetTimestamps`] encoding.
+///
+/// Native timestamps are UTC, so each keeps its local offset in a companion column.
+enum TimeBuilder {
+    String(StringBuilder),
+    Native(TimestampSecondBuilder, Int32Builder),
+}
+
+impl TimeBuilder {
+    fn new(encoding: ParquetTimestamps) -> Self {
+        match encoding {
+            ParquetTimestamps::String => {
+                Self::String(StringBuilder::with_capacity(BATCH_SIZE, BATCH_SIZE * 30))
+            }
+            ParquetTimestamps::Native => Self::Native(
+                TimestampSecondBuilder::with_capacity(BATCH_SIZE).with_timezone(UTC),
+                Int32Builder::with_capacity(BATCH_SIZE),
+            ),
+        }
+    }
+
+    fn append(
+        &mut self,
+        time: Option<&chrono::DateTime<chrono::FixedOffset>>,
+        datetime_cache: &mut DateTimeCache,
+    ) {
+        match self {
+            Self::String(builder) => {
+                builder.append_option(time.map(|time| datetime_cache.get(time)))
+            }
+            Self::Native(builder, offset) => {
+                builder.append_option(time.map(|time| time.timestamp()));
+                offset.append_option(time.map(|time| time.offset().local_minus_utc()));
+            }
+        }
+    }
+
+    fn finish(&mut self, arrays: &mut Vec<ArrayRef>) {
+        match self {
+            Self::String(builder) => finish_string(builder, BATCH_SIZE * 30, arrays),
+            Self::Native(builder, offset) => {
+                arrays.push(Arc::new(builder.finish()) as ArrayRef);
+                arrays.push(Arc::new(offset.finish()) as ArrayRef);
+            }
+        }
+    }
+}
+
+/// Builders for `dateTime` and the `dstPolicy` input column when shown.
+struct DateTimeBuilders {
+    time: TimeBuilder,
+    dst_policy: Option<(DstPolicy, StringBuilder)>,
+}
+
+impl DateTimeBuilders {
+    fn new(encoding: ParquetTimestamps, dst_policy: Option<DstPolicy>) -> Self {
+        Self {
+            time: TimeBuilder::new(encoding),
+            dst_policy: dst_policy.map(|policy| {
+                (
+                    policy,
+                    StringBuilder::with_capacity(BATCH_SIZE, BATCH_SIZE * 8),
+                )
+            }),
+        }
+    }
+
+    fn append(
+        &mut self,
+        time: &chrono::DateTime<chrono::FixedOffset>,
+        datetime_cache: &mut DateTimeCache,
+    ) {
+        self.time.append(Some(time), datetime_cache);
+        if let Some((policy, builder)) = self.dst_policy.as_mut() {
+            builder.append_value(policy.as_str());
+        }
+    }
+
+    fn finish(&mut self, arrays: &mut Vec<ArrayRef>) {
+        self.time.finish(arrays);
+        if let Some((_, builder)) = self.dst_policy.as_mut() {
+            finish_string(builder, BATCH_SIZE * 8, arrays);
+        }
+    }
 }
 
 fn parquet_error(message: impl Into<String>) -> io::Error {
-    io::Error::other(message.into())
+    io::Error::other(crate::Error::output(message))
+}
+
+/// Builders for the `id` and `name` columns of feature-file locations.
+struct SiteBuilders {
+    id: StringBuilder,
+    name: StringBuilder,
+}
+
+impl SiteBuilders {
+    fn new() -> Self {
+        Self {
+            id: StringBuilder::with_capacity(BATCH_SIZE, BATCH_SIZE * 8),
+            name: StringBuilder::with_capacity(BATCH_SIZE, BATCH_SIZE * 16),
+        }
+    }
+
+    fn append(&mut self, site: Option<&Site>) {
+        self.id
+            .append_option(site.and_then(|site| site.id.as_deref()));
+        self.name
+            .append_option(site.and_then(|site| site.name.as_deref()));
+    }
+
+    fn finish(&mut self, arrays: &mut Vec<ArrayRef>) {
+        finish_string(&mut self.id, BATCH_SIZE * 8, arrays);
+        finish_string(&mut self.name, BATCH_SIZE * 16, arrays);
+    }
+}
+
+/// Column builders for one command's rows, flushed to a writer every [`BATCH_SIZE`] rows.
+trait BatchBuilders: Sized {
+    type Row;
+    type Layout: Copy;
+    const COMMAND: Command;
+
+    fn layout(params: &Parameters) -> Self::Layout;
+    fn headers(layout: Self::Layout, params: &Parameters) -> Vec<&str>;
+    fn new(layout: Self::Layout, encoding: ParquetTimestamps) -> Self;
+    fn normalize(result: &CalculationResult) -> Option<Self::Row>;
+    /// Returns the row's `dateTime` and latitude, which select its dataset partition.
+    fn partition_values(row: &Self::Row) -> (&chrono::DateTime<chrono::FixedOffset>, f64);
+    fn site(row: &Self::Row) -> Option<&Site>;
+    fn append_row(
+        &mut self,
+        row: &Self::Row,
+        params: &Parameters,
+        layout: Self::Layout,
+        datetime_cache: &mut DateTimeCache,
+    ) -> io::Result<()>;
+    fn flush<S: BatchSink>(&mut self, sink: &mut S, schema: &BatchSchema) -> io::Result<()>;
 }
 
 struct PositionBatchBuilders {
+    site: Option<SiteBuilders>,
     latitude: Option<Float64Builder>,
     longitude: Option<Float64Builder>,
     elevation: Option<Float64Builder>,
     pressure: Option<Float64Builder>,
     temperature: Option<Float64Builder>,
-    date_time: StringBuilder,
+    date_time: DateTimeBuilders,
     delta_t: Option<Float64Builder>,
     azimuth: Float64Builder,
     angle: Float64Builder,
+    obstructed: Option<BooleanBuilder>,
+    irradiance: Option<[Float64Builder; 5]>,
+    incidence: Vec<Float64Builder>,
+    shadow: Option<[Float64Builder; 4]>,
+    geometry: Option<[Float64Builder; 7]>,
 }
 
-impl PositionBatchBuilders {
-    fn new(layout: PositionLayout) -> Self {
+impl BatchBuilders for PositionBatchBuilders {
+    type Row = PositionRow;
+    type Layout = PositionLayout;
+    const COMMAND: Command = Command::Position;
+
+    fn layout(params: &Parameters) -> Self::Layout {
+        PositionLayout::from_params(params)
+    }
+
+    fn headers(layout: Self::Layout, _params: &Parameters) -> Vec<&str> {
+        layout.csv_headers()
+    }
+
+    fn normalize(result: &CalculationResult) -> Option<Self::Row> {
+        normalize_position_result(result)
+    }
+
+    fn partition_values(row: &Self::Row) -> (&chrono::DateTime<chrono::FixedOffset>, f64) {
+        (&row.datetime, row.lat)
+    }
+
+    fn site(row: &Self::Row) -> Option<&Site> {
+        row.site.as_deref()
+    }
+
+    fn new(layout: PositionLayout, encoding: ParquetTimestamps) -> Self {
         Self {
+            site: layout.include_site.then(SiteBuilders::new),
             latitude: layout
                 .show_inputs
                 .then(|| Float64Builder::with_capacity(BATCH_SIZE)),
@@ -66,22 +246,43 @@ impl PositionBatchBuilders {
                 .then(|| Float64Builder::with_capacity(BATCH_SIZE)),
             temperature: (layout.show_inputs && layout.include_refraction)
                 .then(|| Float64Builder::with_capacity(BATCH_SIZE)),
-            date_time: StringBuilder::with_capacity(BATCH_SIZE, BATCH_SIZE * 30),
+            date_time: DateTimeBuilders::new(encoding, layout.dst_policy),
             delta_t: layout
                 .show_inputs
                 .then(|| Float64Builder::with_capacity(BATCH_SIZE)),
             azimuth: Float64Builder::with_capacity(BATCH_SIZE),
             angle: Float64Builder::with_capacity(BATCH_SIZE),
+            obstructed: layout
+                .include_obstructed
+                .then(|| BooleanBuilder::with_capacity(BATCH_SIZE)),
+            irradiance: layout
+                .include_irradiance
+                .then(|| std::array::from_fn(|_| Float64Builder::with_capacity(BATCH_SIZE))),
+            incidence: layout
+                .tracker
+                .map_or(&[][..], incidence_columns)
+                .iter()
+                .map(|_| Float64Builder::with_capacity(BATCH_SIZE))
+                .collect(),
+            shadow: layout
+                .include_shadow
+                .then(|| std::array::from_fn(|_| Float64Builder::with_capacity(BATCH_SIZE))),
+            geometry: layout
+                .include_geometry
+                .then(|| std::array::from_fn(|_| Float64Builder::with_capacity(BATCH_SIZE))),
         }
     }
 
     fn append_row(
         &mut self,
-        row: &crate::output::PositionRow,
+        row: &PositionRow,
         params: &Parameters,
         layout: PositionLayout,
         datetime_cache: &mut DateTimeCache,
-    ) {
+    ) -> io::Result<()> {
+        if let Some(site) = self.site.as_mut() {
+            site.append(row.site.as_deref());
+        }
         if layout.show_inputs {
             self.latitude.as_mut().unwrap().append_value(row.lat);
             self.longitude.as_mut().unwrap().append_value(row.lon);
@@ -102,121 +303,185 @@ impl PositionBatchBuilders {
             self.delta_t.as_mut().unwrap().append_value(row.deltat);
         }
 
-        self.date_time
-            .append_value(cached_datetime(datetime_cache, &row.datetime));
+        self.date_time.append(&row.datetime, datetime_cache);
         self.azimuth.append_value(row.azimuth);
         self.angle
             .append_value(row.angle(layout.uses_elevation_angle()));
+        if let Some(obstructed) = self.obstructed.as_mut() {
+            obstructed.append_option(row.obstructed);
+        }
+        if let Some(builders) = self.irradiance.as_mut() {
+            for (builder, value) in builders.iter_mut().zip(irradiance_values(row.irradiance)) {
+                builder.append_option(value);
+            }
+        }
+        if let Some(tracker) = layout.tracker {
+            for (builder, value) in self
+                .incidence
+                .iter_mut()
+                .zip(incidence_values(row.incidence, tracker))
+            {
+                builder.append_option(value);
+            }
+        }
+        if let Some(builders) = self.shadow.as_mut() {
+            for (builder, value) in builders.iter_mut().zip(shadow_values(row.shadow)) {
+                builder.append_option(value);
+            }
+        }
+        if let Some(builders) = self.geometry.as_mut() {
+            for (builder, value) in builders.iter_mut().zip(geometry_values(row.geometry)) {
+                builder.append_option(value);
+            }
+        }
+        Ok(())
     }
 
-    fn flush<W: Write + Send>(
-        &mut self,
-        writer: &mut ArrowWriter<W>,
-        schema: &Arc<Schema>,
-    ) -> io::Result<()> {
-        let mut arrays = Vec::with_capacity(schema.fields().len());
+    fn flush<S: BatchSink>(&mut self, sink: &mut S, schema: &BatchSchema) -> io::Result<()> {
+        let mut arrays = Vec::with_capacity(schema.columns.fields().len());
+        if let Some(site) = self.site.as_mut() {
+            site.finish(&mut arrays);
+        }
         finish_optional_f64(&mut self.latitude, &mut arrays);
         finish_optional_f64(&mut self.longitude, &mut arrays);
         finish_optional_f64(&mut self.elevation, &mut arrays);
         finish_optional_f64(&mut self.pressure, &mut arrays);
         finish_optional_f64(&mut self.temperature, &mut arrays);
-        finish_string(&mut self.date_time, BATCH_SIZE * 30, &mut arrays);
+        self.date_time.finish(&mut arrays);
         finish_optional_f64(&mut self.delta_t, &mut arrays);
         finish_f64(&mut self.azimuth, &mut arrays);
         finish_f64(&mut self.angle, &mut arrays);
-        write_batch(writer, schema, arrays)
+        finish_optional_bool(&mut self.obstructed, &mut arrays);
+        for builder in self.irradiance.iter_mut().flatten() {
+            finish_f64(builder, &mut arrays);
+        }
+        for builder in &mut self.incidence {
+            finish_f64(builder, &mut arrays);
+        }
+        for builder in self.shadow.iter_mut().flatten() {
+            finish_f64(builder, &mut arrays);
+        }
+        for builder in self.geometry.iter_mut().flatten() {
+            finish_f64(builder, &mut arrays);
+        }
+        write_batch(sink, schema, arrays)
     }
 }
 
 struct SunriseBatchBuilders {
+    site: Option<SiteBuilders>,
     latitude: Option<Float64Builder>,
     longitude: Option<Float64Builder>,
-    date_time: StringBuilder,
+    date_time: DateTimeBuilders,
     delta_t: Option<Float64Builder>,
     kind: StringBuilder,
-    sunrise: StringBuilder,
-    transit: StringBuilder,
-    sunset: StringBuilder,
-    civil_start: Option<StringBuilder>,
-    civil_end: Option<StringBuilder>,
-    nautical_start: Option<StringBuilder>,
-    nautical_end: Option<StringBuilder>,
-    astronomical_start: Option<StringBuilder>,
-    astronomical_end: Option<StringBuilder>,
-}
-
-impl SunriseBatchBuilders {
-    fn new(layout: SunriseLayout) -> Self {
+    sunrise: TimeBuilder,
+    transit: TimeBuilder,
+    sunset: TimeBuilder,
+    civil_start: Option<TimeBuilder>,
+    civil_end: Option<TimeBuilder>,
+    nautical_start: Option<TimeBuilder>,
+    nautical_end: Option<TimeBuilder>,
+    astronomical_start: Option<TimeBuilder>,
+    astronomical_end: Option<TimeBuilder>,
+    photo_hours: Option<[TimeBuilder; 8]>,
+    horizons: Vec<TimeBuilder>,
+    durations: Option<[Int64Builder; 4]>,
+    noon_elevation: Option<Float64Builder>,
+    day_length_change: Option<Int64Builder>,
+}
+
+impl BatchBuilders for SunriseBatchBuilders {
+    type Row = SunriseRow;
+    type Layout = SunriseLayout;
+    const COMMAND: Command = Command::Sunrise;
+
+    fn layout(params: &Parameters) -> Self::Layout {
+        SunriseLayout::from_params(params)
+    }
+
+    fn headers(layout: Self::Layout, params: &Parameters) -> Vec<&str> {
+        layout.csv_headers(&params.calculation.horizons)
+    }
+
+    fn normalize(result: &CalculationResult) -> Option<Self::Row> {
+        normalize_sunrise_result(result)
+    }
+
+    fn partition_values(row: &Self::Row) -> (&chrono::DateTime<chrono::FixedOffset>, f64) {
+        (&row.date_time, row.lat)
+    }
+
+    fn site(row: &Self::Row) -> Option<&Site> {
+        row.site.as_deref()
+    }
+
+    fn new(layout: SunriseLayout, encoding: ParquetTimestamps) -> Self {
         Self {
+            site: layout.include_site.then(SiteBuilders::new),
             latitude: layout
                 .show_inputs
                 .then(|| Float64Builder::with_capacity(BATCH_SIZE)),
             longitude: layout
                 .show_inputs
                 .then(|| Float64Builder::with_capacity(BATCH_SIZE)),
-            date_time: StringBuilder::with_capacity(BATCH_SIZE, BATCH_SIZE * 30),
+            date_time: DateTimeBuilders::new(encoding, layout.dst_policy),
             delta_t: layout
                 .show_inputs
                 .then(|| Float64Builder::with_capacity(BATCH_SIZE)),
             kind: StringBuilder::with_capacity(BATCH_SIZE, BATCH_SIZE * 10),
-            sunrise: StringBuilder::with_capacity(BATCH_SIZE, BATCH_SIZE * 25),
-            transit: StringBuilder::with_capacity(BATCH_SIZE, BATCH_SIZE * 25),
-            sunset: StringBuilder::with_capacity(BATCH_SIZE, BATCH_SIZE * 25),
-            civil_start: layout
-                .include_twilight
-                .then(|| StringBuilder::with_capacity(BATCH_SIZE, BATCH_SIZE * 25)),
-            civil_end: layout
-                .include_twilight
-                .then(|| StringBuilder::with_capacity(BATCH_SIZE, BATCH_SIZE * 25)),
-            nautical_start: layout
-                .include_twilight
-                .then(|| StringBuilder::with_capacity(BATCH_SIZE, BATCH_SIZE * 25)),
-            nautical_end: layout
-                .include_twilight
-                .then(|| StringBuilder::with_capacity(BATCH_SIZE, BATCH_SIZE * 25)),
-            astronomical_start: layout
-                .include_twilight
-                .then(|| StringBuilder::with_capacity(BATCH_SIZE, BATCH_SIZE * 25)),
-            astronomical_end: layout
-                .include_twilight
-                .then(|| StringBuilder::with_capacity(BATCH_SIZE, BATCH_SIZE * 25)),
+            sunrise: TimeBuilder::new(encoding),
+            transit: TimeBuilder::new(encoding),
+            sunset: TimeBuilder::new(encoding),
+            civil_start: layout.include_twilight.then(|| TimeBuilder::new(encoding)),
+            civil_end: layout.include_twilight.then(|| TimeBuilder::new(encoding)),
+            nautical_start: layout.include_twilight.then(|| TimeBuilder::new(encoding)),
+            nautical_end: layout.include_twilight.then(|| TimeBuilder::new(encoding)),
+            astronomical_start: layout.include_twilight.then(|| TimeBuilder::new(encoding)),
+            astronomical_end: layout.include_twilight.then(|| TimeBuilder::new(encoding)),
+            photo_hours: layout
+                .include_photo_hours
+                .then(|| std::array::from_fn(|_| TimeBuilder::new(encoding))),
+            horizons: (0..layout.horizon_count * 2)
+                .map(|_| TimeBuilder::new(encoding))
+                .collect(),
+            durations: layout
+                .include_stats
+                .then(|| std::array::from_fn(|_| Int64Builder::with_capacity(BATCH_SIZE))),
+            noon_elevation: layout
+                .include_stats
+                .then(|| Float64Builder::with_capacity(BATCH_SIZE)),
+            day_length_change: layout
+                .include_stats
+                .then(|| Int64Builder::with_capacity(BATCH_SIZE)),
         }
     }
 
     fn append_row(
         &mut self,
-        row: &crate::output::SunriseRow,
+        row: &SunriseRow,
+        _params: &Parameters,
         layout: SunriseLayout,
         datetime_cache: &mut DateTimeCache,
     ) -> io::Result<()> {
+        if let Some(site) = self.site.as_mut() {
+            site.append(row.site.as_deref());
+        }
         if layout.show_inputs {
             self.latitude.as_mut().unwrap().append_value(row.lat);
             self.longitude.as_mut().unwrap().append_value(row.lon);
             self.delta_t.as_mut().unwrap().append_value(row.deltat);
         }
 
-        self.date_time
-            .append_value(cached_datetime(datetime_cache, &row.date_time));
+        self.date_time.append(&row.date_time, datetime_cache);
         self.kind.append_value(row.type_label);
 
+        // Skyline periods that run past midnight have NORMAL rows with a missing end.
         match row.type_label {
-            "NORMAL" => {
-                append_time(
-                    &mut self.sunrise,
-                    row.sunrise.as_ref().expect("sunrise expected"),
-                    datetime_cache,
-                );
-                append_time(&mut self.transit, &row.transit, datetime_cache);
-                append_time(
-                    &mut self.sunset,
-                    row.sunset.as_ref().expect("sunset expected"),
-                    datetime_cache,
-                );
-            }
-            "ALL_DAY" | "ALL_NIGHT" => {
-                self.sunrise.append_null();
-                append_time(&mut self.transit, &row.transit, datetime_cache);
-                self.sunset.append_null();
+            "NORMAL" | "ALL_DAY" | "ALL_NIGHT" => {
+                self.sunrise.append(row.sunrise.as_ref(), datetime_cache);
+                self.transit.append(Some(&row.transit), datetime_cache);
+                self.sunset.append(row.sunset.as_ref(), datetime_cache);
             }
             other => return Err(parquet_error(format!("Unknown sunrise type: {other}"))),
         }
@@ -247,44 +512,432 @@ impl SunriseBatchBuilders {
             row.astro_end.as_ref(),
             datetime_cache,
         );
+        if let Some(builders) = self.photo_hours.as_mut() {
+            for (builder, time) in builders.iter_mut().zip(photo_hours_values(row.photo_hours)) {
+                builder.append(time.as_ref(), datetime_cache);
+            }
+        }
+        for (builder, time) in self
+            .horizons
+            .iter_mut()
+            .zip(horizon_values(&row.horizons, layout.horizon_count))
+        {
+            builder.append(time.as_ref(), datetime_cache);
+        }
+
+        if let Some(builders) = self.durations.as_mut() {
+            let (durations, noon_elevation) = day_stats_values(row.stats);
+            for (builder, value) in builders.iter_mut().zip(durations) {
+                builder.append_option(value);
+            }
+            if let Some(builder) = self.noon_elevation.as_mut() {
+                builder.append_option(noon_elevation);
+            }
+            if let Some(builder) = self.day_length_change.as_mut() {
+                builder.append_option(durations[4]);
+            }
+        }
 
         Ok(())
     }
 
-    fn flush<W: Write + Send>(
+    fn flush<S: BatchSink>(&mut self, sink: &mut S, schema: &BatchSchema) -> io::Result<()> {
+        let mut arrays = Vec::with_capacity(schema.columns.fields().len());
+        if let Some(site) = self.site.as_mut() {
+            site.finish(&mut arrays);
+        }
+        finish_optional_f64(&mut self.latitude, &mut arrays);
+        finish_optional_f64(&mut self.longitude, &mut arrays);
+        self.date_time.finish(&mut arrays);
+        finish_optional_f64(&mut self.delta_t, &mut arrays);
+        finish_string(&mut self.kind, BATCH_SIZE * 10, &mut arrays);
+        self.sunrise.finish(&mut arrays);
+        self.transit.finish(&mut arrays);
+        self.sunset.finish(&mut arrays);
+        for builder in [
+            &mut self.civil_start,
+            &mut self.civil_end,
+            &mut self.nautical_start,
+            &mut self.nautical_end,
+            &mut self.astronomical_start,
+            &mut self.astronomical_end,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            builder.finish(&mut arrays);
+        }
+        for builder in self.photo_hours.iter_mut().flatten() {
+            builder.finish(&mut arrays);
+        }
+        for builder in &mut self.horizons {
+            builder.finish(&mut arrays);
+        }
+        for builder in self.durations.iter_mut().flatten() {
+            finish_i64(builder, &mut arrays);
+        }
+        finish_optional_f64(&mut self.noon_elevation, &mut arrays);
+        if let Some(builder) = self.day_length_change.as_mut() {
+            finish_i64(builder, &mut arrays);
+        }
+        write_batch(sink, schema, arrays)
+    }
+}
+
+struct TransitionBatchBuilders {
+    site: Option<SiteBuilders>,
+    latitude: Option<Float64Builder>,
+    longitude: Option<Float64Builder>,
+    date_time: DateTimeBuilders,
+    delta_t: Option<Float64Builder>,
+    before: StringBuilder,
+    after: StringBuilder,
+    horizon: Float64Builder,
+}
+
+impl BatchBuilders for TransitionBatchBuilders {
+    type Row = TransitionRow;
+    type Layout = TransitionLayout;
+    const COMMAND: Command = Command::Events;
+
+    fn layout(params: &Parameters) -> Self::Layout {
+        TransitionLayout::from_params(params)
+    }
+
+    fn headers(layout: Self::Layout, _params: &Parameters) -> Vec<&str> {
+        layout.csv_headers()
+    }
+
+    fn normalize(result: &CalculationResult) -> Option<Self::Row> {
+        normalize_transition_result(result)
+    }
+
+    fn partition_values(row: &Self::Row) -> (&chrono::DateTime<chrono::FixedOffset>, f64) {
+        (&row.datetime, row.lat)
+    }
+
+    fn site(row: &Self::Row) -> Option<&Site> {
+        row.site.as_deref()
+    }
+
+    fn new(layout: TransitionLayout, encoding: ParquetTimestamps) -> Self {
+        Self {
+            site: layout.include_site.then(SiteBuilders::new),
+            latitude: layout
+                .show_inputs
+                .then(|| Float64Builder::with_capacity(BATCH_SIZE)),
+            longitude: layout
+                .show_inputs
+                .then(|| Float64Builder::with_capacity(BATCH_SIZE)),
+            date_time: DateTimeBuilders::new(encoding, layout.dst_policy),
+            delta_t: layout
+                .show_inputs
+                .then(|| Float64Builder::with_capacity(BATCH_SIZE)),
+            before: StringBuilder::with_capacity(BATCH_SIZE, BATCH_SIZE * 20),
+            after: StringBuilder::with_capacity(BATCH_SIZE, BATCH_SIZE * 20),
+            horizon: Float64Builder::with_capacity(BATCH_SIZE),
+        }
+    }
+
+    fn append_row(
         &mut self,
-        writer: &mut ArrowWriter<W>,
-        schema: &Arc<Schema>,
+        row: &TransitionRow,
+        _params: &Parameters,
+        layout: TransitionLayout,
+        datetime_cache: &mut DateTimeCache,
     ) -> io::Result<()> {
-        let mut arrays = Vec::with_capacity(schema.fields().len());
+        if let Some(site) = self.site.as_mut() {
+            site.append(row.site.as_deref());
+        }
+        if layout.show_inputs {
+            self.latitude.as_mut().unwrap().append_value(row.lat);
+            self.longitude.as_mut().unwrap().append_value(row.lon);
+            self.delta_t.as_mut().unwrap().append_value(row.deltat);
+        }
+
+        self.date_time.append(&row.datetime, datetime_cache);
+        self.before.append_value(row.before);
+        self.after.append_value(row.after);
+        self.horizon.append_value(row.horizon);
+        Ok(())
+    }
+
+    fn flush<S: BatchSink>(&mut self, sink: &mut S, schema: &BatchSchema) -> io::Result<()> {
+        let mut arrays = Vec::with_capacity(schema.columns.fields().len());
+        if let Some(site) = self.site.as_mut() {
+            site.finish(&mut arrays);
+        }
         finish_optional_f64(&mut self.latitude, &mut arrays);
         finish_optional_f64(&mut self.longitude, &mut arrays);
-        finish_string(&mut self.date_time, BATCH_SIZE * 30, &mut arrays);
+        self.date_time.finish(&mut arrays);
         finish_optional_f64(&mut self.delta_t, &mut arrays);
-        finish_string(&mut self.kind, BATCH_SIZE * 10, &mut arrays);
-        finish_string(&mut self.sunrise, BATCH_SIZE * 25, &mut arrays);
-        finish_string(&mut self.transit, BATCH_SIZE * 25, &mut arrays);
-        finish_string(&mut self.sunset, BATCH_SIZE * 25, &mut arrays);
-        finish_optional_string(&mut self.civil_start, BATCH_SIZE * 25, &mut arrays);
-        finish_optional_string(&mut self.civil_end, BATCH_SIZE * 25, &mut arrays);
-        finish_optional_string(&mut self.nautical_start, BATCH_SIZE * 25, &mut arrays);
-        finish_optional_string(&mut self.nautical_end, BATCH_SIZE * 25, &mut arrays);
-        finish_optional_string(&mut self.astronomical_start, BATCH_SIZE * 25, &mut arrays);
-        finish_optional_string(&mut self.astronomical_end, BATCH_SIZE * 25, &mut arrays);
-        write_batch(writer, schema, arrays)
+        finish_string(&mut self.before, BATCH_SIZE * 20, &mut arrays);
+        finish_string(&mut self.after, BATCH_SIZE * 20, &mut arrays);
+        finish_f64(&mut self.horizon, &mut arrays);
+        write_batch(sink, schema, arrays)
+    }
+}
+
+/// Builders for the `illuminated_fraction`, `phase_angle` and `phase` moon columns.
+struct IlluminationBuilders {
+    fraction: Float64Builder,
+    phase_angle: Float64Builder,
+    phase: StringBuilder,
+}
+
+impl IlluminationBuilders {
+    fn new() -> Self {
+        Self {
+            fraction: Float64Builder::with_capacity(BATCH_SIZE),
+            phase_angle: Float64Builder::with_capacity(BATCH_SIZE),
+            phase: StringBuilder::with_capacity(BATCH_SIZE, BATCH_SIZE * 16),
+        }
+    }
+
+    fn append(&mut self, illumination: &MoonIllumination) {
+        self.fraction
+            .append_value(illumination.illuminated_fraction);
+        self.phase_angle.append_value(illumination.phase_angle);
+        self.phase.append_value(illumination.phase.as_str());
+    }
+
+    fn finish(&mut self, arrays: &mut Vec<ArrayRef>) {
+        finish_f64(&mut self.fraction, arrays);
+        finish_f64(&mut self.phase_angle, arrays);
+        finish_string(&mut self.phase, BATCH_SIZE * 16, arrays);
+    }
+}
+
+struct MoonPositionBatchBuilders {
+    site: Option<SiteBuilders>,
+    latitude: Option<Float64Builder>,
+    longitude: Option<Float64Builder>,
+    elevation: Option<Float64Builder>,
+    pressure: Option<Float64Builder>,
+    temperature: Option<Float64Builder>,
+    date_time: DateTimeBuilders,
+    delta_t: Option<Float64Builder>,
+    azimuth: Float64Builder,
+    altitude: Float64Builder,
+    distance: Float64Builder,
+    illumination: IlluminationBuilders,
+}
+
+impl BatchBuilders for MoonPositionBatchBuilders {
+    type Row = MoonPositionRow;
+    type Layout = MoonPositionLayout;
+    const COMMAND: Command = Command::MoonPosition;
+
+    fn layout(params: &Parameters) -> Self::Layout {
+        MoonPositionLayout::from_params(params)
+    }
+
+    fn headers(layout: Self::Layout, _params: &Parameters) -> Vec<&str> {
+        layout.csv_headers()
+    }
+
+    fn normalize(result: &CalculationResult) -> Option<Self::Row> {
+        normalize_moon_position_result(result)
+    }
+
+    fn partition_values(row: &Self::Row) -> (&chrono::DateTime<chrono::FixedOffset>, f64) {
+        (&row.datetime, row.lat)
+    }
+
+    fn site(row: &Self::Row) -> Option<&Site> {
+        row.site.as_deref()
+    }
+
+    fn new(layout: MoonPositionLayout, encoding: ParquetTimestamps) -> Self {
+        Self {
+            site: layout.include_site.then(SiteBuilders::new),
+            latitude: layout
+                .show_inputs
+                .then(|| Float64Builder::with_capacity(BATCH_SIZE)),
+            longitude: layout
+                .show_inputs
+                .then(|| Float64Builder::with_capacity(BATCH_SIZE)),
+            elevation: layout
+                .show_inputs
+                .then(|| Float64Builder::with_capacity(BATCH_SIZE)),
+            pressure: (layout.show_inputs && layout.include_refraction)
+                .then(|| Float64Builder::with_capacity(BATCH_SIZE)),
+            temperature: (layout.show_inputs && layout.include_refraction)
+                .then(|| Float64Builder::with_capacity(BATCH_SIZE)),
+            date_time: DateTimeBuilders::new(encoding, layout.dst_policy),
+            delta_t: layout
+                .show_inputs
+                .then(|| Float64Builder::with_capacity(BATCH_SIZE)),
+            azimuth: Float64Builder::with_capacity(BATCH_SIZE),
+            altitude: Float64Builder::with_capacity(BATCH_SIZE),
+            distance: Float64Builder::with_capacity(BATCH_SIZE),
+            illumination: IlluminationBuilders::new(),
+        }
+    }
+
+    fn append_row(
+        &mut self,
+        row: &MoonPositionRow,
+        params: &Parameters,
+        layout: MoonPositionLayout,
+        datetime_cache: &mut DateTimeCache,
+    ) -> io::Result<()> {
+        if let Some(site) = self.site.as_mut() {
+            site.append(row.site.as_deref());
+        }
+        if layout.show_inputs {
+            self.latitude.as_mut().unwrap().append_value(row.lat);
+            self.longitude.as_mut().unwrap().append_value(row.lon);
+            self.elevation
+                .as_mut()
+                .unwrap()
+                .append_value(params.environment.elevation);
+            if layout.include_refraction {
+                self.pressure
+                    .as_mut()
+                    .unwrap()
+                    .append_value(params.environment.pressure);
+                self.temperature
+                    .as_mut()
+                    .unwrap()
+                    .append_value(params.environment.temperature);
+            }
+            self.delta_t.as_mut().unwrap().append_value(row.deltat);
+        }
+
+        self.date_time.append(&row.datetime, datetime_cache);
+        self.azimuth.append_value(row.position.azimuth);
+        self.altitude.append_value(row.position.altitude);
+        self.distance.append_value(row.position.distance);
+        self.illumination.append(&row.position.illumination);
+        Ok(())
+    }
+
+    fn flush<S: BatchSink>(&mut self, sink: &mut S, schema: &BatchSchema) -> io::Result<()> {
+        let mut arrays = Vec::with_capacity(schema.columns.fields().len());
+        if let Some(site) = self.site.as_mut() {
+            site.finish(&mut arrays);
+        }
+        finish_optional_f64(&mut self.latitude, &mut arrays);
+        finish_optional_f64(&mut self.longitude, &mut arrays);
+        finish_optional_f64(&mut self.elevation, &mut arrays);
+        finish_optional_f64(&mut self.pressure, &mut arrays);
+        finish_optional_f64(&mut self.temperature, &mut arrays);
+        self.date_time.finish(&mut arrays);
+        finish_optional_f64(&mut self.delta_t, &mut arrays);
+        finish_f64(&mut self.azimuth, &mut arrays);
+        finish_f64(&mut self.altitude, &mut arrays);
+        finish_f64(&mut self.distance, &mut arrays);
+        self.illumination.finish(&mut arrays);
+        write_batch(sink, schema, arrays)
+    }
+}
+
+struct MoonriseBatchBuilders {
+    site: Option<SiteBuilders>,
+    latitude: Option<Float64Builder>,
+    longitude: Option<Float64Builder>,
+    date_time: DateTimeBuilders,
+    delta_t: Option<Float64Builder>,
+    kind: StringBuilder,
+    moonrise: TimeBuilder,
+    moonset: TimeBuilder,
+    illumination: IlluminationBuilders,
+}
+
+impl BatchBuilders for MoonriseBatchBuilders {
+    type Row = MoonriseRow;
+    type Layout = MoonriseLayout;
+    const COMMAND: Command = Command::Moonrise;
+
+    fn layout(params: &Parameters) -> Self::Layout {
+        MoonriseLayout::from_params(params)
+    }
+
+    fn headers(layout: Self::Layout, _params: &Parameters) -> Vec<&str> {
+        layout.csv_headers()
+    }
+
+    fn normalize(result: &CalculationResult) -> Option<Self::Row> {
+        normalize_moonrise_result(result)
+    }
+
+    fn partition_values(row: &Self::Row) -> (&chrono::DateTime<chrono::FixedOffset>, f64) {
+        (&row.date_time, row.lat)
+    }
+
+    fn site(row: &Self::Row) -> Option<&Site> {
+        row.site.as_deref()
+    }
+
+    fn new(layout: MoonriseLayout, encoding: ParquetTimestamps) -> Self {
+        Self {
+            site: layout.include_site.then(SiteBuilders::new),
+            latitude: layout
+                .show_inputs
+                .then(|| Float64Builder::with_capacity(BATCH_SIZE)),
+            longitude: layout
+                .show_inputs
+                .then(|| Float64Builder::with_capacity(BATCH_SIZE)),
+            date_time: DateTimeBuilders::new(encoding, layout.dst_policy),
+            delta_t: layout
+                .show_inputs
+                .then(|| Float64Builder::with_capacity(BATCH_SIZE)),
+            kind: StringBuilder::with_capacity(BATCH_SIZE, BATCH_SIZE * 11),
+            moonrise: TimeBuilder::new(encoding),
+            moonset: TimeBuilder::new(encoding),
+            illumination: IlluminationBuilders::new(),
+        }
+    }
+
+    fn append_row(
+        &mut self,
+        row: &MoonriseRow,
+        _params: &Parameters,
+        layout: MoonriseLayout,
+        datetime_cache: &mut DateTimeCache,
+    ) -> io::Result<()> {
+        if let Some(site) = self.site.as_mut() {
+            site.append(row.site.as_deref());
+        }
+        if layout.show_inputs {
+            self.latitude.as_mut().unwrap().append_value(row.lat);
+            self.longitude.as_mut().unwrap().append_value(row.lon);
+            self.delta_t.as_mut().unwrap().append_value(row.deltat);
+        }
+
+        self.date_time.append(&row.date_time, datetime_cache);
+        self.kind.append_value(row.type_label);
+        self.moonrise.append(row.moonrise.as_ref(), datetime_cache);
+        self.moonset.append(row.moonset.as_ref(), datetime_cache);
+        self.illumination.append(&row.illumination);
+        Ok(())
+    }
+
+    fn flush<S: BatchSink>(&mut self, sink: &mut S, schema: &BatchSchema) -> io::Result<()> {
+        let mut arrays = Vec::with_capacity(schema.columns.fields().len());
+        if let Some(site) = self.site.as_mut() {
+            site.finish(&mut arrays);
+        }
+        finish_optional_f64(&mut self.latitude, &mut arrays);
+        finish_optional_f64(&mut self.longitude, &mut arrays);
+        self.date_time.finish(&mut arrays);
+        finish_optional_f64(&mut self.delta_t, &mut arrays);
+        finish_string(&mut self.kind, BATCH_SIZE * 11, &mut arrays);
+        self.moonrise.finish(&mut arrays);
+        self.moonset.finish(&mut arrays);
+        self.illumination.finish(&mut arrays);
+        write_batch(sink, schema, arrays)
     }
 }
 
 fn append_optional_time(
-    builder: &mut Option<StringBuilder>,
+    builder: &mut Option<TimeBuilder>,
     time: Option<&chrono::DateTime<chrono::FixedOffset>>,
     datetime_cache: &mut DateTimeCache,
 ) {
     if let Some(builder) = builder {
-        match time {
-            Some(time) => append_time(builder, time, datetime_cache),
-            None => builder.append_null(),
-        }
+        builder.append(time, datetime_cache);
     }
 }
 
@@ -300,56 +953,59 @@ fn finish_f64(builder: &mut Float64Builder, arrays: &mut Vec<ArrayRef>) {
     *builder = Float64Builder::with_capacity(BATCH_SIZE);
 }
 
-fn finish_string(builder: &mut StringBuilder, capacity: usize, arrays: &mut Vec<ArrayRef>) {
+fn finish_i64(builder: &mut Int64Builder, arrays: &mut Vec<ArrayRef>) {
     arrays.push(Arc::new(builder.finish()) as ArrayRef);
-    *builder = StringBuilder::with_capacity(BATCH_SIZE, capacity);
+    *builder = Int64Builder::with_capacity(BATCH_SIZE);
 }
 
-fn finish_optional_string(
-    builder: &mut Option<StringBuilder>,
-    capacity: usize,
-    arrays: &mut Vec<ArrayRef>,
-) {
+fn finish_optional_bool(builder: &mut Option<BooleanBuilder>, arrays: &mut Vec<ArrayRef>) {
     if let Some(builder) = builder {
         arrays.push(Arc::new(builder.finish()) as ArrayRef);
-        *builder = StringBuilder::with_capacity(BATCH_SIZE, capacity);
+        *builder = BooleanBuilder::with_capacity(BATCH_SIZE);
     }
 }
 
+fn finish_string(builder: &mut StringBuilder, capacity: usize, arrays: &mut Vec<ArrayRef>) {
+    arrays.push(Arc::new(builder.finish()) as ArrayRef);
+    *builder = StringBuilder::with_capacity(BATCH_SIZE, capacity);
+}
+
 pub fn write_parquet<W: Write + Send>(
-    results: Box<dyn Iterator<Item = Result<CalculationResult, String>>>,
+    results: CalculationStream,
     command: Command,
     params: &Parameters,
     writer: W,
 ) -> io::Result<usize> {
     match command {
-        Command::Position => write_position_parquet(results, params, writer),
-        Command::Sunrise => write_sunrise_parquet(results, params, writer),
+        Command::Position => write_stream::<PositionBatchBuilders, _>(results, params, writer),
+        Command::Sunrise => write_stream::<SunriseBatchBuilders, _>(results, params, writer),
+        Command::Events => write_stream::<TransitionBatchBuilders, _>(results, params, writer),
+        Command::MoonPosition => {
+            write_stream::<MoonPositionBatchBuilders, _>(results, params, writer)
+        }
+        Command::Moonrise => write_stream::<MoonriseBatchBuilders, _>(results, params, writer),
     }
 }
 
-fn write_position_parquet<W: Write + Send>(
-    results: Box<dyn Iterator<Item = Result<CalculationResult, String>>>,
+fn write_stream<B: BatchBuilders, W: Write + Send>(
+    results: CalculationStream,
     params: &Parameters,
     writer: W,
 ) -> io::Result<usize> {
-    let layout = PositionLayout::from_params(params);
-    let schema = build_position_schema(layout);
-    let props = WriterProperties::builder()
-        .set_compression(Compression::SNAPPY)
-        .build();
-    let mut writer = ArrowWriter::try_new(writer, schema.clone(), Some(props))
+    let layout = B::layout(params);
+    let encoding = params.output.parquet.timestamps;
+    let schema = BatchSchema::new(B::headers(layout, params), params)?;
+    let props = writer_properties(B::COMMAND, params)?;
+    let mut writer = ArrowWriter::try_new(writer, schema.output.clone(), Some(props))
         .map_err(|e| parquet_error(format!("Parquet writer error: {e}")))?;
-    let mut builders = PositionBatchBuilders::new(layout);
-    let mut datetime_cache = DateTimeCache::with_capacity(2048);
+    let mut builders = B::new(layout, encoding);
+    let mut datetime_cache = DateTimeCache::with_capacity(&params.output.time_format, 2048);
     let mut batch_count = 0;
     let mut total_count = 0;
 
     for result in results {
-        let result = result.map_err(io::Error::other)?;
-        let row = normalize_position_result(&result)
-            .ok_or_else(|| parquet_error("Unexpected calculation result for position"))?;
-        builders.append_row(&row, params, layout, &mut datetime_cache);
+        let row = row_from_result::<B>(result)?;
+        builders.append_row(&row, params, layout, &mut datetime_cache)?;
         batch_count += 1;
         total_count += 1;
 
@@ -369,34 +1025,72 @@ fn write_position_parquet<W: Write + Send>(
     Ok(total_count)
 }
 
-fn write_sunrise_parquet<W: Write + Send>(
-    results: Box<dyn Iterator<Item = Result<CalculationResult, String>>>,
+/// Writes results as an Arrow IPC stream with the same schema as Parquet output.
+///
+/// With `flush_each_record`, every record is sent as its own batch and flushed immediately.
+pub fn write_arrow<W: Write + Send>(
+    results: CalculationStream,
+    command: Command,
     params: &Parameters,
     writer: W,
+    flush_each_record: bool,
 ) -> io::Result<usize> {
-    let layout = SunriseLayout::from_params(params);
-    let schema = build_sunrise_schema(layout);
-    let props = WriterProperties::builder()
-        .set_compression(Compression::SNAPPY)
-        .build();
-    let mut writer = ArrowWriter::try_new(writer, schema.clone(), Some(props))
-        .map_err(|e| parquet_error(format!("Parquet writer error: {e}")))?;
-    let mut builders = SunriseBatchBuilders::new(layout);
-    let mut datetime_cache = DateTimeCache::with_capacity(2048);
+    match command {
+        Command::Position => {
+            write_ipc_stream::<PositionBatchBuilders, _>(results, params, writer, flush_each_record)
+        }
+        Command::Sunrise => {
+            write_ipc_stream::<SunriseBatchBuilders, _>(results, params, writer, flush_each_record)
+        }
+        Command::Events => write_ipc_stream::<TransitionBatchBuilders, _>(
+            results,
+            params,
+            writer,
+            flush_each_record,
+        ),
+        Command::MoonPosition => write_ipc_stream::<MoonPositionBatchBuilders, _>(
+            results,
+            params,
+            writer,
+            flush_each_record,
+        ),
+        Command::Moonrise => {
+            write_ipc_stream::<MoonriseBatchBuilders, _>(results, params, writer, flush_each_record)
+        }
+    }
+}
+
+fn write_ipc_stream<B: BatchBuilders, W: Write + Send>(
+    results: CalculationStream,
+    params: &Parameters,
+    writer: W,
+    flush_each_record: bool,
+) -> io::Result<usize> {
+    let layout = B::layout(params);
+    let encoding = params.output.parquet.timestamps;
+    let schema = BatchSchema::new(B::headers(layout, params), params)?;
+    let mut writer = StreamWriter::try_new_buffered(writer, &schema.output)
+        .map_err(|e| parquet_error(format!("Arrow writer error: {e}")))?;
+    let batch_size = if flush_each_record { 1 } else { BATCH_SIZE };
+    let mut builders = B::new(layout, encoding);
+    let mut datetime_cache = DateTimeCache::with_capacity(&params.output.time_format, 2048);
     let mut batch_count = 0;
     let mut total_count = 0;
 
     for result in results {
-        let result = result.map_err(io::Error::other)?;
-        let row = normalize_sunrise_result(&result)
-            .ok_or_else(|| parquet_error("Unexpected calculation result for sunrise"))?;
-        builders.append_row(&row, layout, &mut datetime_cache)?;
+        let row = row_from_result::<B>(result)?;
+        builders.append_row(&row, params, layout, &mut datetime_cache)?;
         batch_count += 1;
         total_count += 1;
 
-        if batch_count == BATCH_SIZE {
+        if batch_count == batch_size {
             builders.flush(&mut writer, &schema)?;
             batch_count = 0;
+            if flush_each_record {
+                writer
+                    .flush()
+                    .map_err(|e| parquet_error(format!("Failed to flush arrow stream: {e}")))?;
+            }
         }
     }
 
@@ -405,53 +1099,477 @@ fn write_sunrise_parquet<W: Write + Send>(
     }
 
     writer
-        .close()
-        .map_err(|e| parquet_error(format!("Failed to close parquet: {e}")))?;
+        .finish()
+        .map_err(|e| parquet_error(format!("Failed to finish arrow stream: {e}")))?;
+    writer
+        .flush()
+        .map_err(|e| parquet_error(format!("Failed to flush arrow stream: {e}")))?;
     Ok(total_count)
 }
 
-fn write_batch<W: Write + Send>(
-    writer: &mut ArrowWriter<W>,
-    schema: &Arc<Schema>,
+/// Writes results as a Hive-partitioned dataset of `year=YYYY/month=MM[/lat_band=B]`
+/// directories below `dir`, using the local date of `dateTime`.
+///
+/// All open partition files are closed when the year or month changes, so time-ordered input
+/// keeps one file per latitude band open at a time. A partition that is reached again later,
+/// or whose file reaches the row or size limit, continues in the next `part-NNNN.parquet`.
+pub fn write_parquet_dataset(
+    results: CalculationStream,
+    command: Command,
+    params: &Parameters,
+    dir: &Path,
+) -> io::Result<usize> {
+    match command {
+        Command::Position => write_dataset::<PositionBatchBuilders>(results, params, dir),
+        Command::Sunrise => write_dataset::<SunriseBatchBuilders>(results, params, dir),
+        Command::Events => write_dataset::<TransitionBatchBuilders>(results, params, dir),
+        Command::MoonPosition => write_dataset::<MoonPositionBatchBuilders>(results, params, dir),
+        Command::Moonrise => write_dataset::<MoonriseBatchBuilders>(results, params, dir),
+    }
+}
+
+fn write_dataset<B: BatchBuilders>(
+    results: CalculationStream,
+    params: &Parameters,
+    dir: &Path,
+) -> io::Result<usize> {
+    let dataset = &params.output.parquet.dataset;
+    let layout = B::layout(params);
+    let encoding = params.output.parquet.timestamps;
+    let schema = BatchSchema::new(B::headers(layout, params), params)?;
+    let props = writer_properties(B::COMMAND, params)?;
+    let mut open: AHashMap<PathBuf, PartitionWriter<B>> = AHashMap::new();
+    let mut next_part: AHashMap<PathBuf, usize> = AHashMap::new();
+    let mut current_month = None;
+    // Per-row zones order records by location, so every location runs through all months;
+    // partitions then stay open to the end instead of being reopened for each location.
+    let mut location_major = None;
+    let mut datetime_cache = DateTimeCache::with_capacity(&params.output.time_format, 2048);
+    let mut total_count = 0;
+
+    for result in results {
+        let row = row_from_result::<B>(result)?;
+        let location_major = *location_major
+            .get_or_insert_with(|| B::site(&row).is_some_and(|site| site.timezone.is_some()));
+        let (datetime, lat) = B::partition_values(&row);
+        let month = (datetime.year(), datetime.month());
+        if !location_major && current_month != Some(month) {
+            for (_, partition) in open.drain() {
+                partition.close(&schema)?;
+            }
+            current_month = Some(month);
+        }
+
+        let partition_dir = partition_path(dir, month, lat, dataset.lat_band);
+        if open
+            .get(&partition_dir)
+            .is_some_and(|partition| partition.is_full(dataset))
+            && let Some(partition) = open.remove(&partition_dir)
+        {
+            partition.close(&schema)?;
+        }
+        let partition = match open.entry(partition_dir) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let part = next_part.entry(entry.key().clone()).or_insert(0);
+                let partition =
+                    PartitionWriter::create(entry.key(), *part, &schema, &props, layout, encoding)?;
+                *part += 1;
+                entry.insert(partition)
+            }
+        };
+        partition.append_row(&row, params, layout, &mut datetime_cache, &schema, dataset)?;
+        total_count += 1;
+    }
+
+    for (_, partition) in open.drain() {
+        partition.close(&schema)?;
+    }
+    Ok(total_count)
+}
+
+/// Returns the Hive-style directory for one year, month and optional latitude band.
+fn partition_path(dir: &Path, (year, month): (i32, u32), lat: f64, band: Option<f64>) -> PathBuf {
+    let mut path = dir
+        .join(format!("year={year}"))
+        .join(format!("month={month:02}"));
+    if let Some(band) = band {
+        // Rounding keeps labels such as 47.1 clean; adding zero turns -0 into 0.
+        let start = ((lat / band).floor() * band * 1e6).round() / 1e6 + 0.0;
+        path.push(format!("lat_band={start}"));
+    }
+    path
+}
+
+/// One open part file of a dataset partition.
+struct PartitionWriter<B> {
+    writer: ArrowWriter<BufWriter<File>>,
+    builders: B,
+    batch_count: usize,
+    file_rows: usize,
+}
+
+impl<B: BatchBuilders> PartitionWriter<B> {
+    fn create(
+        dir: &Path,
+        part: usize,
+        schema: &BatchSchema,
+        props: &WriterProperties,
+        layout: B::Layout,
+        encoding: ParquetTimestamps,
+    ) -> io::Result<Self> {
+        fs::create_dir_all(dir)
+            .map_err(|e| parquet_error(format!("Failed to create {}: {e}", dir.display())))?;
+        let path = dir.join(format!("part-{part:04}.parquet"));
+        let file = File::create_new(&path)
+            .map_err(|e| parquet_error(format!("Failed to create {}: {e}", path.display())))?;
+        let writer = ArrowWriter::try_new(
+            BufWriter::new(file),
+            schema.output.clone(),
+            Some(props.clone()),
+        )
+        .map_err(|e| parquet_error(format!("Parquet writer error: {e}")))?;
+        Ok(Self {
+            writer,
+            builders: B::new(layout, encoding),
+            batch_count: 0,
+            file_rows: 0,
+        })
+    }
+
+    /// Returns whether the file has reached the row limit or, counting written batches
+    /// only, the size limit.
+    fn is_full(&self, dataset: &ParquetDataset) -> bool {
+        dataset
+            .max_file_rows
+            .is_some_and(|rows| self.file_rows >= rows)
+            || dataset.max_file_bytes.is_some_and(|bytes| {
+                (self.writer.bytes_written() + self.writer.in_progress_size()) as u64 >= bytes
+            })
+    }
+
+    fn append_row(
+        &mut self,
+        row: &B::Row,
+        params: &Parameters,
+        layout: B::Layout,
+        datetime_cache: &mut DateTimeCache,
+        schema: &BatchSchema,
+        dataset: &ParquetDataset,
+    ) -> io::Result<()> {
+        self.builders
+            .append_row(row, params, layout, datetime_cache)?;
+        self.batch_count += 1;
+        self.file_rows += 1;
+        if self.batch_count == BATCH_SIZE || self.is_full(dataset) {
+            self.builders.flush(&mut self.writer, schema)?;
+            self.batch_count = 0;
+        }
+        Ok(())
+    }
+
+    fn close(mut self, schema: &BatchSchema) -> io::Result<()> {
+        if self.batch_count > 0 {
+            self.builders.flush(&mut self.writer, schema)?;
+        }
+        self.writer
+            .into_inner()
+            .map_err(|e| parquet_error(format!("Failed to close parquet: {e}")))?
+            .flush()
+    }
+}
+
+fn row_from_result<B: BatchBuilders>(
+    result: Result<CalculationResult, crate::Error>,
+) -> io::Result<B::Row> {
+    let result = result.map_err(io::Error::other)?;
+    B::normalize(&result)
+        .ok_or_else(|| parquet_error(format!("Unexpected calculation result for {}", B::COMMAND)))
+}
+
+/// Builds writer properties from the Parquet options, embedding the run's settings as
+/// `sunce.*` key-value metadata.
+fn writer_properties(command: Command, params: &Parameters) -> io::Result<WriterProperties> {
+    let options = &params.output.parquet;
+    let compression = match options.compression {
+        ParquetCompression::None => Compression::UNCOMPRESSED,
+        ParquetCompression::Snappy => Compression::SNAPPY,
+        ParquetCompression::Lz4 => Compression::LZ4_RAW,
+        ParquetCompression::Gzip(level) => Compression::GZIP(
+            level
+                .map(GzipLevel::try_new)
+                .transpose()
+                .map_err(|e| parquet_error(format!("Invalid gzip level: {e}")))?
+                .unwrap_or_default(),
+        ),
+        ParquetCompression::Zstd(level) => Compression::ZSTD(
+            level
+                .map(ZstdLevel::try_new)
+                .transpose()
+                .map_err(|e| parquet_error(format!("Invalid zstd level: {e}")))?
+                .unwrap_or_default(),
+        ),
+    };
+    let mut builder = WriterProperties::builder()
+        .set_compression(compression)
+        .set_dictionary_enabled(options.dictionary)
+        .set_key_value_metadata(Some(run_metadata(command, params)));
+    if let Some(rows) = options.row_group_size {
+        builder = builder.set_max_row_group_row_count(Some(rows));
+    }
+    Ok(builder.build())
+}
+
+/// Key/value metadata recording the settings that shape a file's columns and values.
+fn run_metadata(command: Command, params: &Parameters) -> Vec<KeyValue> {
+    let calculation = &params.calculation;
+    let mut metadata = Vec::new();
+    let mut push = |key: &str, value: String| {
+        metadata.push(KeyValue::new(format!("sunce.{key}"), value));
+    };
+    push("version", env!("CARGO_PKG_VERSION").to_string());
+    push("command", command.to_string());
+    push(
+        "deltat",
+        params
+            .deltat
+            .map_or_else(|| "estimated".to_string(), |deltat| deltat.to_string()),
+    );
+    if let Some(timezone) = &params.timezone {
+        push("timezone", timezone.as_str().to_string());
+    }
+    if let Some(timezone) = &params.output_timezone {
+        push("output_timezone", timezone.as_str().to_string());
+    }
+    if let Some(policy) = params.dst_policy {
+        push("dst_policy", policy.to_string());
+    }
+    if matches!(command, Command::Position | Command::MoonPosition) {
+        let environment = &params.environment;
+        if command == Command::Position {
+            push("algorithm", calculation.algorithm.to_string());
+        }
+        push("refraction", environment.refraction.to_string());
+        if environment.refraction {
+            push("pressure", environment.pressure.to_string());
+            push("temperature", environment.temperature.to_string());
+        }
+        push("elevation", environment.elevation.to_string());
+    }
+    match command {
+        Command::Position => {
+            if calculation.irradiance {
+                push(
+                    "linke_turbidity",
+                    params.environment.linke_turbidity.to_string(),
+                );
+            }
+            if let Some(surface) = calculation.surface {
+                push("tracker", surface.tracker.to_string());
+                push("surface_tilt", surface.tilt.to_string());
+                push("surface_azimuth", surface.azimuth.to_string());
+            }
+            if let Some(height) = calculation.shadow_height {
+                push("shadow_height", height.to_string());
+            }
+        }
+        Command::Sunrise | Command::Events => {
+            if let Some(horizon) = calculation.horizon {
+                push("horizon", horizon.to_string());
+            }
+            if calculation.twilight {
+                push("twilight", "true".to_string());
+            }
+            if calculation.day_stats {
+                push("day_stats", "true".to_string());
+            }
+            if calculation.photo_hours {
+                push("photo_hours", "true".to_string());
+            }
+            if !calculation.horizons.is_empty() {
+                let horizons = calculation
+                    .horizons
+                    .iter()
+                    .map(|horizon| format!("{}={}", horizon.label(), horizon.degrees()))
+                    .collect::<Vec<_>>();
+                push("horizons", horizons.join(","));
+            }
+        }
+        Command::MoonPosition | Command::Moonrise => {}
+    }
+    if matches!(command, Command::Position | Command::Sunrise) && calculation.has_horizon_profile()
+    {
+        let source = match &calculation.horizon_profile_file {
+            Some(InputPath::File(path)) => path.display().to_string(),
+            Some(InputPath::Stdin) => "-".to_string(),
+            None => "in-memory".to_string(),
+        };
+        push("horizon_profile", source);
+    }
+    metadata
+}
+
+/// Destination for finished record batches: a Parquet file or an Arrow IPC stream.
+trait BatchSink {
+    fn write_batch(&mut self, batch: &RecordBatch) -> io::Result<()>;
+}
+
+impl<W: Write + Send> BatchSink for ArrowWriter<W> {
+    fn write_batch(&mut self, batch: &RecordBatch) -> io::Result<()> {
+        self.write(batch)
+            .map_err(|e| parquet_error(format!("Failed to write batch: {e}")))
+    }
+}
+
+impl<W: Write> BatchSink for StreamWriter<W> {
+    fn write_batch(&mut self, batch: &RecordBatch) -> io::Result<()> {
+        self.write(batch)
+            .map_err(|e| parquet_error(format!("Failed to write batch: {e}")))
+    }
+}
+
+fn write_batch<S: BatchSink>(
+    sink: &mut S,
+    schema: &BatchSchema,
     arrays: Vec<ArrayRef>,
 ) -> io::Result<()> {
-    let batch = RecordBatch::try_new(schema.clone(), arrays)
+    let mut batch = RecordBatch::try_new(schema.columns.clone(), arrays)
         .map_err(|e| parquet_error(format!("Failed to create batch: {e}")))?;
-    writer
-        .write(&batch)
-        .map_err(|e| parquet_error(format!("Failed to write batch: {e}")))
+    if let Some(projection) = &schema.projection {
+        batch = batch
+            .project(projection)
+            .map_err(|e| parquet_error(format!("Failed to select columns: {e}")))?;
+    }
+    sink.write_batch(&batch)
 }
 
-fn parquet_field(name: &'static str) -> Field {
+fn parquet_field(name: &str, encoding: ParquetTimestamps) -> Field {
     let data_type = match name {
-        "latitude" | "longitude" | "elevation" | "pressure" | "temperature" | "deltaT"
-        | "azimuth" | "zenith" | "elevation-angle" => DataType::Float64,
+        name if encoding == ParquetTimestamps::Native && is_datetime_column(name) => {
+            DataType::Timestamp(TimeUnit::Second, Some(UTC.into()))
+        }
+        "latitude"
+        | "longitude"
+        | "elevation"
+        | "pressure"
+        | "temperature"
+        | "deltaT"
+        | "azimuth"
+        | "zenith"
+        | "elevation-angle"
+        | "horizon"
+        | "extraterrestrial"
+        | "air_mass"
+        | "ghi"
+        | "dni"
+        | "dhi"
+        | "incidence"
+        | "cos_incidence"
+        | "tracker_rotation"
+        | "tracker_tilt"
+        | "tracker_azimuth"
+        | "shadow_length"
+        | "shadow_azimuth"
+        | "shadow_east"
+        | "shadow_north"
+        | "declination"
+        | "right_ascension"
+        | "hour_angle"
+        | "equation_of_time"
+        | "earth_sun_distance"
+        | "julian_day"
+        | "sidereal_time"
+        | "noon_elevation"
+        | "altitude"
+        | "distance"
+        | "illuminated_fraction"
+        | "phase_angle" => DataType::Float64,
+        "day_length"
+        | "civil_twilight"
+        | "nautical_twilight"
+        | "astronomical_twilight"
+        | "day_length_change" => DataType::Int64,
+        "obstructed" => DataType::Boolean,
         _ => DataType::Utf8,
     };
-    let nullable = matches!(
-        name,
-        "sunrise"
-            | "sunset"
-            | "civil_start"
-            | "civil_end"
-            | "nautical_start"
-            | "nautical_end"
-            | "astronomical_start"
-            | "astronomical_end"
-    );
+    // Crossing times end in `_start`/`_end`, including user-labelled `--horizons` columns.
+    let nullable = name.ends_with("_start")
+        || name.ends_with("_end")
+        || matches!(
+            name,
+            "id" | "name"
+                | "air_mass"
+                | "incidence"
+                | "cos_incidence"
+                | "tracker_rotation"
+                | "tracker_tilt"
+                | "tracker_azimuth"
+                | "shadow_length"
+                | "shadow_azimuth"
+                | "shadow_east"
+                | "shadow_north"
+                | "sunrise"
+                | "sunset"
+                | "moonrise"
+                | "moonset"
+        );
     Field::new(name, data_type, nullable)
 }
 
-fn build_schema(columns: Vec<&'static str>) -> Arc<Schema> {
-    Arc::new(Schema::new(
-        columns.into_iter().map(parquet_field).collect::<Vec<_>>(),
-    ))
+/// Schema of the full builder columns, plus the `--columns` selection that is written.
+struct BatchSchema {
+    columns: Arc<Schema>,
+    output: Arc<Schema>,
+    projection: Option<Vec<usize>>,
 }
 
-fn build_position_schema(layout: PositionLayout) -> Arc<Schema> {
-    build_schema(layout.csv_headers())
+impl BatchSchema {
+    /// Builds the schema; a selected timestamp column keeps its UTC offset column alongside.
+    fn new(headers: Vec<&str>, params: &Parameters) -> io::Result<Self> {
+        let columns = build_schema(headers, params.output.parquet.timestamps);
+        let Some(selected) = params.output.columns.as_ref() else {
+            return Ok(Self {
+                output: columns.clone(),
+                columns,
+                projection: None,
+            });
+        };
+
+        let mut projection = Vec::with_capacity(selected.len() + 1);
+        for name in selected {
+            let index = columns
+                .index_of(name)
+                .map_err(|_| parquet_error(format!("Unknown column: {name}")))?;
+            projection.push(index);
+            if let Ok(offset) = columns.index_of(&utc_offset_column(name)) {
+                projection.push(offset);
+            }
+        }
+        let output = columns
+            .project(&projection)
+            .map_err(|e| parquet_error(format!("Failed to select columns: {e}")))?;
+        Ok(Self {
+            columns,
+            output: Arc::new(output),
+            projection: Some(projection),
+        })
+    }
 }
 
-fn build_sunrise_schema(layout: SunriseLayout) -> Arc<Schema> {
-    build_schema(layout.csv_headers())
+fn build_schema(columns: Vec<&str>, encoding: ParquetTimestamps) -> Arc<Schema> {
+    let mut fields = Vec::with_capacity(columns.len() * 2);
+    for name in columns {
+        let field = parquet_field(name, encoding);
+        let offset =
+            (encoding == ParquetTimestamps::Native && is_datetime_column(name)).then(|| {
+                Field::new(
+                    utc_offset_column(name),
+                    DataType::Int32,
+                    field.is_nullable(),
+                )
+            });
+        fields.push(field);
+        fields.extend(offset);
+    }
+    Arc::new(Schema::new(fields))
 }
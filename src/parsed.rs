@@ -21,6 +21,7 @@ pub struct ParsedOptionUsage {
     pub headers: bool,
     pub show_inputs: bool,
     pub perf: bool,
+    pub threads: bool,
     pub step: bool,
     pub no_refraction: bool,
     pub elevation_angle: bool,
@@ -29,7 +30,37 @@ pub struct ParsedOptionUsage {
     pub pressure: bool,
     pub algorithm: bool,
     pub horizon: bool,
+    pub horizons: bool,
     pub twilight: bool,
+    pub day_stats: bool,
+    pub photo_hours: bool,
+    pub horizon_profile: bool,
+    pub irradiance: bool,
+    pub linke_turbidity: bool,
+    pub surface_tilt: bool,
+    pub surface_azimuth: bool,
+    pub tracker: bool,
+    pub shadow_height: bool,
+    pub geometry: bool,
+    pub grid_km: bool,
+    pub grid_cos_lat: bool,
+    pub parquet_timestamps: bool,
+    pub parquet_compression: bool,
+    pub parquet_row_group_size: bool,
+    pub parquet_dictionary: bool,
+    pub output_dir: bool,
+    pub partition_lat_band: bool,
+    pub max_file_rows: bool,
+    pub max_file_size: bool,
+    pub output: bool,
+    pub table: bool,
+    pub upsert: bool,
+    pub columns: bool,
+    pub list_columns: bool,
+    pub time_format: bool,
+    pub timezone: bool,
+    pub input_timezone: bool,
+    pub output_timezone: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -1,6 +1,7 @@
 //! Execution planning: convert parsed CLI data into a job specification used by main.
 
-use crate::data::{self, Command, CoordTimeStream, DataSource, Parameters};
+use crate::compute::resolve_horizon_profile;
+use crate::data::{self, Command, CoordTimeStream, Parameters};
 use crate::error::PlannerError;
 use crate::predicate::PredicateJob;
 use crate::validate::{StreamRequest, ValidCommand};
@@ -11,6 +12,7 @@ pub struct ComputePlan {
     pub params: Parameters,
     pub allow_time_cache: bool,
     pub flush_each_record: bool,
+    pub threads: usize,
 }
 
 pub enum RunPlan {
@@ -29,26 +31,20 @@ fn build_stream_plan(request: StreamRequest) -> Result<RunPlan, PlannerError> {
     let StreamRequest {
         command,
         source,
-        params,
+        mut params,
     } = request;
-    let data_iter = match &source {
-        DataSource::Separate(loc_source, time_source) => data::expand_cartesian_product(
-            loc_source.clone(),
-            time_source.clone(),
-            params.step,
-            params.timezone.clone(),
-            command,
-        )
-        .map_err(PlannerError::from),
-        DataSource::Paired(path) => data::expand_paired_file(path.clone(), params.timezone.clone())
-            .map_err(PlannerError::from),
-    }?;
+    // Loaded before any output is written, so a bad skyline file fails up front.
+    resolve_horizon_profile(&mut params)?;
+    let data_iter = data::expand_data_source(&source, &params, command)?;
+    let flush_each_record = source.uses_stdin() || source.is_watch_mode(&params.step);
 
     Ok(RunPlan::Stream(ComputePlan {
         data_iter,
         command,
         allow_time_cache: !source.is_watch_mode(&params.step),
-        flush_each_record: source.uses_stdin() || source.is_watch_mode(&params.step),
+        flush_each_record,
+        // Chunked workers would hold back records that interactive streams emit immediately.
+        threads: if flush_each_record { 1 } else { params.threads },
         params,
     }))
 }
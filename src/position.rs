@@ -2,14 +2,21 @@
 
 use crate::compute::CalculationResult;
 use crate::data::{CalculationAlgorithm, Parameters};
+use crate::error::Error;
+use crate::geometry::{solar_geometry, solar_geometry_from_parts};
+use crate::incidence::surface_incidence;
+use crate::irradiance::clear_sky_irradiance;
+use crate::shadow::shadow;
+use crate::sunrise::{clearance_above_skyline, skyline_clearance};
 use chrono::{DateTime, FixedOffset};
-use solar_positioning::RefractionCorrection;
+use solar_positioning::spa::SpaTimeDependent;
 use solar_positioning::time::DeltaT;
+use solar_positioning::{RefractionCorrection, SolarPosition};
 use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 
 pub(crate) const TIME_CACHE_CAPACITY: usize = 2048;
-pub(crate) type SpaTimeParts = Arc<solar_positioning::spa::SpaTimeDependent>;
+pub(crate) type SpaTimeParts = Arc<SpaTimeDependent>;
 pub(crate) type SpaCacheValue = Result<(SpaTimeParts, f64), String>;
 pub(crate) type SpaCache = HashMap<DateTime<FixedOffset>, SpaCacheValue>;
 
@@ -36,6 +43,84 @@ pub(crate) fn refraction_correction(
     }
 }
 
+/// Builds a position result with the derived values `params` asks for.
+///
+/// `time_parts` are the cached SPA parts for `dt`, if any; geometry columns reuse them.
+pub(crate) fn position_result(
+    lat: f64,
+    lon: f64,
+    dt: DateTime<FixedOffset>,
+    position: SolarPosition,
+    deltat: f64,
+    time_parts: Option<&SpaTimeDependent>,
+    params: &Parameters,
+) -> Result<CalculationResult, String> {
+    // Same criterion as skyline sunrise/sunset, so the sun is clear from a reported sunrise on.
+    let obstructed = params
+        .calculation
+        .horizon_profile
+        .as_ref()
+        .map(|profile| {
+            // The criterion uses the geometric SPA position at sea level: reuse the computed
+            // one when it is exactly that, or the cached time parts when they are available.
+            let geometric = params.calculation.algorithm == CalculationAlgorithm::Spa
+                && !params.environment.refraction
+                && params.environment.elevation == 0.0;
+            let clearance = if geometric {
+                clearance_above_skyline(&position, profile).0
+            } else if let Some(parts) = time_parts {
+                let position = solar_positioning::spa::spa_with_time_dependent_parts(
+                    lat, lon, 0.0, None, parts,
+                )
+                .map_err(|e| format!("Failed to calculate solar position: {}", e))?;
+                clearance_above_skyline(&position, profile).0
+            } else {
+                skyline_clearance(lat, lon, dt, deltat, profile)?.0
+            };
+            Ok::<_, String>(clearance < 0.0)
+        })
+        .transpose()?;
+    let irradiance = params.calculation.irradiance.then(|| {
+        clear_sky_irradiance(
+            dt,
+            position.zenith_angle(),
+            params.environment.elevation,
+            params.environment.pressure,
+            params.environment.linke_turbidity,
+        )
+    });
+    let incidence = params
+        .calculation
+        .surface
+        .map(|surface| surface_incidence(&surface, position.zenith_angle(), position.azimuth()));
+    let shadow = params
+        .calculation
+        .shadow_height
+        .and_then(|height| shadow(height, position.zenith_angle(), position.azimuth()));
+    let geometry = params
+        .calculation
+        .geometry
+        .then(|| match time_parts {
+            Some(parts) => solar_geometry_from_parts(dt, lon, deltat, parts),
+            None => solar_geometry(dt, lon, deltat),
+        })
+        .transpose()?;
+
+    Ok(CalculationResult::Position {
+        lat,
+        lon,
+        datetime: dt,
+        position,
+        deltat,
+        obstructed,
+        irradiance,
+        incidence,
+        shadow,
+        geometry,
+        site: None,
+    })
+}
+
 pub fn solar_elevation_at(
     lat: f64,
     lon: f64,
@@ -49,18 +134,19 @@ pub fn solar_elevation_at(
     Ok(90.0 - position.zenith_angle())
 }
 
+/// Calculates the solar position for a single location and instant.
 pub fn calculate_position(
     lat: f64,
     lon: f64,
     dt: DateTime<FixedOffset>,
     params: &Parameters,
-) -> Result<CalculationResult, String> {
+) -> Result<CalculationResult, Error> {
     let deltat = resolve_deltat(dt, params);
     let refraction = refraction_correction(params)?;
 
     let position = if params.calculation.algorithm == CalculationAlgorithm::Grena3 {
         solar_positioning::grena3::solar_position(dt, lat, lon, deltat, refraction)
-            .map_err(|e| format!("Failed to calculate solar position: {}", e))?
+            .map_err(|e| Error::calculation(format!("Failed to calculate solar position: {}", e)))?
     } else {
         solar_positioning::spa::solar_position(
             dt,
@@ -70,16 +156,10 @@ pub fn calculate_position(
             deltat,
             refraction,
         )
-        .map_err(|e| format!("Failed to calculate solar position: {}", e))?
+        .map_err(|e| Error::calculation(format!("Failed to calculate solar position: {}", e)))?
     };
 
-    Ok(CalculationResult::Position {
-        lat,
-        lon,
-        datetime: dt,
-        position,
-        deltat,
-    })
+    position_result(lat, lon, dt, position, deltat, None, params).map_err(Error::calculation)
 }
 
 pub(crate) fn time_cache_get(
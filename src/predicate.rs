@@ -2,8 +2,12 @@
 
 use crate::compute::SolarState;
 use crate::data::{Parameters, Predicate as CliPredicate};
+use crate::moon::moon_altitude_at;
 use crate::position::solar_elevation_at;
-use crate::sunrise::{is_after_sunset, next_state_transition, solar_state_at};
+use crate::sunrise::{
+    BLUE_HOUR, GOLDEN_HOUR, PhotoHour, is_after_sunset, next_state_transition, photo_hour_at,
+    solar_state_at,
+};
 use chrono::{DateTime, FixedOffset};
 
 const ANGLE_WAIT_MIN_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
@@ -29,9 +33,11 @@ pub enum SolarStatePredicate {
 #[derive(Debug, Clone, Copy)]
 pub enum PredicateCheck {
     State(SolarStatePredicate),
+    PhotoHour(PhotoHour),
     AfterSunset,
     ElevationAbove(f64),
     ElevationBelow(f64),
+    MoonAltitudeBelow(f64),
 }
 
 #[derive(Debug, Clone)]
@@ -56,9 +62,12 @@ impl PredicateCheck {
             CliPredicate::IsAstronomicalNight => {
                 Self::State(SolarStatePredicate::AstronomicalNight)
             }
+            CliPredicate::IsGoldenHour => Self::PhotoHour(PhotoHour::Golden),
+            CliPredicate::IsBlueHour => Self::PhotoHour(PhotoHour::Blue),
             CliPredicate::AfterSunset => Self::AfterSunset,
             CliPredicate::SunAbove(threshold) => Self::ElevationAbove(threshold),
             CliPredicate::SunBelow(threshold) => Self::ElevationBelow(threshold),
+            CliPredicate::MoonBelow(threshold) => Self::MoonAltitudeBelow(threshold),
         }
     }
 }
@@ -112,6 +121,14 @@ fn angle_wait_duration(current_elevation: f64, threshold: f64) -> std::time::Dur
     std::time::Duration::from_secs_f64(seconds)
 }
 
+/// Elevation limits of a photographic light period.
+fn photo_hour_band(hour: PhotoHour) -> (f64, f64) {
+    match hour {
+        PhotoHour::Golden => GOLDEN_HOUR,
+        PhotoHour::Blue => BLUE_HOUR,
+    }
+}
+
 pub fn run_once(job: &PredicateJob) -> Result<bool, String> {
     let now = resolve_time(&job.time, &job.params)?;
     match job.check {
@@ -119,6 +136,9 @@ pub fn run_once(job: &PredicateJob) -> Result<bool, String> {
             predicate,
             solar_state_at(job.lat, job.lon, now, &job.params)?,
         )),
+        PredicateCheck::PhotoHour(hour) => {
+            Ok(photo_hour_at(job.lat, job.lon, now, &job.params)? == Some(hour))
+        }
         PredicateCheck::AfterSunset => is_after_sunset(job.lat, job.lon, now, &job.params),
         PredicateCheck::ElevationAbove(threshold) => {
             Ok(solar_elevation_at(job.lat, job.lon, now, &job.params)? > threshold)
@@ -126,6 +146,9 @@ pub fn run_once(job: &PredicateJob) -> Result<bool, String> {
         PredicateCheck::ElevationBelow(threshold) => {
             Ok(solar_elevation_at(job.lat, job.lon, now, &job.params)? < threshold)
         }
+        PredicateCheck::MoonAltitudeBelow(threshold) => {
+            Ok(moon_altitude_at(job.lat, job.lon, now, &job.params)? < threshold)
+        }
     }
 }
 
@@ -168,6 +191,30 @@ pub fn wait_until_true(job: &PredicateJob) -> Result<(), String> {
             }
             std::thread::sleep(angle_wait_duration(elevation, threshold));
         },
+        // The moon's altitude changes no faster than the sun's, so the same polling applies.
+        PredicateCheck::MoonAltitudeBelow(threshold) => loop {
+            let now = resolve_time(&job.time, &job.params)?;
+            let altitude = moon_altitude_at(job.lat, job.lon, now, &job.params)?;
+            if altitude < threshold {
+                return Ok(());
+            }
+            std::thread::sleep(angle_wait_duration(altitude, threshold));
+        },
+        // Poll against the nearer edge of the elevation band, like the sun angle predicates.
+        PredicateCheck::PhotoHour(hour) => loop {
+            let now = resolve_time(&job.time, &job.params)?;
+            if photo_hour_at(job.lat, job.lon, now, &job.params)? == Some(hour) {
+                return Ok(());
+            }
+            let elevation = solar_elevation_at(job.lat, job.lon, now, &job.params)?;
+            let (low, high) = photo_hour_band(hour);
+            let edge = if (elevation - low).abs() < (elevation - high).abs() {
+                low
+            } else {
+                high
+            };
+            std::thread::sleep(angle_wait_duration(elevation, edge));
+        },
         PredicateCheck::State(predicate) => loop {
             let now = resolve_time(&job.time, &job.params)?;
             if state_matches(
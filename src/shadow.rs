@@ -0,0 +1,58 @@
+//! Shadows cast by vertical objects on level ground.
+
+/// Shadow of a vertical object, in metres and degrees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Shadow {
+    /// Distance from the object's base to the tip of the shadow.
+    pub length: f64,
+    /// Direction the shadow points, clockwise from north.
+    pub azimuth: f64,
+    /// Eastward offset of the shadow tip from the object's base.
+    pub east: f64,
+    /// Northward offset of the shadow tip from the object's base.
+    pub north: f64,
+}
+
+/// Calculates the shadow of an object `height` metres tall with the sun at `zenith` and
+/// `azimuth` degrees.
+///
+/// Returns `None` with the sun at or below the horizon, where the shadow is unbounded.
+pub fn shadow(height: f64, zenith: f64, azimuth: f64) -> Option<Shadow> {
+    if zenith >= 90.0 {
+        return None;
+    }
+    let length = height * zenith.to_radians().tan();
+    let azimuth = (azimuth + 180.0).rem_euclid(360.0);
+    let (sin_azimuth, cos_azimuth) = azimuth.to_radians().sin_cos();
+    Some(Shadow {
+        length,
+        azimuth,
+        east: length * sin_azimuth,
+        north: length * cos_azimuth,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shadow_points_away_from_sun() {
+        let noon = shadow(10.0, 45.0, 180.0).unwrap();
+        assert!((noon.length - 10.0).abs() < 1e-9);
+        assert_eq!(noon.azimuth, 0.0);
+        assert!(noon.east.abs() < 1e-9);
+        assert!((noon.north - 10.0).abs() < 1e-9);
+
+        let morning = shadow(2.0, 60.0, 90.0).unwrap();
+        assert!((morning.length - 2.0 * 3f64.sqrt()).abs() < 1e-9);
+        assert_eq!(morning.azimuth, 270.0);
+        assert!((morning.east + morning.length).abs() < 1e-9);
+    }
+
+    #[test]
+    fn no_shadow_with_sun_down() {
+        assert_eq!(shadow(10.0, 90.0, 180.0), None);
+        assert_eq!(shadow(10.0, 120.0, 0.0), None);
+    }
+}
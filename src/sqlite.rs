@@ -0,0 +1,298 @@
+//! SQLite output format support.
+
+use crate::compute::CalculationStream;
+use crate::data::{Command, Parameters};
+use crate::error::OutputError;
+use crate::output::{
+    DateTimeCache, FixedDecimalCache, MoonPositionLayout, MoonPositionRow, MoonriseLayout,
+    MoonriseRow, OutputRowExt, PositionLayout, PositionRow, SunriseLayout, SunriseRow,
+    TransitionLayout, TransitionRow, column_indices, is_datetime_column, is_numeric_column,
+    project, project_values, row_from_result,
+};
+use rusqlite::Connection;
+use rusqlite::types::Value;
+
+/// Rows inserted per transaction.
+const BATCH_SIZE: usize = 10_000;
+/// Extra `--upsert` column holding the `dateTime` instant as Unix seconds, so the key does not
+/// depend on `--timezone` or `--time-format`.
+const EPOCH_KEY_COLUMN: &str = "dateTime_epoch";
+/// Columns identifying a row for `--upsert`.
+const KEY_COLUMNS: [&str; 3] = ["latitude", "longitude", EPOCH_KEY_COLUMN];
+
+/// Storage class of an output column, chosen from its name like the Parquet schema.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SqlType {
+    Integer,
+    Real,
+    Text,
+}
+
+impl SqlType {
+    /// Date/time columns are integers when `epoch` formats are selected.
+    fn of(name: &str, epoch: bool) -> Self {
+        match name {
+            name if epoch && is_datetime_column(name) => Self::Integer,
+            "day_length"
+            | "civil_twilight"
+            | "nautical_twilight"
+            | "astronomical_twilight"
+            | "day_length_change"
+            | "obstructed" => Self::Integer,
+            name if is_numeric_column(name) => Self::Real,
+            _ => Self::Text,
+        }
+    }
+
+    fn declaration(self) -> &'static str {
+        match self {
+            Self::Integer => "INTEGER",
+            Self::Real => "REAL",
+            Self::Text => "TEXT",
+        }
+    }
+
+    /// Converts a CSV field to a typed value; empty fields become `NULL`.
+    fn value(self, field: &str) -> Value {
+        if field.is_empty() {
+            return Value::Null;
+        }
+        let parsed = match self {
+            Self::Integer => match field {
+                "true" => Some(Value::Integer(1)),
+                "false" => Some(Value::Integer(0)),
+                _ => field.parse().ok().map(Value::Integer),
+            },
+            Self::Real => field.parse().ok().map(Value::Real),
+            Self::Text => None,
+        };
+        parsed.unwrap_or_else(|| Value::Text(field.to_string()))
+    }
+}
+
+fn sqlite_error(err: rusqlite::Error) -> OutputError {
+    OutputError::output(format!("SQLite error: {err}"))
+}
+
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Writes results into a SQLite table, creating the database file and table as needed.
+///
+/// Rows are inserted in transactions of [`BATCH_SIZE`] rows, or one row each with
+/// `flush_each_record`. Returns the number of records written.
+pub(crate) fn write_sqlite(
+    results: CalculationStream,
+    command: Command,
+    params: &Parameters,
+    flush_each_record: bool,
+) -> Result<usize, OutputError> {
+    let options = &params.output.sqlite;
+    let path = options
+        .path
+        .as_deref()
+        .ok_or_else(|| OutputError::from("SQLite output requires --output=<file>"))?;
+    let table = options.table.clone().unwrap_or_else(|| command.to_string());
+    let connection = Connection::open(path)
+        .map_err(|err| OutputError::io(format!("Failed to open {}: {err}", path.display())))?;
+    let batch_size = if flush_each_record { 1 } else { BATCH_SIZE };
+
+    match command {
+        Command::Position => write_table::<PositionRow>(
+            results,
+            params,
+            PositionLayout::from_params(params),
+            &connection,
+            &table,
+            batch_size,
+        ),
+        Command::Sunrise => write_table::<SunriseRow>(
+            results,
+            params,
+            SunriseLayout::from_params(params),
+            &connection,
+            &table,
+            batch_size,
+        ),
+        Command::Events => write_table::<TransitionRow>(
+            results,
+            params,
+            TransitionLayout::from_params(params),
+            &connection,
+            &table,
+            batch_size,
+        ),
+        Command::MoonPosition => write_table::<MoonPositionRow>(
+            results,
+            params,
+            MoonPositionLayout::from_params(params),
+            &connection,
+            &table,
+            batch_size,
+        ),
+        Command::Moonrise => write_table::<MoonriseRow>(
+            results,
+            params,
+            MoonriseLayout::from_params(params),
+            &connection,
+            &table,
+            batch_size,
+        ),
+    }
+}
+
+fn write_table<R: OutputRowExt>(
+    results: CalculationStream,
+    params: &Parameters,
+    layout: R::Layout,
+    connection: &Connection,
+    table: &str,
+    batch_size: usize,
+) -> Result<usize, OutputError> {
+    let all_headers = R::headers(layout, params);
+    let selection = column_indices(&all_headers, params);
+    let selection = selection.as_deref();
+    let mut headers = project(&all_headers, selection);
+    let upsert = params.output.sqlite.upsert;
+    if upsert {
+        if let Some(missing) = KEY_COLUMNS[..2].iter().find(|key| !headers.contains(key)) {
+            return Err(OutputError::from(format!(
+                "Option --upsert requires the {missing} column"
+            )));
+        }
+        headers.push(EPOCH_KEY_COLUMN);
+    }
+    let epoch = params.output.time_format.is_epoch();
+    let types: Vec<SqlType> = headers
+        .iter()
+        .map(|name| match *name {
+            EPOCH_KEY_COLUMN => SqlType::Integer,
+            name => SqlType::of(name, epoch),
+        })
+        .collect();
+
+    connection
+        .execute_batch(&create_table_sql(table, &headers, &types, upsert))
+        .map_err(sqlite_error)?;
+    let mut statement = connection
+        .prepare(&insert_sql(table, &headers, upsert))
+        .map_err(sqlite_error)?;
+
+    let mut datetime_cache = DateTimeCache::with_capacity(&params.output.time_format, 2048);
+    let mut fixed_decimal_cache = FixedDecimalCache::with_capacity(256);
+    let mut row_values = Vec::new();
+    let mut scratch = Vec::new();
+    let mut pending = 0;
+    let mut count = 0;
+
+    connection.execute_batch("BEGIN").map_err(sqlite_error)?;
+    for result in results {
+        let row = row_from_result::<R>(result)?;
+        row.csv_values(
+            params,
+            layout,
+            &mut datetime_cache,
+            &mut fixed_decimal_cache,
+            &mut row_values,
+        );
+        project_values(&mut row_values, selection, &mut scratch);
+        if upsert {
+            row_values.push(row.datetime().timestamp().to_string());
+        }
+        statement
+            .execute(rusqlite::params_from_iter(
+                types
+                    .iter()
+                    .zip(&row_values)
+                    .map(|(sql_type, field)| sql_type.value(field)),
+            ))
+            .map_err(sqlite_error)?;
+        pending += 1;
+        count += 1;
+
+        if pending == batch_size {
+            connection
+                .execute_batch("COMMIT; BEGIN")
+                .map_err(sqlite_error)?;
+            pending = 0;
+        }
+    }
+    connection.execute_batch("COMMIT").map_err(sqlite_error)?;
+    Ok(count)
+}
+
+fn create_table_sql(table: &str, headers: &[&str], types: &[SqlType], upsert: bool) -> String {
+    let columns = headers
+        .iter()
+        .zip(types)
+        .map(|(name, sql_type)| {
+            let not_null = if *name == EPOCH_KEY_COLUMN {
+                " NOT NULL"
+            } else {
+                ""
+            };
+            format!(
+                "{} {}{not_null}",
+                quote_identifier(name),
+                sql_type.declaration()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut sql = format!(
+        "CREATE TABLE IF NOT EXISTS {} ({columns});",
+        quote_identifier(table)
+    );
+    if upsert {
+        sql.push_str(&format!(
+            "CREATE UNIQUE INDEX IF NOT EXISTS {} ON {} ({});",
+            quote_identifier(&format!("{table}_key")),
+            quote_identifier(table),
+            key_columns()
+        ));
+    }
+    sql
+}
+
+fn insert_sql(table: &str, headers: &[&str], upsert: bool) -> String {
+    let columns = headers
+        .iter()
+        .map(|name| quote_identifier(name))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let placeholders = (1..=headers.len())
+        .map(|index| format!("?{index}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut sql = format!(
+        "INSERT INTO {} ({columns}) VALUES ({placeholders})",
+        quote_identifier(table)
+    );
+    if upsert {
+        let updates = headers
+            .iter()
+            .filter(|name| !KEY_COLUMNS.contains(name))
+            .map(|name| {
+                let column = quote_identifier(name);
+                format!("{column} = excluded.{column}")
+            })
+            .collect::<Vec<_>>();
+        // With only key columns selected there is nothing to refresh.
+        let action = if updates.is_empty() {
+            "DO NOTHING".to_string()
+        } else {
+            format!("DO UPDATE SET {}", updates.join(", "))
+        };
+        sql.push_str(&format!(" ON CONFLICT ({}) {action}", key_columns()));
+    }
+    sql
+}
+
+fn key_columns() -> String {
+    KEY_COLUMNS
+        .iter()
+        .map(|name| quote_identifier(name))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
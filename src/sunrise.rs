@@ -1,13 +1,38 @@
 //! Sunrise, twilight, and solar-state calculations.
 
 use crate::compute::{CalculationResult, SolarState};
-use crate::data::Parameters;
+use crate::data::{CustomHorizon, HorizonProfile, Parameters};
+use crate::error::Error;
 use crate::position::resolve_deltat;
-use chrono::{DateTime, Days, FixedOffset, NaiveDate};
-use solar_positioning::{Horizon, SunriseResult};
+use chrono::{DateTime, Days, Duration, FixedOffset, NaiveDate, NaiveTime, Timelike};
+use solar_positioning::{Horizon, SolarPosition, SunriseResult};
 
 const MAX_WAIT_SEARCH_DAYS: u64 = 370;
 
+/// Horizons separating adjacent solar states, with the darker and brighter state on either side.
+const STATE_BOUNDARIES: [(Horizon, SolarState, SolarState); 4] = [
+    (
+        Horizon::SunriseSunset,
+        SolarState::CivilTwilight,
+        SolarState::Daylight,
+    ),
+    (
+        Horizon::CivilTwilight,
+        SolarState::NauticalTwilight,
+        SolarState::CivilTwilight,
+    ),
+    (
+        Horizon::NauticalTwilight,
+        SolarState::AstronomicalTwilight,
+        SolarState::NauticalTwilight,
+    ),
+    (
+        Horizon::AstronomicalTwilight,
+        SolarState::Night,
+        SolarState::AstronomicalTwilight,
+    ),
+];
+
 #[derive(Debug, Clone)]
 struct TwilightResults {
     sunrise_sunset: SunriseResult<DateTime<FixedOffset>>,
@@ -22,6 +47,19 @@ fn calculate_twilight_results(
     dt: DateTime<FixedOffset>,
     deltat: f64,
 ) -> Result<TwilightResults, String> {
+    calculate_horizon_results(lat, lon, dt, deltat, &[]).map(|(results, _)| results)
+}
+
+type LocalSunriseResult = SunriseResult<DateTime<FixedOffset>>;
+
+/// Calculates the standard horizons together with `custom` ones, in degrees, in a single pass.
+fn calculate_horizon_results(
+    lat: f64,
+    lon: f64,
+    dt: DateTime<FixedOffset>,
+    deltat: f64,
+    custom: &[f64],
+) -> Result<(TwilightResults, Vec<LocalSunriseResult>), String> {
     const HORIZONS: [Horizon; 4] = [
         Horizon::SunriseSunset,
         Horizon::CivilTwilight,
@@ -29,32 +67,45 @@ fn calculate_twilight_results(
         Horizon::AstronomicalTwilight,
     ];
 
+    let horizons = HORIZONS
+        .into_iter()
+        .chain(custom.iter().copied().map(Horizon::Custom));
     let mut results = solar_positioning::spa::sunrise_sunset_multiple(
-        dt, lat, lon, deltat, HORIZONS,
+        dt, lat, lon, deltat, horizons,
     )
     .map(|res| {
         res.map(|(_, r)| r)
             .map_err(|e| format!("Failed to calculate twilight: {}", e))
     });
-
-    Ok(TwilightResults {
-        sunrise_sunset: results
+    let mut next = || {
+        results
             .next()
             .transpose()?
-            .ok_or_else(|| "Failed to calculate twilight: incomplete result set".to_string())?,
-        civil: results
-            .next()
-            .transpose()?
-            .ok_or_else(|| "Failed to calculate twilight: incomplete result set".to_string())?,
-        nautical: results
-            .next()
-            .transpose()?
-            .ok_or_else(|| "Failed to calculate twilight: incomplete result set".to_string())?,
-        astronomical: results
-            .next()
-            .transpose()?
-            .ok_or_else(|| "Failed to calculate twilight: incomplete result set".to_string())?,
-    })
+            .ok_or_else(|| "Failed to calculate twilight: incomplete result set".to_string())
+    };
+
+    let twilight = TwilightResults {
+        sunrise_sunset: next()?,
+        civil: next()?,
+        nautical: next()?,
+        astronomical: next()?,
+    };
+    let custom = (0..custom.len())
+        .map(|_| next())
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((twilight, custom))
+}
+
+impl TwilightResults {
+    /// Results in [`STATE_BOUNDARIES`] order.
+    fn by_boundary(&self) -> [&SunriseResult<DateTime<FixedOffset>>; 4] {
+        [
+            &self.sunrise_sunset,
+            &self.civil,
+            &self.nautical,
+            &self.astronomical,
+        ]
+    }
 }
 
 fn is_above_horizon(
@@ -111,6 +162,38 @@ fn local_noon(date: NaiveDate, params: &Parameters) -> Result<DateTime<FixedOffs
     local_datetime(date, 12, 0, 0, params)
 }
 
+/// Returns local midnight at the start and end of the day containing `dt`.
+///
+/// Uses the configured timezone when `dt` belongs to it, so DST days have their real length;
+/// otherwise keeps the fixed offset `dt` was given with.
+pub(crate) fn local_day_bounds(
+    dt: DateTime<FixedOffset>,
+    params: &Parameters,
+) -> Result<(DateTime<FixedOffset>, DateTime<FixedOffset>), String> {
+    let date = dt.date_naive();
+    let in_zone = local_datetime(
+        date,
+        dt.time().hour(),
+        dt.time().minute(),
+        dt.time().second(),
+        params,
+    )
+    .is_ok_and(|local| local.offset() == dt.offset());
+    if in_zone {
+        let next = date
+            .checked_add_days(Days::new(1))
+            .ok_or_else(|| "Date out of range".to_string())?;
+        return Ok((local_midnight(date, params)?, local_midnight(next, params)?));
+    }
+
+    let start = date
+        .and_time(NaiveTime::MIN)
+        .and_local_timezone(*dt.offset())
+        .single()
+        .ok_or_else(|| "Date out of range".to_string())?;
+    Ok((start, start + Duration::days(1)))
+}
+
 fn next_matching_state_start(
     results: &TwilightResults,
     target: SolarState,
@@ -214,21 +297,556 @@ pub fn is_after_sunset(
     })
 }
 
+/// Classifies the solar state from the geometric (unrefracted) elevation of the sun's centre.
+fn state_for_elevation(elevation: f64) -> SolarState {
+    STATE_BOUNDARIES
+        .iter()
+        .find(|(horizon, _, _)| elevation >= horizon.elevation_angle())
+        .map_or(SolarState::Night, |(_, _, brighter)| *brighter)
+}
+
+fn state_at(
+    lat: f64,
+    lon: f64,
+    dt: DateTime<FixedOffset>,
+    deltat: f64,
+) -> Result<SolarState, String> {
+    let position = solar_positioning::spa::solar_position(dt, lat, lon, 0.0, deltat, None)
+        .map_err(|e| format!("Failed to calculate solar position: {}", e))?;
+    Ok(state_for_elevation(90.0 - position.zenith_angle()))
+}
+
+fn state_level(state: SolarState) -> usize {
+    match state {
+        SolarState::Daylight => 0,
+        SolarState::CivilTwilight => 1,
+        SolarState::NauticalTwilight => 2,
+        SolarState::AstronomicalTwilight => 3,
+        SolarState::Night => 4,
+    }
+}
+
+/// One step between adjacent solar states.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct StateStep {
+    time: DateTime<FixedOffset>,
+    before: SolarState,
+    after: SolarState,
+    horizon: f64,
+}
+
+/// Splits a change from `before` to `after` into steps across each horizon in between.
+fn push_steps(
+    time: DateTime<FixedOffset>,
+    before: SolarState,
+    after: SolarState,
+    out: &mut Vec<StateStep>,
+) {
+    let (from, to) = (state_level(before), state_level(after));
+    let levels: Vec<usize> = if from < to {
+        (from..to).collect()
+    } else {
+        (to..from).rev().collect()
+    };
+    for level in levels {
+        let (horizon, darker, brighter) = STATE_BOUNDARIES[level];
+        let (before, after) = if from < to {
+            (brighter, darker)
+        } else {
+            (darker, brighter)
+        };
+        out.push(StateStep {
+            time,
+            before,
+            after,
+            horizon: horizon.elevation_angle(),
+        });
+    }
+}
+
+/// Locates state changes between `start` and `end` to within one second.
+fn bisect_steps(
+    lat: f64,
+    lon: f64,
+    deltat: f64,
+    (start, start_state): (DateTime<FixedOffset>, SolarState),
+    (end, end_state): (DateTime<FixedOffset>, SolarState),
+    out: &mut Vec<StateStep>,
+) -> Result<(), String> {
+    if start_state == end_state {
+        return Ok(());
+    }
+    if end - start <= Duration::seconds(1) {
+        push_steps(end, start_state, end_state, out);
+        return Ok(());
+    }
+    let mid = start + (end - start) / 2;
+    let mid_state = state_at(lat, lon, mid, deltat)?;
+    bisect_steps(
+        lat,
+        lon,
+        deltat,
+        (start, start_state),
+        (mid, mid_state),
+        out,
+    )?;
+    bisect_steps(lat, lon, deltat, (mid, mid_state), (end, end_state), out)
+}
+
+/// Lists the solar-state transitions during the local day of `dt`, in time order.
+///
+/// Sunrise/twilight times are used where they agree with the sun's computed elevation;
+/// remaining changes are located by bisection. Polar day and night produce no transitions.
+pub fn calculate_transitions(
+    lat: f64,
+    lon: f64,
+    dt: DateTime<FixedOffset>,
+    params: &Parameters,
+) -> Result<Vec<CalculationResult>, Error> {
+    // Distance from each candidate crossing at which the state is sampled. Tolerates small
+    // timing differences between the sunrise algorithm and the position algorithm.
+    const PROBE: Duration = Duration::seconds(90);
+
+    let (day_start, day_end) = local_day_bounds(dt, params)?;
+    let noon = day_start + Duration::hours(12);
+    let deltat = resolve_deltat(noon, params);
+
+    // Sunrise/sunset results bracket the local transit, so at high latitudes crossings on this
+    // day can belong to the previous or next day's results.
+    let mut candidates: Vec<StateStep> = Vec::new();
+    for anchor in [noon - Duration::days(1), noon, noon + Duration::days(1)] {
+        let results =
+            calculate_twilight_results(lat, lon, anchor, deltat).map_err(Error::calculation)?;
+        for (result, (horizon, darker, brighter)) in
+            results.by_boundary().into_iter().zip(STATE_BOUNDARIES)
+        {
+            if let SunriseResult::RegularDay {
+                sunrise, sunset, ..
+            } = result
+            {
+                for (time, before, after) in
+                    [(*sunrise, darker, brighter), (*sunset, brighter, darker)]
+                {
+                    if day_start < time && time <= day_end {
+                        candidates.push(StateStep {
+                            time,
+                            before,
+                            after,
+                            horizon: horizon.elevation_angle(),
+                        });
+                    }
+                }
+            }
+        }
+    }
+    candidates.sort_by_key(|step| step.time);
+
+    let mut probes = vec![day_start, day_end];
+    for step in &candidates {
+        probes.push((step.time - PROBE).max(day_start));
+        probes.push((step.time + PROBE).min(day_end));
+    }
+    probes.sort_unstable();
+    probes.dedup();
+
+    let mut steps = Vec::new();
+    let start_state = state_at(lat, lon, day_start, deltat).map_err(Error::calculation)?;
+    let mut previous = (day_start, start_state);
+    for &probe in &probes[1..] {
+        let (start, start_state) = previous;
+        let end_state = state_at(lat, lon, probe, deltat).map_err(Error::calculation)?;
+        previous = (probe, end_state);
+        if start_state == end_state {
+            continue;
+        }
+
+        let mut current = (start, start_state);
+        for candidate in candidates
+            .iter()
+            .filter(|step| start < step.time && step.time <= probe)
+        {
+            let toward_end = state_level(candidate.after).abs_diff(state_level(end_state))
+                < state_level(current.1).abs_diff(state_level(end_state));
+            if candidate.before == current.1 && toward_end {
+                steps.push(*candidate);
+                current = (candidate.time, candidate.after);
+            }
+        }
+        bisect_steps(lat, lon, deltat, current, (probe, end_state), &mut steps)
+            .map_err(Error::calculation)?;
+    }
+
+    Ok(steps
+        .into_iter()
+        .map(|step| CalculationResult::Transition {
+            lat,
+            lon,
+            datetime: step.time,
+            before: step.before,
+            after: step.after,
+            horizon: step.horizon,
+            deltat,
+            site: None,
+        })
+        .collect())
+}
+
+/// Returns the sun's height above the skyline and its geometric elevation, in degrees.
+///
+/// Like standard sunrise/sunset, the sun counts as clear of the skyline from when its upper
+/// limb appears under standard refraction, so a flat profile at 0 degrees matches `sunrise`.
+pub(crate) fn skyline_clearance(
+    lat: f64,
+    lon: f64,
+    dt: DateTime<FixedOffset>,
+    deltat: f64,
+    profile: &HorizonProfile,
+) -> Result<(f64, f64), String> {
+    let position = solar_positioning::spa::solar_position(dt, lat, lon, 0.0, deltat, None)
+        .map_err(|e| format!("Failed to calculate solar position: {}", e))?;
+    Ok(clearance_above_skyline(&position, profile))
+}
+
+/// Like [`skyline_clearance`], for a geometric SPA position at zero observer elevation.
+pub(crate) fn clearance_above_skyline(
+    position: &SolarPosition,
+    profile: &HorizonProfile,
+) -> (f64, f64) {
+    let elevation = 90.0 - position.zenith_angle();
+    let skyline =
+        profile.elevation_at(position.azimuth()) + Horizon::SunriseSunset.elevation_angle();
+    (elevation - skyline, elevation)
+}
+
+/// Lists the periods with the sun clear of the skyline during the local day of `dt`.
+///
+/// The day is sampled every two minutes while the sun is high enough to clear the lowest
+/// point of the profile, and each change is located to within one second. Skyline gaps the
+/// sun crosses in less than the sampling interval may be missed.
+pub fn calculate_skyline_sunrise(
+    lat: f64,
+    lon: f64,
+    dt: DateTime<FixedOffset>,
+    params: &Parameters,
+    profile: &HorizonProfile,
+) -> Result<Vec<CalculationResult>, Error> {
+    const SAMPLE_STEP: Duration = Duration::minutes(2);
+    const MAX_SKIP: Duration = Duration::hours(1);
+    // The sun's elevation changes by at most 15 degrees per hour.
+    const MAX_DEGREES_PER_MINUTE: f64 = 0.25;
+
+    let deltat = resolve_deltat(dt, params);
+    let (day_start, day_end) = local_day_bounds(dt, params)?;
+    let transit = match solar_positioning::spa::sunrise_sunset_for_horizon(
+        dt,
+        lat,
+        lon,
+        deltat,
+        Horizon::SunriseSunset,
+    )
+    .map_err(|e| Error::calculation(format!("Failed to calculate sunrise/sunset: {}", e)))?
+    {
+        SunriseResult::RegularDay { transit, .. }
+        | SunriseResult::AllDay { transit }
+        | SunriseResult::AllNight { transit } => transit,
+    };
+    let lowest = profile.min_elevation() + Horizon::SunriseSunset.elevation_angle();
+    let clearance_at =
+        |time| skyline_clearance(lat, lon, time, deltat, profile).map_err(Error::calculation);
+    let clear_at = |time| clearance_at(time).map(|(clearance, _)| clearance >= 0.0);
+
+    let (clearance, mut elevation) = clearance_at(day_start)?;
+    let mut previous = (day_start, clearance >= 0.0);
+    let mut current_start = previous.1.then_some(None);
+    let mut periods = Vec::new();
+    while previous.0 < day_end {
+        let step = if elevation < lowest {
+            let minutes = ((lowest - elevation) / MAX_DEGREES_PER_MINUTE).floor() as i64;
+            Duration::minutes(minutes).clamp(SAMPLE_STEP, MAX_SKIP)
+        } else {
+            SAMPLE_STEP
+        };
+        let time = (previous.0 + step).min(day_end);
+        let (clearance, next_elevation) = clearance_at(time)?;
+        elevation = next_elevation;
+        let clear = clearance >= 0.0;
+
+        if clear != previous.1 {
+            // Whole seconds, so the reported time is the first second on the new side.
+            let (mut low, mut high) = (previous.0, time);
+            while high - low > Duration::seconds(1) {
+                let mid = low + Duration::seconds((high - low).num_seconds() / 2);
+                if clear_at(mid)? == clear {
+                    high = mid;
+                } else {
+                    low = mid;
+                }
+            }
+            if clear {
+                current_start = Some(Some(high));
+            } else if let Some(start) = current_start.take() {
+                periods.push((start, Some(high)));
+            }
+        }
+        previous = (time, clear);
+    }
+    if let Some(start) = current_start {
+        periods.push((start, None));
+    }
+
+    let result = |sunrise, sunset, visible| CalculationResult::SkylineSunrise {
+        lat,
+        lon,
+        date: dt,
+        sunrise,
+        transit,
+        sunset,
+        visible,
+        deltat,
+        site: None,
+    };
+    if periods.is_empty() {
+        return Ok(vec![result(None, None, false)]);
+    }
+    Ok(periods
+        .into_iter()
+        .map(|(sunrise, sunset)| result(sunrise, sunset, true))
+        .collect())
+}
+
+/// Daily quantities derived from sunrise and twilight results. Durations are in seconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DayStats {
+    /// Time from sunrise to sunset.
+    pub day_length: i64,
+    /// Time in civil twilight, morning and evening combined.
+    pub civil_twilight: i64,
+    /// Time in nautical twilight, morning and evening combined.
+    pub nautical_twilight: i64,
+    /// Time in astronomical twilight, morning and evening combined.
+    pub astronomical_twilight: i64,
+    /// Geometric elevation angle of the sun's centre at transit, in degrees.
+    pub noon_elevation: f64,
+    /// Day length minus the previous day's day length.
+    pub day_length_change: i64,
+}
+
+/// Elevation band of the golden hour, in degrees.
+pub(crate) const GOLDEN_HOUR: (f64, f64) = (-4.0, 6.0);
+/// Elevation band of the blue hour, in degrees.
+pub(crate) const BLUE_HOUR: (f64, f64) = (-6.0, -4.0);
+
+/// Golden-hour and blue-hour periods of one day.
+///
+/// A time is `None` when the sun does not cross that elevation on the day, as in polar regions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PhotoHours {
+    pub golden_morning_start: Option<DateTime<FixedOffset>>,
+    pub golden_morning_end: Option<DateTime<FixedOffset>>,
+    pub golden_evening_start: Option<DateTime<FixedOffset>>,
+    pub golden_evening_end: Option<DateTime<FixedOffset>>,
+    pub blue_morning_start: Option<DateTime<FixedOffset>>,
+    pub blue_morning_end: Option<DateTime<FixedOffset>>,
+    pub blue_evening_start: Option<DateTime<FixedOffset>>,
+    pub blue_evening_end: Option<DateTime<FixedOffset>>,
+}
+
+/// A photographic light period, as checked by `--is-golden-hour` and `--is-blue-hour`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PhotoHour {
+    Golden,
+    Blue,
+}
+
+/// Lower blue-hour, lower golden-hour, and upper golden-hour horizons, in degrees.
+const PHOTO_HOUR_HORIZONS: [f64; 3] = [BLUE_HOUR.0, GOLDEN_HOUR.0, GOLDEN_HOUR.1];
+
+/// Sunrise results for the [`PHOTO_HOUR_HORIZONS`].
+fn photo_hour_results(
+    lat: f64,
+    lon: f64,
+    dt: DateTime<FixedOffset>,
+    deltat: f64,
+) -> Result<[SunriseResult<DateTime<FixedOffset>>; 3], String> {
+    let horizons = PHOTO_HOUR_HORIZONS.map(Horizon::Custom);
+    let mut results = horizons.into_iter().map(|horizon| {
+        solar_positioning::spa::sunrise_sunset_for_horizon(dt, lat, lon, deltat, horizon)
+            .map_err(|e| format!("Failed to calculate golden/blue hour: {}", e))
+    });
+    let mut next = || {
+        results.next().transpose()?.ok_or_else(|| {
+            "Failed to calculate golden/blue hour: incomplete result set".to_string()
+        })
+    };
+    Ok([next()?, next()?, next()?])
+}
+
+/// Rising and setting time of a regular day; `None` for both in polar day or night.
+fn crossings(
+    result: &SunriseResult<DateTime<FixedOffset>>,
+) -> (Option<DateTime<FixedOffset>>, Option<DateTime<FixedOffset>>) {
+    match result {
+        SunriseResult::RegularDay {
+            sunrise, sunset, ..
+        } => (Some(*sunrise), Some(*sunset)),
+        SunriseResult::AllDay { .. } | SunriseResult::AllNight { .. } => (None, None),
+    }
+}
+
+/// Builds golden and blue hours from the sunrise results for the [`PHOTO_HOUR_HORIZONS`].
+fn photo_hours(results: [&LocalSunriseResult; 3]) -> PhotoHours {
+    let [
+        (blue_rise, blue_set),
+        (low_rise, low_set),
+        (high_rise, high_set),
+    ] = results.map(crossings);
+    PhotoHours {
+        golden_morning_start: low_rise,
+        golden_morning_end: high_rise,
+        golden_evening_start: high_set,
+        golden_evening_end: low_set,
+        blue_morning_start: blue_rise,
+        blue_morning_end: low_rise,
+        blue_evening_start: low_set,
+        blue_evening_end: blue_set,
+    }
+}
+
+/// Returns the photographic light period at `dt`, if any.
+pub fn photo_hour_at(
+    lat: f64,
+    lon: f64,
+    dt: DateTime<FixedOffset>,
+    params: &Parameters,
+) -> Result<Option<PhotoHour>, String> {
+    let [blue, low, high] = photo_hour_results(lat, lon, dt, resolve_deltat(dt, params))?;
+    Ok(if is_above_horizon(&high, dt) {
+        None
+    } else if is_above_horizon(&low, dt) {
+        Some(PhotoHour::Golden)
+    } else if is_above_horizon(&blue, dt) {
+        Some(PhotoHour::Blue)
+    } else {
+        None
+    })
+}
+
+/// Returns the time above the horizon of `result`, in seconds.
+///
+/// Polar day lasts the whole local day of `dt`, so DST days keep their real length.
+fn time_above(
+    result: &SunriseResult<DateTime<FixedOffset>>,
+    dt: DateTime<FixedOffset>,
+    params: &Parameters,
+) -> Result<i64, String> {
+    Ok(match result {
+        SunriseResult::RegularDay {
+            sunrise, sunset, ..
+        } => (*sunset - *sunrise).num_seconds(),
+        SunriseResult::AllDay { .. } => {
+            let (start, end) = local_day_bounds(dt, params)?;
+            (end - start).num_seconds()
+        }
+        SunriseResult::AllNight { .. } => 0,
+    })
+}
+
+fn calculate_day_stats(
+    lat: f64,
+    lon: f64,
+    dt: DateTime<FixedOffset>,
+    deltat: f64,
+    params: &Parameters,
+    results: &TwilightResults,
+) -> Result<DayStats, String> {
+    let [day, civil, nautical, astronomical] = results
+        .by_boundary()
+        .map(|result| time_above(result, dt, params));
+    let (day, civil, nautical, astronomical) = (day?, civil?, nautical?, astronomical?);
+
+    let transit = match results.sunrise_sunset {
+        SunriseResult::RegularDay { transit, .. }
+        | SunriseResult::AllDay { transit }
+        | SunriseResult::AllNight { transit } => transit,
+    };
+    let position = solar_positioning::spa::solar_position(transit, lat, lon, 0.0, deltat, None)
+        .map_err(|e| format!("Failed to calculate solar position: {}", e))?;
+
+    let previous_dt = dt - Duration::days(1);
+    let previous = solar_positioning::spa::sunrise_sunset_for_horizon(
+        previous_dt,
+        lat,
+        lon,
+        resolve_deltat(previous_dt, params),
+        Horizon::SunriseSunset,
+    )
+    .map_err(|e| format!("Failed to calculate sunrise/sunset: {}", e))?;
+
+    Ok(DayStats {
+        day_length: day,
+        civil_twilight: civil - day,
+        nautical_twilight: nautical - civil,
+        astronomical_twilight: astronomical - nautical,
+        noon_elevation: 90.0 - position.zenith_angle(),
+        day_length_change: day - time_above(&previous, previous_dt, params)?,
+    })
+}
+
+/// Calculates sunrise, transit, and sunset (and twilight, golden/blue hours, and custom horizons
+/// when enabled) for the day of `dt`.
 pub fn calculate_sunrise(
     lat: f64,
     lon: f64,
     dt: DateTime<FixedOffset>,
     params: &Parameters,
-) -> Result<CalculationResult, String> {
+) -> Result<CalculationResult, Error> {
     let deltat = resolve_deltat(dt, params);
 
-    if params.calculation.twilight {
+    // Twilight, golden/blue hours, and custom horizons share one multi-horizon pass, whose
+    // twilight results are computed even when their columns are not shown. Day statistics
+    // reuse them and add a sunrise pass for the previous day.
+    if params.calculation.twilight
+        || params.calculation.day_stats
+        || params.calculation.photo_hours
+        || !params.calculation.horizons.is_empty()
+    {
+        let photo_horizons: &[f64] = if params.calculation.photo_hours {
+            &PHOTO_HOUR_HORIZONS
+        } else {
+            &[]
+        };
+        let extra: Vec<f64> = photo_horizons
+            .iter()
+            .copied()
+            .chain(
+                params
+                    .calculation
+                    .horizons
+                    .iter()
+                    .map(CustomHorizon::degrees),
+            )
+            .collect();
+        let (results, mut horizons) =
+            calculate_horizon_results(lat, lon, dt, deltat, &extra).map_err(Error::calculation)?;
+        let photo_hours = match horizons.as_slice() {
+            [blue, low, high, ..] if params.calculation.photo_hours => {
+                Some(Box::new(photo_hours([blue, low, high])))
+            }
+            _ => None,
+        };
+        horizons.drain(..photo_horizons.len());
+        let stats = params
+            .calculation
+            .day_stats
+            .then(|| calculate_day_stats(lat, lon, dt, deltat, params, &results))
+            .transpose()
+            .map_err(Error::calculation)?;
         let TwilightResults {
             sunrise_sunset,
             civil,
             nautical,
             astronomical,
-        } = calculate_twilight_results(lat, lon, dt, deltat)?;
+        } = results;
 
         Ok(CalculationResult::SunriseWithTwilight {
             lat,
@@ -239,6 +857,10 @@ pub fn calculate_sunrise(
             nautical,
             astronomical,
             deltat,
+            stats,
+            photo_hours,
+            horizons,
+            site: None,
         })
     } else {
         let horizon = params
@@ -249,7 +871,9 @@ pub fn calculate_sunrise(
 
         let result =
             solar_positioning::spa::sunrise_sunset_for_horizon(dt, lat, lon, deltat, horizon)
-                .map_err(|e| format!("Failed to calculate sunrise/sunset: {}", e))?;
+                .map_err(|e| {
+                    Error::calculation(format!("Failed to calculate sunrise/sunset: {}", e))
+                })?;
 
         Ok(CalculationResult::Sunrise {
             lat,
@@ -257,6 +881,7 @@ pub fn calculate_sunrise(
             date: dt,
             result,
             deltat,
+            site: None,
         })
     }
 }
@@ -352,6 +977,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn elevation_classification_matches_horizons() {
+        assert_eq!(state_for_elevation(10.0), SolarState::Daylight);
+        assert_eq!(state_for_elevation(-0.83337), SolarState::Daylight);
+        assert_eq!(state_for_elevation(-1.0), SolarState::CivilTwilight);
+        assert_eq!(state_for_elevation(-6.5), SolarState::NauticalTwilight);
+        assert_eq!(state_for_elevation(-17.9), SolarState::AstronomicalTwilight);
+        assert_eq!(state_for_elevation(-30.0), SolarState::Night);
+    }
+
+    #[test]
+    fn multi_level_change_is_split_into_adjacent_steps() {
+        let time = FixedOffset::east_opt(0)
+            .unwrap()
+            .with_ymd_and_hms(2024, 3, 21, 6, 0, 0)
+            .unwrap();
+        let mut steps = Vec::new();
+        push_steps(
+            time,
+            SolarState::AstronomicalTwilight,
+            SolarState::CivilTwilight,
+            &mut steps,
+        );
+
+        let states: Vec<_> = steps.iter().map(|s| (s.before, s.after)).collect();
+        assert_eq!(
+            states,
+            vec![
+                (
+                    SolarState::AstronomicalTwilight,
+                    SolarState::NauticalTwilight
+                ),
+                (SolarState::NauticalTwilight, SolarState::CivilTwilight),
+            ]
+        );
+        assert_eq!(steps[0].horizon, -12.0);
+        assert_eq!(steps[1].horizon, -6.0);
+    }
+
     #[test]
     fn next_state_transition_finds_same_day_sunrise() {
         let now = FixedOffset::east_opt(0)
@@ -1,7 +1,11 @@
 //! Semantic validation that turns parsed CLI input into executable commands.
 
-use crate::data::{self, Command, DataSource, LocationSource, Parameters, Predicate, TimeSource};
+use crate::data::{
+    self, Command, DataSource, LocationSource, OutputFormat, Parameters, ParquetTimestamps,
+    Predicate, TimeSource, Tracker,
+};
 use crate::error::{CliError, predicate_error};
+use crate::output;
 use crate::parsed::{ParsedCommand, ParsedInput, ParsedOptionUsage, ParsedTimeSource};
 use crate::predicate::{PredicateCheck, PredicateJob, PredicateTime};
 
@@ -22,10 +26,26 @@ pub enum ValidCommand {
 enum ValidationMode {
     Position,
     Sunrise,
+    Events,
     Predicate,
 }
 
-pub fn validate(parsed: ParsedCommand) -> Result<ValidCommand, CliError> {
+pub fn validate(mut parsed: ParsedCommand) -> Result<ValidCommand, CliError> {
+    if parsed.usage.timezone && (parsed.usage.input_timezone || parsed.usage.output_timezone) {
+        return Err(
+            "Option --timezone cannot be combined with --input-timezone or --output-timezone"
+                .into(),
+        );
+    }
+    // One location needs one lookup, after which --timezone=auto works like naming its zone.
+    if parsed.params.timezone_lookup
+        && let ParsedInput::Separate(LocationSource::Single(lat, lon), _) = parsed.input
+    {
+        let timezone = crate::data::time_utils::lookup_timezone(lat, lon)?;
+        parsed.params.output_timezone = Some(timezone.clone());
+        parsed.params.timezone = Some(timezone);
+        parsed.params.timezone_lookup = false;
+    }
     match parsed.command {
         Command::Position => {
             validate_position(parsed.input, parsed.params, parsed.predicate, parsed.usage)
@@ -33,6 +53,15 @@ pub fn validate(parsed: ParsedCommand) -> Result<ValidCommand, CliError> {
         Command::Sunrise => {
             validate_sunrise(parsed.input, parsed.params, parsed.predicate, parsed.usage)
         }
+        Command::Events => {
+            validate_events(parsed.input, parsed.params, parsed.predicate, parsed.usage)
+        }
+        Command::MoonPosition => {
+            validate_moon_position(parsed.input, parsed.params, parsed.predicate, parsed.usage)
+        }
+        Command::Moonrise => {
+            validate_moonrise(parsed.input, parsed.params, parsed.predicate, parsed.usage)
+        }
     }
 }
 
@@ -43,15 +72,16 @@ fn validate_input(
 ) -> Result<DataSource, CliError> {
     match input {
         ParsedInput::Paired(path) => Ok(DataSource::Paired(path)),
-        ParsedInput::Separate(loc, time) => Ok(DataSource::Separate(
-            loc,
-            resolve_time_source(time, params, mode)?,
-        )),
+        ParsedInput::Separate(loc, time) => {
+            let time = resolve_time_source(time, &loc, params, mode)?;
+            Ok(DataSource::Separate(loc, time))
+        }
     }
 }
 
 fn resolve_time_source(
     time: ParsedTimeSource,
+    loc: &LocationSource,
     params: &Parameters,
     mode: ValidationMode,
 ) -> Result<TimeSource, CliError> {
@@ -82,15 +112,28 @@ fn resolve_time_source(
                 );
             }
 
-            data::parse_datetime_string(&value, params.timezone.as_ref().map(|tz| tz.as_str()))
-                .map(TimeSource::Single)
-                .map_err(|err| {
-                    if matches!(mode, ValidationMode::Predicate) {
-                        predicate_error(err)
-                    } else {
-                        CliError::from(err)
-                    }
-                })
+            // Coordinate files and --timezone=auto may give each location its own timezone,
+            // so defer the offset.
+            if (matches!(loc, LocationSource::File(_)) || params.timezone_lookup)
+                && crate::data::time_utils::is_local_datetime(&value)
+            {
+                return Ok(TimeSource::Local(value));
+            }
+
+            let tz = params.timezone.as_ref().map(|tz| tz.as_str());
+            data::time_utils::parse_datetime_with_policy(
+                &value,
+                tz,
+                params.dst_policy.unwrap_or_default(),
+            )
+            .map(TimeSource::Single)
+            .map_err(|err| {
+                if matches!(mode, ValidationMode::Predicate) {
+                    predicate_error(err)
+                } else {
+                    CliError::from(err)
+                }
+            })
         }
     }
 }
@@ -105,6 +148,37 @@ fn validate_predicate_common(
         (usage.headers, "--headers/--no-headers"),
         (usage.show_inputs, "--show-inputs/--no-show-inputs"),
         (usage.perf, "--perf"),
+        (usage.threads, "--threads"),
+        (usage.horizon_profile, "--horizon-profile"),
+        (usage.irradiance, "--irradiance"),
+        (usage.linke_turbidity, "--linke-turbidity"),
+        (usage.surface_tilt, "--surface-tilt"),
+        (usage.surface_azimuth, "--surface-azimuth"),
+        (usage.tracker, "--tracker"),
+        (usage.shadow_height, "--shadow-height"),
+        (usage.geometry, "--geometry"),
+        (usage.day_stats, "--day-stats"),
+        (usage.photo_hours, "--photo-hours"),
+        (usage.horizons, "--horizons"),
+        (usage.grid_km, "--grid-km"),
+        (usage.parquet_timestamps, "--parquet-timestamps"),
+        (usage.parquet_compression, "--parquet-compression"),
+        (usage.parquet_row_group_size, "--parquet-row-group-size"),
+        (
+            usage.parquet_dictionary,
+            "--parquet-dictionary/--no-parquet-dictionary",
+        ),
+        (usage.output_dir, "--output-dir"),
+        (usage.partition_lat_band, "--partition-lat-band"),
+        (usage.max_file_rows, "--max-file-rows"),
+        (usage.max_file_size, "--max-file-size"),
+        (usage.output, "--output"),
+        (usage.table, "--table"),
+        (usage.upsert, "--upsert"),
+        (usage.columns, "--columns"),
+        (usage.list_columns, "--list-columns"),
+        (usage.time_format, "--time-format"),
+        (usage.output_timezone, "--output-timezone"),
     ]
     .into_iter()
     .find_map(|(used, name)| used.then_some(name))
@@ -128,18 +202,18 @@ fn validate_predicate_common(
         DataSource::Paired(_) => Err(predicate_error(
             "Predicate mode requires explicit latitude, longitude, and datetime arguments",
         )),
-        DataSource::Separate(LocationSource::File(_), _) => Err(predicate_error(
-            "Predicate mode does not support coordinate file input",
-        )),
+        DataSource::Separate(LocationSource::File(_) | LocationSource::Features(_), _) => Err(
+            predicate_error("Predicate mode does not support coordinate file input"),
+        ),
         DataSource::Separate(_, TimeSource::File(_)) => Err(predicate_error(
             "Predicate mode does not support datetime file input",
         )),
-        DataSource::Separate(LocationSource::Range { .. }, _) => Err(predicate_error(
-            "Predicate mode requires a single latitude/longitude pair",
-        )),
-        DataSource::Separate(_, TimeSource::Range(_)) => Err(predicate_error(
-            "Predicate mode requires a single explicit instant",
-        )),
+        DataSource::Separate(LocationSource::Range { .. } | LocationSource::Grid { .. }, _) => Err(
+            predicate_error("Predicate mode requires a single latitude/longitude pair"),
+        ),
+        DataSource::Separate(_, TimeSource::Range(_) | TimeSource::Local(_)) => Err(
+            predicate_error("Predicate mode requires a single explicit instant"),
+        ),
         DataSource::Separate(
             LocationSource::Single(_, _),
             TimeSource::Single(_) | TimeSource::Now,
@@ -153,7 +227,7 @@ fn validate_position(
     predicate: Option<Predicate>,
     usage: ParsedOptionUsage,
 ) -> Result<ValidCommand, CliError> {
-    validate_position_options(&usage)?;
+    validate_position_options(&params, &usage)?;
     let source = validate_input(
         input,
         &params,
@@ -174,9 +248,12 @@ fn validate_position(
     if params.wait {
         return Err(predicate_error("Option --wait requires a predicate option"));
     }
+    validate_output_options(&params, &usage, Command::Position)?;
     if params.output.show_inputs.is_none() {
-        params.output.show_inputs = Some(should_auto_show_inputs(&source));
+        params.output.show_inputs = Some(should_auto_show_inputs(&source, params.output.format));
     }
+    params.output.site_columns = source.has_sites();
+    resolve_columns(Command::Position, &mut params)?;
 
     Ok(ValidCommand::Stream(StreamRequest {
         command: Command::Position,
@@ -191,7 +268,7 @@ fn validate_sunrise(
     predicate: Option<Predicate>,
     usage: ParsedOptionUsage,
 ) -> Result<ValidCommand, CliError> {
-    validate_sunrise_options(&usage)?;
+    validate_sunrise_options(&params, &usage)?;
     let source = validate_input(
         input,
         &params,
@@ -212,9 +289,12 @@ fn validate_sunrise(
     if params.wait {
         return Err(predicate_error("Option --wait requires a predicate option"));
     }
+    validate_output_options(&params, &usage, Command::Sunrise)?;
     if params.output.show_inputs.is_none() {
-        params.output.show_inputs = Some(should_auto_show_inputs(&source));
+        params.output.show_inputs = Some(should_auto_show_inputs(&source, params.output.format));
     }
+    params.output.site_columns = source.has_sites();
+    resolve_columns(Command::Sunrise, &mut params)?;
 
     Ok(ValidCommand::Stream(StreamRequest {
         command: Command::Sunrise,
@@ -223,6 +303,109 @@ fn validate_sunrise(
     }))
 }
 
+fn validate_events(
+    input: ParsedInput,
+    mut params: Parameters,
+    predicate: Option<Predicate>,
+    usage: ParsedOptionUsage,
+) -> Result<ValidCommand, CliError> {
+    validate_events_options(&usage)?;
+    if predicate.is_some() {
+        return Err(predicate_error(
+            "Predicate options are not valid for events command",
+        ));
+    }
+    let source = validate_input(input, &params, ValidationMode::Events)?;
+
+    if params.wait {
+        return Err(predicate_error("Option --wait requires a predicate option"));
+    }
+    validate_output_options(&params, &usage, Command::Events)?;
+    if params.output.show_inputs.is_none() {
+        params.output.show_inputs = Some(should_auto_show_inputs(&source, params.output.format));
+    }
+    params.output.site_columns = source.has_sites();
+    resolve_columns(Command::Events, &mut params)?;
+
+    Ok(ValidCommand::Stream(StreamRequest {
+        command: Command::Events,
+        source,
+        params,
+    }))
+}
+
+fn validate_moon_position(
+    input: ParsedInput,
+    mut params: Parameters,
+    predicate: Option<Predicate>,
+    usage: ParsedOptionUsage,
+) -> Result<ValidCommand, CliError> {
+    validate_moon_position_options(&usage)?;
+    let source = validate_input(
+        input,
+        &params,
+        if predicate.is_some() {
+            ValidationMode::Predicate
+        } else {
+            ValidationMode::Position
+        },
+    )?;
+
+    if let Some(predicate) = predicate {
+        validate_moon_position_predicate_mode(&source, predicate, &params, &usage)?;
+        return Ok(ValidCommand::Predicate(build_predicate_job(
+            source, params, predicate,
+        )));
+    }
+
+    if params.wait {
+        return Err(predicate_error("Option --wait requires a predicate option"));
+    }
+    validate_output_options(&params, &usage, Command::MoonPosition)?;
+    if params.output.show_inputs.is_none() {
+        params.output.show_inputs = Some(should_auto_show_inputs(&source, params.output.format));
+    }
+    params.output.site_columns = source.has_sites();
+    resolve_columns(Command::MoonPosition, &mut params)?;
+
+    Ok(ValidCommand::Stream(StreamRequest {
+        command: Command::MoonPosition,
+        source,
+        params,
+    }))
+}
+
+fn validate_moonrise(
+    input: ParsedInput,
+    mut params: Parameters,
+    predicate: Option<Predicate>,
+    usage: ParsedOptionUsage,
+) -> Result<ValidCommand, CliError> {
+    validate_moonrise_options(&usage)?;
+    if predicate.is_some() {
+        return Err(predicate_error(
+            "Predicate options are not valid for moonrise command",
+        ));
+    }
+    let source = validate_input(input, &params, ValidationMode::Sunrise)?;
+
+    if params.wait {
+        return Err(predicate_error("Option --wait requires a predicate option"));
+    }
+    validate_output_options(&params, &usage, Command::Moonrise)?;
+    if params.output.show_inputs.is_none() {
+        params.output.show_inputs = Some(should_auto_show_inputs(&source, params.output.format));
+    }
+    params.output.site_columns = source.has_sites();
+    resolve_columns(Command::Moonrise, &mut params)?;
+
+    Ok(ValidCommand::Stream(StreamRequest {
+        command: Command::Moonrise,
+        source,
+        params,
+    }))
+}
+
 fn validate_position_predicate_mode(
     source: &DataSource,
     predicate: Predicate,
@@ -244,6 +427,9 @@ fn validate_position_predicate_mode(
             }
             Ok(())
         }
+        Predicate::MoonBelow(_) => Err(predicate_error(
+            "Moon predicates require the moon-position command",
+        )),
         _ => Err(predicate_error(
             "Sunrise predicates require the sunrise command",
         )),
@@ -263,6 +449,8 @@ fn validate_sunrise_predicate_mode(
         | Predicate::IsNauticalTwilight
         | Predicate::IsAstronomicalTwilight
         | Predicate::IsAstronomicalNight
+        | Predicate::IsGoldenHour
+        | Predicate::IsBlueHour
         | Predicate::AfterSunset => {
             if usage.twilight {
                 return Err(predicate_error(
@@ -279,17 +467,74 @@ fn validate_sunrise_predicate_mode(
         Predicate::SunAbove(_) | Predicate::SunBelow(_) => Err(predicate_error(
             "Sun angle predicates require the position command",
         )),
+        Predicate::MoonBelow(_) => Err(predicate_error(
+            "Moon predicates require the moon-position command",
+        )),
+    }
+}
+
+fn validate_moon_position_predicate_mode(
+    source: &DataSource,
+    predicate: Predicate,
+    params: &Parameters,
+    usage: &ParsedOptionUsage,
+) -> Result<(), CliError> {
+    validate_predicate_common(source, params, usage)?;
+    match predicate {
+        Predicate::MoonBelow(threshold) => {
+            if !(-90.0..=90.0).contains(&threshold) {
+                return Err(predicate_error(
+                    "Altitude threshold must be between -90 and 90 degrees",
+                ));
+            }
+            Ok(())
+        }
+        Predicate::SunAbove(_) | Predicate::SunBelow(_) => Err(predicate_error(
+            "Sun angle predicates require the position command",
+        )),
+        _ => Err(predicate_error(
+            "Sunrise predicates require the sunrise command",
+        )),
     }
 }
 
-fn validate_position_options(usage: &ParsedOptionUsage) -> Result<(), CliError> {
+fn validate_position_options(
+    params: &Parameters,
+    usage: &ParsedOptionUsage,
+) -> Result<(), CliError> {
     validate_command_options(
-        &[(usage.horizon, "--horizon"), (usage.twilight, "--twilight")],
+        &[
+            (usage.horizon, "--horizon"),
+            (usage.twilight, "--twilight"),
+            (usage.day_stats, "--day-stats"),
+            (usage.photo_hours, "--photo-hours"),
+            (usage.horizons, "--horizons"),
+        ],
         "position",
-    )
+    )?;
+    if usage.linke_turbidity && !usage.irradiance {
+        return Err("Option --linke-turbidity requires --irradiance".into());
+    }
+    if params
+        .calculation
+        .surface
+        .is_some_and(|surface| surface.tracker == Tracker::DualAxis)
+        && let Some(name) = [
+            (usage.surface_tilt, "--surface-tilt"),
+            (usage.surface_azimuth, "--surface-azimuth"),
+        ]
+        .into_iter()
+        .find_map(|(used, name)| used.then_some(name))
+    {
+        return Err(format!("Option {} not valid with --tracker=dual-axis", name).into());
+    }
+    Ok(())
 }
 
-fn validate_sunrise_options(usage: &ParsedOptionUsage) -> Result<(), CliError> {
+fn validate_sunrise_options(
+    params: &Parameters,
+    usage: &ParsedOptionUsage,
+) -> Result<(), CliError> {
     validate_command_options(
         &[
             (usage.step, "--step"),
@@ -299,11 +544,266 @@ fn validate_sunrise_options(usage: &ParsedOptionUsage) -> Result<(), CliError> {
             (usage.temperature, "--temperature"),
             (usage.pressure, "--pressure"),
             (usage.algorithm, "--algorithm"),
+            (usage.irradiance, "--irradiance"),
+            (usage.linke_turbidity, "--linke-turbidity"),
+            (usage.surface_tilt, "--surface-tilt"),
+            (usage.surface_azimuth, "--surface-azimuth"),
+            (usage.tracker, "--tracker"),
+            (usage.shadow_height, "--shadow-height"),
+            (usage.geometry, "--geometry"),
         ],
         "sunrise",
+    )?;
+    let horizon_options: &[(bool, &str)] = &[
+        (usage.horizon, "--horizon"),
+        (usage.horizon_profile, "--horizon-profile"),
+    ];
+    // Each sunrise option with the options it excludes; the first conflict found is reported.
+    let conflicts = [
+        (
+            usage.horizon_profile,
+            "--horizon-profile",
+            &[(usage.horizon, "--horizon"), (usage.twilight, "--twilight")][..],
+        ),
+        (usage.day_stats, "--day-stats", horizon_options),
+        (usage.photo_hours, "--photo-hours", horizon_options),
+        (usage.horizons, "--horizons", horizon_options),
+    ];
+    for (used, option, excluded) in conflicts {
+        if let Some((_, name)) = excluded.iter().find(|(other, _)| used && *other) {
+            return Err(format!("Option {} cannot be used with {}", option, name).into());
+        }
+    }
+    let horizons = &params.calculation.horizons;
+    let headers = output::SunriseLayout::from_params(params).csv_headers(horizons);
+    for horizon in horizons.iter() {
+        let columns = horizon.columns();
+        if headers.iter().filter(|name| columns.contains(name)).count() > 2 {
+            return Err(format!(
+                "Horizon column {} clashes with another sunrise column; choose a different label",
+                columns[0]
+            )
+            .into());
+        }
+    }
+    Ok(())
+}
+
+fn validate_events_options(usage: &ParsedOptionUsage) -> Result<(), CliError> {
+    validate_command_options(
+        &[
+            (usage.step, "--step"),
+            (usage.no_refraction, "--no-refraction"),
+            (usage.elevation_angle, "--elevation-angle"),
+            (usage.elevation, "--elevation"),
+            (usage.temperature, "--temperature"),
+            (usage.pressure, "--pressure"),
+            (usage.algorithm, "--algorithm"),
+            (usage.horizon, "--horizon"),
+            (usage.twilight, "--twilight"),
+            (usage.horizon_profile, "--horizon-profile"),
+            (usage.irradiance, "--irradiance"),
+            (usage.linke_turbidity, "--linke-turbidity"),
+            (usage.surface_tilt, "--surface-tilt"),
+            (usage.surface_azimuth, "--surface-azimuth"),
+            (usage.tracker, "--tracker"),
+            (usage.shadow_height, "--shadow-height"),
+            (usage.geometry, "--geometry"),
+            (usage.day_stats, "--day-stats"),
+            (usage.photo_hours, "--photo-hours"),
+            (usage.horizons, "--horizons"),
+        ],
+        "events",
+    )
+}
+
+fn validate_moon_position_options(usage: &ParsedOptionUsage) -> Result<(), CliError> {
+    validate_command_options(
+        &[
+            (usage.algorithm, "--algorithm"),
+            (usage.elevation_angle, "--elevation-angle"),
+            (usage.horizon, "--horizon"),
+            (usage.twilight, "--twilight"),
+            (usage.day_stats, "--day-stats"),
+            (usage.photo_hours, "--photo-hours"),
+            (usage.horizons, "--horizons"),
+            (usage.horizon_profile, "--horizon-profile"),
+            (usage.irradiance, "--irradiance"),
+            (usage.linke_turbidity, "--linke-turbidity"),
+            (usage.surface_tilt, "--surface-tilt"),
+            (usage.surface_azimuth, "--surface-azimuth"),
+            (usage.tracker, "--tracker"),
+            (usage.shadow_height, "--shadow-height"),
+            (usage.geometry, "--geometry"),
+        ],
+        "moon-position",
     )
 }
 
+fn validate_moonrise_options(usage: &ParsedOptionUsage) -> Result<(), CliError> {
+    validate_command_options(
+        &[
+            (usage.step, "--step"),
+            (usage.no_refraction, "--no-refraction"),
+            (usage.elevation_angle, "--elevation-angle"),
+            (usage.elevation, "--elevation"),
+            (usage.temperature, "--temperature"),
+            (usage.pressure, "--pressure"),
+            (usage.algorithm, "--algorithm"),
+            (usage.horizon, "--horizon"),
+            (usage.twilight, "--twilight"),
+            (usage.horizon_profile, "--horizon-profile"),
+            (usage.irradiance, "--irradiance"),
+            (usage.linke_turbidity, "--linke-turbidity"),
+            (usage.surface_tilt, "--surface-tilt"),
+            (usage.surface_azimuth, "--surface-azimuth"),
+            (usage.tracker, "--tracker"),
+            (usage.shadow_height, "--shadow-height"),
+            (usage.geometry, "--geometry"),
+            (usage.day_stats, "--day-stats"),
+            (usage.photo_hours, "--photo-hours"),
+            (usage.horizons, "--horizons"),
+        ],
+        "moonrise",
+    )
+}
+
+fn validate_output_options(
+    params: &Parameters,
+    usage: &ParsedOptionUsage,
+    command: Command,
+) -> Result<(), CliError> {
+    if params.output.format == OutputFormat::Ics && command != Command::Sunrise {
+        return Err(format!("Option --format=ics not valid for {} command", command).into());
+    }
+    if usage.time_format {
+        if params.output.format == OutputFormat::Ics {
+            return Err("Option --time-format not valid with --format=ics".into());
+        }
+        if params.output.format.is_parquet()
+            && params.output.parquet.timestamps == ParquetTimestamps::Native
+        {
+            return Err("Option --time-format requires --parquet-timestamps=string".into());
+        }
+    }
+    if let Some(name) = [
+        (usage.columns, "--columns"),
+        (usage.list_columns, "--list-columns"),
+    ]
+    .into_iter()
+    .find_map(|(used, name)| used.then_some(name))
+    {
+        if usage.show_inputs {
+            return Err(format!(
+                "Option {} cannot be used with --show-inputs/--no-show-inputs",
+                name
+            )
+            .into());
+        }
+        if params.output.format == OutputFormat::Ics {
+            return Err(format!("Option {} not valid with --format=ics", name).into());
+        }
+    }
+    if !params.output.format.is_parquet()
+        && let Some(name) = [
+            (usage.parquet_timestamps, "--parquet-timestamps"),
+            (usage.parquet_compression, "--parquet-compression"),
+            (usage.parquet_row_group_size, "--parquet-row-group-size"),
+            (
+                usage.parquet_dictionary,
+                "--parquet-dictionary/--no-parquet-dictionary",
+            ),
+            (usage.output_dir, "--output-dir"),
+        ]
+        .into_iter()
+        .find_map(|(used, name)| used.then_some(name))
+    {
+        return Err(format!("Option {} requires --format=parquet", name).into());
+    }
+    if !usage.output_dir
+        && let Some(name) = [
+            (usage.partition_lat_band, "--partition-lat-band"),
+            (usage.max_file_rows, "--max-file-rows"),
+            (usage.max_file_size, "--max-file-size"),
+        ]
+        .into_iter()
+        .find_map(|(used, name)| used.then_some(name))
+    {
+        return Err(format!("Option {} requires --output-dir", name).into());
+    }
+    if params.output.format.is_sqlite() {
+        if !usage.output {
+            return Err("Option --format=sqlite requires --output=<file>".into());
+        }
+        if params.output.sqlite.upsert && params.output.show_inputs == Some(false) {
+            return Err(
+                "Option --upsert requires the latitude and longitude columns; remove --no-show-inputs"
+                    .into(),
+            );
+        }
+        // Skyline periods of one day share the row key, so all but the last would be lost.
+        if params.output.sqlite.upsert && usage.horizon_profile && command == Command::Sunrise {
+            return Err("Option --upsert cannot be used with --horizon-profile for sunrise".into());
+        }
+    } else if let Some(name) = [
+        (usage.output, "--output"),
+        (usage.table, "--table"),
+        (usage.upsert, "--upsert"),
+    ]
+    .into_iter()
+    .find_map(|(used, name)| used.then_some(name))
+    {
+        return Err(format!("Option {} requires --format=sqlite", name).into());
+    }
+    Ok(())
+}
+
+/// Checks `--columns` against the command's column catalogue, or prints the catalogue for
+/// `--list-columns`. Input columns are always available for selection.
+fn resolve_columns(command: Command, params: &mut Parameters) -> Result<(), CliError> {
+    if params.output.columns.is_none() && !params.output.list_columns {
+        return Ok(());
+    }
+    params.output.show_inputs = Some(true);
+    let catalogue = output::full_column_catalogue(command, params);
+    let describe = |separator: &str| {
+        catalogue
+            .iter()
+            .map(|(name, option)| match option {
+                Some(option) => format!("{name} (needs {option})"),
+                None => name.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(separator)
+    };
+    if params.output.list_columns {
+        return Err(CliError::Exit(describe("\n")));
+    }
+
+    let columns = params.output.columns.as_deref().unwrap_or_default();
+    for (index, name) in columns.iter().enumerate() {
+        match catalogue.iter().find(|(listed, _)| listed == name) {
+            Some((_, None)) => {}
+            Some((_, Some(option))) => {
+                return Err(format!("Column {} needs {}", name, option).into());
+            }
+            None => {
+                return Err(format!(
+                    "Unknown column: {} (available for {}: {})",
+                    name,
+                    command,
+                    describe(", ")
+                )
+                .into());
+            }
+        }
+        if columns[..index].contains(name) {
+            return Err(format!("Duplicate column: {}", name).into());
+        }
+    }
+    Ok(())
+}
+
 fn validate_command_options(
     disallowed: &[(bool, &'static str)],
     command_name: &'static str,
@@ -319,14 +819,22 @@ fn validate_command_options(
         .unwrap_or(Ok(()))
 }
 
-fn should_auto_show_inputs(source: &DataSource) -> bool {
-    match source {
-        DataSource::Separate(loc, time) => {
-            matches!(loc, LocationSource::Range { .. } | LocationSource::File(_))
-                || matches!(time, TimeSource::Range(_) | TimeSource::File(_))
+/// Shows inputs by default for multi-record inputs, and always for SQLite so that table rows
+/// stay identifiable across runs.
+fn should_auto_show_inputs(source: &DataSource, format: OutputFormat) -> bool {
+    format.is_sqlite()
+        || match source {
+            DataSource::Separate(loc, time) => {
+                matches!(
+                    loc,
+                    LocationSource::Range { .. }
+                        | LocationSource::File(_)
+                        | LocationSource::Features(_)
+                        | LocationSource::Grid { .. }
+                ) || matches!(time, TimeSource::Range(_) | TimeSource::File(_))
+            }
+            DataSource::Paired(_) => true,
         }
-        DataSource::Paired(_) => true,
-    }
 }
 
 fn build_predicate_job(
@@ -348,7 +856,9 @@ fn build_predicate_job(
         time: match time {
             TimeSource::Single(dt) => PredicateTime::Fixed(dt),
             TimeSource::Now => PredicateTime::Now,
-            TimeSource::Range(_) | TimeSource::File(_) => unreachable!("validated above"),
+            TimeSource::Range(_) | TimeSource::Local(_) | TimeSource::File(_) => {
+                unreachable!("validated above")
+            }
         },
         check: PredicateCheck::from_cli(predicate),
         wait: params.wait,
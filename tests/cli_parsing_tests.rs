@@ -252,7 +252,288 @@ fn test_invalid_cli_combinations() {
             ],
             "cannot be used multiple times",
         ),
+        (
+            &["--threads=0", "52.0", "13.4", "2024-01-01", "position"],
+            "Invalid threads value: 0",
+        ),
+        (
+            &["--threads=many", "52.0", "13.4", "2024-01-01", "position"],
+            "Invalid threads value: many",
+        ),
+        (
+            &["52.0", "13.4", "2024-01-01", "sunrise", "--irradiance"],
+            "--irradiance not valid for sunrise",
+        ),
+        (
+            &[
+                "52.0",
+                "13.4",
+                "2024-01-01",
+                "position",
+                "--linke-turbidity=4",
+            ],
+            "Option --linke-turbidity requires --irradiance",
+        ),
+        (
+            &[
+                "52.0",
+                "13.4",
+                "2024-01-01",
+                "position",
+                "--irradiance",
+                "--linke-turbidity=0.5",
+            ],
+            "Invalid linke turbidity value: 0.5",
+        ),
+        (
+            &[
+                "52.0",
+                "13.4",
+                "2024-01-01",
+                "position",
+                "--shadow-height=0",
+            ],
+            "Invalid shadow height value: 0",
+        ),
+        (
+            &["--grid-km=-5", "47:48", "11:12", "2024-01-01", "position"],
+            "Invalid grid spacing value: -5",
+        ),
+        (
+            &["--grid-cos-lat", "47", "11", "2024-01-01", "position"],
+            "Option --grid-cos-lat requires --grid-km",
+        ),
+        (
+            &["--grid-km=10", "48:47", "11:12", "2024-01-01", "position"],
+            "Grid latitude bounds must be ascending",
+        ),
+        (
+            &[
+                "--grid-km=10",
+                "47:48:0.5",
+                "11:12",
+                "2024-01-01",
+                "position",
+            ],
+            "Invalid latitude bounds: 47:48:0.5",
+        ),
+        (
+            &[
+                "--parquet-timestamps=string",
+                "52.0",
+                "13.4",
+                "2024-01-01",
+                "position",
+            ],
+            "Option --parquet-timestamps requires --format=parquet",
+        ),
+        (
+            &[
+                "--parquet-compression=zstd:30",
+                "52.0",
+                "13.4",
+                "2024-01-01",
+                "position",
+            ],
+            "Invalid compression level in 'zstd:30'",
+        ),
+        (
+            &[
+                "--parquet-compression=snappy:2",
+                "52.0",
+                "13.4",
+                "2024-01-01",
+                "position",
+            ],
+            "does not take a level",
+        ),
+        (
+            &[
+                "--parquet-row-group-size=0",
+                "52.0",
+                "13.4",
+                "2024-01-01",
+                "position",
+            ],
+            "Invalid parquet row group size value: 0",
+        ),
+        (
+            &[
+                "--no-parquet-dictionary",
+                "52.0",
+                "13.4",
+                "2024-01-01",
+                "position",
+            ],
+            "Option --parquet-dictionary/--no-parquet-dictionary requires --format=parquet",
+        ),
+        (
+            &["--output-dir=out", "52.0", "13.4", "2024-01-01", "position"],
+            "Option --output-dir requires --format=parquet",
+        ),
+        (
+            &["--upsert", "52.0", "13.4", "2024-01-01", "position"],
+            "Option --upsert requires --format=sqlite",
+        ),
+        (
+            &["--columns=", "52.0", "13.4", "2024-01-01", "position"],
+            "Invalid columns value:",
+        ),
+        (
+            &[
+                "--columns=azimuth,azimuth",
+                "52.0",
+                "13.4",
+                "2024-01-01",
+                "position",
+            ],
+            "Duplicate column: azimuth",
+        ),
+        (
+            &[
+                "--columns=azimuth",
+                "--no-show-inputs",
+                "52.0",
+                "13.4",
+                "2024-01-01",
+                "position",
+            ],
+            "Option --columns cannot be used with --show-inputs/--no-show-inputs",
+        ),
+        (
+            &[
+                "--format=ics",
+                "--columns=sunrise",
+                "52.0",
+                "13.4",
+                "2024-01-01",
+                "sunrise",
+            ],
+            "Option --columns not valid with --format=ics",
+        ),
+        (
+            &["--time-format=%Q", "52.0", "13.4", "2024-01-01", "position"],
+            "Invalid strftime pattern in time format: '%Q'",
+        ),
+        (
+            &[
+                "--time-format=epoch",
+                "52.0",
+                "13.4",
+                "2024-01-01",
+                "position",
+            ],
+            "Invalid time format: 'epoch'",
+        ),
+        (
+            &[
+                "--format=ics",
+                "--time-format=utc",
+                "52.0",
+                "13.4",
+                "2024-01-01",
+                "sunrise",
+            ],
+            "Option --time-format not valid with --format=ics",
+        ),
+        (
+            &[
+                "--timezone=UTC",
+                "--output-timezone=Europe/Berlin",
+                "52.0",
+                "13.4",
+                "2024-01-01",
+                "position",
+            ],
+            "Option --timezone cannot be combined with --input-timezone or --output-timezone",
+        ),
+        (
+            &[
+                "--output-timezone=Mars/Olympus",
+                "52.0",
+                "13.4",
+                "2024-01-01",
+                "position",
+            ],
+            "Invalid timezone: 'Mars/Olympus'",
+        ),
+        (
+            &[
+                "--input-timezone=auto",
+                "52.0",
+                "13.4",
+                "2024-01-01",
+                "position",
+            ],
+            "Invalid timezone: 'auto'",
+        ),
     ]);
+
+    #[cfg(feature = "parquet")]
+    assert_failures(&[
+        (
+            &[
+                "--format=parquet",
+                "--max-file-rows=100",
+                "52.0",
+                "13.4",
+                "2024-01-01",
+                "position",
+            ],
+            "Option --max-file-rows requires --output-dir",
+        ),
+        (
+            &[
+                "--format=parquet",
+                "--output-dir=out",
+                "--max-file-size=10X",
+                "52.0",
+                "13.4",
+                "2024-01-01",
+                "position",
+            ],
+            "Invalid max file size value: 10X",
+        ),
+        (
+            &[
+                "--format=parquet",
+                "--output-dir=out",
+                "--partition-lat-band=0",
+                "52.0",
+                "13.4",
+                "2024-01-01",
+                "position",
+            ],
+            "Invalid partition lat band value: 0",
+        ),
+    ]);
+
+    #[cfg(feature = "sqlite")]
+    assert_failures(&[
+        (
+            &["--format=sqlite", "52.0", "13.4", "2024-01-01", "position"],
+            "Option --format=sqlite requires --output=<file>",
+        ),
+        (
+            &[
+                "--format=sqlite",
+                "--output=out.db",
+                "--upsert",
+                "--no-show-inputs",
+                "52.0",
+                "13.4",
+                "2024-01-01",
+                "position",
+            ],
+            "Option --upsert requires the latitude and longitude columns",
+        ),
+    ]);
+
+    #[cfg(not(feature = "tz-lookup"))]
+    assert_failures(&[(
+        &["--timezone=auto", "52.0", "13.4", "2024-01-01", "position"],
+        "Option --timezone=auto not available in this build (enable feature 'tz-lookup')",
+    )]);
 }
 
 #[test]
@@ -426,6 +707,17 @@ fn test_predicate_flag_parsing_and_validation() {
                 "Option --show-inputs/--no-show-inputs not valid in predicate mode",
             ),
             (&sunrise_perf, "Option --perf not valid in predicate mode"),
+            (
+                &[
+                    "--threads=2",
+                    "52.0",
+                    "13.4",
+                    "2024-03-21T12:00:00Z",
+                    "position",
+                    "--sun-above=10",
+                ],
+                "Option --threads not valid in predicate mode",
+            ),
             (
                 &[
                     "52.0",
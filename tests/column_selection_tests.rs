@@ -0,0 +1,125 @@
+mod common;
+use common::{fields, parse_csv_output, parse_json_output, sunce_command};
+
+fn stdout(args: &[&str]) -> String {
+    let output = sunce_command().args(args).output().unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_columns_select_and_order_csv() {
+    let (headers, rows) = parse_csv_output(&stdout(&[
+        "--format=csv",
+        "--columns=sunset,dateTime,latitude",
+        "52.0",
+        "13.4",
+        "2024-06-21",
+        "sunrise",
+    ]));
+    assert_eq!(headers, fields(&["sunset", "dateTime", "latitude"]));
+    assert_eq!(rows.len(), 1);
+    assert!(rows[0][0].starts_with("2024-06-21T19:30"));
+    assert!(rows[0][1].starts_with("2024-06-21T00:00:00"));
+    assert_eq!(rows[0][2], "52.00000");
+}
+
+#[test]
+fn test_columns_select_and_order_json() {
+    let output = stdout(&[
+        "--format=json",
+        "--columns=zenith,longitude,dateTime",
+        "52.0",
+        "13.4",
+        "2024-06-21T12:00:00",
+        "position",
+    ]);
+    assert!(output.starts_with(r#"{"zenith":"#));
+    let json = parse_json_output(&output);
+    let keys: Vec<&str> = json
+        .as_object()
+        .unwrap()
+        .keys()
+        .map(String::as_str)
+        .collect();
+    assert_eq!(keys.len(), 3);
+    assert_eq!(json["longitude"], 13.4);
+    assert_eq!(json["dateTime"], "2024-06-21T12:00:00+00:00");
+}
+
+#[test]
+fn test_columns_select_text_table() {
+    let output = stdout(&[
+        "--columns=azimuth,dateTime",
+        "52.0",
+        "13.4",
+        "2024-06-21T12:00:00",
+        "position",
+    ]);
+    let header: Vec<&str> = output.lines().next().unwrap().split_whitespace().collect();
+    assert_eq!(header, ["azimuth", "dateTime"]);
+}
+
+#[test]
+fn test_list_columns_prints_catalogue() {
+    let output = stdout(&[
+        "--list-columns",
+        "--twilight",
+        "52.0",
+        "13.4",
+        "2024-06-21",
+        "sunrise",
+    ]);
+    let columns: Vec<&str> = output.lines().collect();
+    assert_eq!(
+        columns[..4],
+        ["latitude", "longitude", "dateTime", "deltaT"]
+    );
+    assert!(columns.contains(&"civil_start"));
+    assert!(columns.contains(&"astronomical_end"));
+    assert!(columns.contains(&"golden_morning_start (needs --photo-hours)"));
+    assert!(columns.contains(&"day_length (needs --day-stats)"));
+
+    let output = stdout(&["--list-columns", "52.0", "13.4", "2024-06-21", "position"]);
+    let columns: Vec<&str> = output.lines().collect();
+    assert!(columns.contains(&"zenith"));
+    assert!(columns.contains(&"elevation-angle (needs --elevation-angle)"));
+    assert!(columns.contains(&"obstructed (needs --horizon-profile)"));
+    assert!(columns.contains(&"tracker_tilt (needs --tracker=dual-axis)"));
+}
+
+#[test]
+fn test_column_of_a_disabled_option_names_the_option() {
+    let output = sunce_command()
+        .args([
+            "--columns=dateTime,civil_start",
+            "52.0",
+            "13.4",
+            "2024-06-21",
+            "sunrise",
+        ])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Column civil_start needs --twilight"));
+}
+
+#[test]
+fn test_unknown_column_lists_available_columns() {
+    let output = sunce_command()
+        .args([
+            "--columns=dateTime,sunrize",
+            "52.0",
+            "13.4",
+            "2024-06-21",
+            "sunrise",
+        ])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Unknown column: sunrize"));
+    assert!(stderr.contains("latitude, longitude, dateTime, deltaT, type, sunrise"));
+    assert!(stderr.contains("civil_start (needs --twilight)"));
+}
@@ -197,6 +197,24 @@ pub fn parse_csv_single_record_map(stdout: &str) -> HashMap<String, String> {
     csv_row_map(&headers, &row)
 }
 
+/// Run sunce with `--format=csv` plus `args` and return its single record by column name.
+pub fn csv_row(args: &[&str]) -> HashMap<String, String> {
+    let output = SunceTest::new().arg("--format=csv").args(args).get_output();
+    assert!(
+        output.status.success(),
+        "sunce failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    parse_csv_single_record_map(&String::from_utf8(output.stdout).unwrap())
+}
+
+/// Parse a numeric field of a CSV record, naming the column on failure.
+pub fn numeric_field(row: &HashMap<String, String>, name: &str) -> f64 {
+    row[name]
+        .parse()
+        .unwrap_or_else(|_| panic!("{name} is not a number: {}", row[name]))
+}
+
 pub fn parse_csv_output_maps(stdout: &str) -> Vec<HashMap<String, String>> {
     let (headers, rows) = parse_csv_output(stdout);
     rows.iter().map(|row| csv_row_map(&headers, row)).collect()
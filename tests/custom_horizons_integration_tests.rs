@@ -0,0 +1,118 @@
+mod common;
+use common::*;
+
+fn horizons_csv(lat: &str, lon: &str, extra: &[&str]) -> (Vec<String>, Vec<String>) {
+    let mut args = vec![
+        "--format=csv",
+        "--timezone=UTC",
+        lat,
+        lon,
+        "2024-06-21",
+        "sunrise",
+    ];
+    args.extend_from_slice(extra);
+    let output = SunceTest::new().args(&args).get_output();
+    assert!(
+        output.status.success(),
+        "sunrise failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    parse_csv_single_record(&String::from_utf8(output.stdout).unwrap())
+}
+
+#[test]
+fn horizons_columns_follow_twilight_in_option_order() {
+    let (headers, record) = horizons_csv(
+        "52.0",
+        "13.4",
+        &["--twilight", "--horizons=-0.833,hunt=-9.5,dusk=-6"],
+    );
+    let start = headers
+        .iter()
+        .position(|name| name == "astronomical_end")
+        .unwrap();
+    assert_eq!(
+        headers[start + 1..],
+        fields(&[
+            "horizon_m0_833_start",
+            "horizon_m0_833_end",
+            "hunt_start",
+            "hunt_end",
+            "dusk_start",
+            "dusk_end",
+        ])
+    );
+
+    // Custom horizons at the standard angles reproduce the fixed columns.
+    let row = csv_row_map(&headers, &record);
+    assert_eq!(row["horizon_m0_833_start"], row["sunrise"]);
+    assert_eq!(row["horizon_m0_833_end"], row["sunset"]);
+    assert_eq!(row["dusk_start"], row["civil_start"]);
+    assert_eq!(row["dusk_end"], row["civil_end"]);
+
+    let hunt_start = parse_rfc3339(&row["hunt_start"]);
+    assert!(parse_rfc3339(&row["nautical_start"]) < hunt_start);
+    assert!(hunt_start < parse_rfc3339(&row["civil_start"]));
+}
+
+#[test]
+fn horizons_are_empty_when_the_sun_never_crosses() {
+    let (headers, record) = horizons_csv("78.2", "15.6", &["--horizons=low=-6,high=40"]);
+    let row = csv_row_map(&headers, &record);
+    assert_eq!(row["type"], "ALL_DAY");
+    for column in ["low_start", "low_end", "high_start", "high_end"] {
+        assert!(row[column].is_empty(), "{column} should be empty");
+    }
+
+    let output = SunceTest::new()
+        .args([
+            "--format=json",
+            "78.2",
+            "15.6",
+            "2024-06-21",
+            "sunrise",
+            "--horizons=low=-6",
+        ])
+        .get_output();
+    let json = parse_json_output(&String::from_utf8(output.stdout).unwrap());
+    assert!(json["low_start"].is_null());
+    assert!(json["low_end"].is_null());
+}
+
+#[test]
+fn horizons_reject_invalid_values_and_conflicts() {
+    let cases: [(&[&str], &str); 6] = [
+        (
+            &["sunrise", "--horizons=-6,,-9"],
+            "Invalid horizons value: ''",
+        ),
+        (
+            &["sunrise", "--horizons=bad-label=-6"],
+            "Invalid horizon label: 'bad-label'",
+        ),
+        (
+            &["sunrise", "--horizons=a=-6,a=-9"],
+            "Duplicate horizon label: 'a'",
+        ),
+        (
+            &["sunrise", "--twilight", "--horizons=civil=-6"],
+            "Horizon column civil_start clashes with another sunrise column",
+        ),
+        (
+            &["sunrise", "--horizons=-6", "--horizon=-3"],
+            "Option --horizons cannot be used with --horizon",
+        ),
+        (
+            &["position", "--horizons=-6"],
+            "Option --horizons not valid for position command",
+        ),
+    ];
+    for (case, message) in cases {
+        let mut args = vec!["52.0", "13.4", "2024-06-21"];
+        args.extend_from_slice(case);
+        SunceTest::new()
+            .args(&args)
+            .assert_failure()
+            .stderr(predicates::str::contains(message));
+    }
+}
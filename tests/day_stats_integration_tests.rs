@@ -0,0 +1,135 @@
+mod common;
+use common::*;
+
+fn day_stats_row(lat: &str, lon: &str, date: &str) -> std::collections::HashMap<String, String> {
+    let output = SunceTest::new()
+        .args(["--format=csv", lat, lon, date, "sunrise", "--day-stats"])
+        .get_output();
+    assert!(
+        output.status.success(),
+        "sunrise failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    parse_csv_single_record_map(&String::from_utf8(output.stdout).unwrap())
+}
+
+fn seconds(row: &std::collections::HashMap<String, String>, name: &str) -> i64 {
+    row[name]
+        .parse()
+        .unwrap_or_else(|_| panic!("{name} is not an integer: {}", row[name]))
+}
+
+#[test]
+fn day_stats_columns_follow_sunrise() {
+    let output = SunceTest::new()
+        .args([
+            "--format=csv",
+            "52.0",
+            "13.4",
+            "2024-06-21",
+            "sunrise",
+            "--twilight",
+            "--day-stats",
+        ])
+        .get_output();
+    let (headers, _) = parse_csv_single_record(&String::from_utf8(output.stdout).unwrap());
+    assert_eq!(
+        headers[headers.len() - 8..],
+        fields(&[
+            "astronomical_start",
+            "astronomical_end",
+            "day_length",
+            "civil_twilight",
+            "nautical_twilight",
+            "astronomical_twilight",
+            "noon_elevation",
+            "day_length_change",
+        ])
+    );
+}
+
+#[test]
+fn durations_match_event_times() {
+    let row = day_stats_row("52.0", "13.4", "2024-03-20");
+    let day_length = parse_rfc3339(&row["sunset"]) - parse_rfc3339(&row["sunrise"]);
+    assert!((seconds(&row, "day_length") - day_length.num_seconds()).abs() <= 1);
+
+    let total: i64 = [
+        "day_length",
+        "civil_twilight",
+        "nautical_twilight",
+        "astronomical_twilight",
+    ]
+    .iter()
+    .map(|name| seconds(&row, name))
+    .sum();
+    assert!(total <= 86_400);
+    assert!(
+        seconds(&row, "day_length_change") > 0,
+        "days lengthen in March"
+    );
+
+    let noon: f64 = row["noon_elevation"].parse().unwrap();
+    assert!((noon - 38.1).abs() < 0.5, "noon elevation {noon}");
+}
+
+#[test]
+fn day_length_shrinks_in_autumn() {
+    let row = day_stats_row("52.0", "13.4", "2024-09-22");
+    assert!(seconds(&row, "day_length_change") < 0);
+}
+
+#[test]
+fn polar_day_and_night() {
+    let summer = day_stats_row("78.2", "15.6", "2024-06-21");
+    assert_eq!(summer["type"], "ALL_DAY");
+    assert_eq!(seconds(&summer, "day_length"), 86_400);
+    assert_eq!(seconds(&summer, "civil_twilight"), 0);
+    assert_eq!(seconds(&summer, "day_length_change"), 0);
+
+    let winter = day_stats_row("78.2", "15.6", "2024-12-21");
+    assert_eq!(winter["type"], "ALL_NIGHT");
+    assert_eq!(seconds(&winter, "day_length"), 0);
+    assert_eq!(seconds(&winter, "civil_twilight"), 0);
+    assert!(seconds(&winter, "astronomical_twilight") > 0);
+    assert!(winter["noon_elevation"].parse::<f64>().unwrap() < 0.0);
+}
+
+#[test]
+fn json_uses_integer_seconds() {
+    let output = SunceTest::new()
+        .args([
+            "--format=json",
+            "52.0",
+            "13.4",
+            "2024-06-21",
+            "sunrise",
+            "--day-stats",
+        ])
+        .get_output();
+    let json = parse_json_output(&String::from_utf8(output.stdout).unwrap());
+    assert!(json["day_length"].is_i64());
+    assert!(json["day_length_change"].is_i64());
+    assert!(json["noon_elevation"].is_f64());
+}
+
+#[test]
+fn day_stats_option_conflicts() {
+    for args in [
+        vec!["52.0", "13.4", "2024-06-21", "position", "--day-stats"],
+        vec!["52.0", "13.4", "2024-06-21", "events", "--day-stats"],
+        vec![
+            "52.0",
+            "13.4",
+            "2024-06-21",
+            "sunrise",
+            "--day-stats",
+            "--horizon=-6",
+        ],
+    ] {
+        SunceTest::new()
+            .args(args)
+            .assert_failure()
+            .stderr(predicates::str::contains("--day-stats"));
+    }
+}
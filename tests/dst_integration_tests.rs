@@ -4,6 +4,7 @@ use common::{
     parse_csv_no_headers_output, parse_csv_output_maps, parse_csv_single_record_map,
     parse_json_output, sunce_command,
 };
+use predicates::prelude::*;
 use serde_json::Value;
 use std::collections::HashMap;
 
@@ -431,3 +432,148 @@ fn test_now_respects_timezone_sources() {
     assert!(output.contains("dateTime"));
     assert!(output.contains("+01:00") || output.contains("+02:00"));
 }
+
+#[test]
+fn test_separate_input_and_output_timezones() {
+    let split = ["--input-timezone=Europe/Berlin", "--output-timezone=UTC"];
+
+    let row = csv_row(
+        &[
+            &["--format=CSV"],
+            &split[..],
+            &["52.0", "13.4", "2024-06-21", "sunrise"],
+        ]
+        .concat(),
+        &[],
+    );
+    assert_eq!(row["dateTime"], "2024-06-20T22:00:00+00:00");
+    assert_eq!(row["sunrise"], "2024-06-21T02:46:15+00:00");
+
+    let datetimes = csv_datetimes(
+        &[
+            &["--format=CSV"],
+            &split[..],
+            &["52.0", "13.4", "2024-03-31", "position", "--step=1h"],
+        ]
+        .concat(),
+        &[],
+    );
+    assert_eq!(datetimes.len(), 23);
+    assert_eq!(datetimes[0], "2024-03-30T23:00:00+00:00");
+    assert_eq!(datetimes[2], "2024-03-31T01:00:00+00:00");
+
+    let mut cmd = sunce_command();
+    cmd.args(["--format=CSV", "--no-headers", "--input-timezone=+05:30"])
+        .args(["--output-timezone=America/New_York", "@-", "position"])
+        .write_stdin("28.6 77.2 2024-01-15T12:00:00\n");
+    let output = String::from_utf8(cmd.assert().success().get_output().stdout.clone()).unwrap();
+    assert!(output.contains("2024-01-15T01:30:00-05:00"));
+
+    let mut cmd = sunce_command();
+    cmd.args(["--format=CSV", "--output-timezone=+09:00"])
+        .args(["52.0", "13.4", "@-", "position"])
+        .env("TZ", "UTC")
+        .write_stdin("2024-06-21T12:00:00\n2024-06-21T12:00:00+02:00\n");
+    let output = String::from_utf8(cmd.assert().success().get_output().stdout.clone()).unwrap();
+    let rows = parse_csv_output_maps(&output);
+    assert_eq!(rows[0]["dateTime"], "2024-06-21T21:00:00+09:00");
+    assert_eq!(rows[1]["dateTime"], "2024-06-21T19:00:00+09:00");
+}
+
+#[test]
+fn test_dst_policy_resolves_ambiguous_and_skipped_times() {
+    let berlin = ["--format=CSV", "--timezone=Europe/Berlin"];
+    for (policy, datetime, expected) in [
+        ("earlier", "2024-10-27T02:30", "2024-10-27T02:30:00+02:00"),
+        ("later", "2024-10-27T02:30", "2024-10-27T02:30:00+01:00"),
+        (
+            "shift-forward",
+            "2024-10-27T02:30",
+            "2024-10-27T02:30:00+02:00",
+        ),
+        (
+            "shift-forward",
+            "2024-03-31T02:30",
+            "2024-03-31T03:30:00+02:00",
+        ),
+    ] {
+        let row = csv_row(
+            &[
+                &berlin[..],
+                &["--show-inputs", &format!("--dst-policy={policy}")],
+                &["52.0", "13.4", datetime, "position"],
+            ]
+            .concat(),
+            &[],
+        );
+        assert_eq!(row["dateTime"], expected, "--dst-policy={policy}");
+        assert_eq!(row["dstPolicy"], policy);
+    }
+
+    for (policy, datetime, message) in [
+        (
+            "reject",
+            "2024-10-27T02:30",
+            "is ambiguous in timezone (DST overlap)",
+        ),
+        ("reject", "2024-03-31T02:30", "does not exist in timezone"),
+        ("later", "2024-03-31T02:30", "does not exist in timezone"),
+    ] {
+        sunce_command()
+            .args(berlin)
+            .args([&format!("--dst-policy={policy}"), "52.0", "13.4", datetime])
+            .arg("position")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(message));
+    }
+
+    let row = csv_row(
+        &[
+            &berlin[..],
+            &["52.0", "13.4", "2024-10-27T02:30", "position"],
+        ]
+        .concat(),
+        &[],
+    );
+    assert!(!row.contains_key("dstPolicy"));
+}
+
+#[test]
+fn test_dst_policy_applies_to_files_and_ranges() {
+    let mut cmd = sunce_command();
+    cmd.args(["--format=CSV", "--no-headers", "--timezone=Europe/Berlin"])
+        .args(["--dst-policy=later", "52.0", "13.4", "@-", "position"])
+        .write_stdin("2024-10-27T02:30:00\n");
+    let output = String::from_utf8(cmd.assert().success().get_output().stdout.clone()).unwrap();
+    assert!(output.contains("2024-10-27T02:30:00+01:00"));
+
+    let mut cmd = sunce_command();
+    cmd.args(["--format=CSV", "--no-headers", "--timezone=Europe/Berlin"])
+        .args(["--dst-policy=shift-forward", "@-", "position"])
+        .write_stdin("52.0 13.4 2024-03-31 02:15\n");
+    let output = String::from_utf8(cmd.assert().success().get_output().stdout.clone()).unwrap();
+    assert!(output.contains("2024-03-31T03:15:00+02:00"));
+
+    // Chile's clocks skip from 00:00 to 01:00, so the range has no local midnight.
+    let santiago = [
+        "--format=CSV",
+        "--timezone=America/Santiago",
+        "-33.4",
+        "-70.6",
+        "2024-09-08",
+        "position",
+        "--step=6h",
+    ];
+    sunce_command()
+        .args(santiago)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Start time does not exist"));
+    let datetimes = csv_datetimes(
+        &[&santiago[..], &["--dst-policy=shift-forward"]].concat(),
+        &[],
+    );
+    assert_eq!(datetimes[0], "2024-09-08T01:00:00-03:00");
+    assert_eq!(datetimes.len(), 4);
+}
@@ -0,0 +1,134 @@
+mod common;
+use common::*;
+use std::collections::HashMap;
+
+fn events_csv(args: &[&str]) -> Vec<HashMap<String, String>> {
+    let mut cmd_args = vec!["--format=csv", "--timezone=UTC"];
+    cmd_args.extend_from_slice(args);
+    cmd_args.push("events");
+    let output = SunceTest::new().args(&cmd_args).get_output();
+    assert!(
+        output.status.success(),
+        "events failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    parse_csv_output_maps(&String::from_utf8(output.stdout).unwrap())
+}
+
+fn assert_chained(rows: &[HashMap<String, String>]) {
+    for pair in rows.windows(2) {
+        assert_eq!(
+            pair[0]["state_after"], pair[1]["state_before"],
+            "transition at {} does not follow {}",
+            pair[1]["dateTime"], pair[0]["dateTime"]
+        );
+        assert!(pair[0]["dateTime"] <= pair[1]["dateTime"]);
+    }
+}
+
+#[test]
+fn mid_latitude_day_has_eight_transitions_matching_twilight_times() {
+    let rows = events_csv(&["52.0", "13.4", "2024-06-21"]);
+    let twilight = parse_csv_single_record_map(
+        &String::from_utf8(
+            SunceTest::new()
+                .args([
+                    "--format=csv",
+                    "--timezone=UTC",
+                    "52.0",
+                    "13.4",
+                    "2024-06-21",
+                    "sunrise",
+                    "--twilight",
+                ])
+                .get_output()
+                .stdout,
+        )
+        .unwrap(),
+    );
+
+    // Astronomical twilight lasts all night at 52°N in June.
+    let expected = [
+        (
+            "NAUTICAL_TWILIGHT",
+            "CIVIL_TWILIGHT",
+            "-6.000",
+            "civil_start",
+        ),
+        ("CIVIL_TWILIGHT", "DAYLIGHT", "-0.833", "sunrise"),
+        ("DAYLIGHT", "CIVIL_TWILIGHT", "-0.833", "sunset"),
+        ("CIVIL_TWILIGHT", "NAUTICAL_TWILIGHT", "-6.000", "civil_end"),
+    ];
+    let nautical: Vec<_> = rows
+        .iter()
+        .filter(|row| row["horizon"] != "-12.000")
+        .collect();
+    assert_eq!(nautical.len(), expected.len());
+    for (row, (before, after, horizon, field)) in nautical.iter().zip(expected) {
+        assert_eq!(row["state_before"], before);
+        assert_eq!(row["state_after"], after);
+        assert_eq!(row["horizon"], horizon);
+        assert_time_close(&row["dateTime"], &twilight[field], 0);
+    }
+    assert_chained(&rows);
+}
+
+#[test]
+fn polar_day_has_no_transitions() {
+    assert!(events_csv(&["78.2", "15.6", "2024-06-21"]).is_empty());
+}
+
+#[test]
+fn transitions_chain_across_midnight_and_polar_periods() {
+    for (lat, lon) in [("69.65", "18.96"), ("-77.8", "166.7"), ("52.5", "13.4")] {
+        let rows = events_csv(&["--no-show-inputs", lat, lon, "2024"]);
+        assert!(!rows.is_empty());
+        assert_chained(&rows);
+    }
+}
+
+#[test]
+fn events_show_inputs_for_ranges_and_support_json() {
+    let rows = events_csv(&["52:53:1", "13.4", "2024-03-20"]);
+    assert_eq!(rows.len(), 16);
+    assert_eq!(rows[0]["latitude"], "52.00000");
+    assert_eq!(rows[8]["latitude"], "53.00000");
+
+    let output = SunceTest::new()
+        .args(["--format=json", "52.0", "13.4", "2024-03-20", "events"])
+        .get_output();
+    let first = parse_json_output(
+        String::from_utf8(output.stdout)
+            .unwrap()
+            .lines()
+            .next()
+            .unwrap(),
+    );
+    assert_eq!(first["state_before"], "NIGHT");
+    assert_eq!(first["state_after"], "ASTRONOMICAL_TWILIGHT");
+    assert_eq!(first["horizon"], -18.0);
+}
+
+#[test]
+fn events_rejects_unrelated_options() {
+    SunceTest::new()
+        .args(["52.0", "13.4", "2024-03-20", "events", "--twilight"])
+        .assert_failure()
+        .stderr(predicates::str::contains(
+            "Option --twilight not valid for events command",
+        ));
+    SunceTest::new()
+        .args([
+            "52.0",
+            "13.4",
+            "2024-03-20T12:00:00Z",
+            "events",
+            "--is-daylight",
+        ])
+        .command()
+        .assert()
+        .code(2)
+        .stderr(predicates::str::contains(
+            "Predicate options are not valid for events command",
+        ));
+}
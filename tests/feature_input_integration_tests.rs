@@ -0,0 +1,133 @@
+mod common;
+use common::*;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tempfile::{TempDir, tempdir};
+
+const SITES_GEOJSON: &str = r#"{
+  "type": "FeatureCollection",
+  "features": [
+    {"type": "Feature", "id": "roof-1", "properties": {"name": "Roof, north"},
+     "geometry": {"type": "Point", "coordinates": [13.4, 52.5]}},
+    {"type": "Feature", "properties": {"id": 2, "name": "Field \"B\""},
+     "geometry": {"type": "MultiPoint", "coordinates": [[11.4, 47.3], [11.5, 47.2]]}}
+  ]
+}"#;
+
+const SITES_KML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<kml xmlns="http://www.opengis.net/kml/2.2">
+  <Document>
+    <Placemark id="hut">
+      <name>Mountain hut</name>
+      <Point><coordinates>11.4,47.3,1800</coordinates></Point>
+    </Placemark>
+  </Document>
+</kml>"#;
+
+fn feature_file(name: &str, contents: &str) -> (TempDir, PathBuf) {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join(name);
+    write_text_file(&path, contents);
+    (dir, path)
+}
+
+fn file_arg(path: &std::path::Path) -> String {
+    format!("@{}", path.display())
+}
+
+fn csv_rows(args: &[&str]) -> (Vec<String>, Vec<HashMap<String, String>>) {
+    let mut cmd_args = vec!["--format=csv"];
+    cmd_args.extend_from_slice(args);
+    let output = SunceTest::new().args(&cmd_args).get_output();
+    assert!(
+        output.status.success(),
+        "sunce failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let (headers, _) = parse_csv_output(&stdout);
+    (headers, parse_csv_output_maps(&stdout))
+}
+
+#[test]
+fn geojson_points_carry_id_and_name() {
+    let (_dir, path) = feature_file("sites.geojson", SITES_GEOJSON);
+    let (headers, rows) = csv_rows(&[&file_arg(&path), "2024-06-21T12:00:00Z", "position"]);
+
+    assert_eq!(
+        headers[..4],
+        fields(&["id", "name", "latitude", "longitude"])
+    );
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[0]["id"], "roof-1");
+    assert_eq!(rows[0]["name"], "Roof, north");
+    assert_eq!(rows[0]["latitude"], "52.50000");
+    assert_eq!(rows[0]["longitude"], "13.40000");
+    assert_eq!(rows[2]["id"], "2");
+    assert_eq!(rows[2]["name"], "Field \"B\"");
+    assert_eq!(rows[2]["latitude"], "47.20000");
+}
+
+#[test]
+fn kml_placemarks_work_for_daily_commands() {
+    let (_dir, path) = feature_file("sites.kml", SITES_KML);
+    let (_, rows) = csv_rows(&[&file_arg(&path), "2024-06", "sunrise"]);
+    assert_eq!(rows.len(), 30);
+    assert!(
+        rows.iter()
+            .all(|row| row["id"] == "hut" && row["name"] == "Mountain hut")
+    );
+
+    let (_, events) = csv_rows(&[&file_arg(&path), "2024-06-21", "events"]);
+    assert!(!events.is_empty());
+    assert!(events.iter().all(|row| row["name"] == "Mountain hut"));
+}
+
+#[test]
+fn labels_in_json_and_without_inputs() {
+    let (_dir, path) = feature_file("sites.geojson", SITES_GEOJSON);
+    let output = SunceTest::new()
+        .args([
+            "--format=json",
+            &file_arg(&path),
+            "2024-06-21T12:00:00Z",
+            "position",
+        ])
+        .get_output();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let first = parse_json_output(stdout.lines().next().unwrap());
+    assert_eq!(first["id"], "roof-1");
+    assert_eq!(first["name"], "Roof, north");
+
+    let (headers, _) = csv_rows(&[
+        "--no-show-inputs",
+        &file_arg(&path),
+        "2024-06-21T12:00:00Z",
+        "position",
+    ]);
+    assert_eq!(
+        headers,
+        fields(&["id", "name", "dateTime", "azimuth", "zenith"])
+    );
+}
+
+#[test]
+fn feature_file_errors() {
+    let (_dir, polygon) = feature_file(
+        "area.geojson",
+        r#"{"type": "Feature", "properties": {}, "geometry": {"type": "Polygon", "coordinates": []}}"#,
+    );
+    SunceTest::new()
+        .args([&file_arg(&polygon), "2024-06-21T12:00:00Z", "position"])
+        .assert_failure()
+        .stderr(predicates::str::contains(
+            "unsupported geometry type 'Polygon'",
+        ));
+
+    SunceTest::new()
+        .args([&file_arg(&polygon), "position"])
+        .assert_failure()
+        .stderr(predicates::str::contains(
+            "Feature files provide locations only",
+        ));
+}
@@ -603,3 +603,100 @@ fn test_cartesian_products_with_file_inputs() {
         }
     }
 }
+
+#[test]
+fn test_per_row_timezone_columns() {
+    let dir = tempdir().unwrap();
+    let coords = file_arg(
+        dir.path(),
+        "sites.txt",
+        "52.52 13.40 Europe/Berlin\n40.71,-74.01,America/New_York\n35.68 139.69 UTC\n",
+    );
+
+    let rows = csv_records(
+        &["--format=CSV", &coords, "2024-06-21", "sunrise"],
+        None,
+        &[("TZ", "UTC")],
+    );
+    assert_field_values(
+        &rows,
+        "dateTime",
+        &[
+            "2024-06-21T00:00:00+02:00",
+            "2024-06-21T00:00:00-04:00",
+            "2024-06-21T00:00:00+00:00",
+        ],
+    );
+    assert_field_prefixes(&rows, "sunrise", &["2024-06-21T04:43", "2024-06-21T05:25"]);
+
+    let rows = csv_records(
+        &["--format=CSV", &coords, "2024-03", "sunrise"],
+        None,
+        &[("TZ", "UTC")],
+    );
+    assert_eq!(rows.len(), 93);
+    assert_eq!(rows[0]["dateTime"], "2024-03-01T00:00:00+01:00");
+    assert_eq!(rows[30]["dateTime"], "2024-03-31T00:00:00+01:00");
+    assert_eq!(rows[31]["dateTime"], "2024-03-01T00:00:00-05:00");
+
+    let paired = file_arg(
+        dir.path(),
+        "paired.txt",
+        "52.52 13.40 2024-06-21 12:00:00 Europe/Berlin\n40.71,-74.01,2024-06-21T12:00:00,America/New_York\n",
+    );
+    let rows = csv_records(
+        &["--format=CSV", &paired, "position"],
+        None,
+        &[("TZ", "UTC")],
+    );
+    assert_field_values(
+        &rows,
+        "dateTime",
+        &["2024-06-21T12:00:00+02:00", "2024-06-21T12:00:00-04:00"],
+    );
+}
+
+#[test]
+fn test_invalid_third_coordinate_field_is_rejected() {
+    let dir = tempdir().unwrap();
+    for (name, content, args, message) in [
+        (
+            "unknown.txt",
+            "52.52 13.40 Mars/Olympus\n",
+            &["2024-06-21", "sunrise"][..],
+            "unknown.txt:1: unknown timezone 'Mars/Olympus'",
+        ),
+        (
+            "paired.txt",
+            "52.52 13.40 2024-06-21T12:00:00\n",
+            &["2024-06-21", "sunrise"][..],
+            "expected 2 fields (lat lon), found 3. File appears to be a paired data file",
+        ),
+        (
+            "late.txt",
+            "40.71 -74.01\n52.52 13.40 Europe/Berlin\n",
+            &["2024-06-21", "sunrise"][..],
+            "late.txt:2: timezone column on some rows only",
+        ),
+        (
+            "missing.txt",
+            "52.52 13.40 Europe/Berlin\n40.71 -74.01\n",
+            &["2024-06-21", "sunrise"][..],
+            "missing.txt:2: timezone column on some rows only",
+        ),
+        (
+            "paired_unknown.txt",
+            "52.52 13.40 2024-06-21T12:00:00 Europe/Berln\n",
+            &["position"][..],
+            "paired_unknown.txt:1: unknown timezone 'Europe/Berln'",
+        ),
+    ] {
+        let path = file_arg(dir.path(), name, content);
+        sunce_command()
+            .arg(&path)
+            .args(args)
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(message));
+    }
+}
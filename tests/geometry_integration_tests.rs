@@ -0,0 +1,83 @@
+mod common;
+use common::*;
+
+#[test]
+fn geometry_columns_follow_position() {
+    let output = SunceTest::new()
+        .args([
+            "--format=csv",
+            "52.0",
+            "13.4",
+            "2024-06-21T11:00:00Z",
+            "position",
+            "--geometry",
+        ])
+        .get_output();
+    let (headers, _) = parse_csv_single_record(&String::from_utf8(output.stdout).unwrap());
+    assert_eq!(
+        headers,
+        fields(&[
+            "dateTime",
+            "azimuth",
+            "zenith",
+            "declination",
+            "right_ascension",
+            "hour_angle",
+            "equation_of_time",
+            "earth_sun_distance",
+            "julian_day",
+            "sidereal_time",
+        ])
+    );
+}
+
+#[test]
+fn geometry_is_consistent_with_the_solar_position() {
+    let row = csv_row(&[
+        "52.0",
+        "13.4",
+        "2024-06-21T11:00:00Z",
+        "position",
+        "--geometry",
+    ]);
+    // Solstice: declination at its maximum, sun near the aphelion.
+    assert!((numeric_field(&row, "declination") - 23.44).abs() < 0.01);
+    assert!((numeric_field(&row, "right_ascension") - 90.0).abs() < 1.0);
+    assert!((numeric_field(&row, "earth_sun_distance") - 1.0162).abs() < 1e-3);
+    assert_eq!(numeric_field(&row, "julian_day"), 2460482.958333);
+
+    // The hour angle combines longitude, UT, and the equation of time.
+    let equation_of_time = numeric_field(&row, "equation_of_time");
+    assert!((-2.5..-1.0).contains(&equation_of_time));
+    let expected = 13.4 - 15.0 + equation_of_time / 4.0;
+    assert!((numeric_field(&row, "hour_angle") - expected).abs() < 0.01);
+
+    // At transit (11:08:18 per the sunrise command) the zenith is latitude minus declination.
+    let zenith = numeric_field(&row, "zenith");
+    let noon = csv_row(&[
+        "52.0",
+        "13.4",
+        "2024-06-21T11:08:18Z",
+        "position",
+        "--geometry",
+    ]);
+    assert!(numeric_field(&noon, "hour_angle").abs() < 0.05);
+    assert!(numeric_field(&noon, "zenith") < zenith);
+    assert!(
+        (numeric_field(&noon, "zenith") - (52.0 - numeric_field(&noon, "declination"))).abs()
+            < 0.05
+    );
+}
+
+#[test]
+fn geometry_is_position_only() {
+    for (command, message) in [
+        ("sunrise", "Option --geometry not valid for sunrise command"),
+        ("events", "Option --geometry not valid for events command"),
+    ] {
+        SunceTest::new()
+            .args(["52.0", "13.4", "2024-06-21", command, "--geometry"])
+            .assert_failure()
+            .stderr(predicates::str::contains(message));
+    }
+}
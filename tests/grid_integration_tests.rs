@@ -0,0 +1,180 @@
+mod common;
+use common::*;
+use serde_json::Value;
+use std::path::Path;
+use tempfile::tempdir;
+
+const AREA_GEOJSON: &str = r#"{
+  "type": "FeatureCollection",
+  "features": [
+    {"type": "Feature", "id": "valley", "properties": {"name": "Inn valley"},
+     "geometry": {"type": "Polygon",
+                  "coordinates": [[[11.0, 47.0], [12.0, 47.0], [12.0, 47.5], [11.0, 47.5], [11.0, 47.0]]]}},
+    {"type": "Feature", "id": "hut",
+     "geometry": {"type": "Point", "coordinates": [11.4, 47.3]}}
+  ]
+}"#;
+
+fn run(args: &[&str]) -> String {
+    let output = SunceTest::new().args(args).get_output();
+    assert!(
+        output.status.success(),
+        "sunce failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn coordinates(rows: &[std::collections::HashMap<String, String>]) -> Vec<(f64, f64)> {
+    rows.iter()
+        .map(|row| {
+            (
+                row["latitude"].parse().unwrap(),
+                row["longitude"].parse().unwrap(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn bounds_are_sampled_at_km_spacing() {
+    let stdout = run(&[
+        "--format=csv",
+        "--grid-km=20",
+        "47:47.4",
+        "11:11.5",
+        "2024-06-21T12:00:00Z",
+        "position",
+    ]);
+    let points = coordinates(&parse_csv_output_maps(&stdout));
+
+    // 20 km is 0.17986 degrees of latitude: three rows of three points.
+    assert_eq!(points.len(), 9);
+    assert_eq!(points[0], (47.0, 11.0));
+    assert_eq!(points[1], (47.0, 11.17986));
+    assert_eq!(points[3], (47.17986, 11.0));
+    assert_eq!(points[8], (47.35973, 11.35973));
+}
+
+#[test]
+fn cos_lat_keeps_east_west_spacing_in_km() {
+    let stdout = run(&[
+        "--format=csv",
+        "--grid-km=20",
+        "--grid-cos-lat",
+        "60",
+        "10:11",
+        "2024-06-21T12:00:00Z",
+        "position",
+    ]);
+    let points = coordinates(&parse_csv_output_maps(&stdout));
+
+    assert_eq!(points.len(), 3);
+    let step = points[1].1 - points[0].1;
+    assert!((step - 0.35973).abs() < 1e-4, "{step}");
+}
+
+#[test]
+fn polygons_in_feature_files_are_sampled() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("area.geojson");
+    write_text_file(&path, AREA_GEOJSON);
+    let file_arg = format!("@{}", path.display());
+
+    let stdout = run(&[
+        "--format=csv",
+        "--grid-km=25",
+        &file_arg,
+        "2024-06-21",
+        "sunrise",
+    ]);
+    let rows = parse_csv_output_maps(&stdout);
+    let valley: Vec<_> = rows.iter().filter(|row| row["id"] == "valley").collect();
+
+    assert_eq!(valley.len(), 15);
+    assert!(valley.iter().all(|row| row["name"] == "Inn valley"));
+    assert_eq!(rows.last().unwrap()["id"], "hut");
+    assert_eq!(rows.last().unwrap()["latitude"], "47.30000");
+
+    let output = SunceTest::new()
+        .args([file_arg.as_str(), "2024-06-21", "sunrise"])
+        .get_output();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("without --grid-km"));
+}
+
+fn geojson(stdout: &str) -> Value {
+    serde_json::from_str(stdout).expect("valid GeoJSON")
+}
+
+#[test]
+fn geojson_output_is_a_feature_collection() {
+    let stdout = run(&[
+        "--format=geojson",
+        "--grid-km=50",
+        "47:47.4",
+        "11:11.4",
+        "2024-06-21",
+        "sunrise",
+    ]);
+    let collection = geojson(&stdout);
+
+    assert_eq!(collection["type"], "FeatureCollection");
+    let features = collection["features"].as_array().unwrap();
+    assert_eq!(features.len(), 1);
+    assert_eq!(features[0]["type"], "Feature");
+    assert_eq!(features[0]["geometry"]["type"], "Point");
+    assert_eq!(
+        features[0]["geometry"]["coordinates"],
+        serde_json::json!([11.0, 47.0])
+    );
+    let properties = &features[0]["properties"];
+    assert_eq!(properties["latitude"], 47.0);
+    assert!(
+        properties["sunrise"]
+            .as_str()
+            .unwrap()
+            .starts_with("2024-06-21T")
+    );
+    assert!(properties["sunset"].is_string());
+}
+
+#[test]
+fn geojson_output_streams_one_feature_per_line() {
+    let stdout = run(&[
+        "--format=geojson",
+        "52.0",
+        "13.4",
+        "2024-06-21",
+        "position",
+        "--step=6h",
+    ]);
+    let lines: Vec<_> = stdout.lines().collect();
+
+    assert_eq!(lines.len(), 6);
+    assert_eq!(lines[0], r#"{"type":"FeatureCollection","features":["#);
+    assert_eq!(lines[5], "]}");
+    assert!(lines[1..4].iter().all(|line| line.ends_with("}},")));
+    let features = geojson(&stdout)["features"].as_array().unwrap().clone();
+    assert_eq!(features.len(), 4);
+    assert!(features.iter().all(|feature| {
+        feature["geometry"]["coordinates"] == serde_json::json!([13.4, 52.0])
+            && feature["properties"]["azimuth"].is_number()
+    }));
+}
+
+#[test]
+fn geojson_output_without_records_is_empty_collection() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("times.txt");
+    write_text_file(Path::new(&path), "# no times\n");
+    let stdout = run(&[
+        "--format=geojson",
+        "52.0",
+        "13.4",
+        &format!("@{}", path.display()),
+        "position",
+    ]);
+
+    assert_eq!(geojson(&stdout)["features"], serde_json::json!([]));
+}
@@ -0,0 +1,174 @@
+mod common;
+use common::*;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tempfile::{TempDir, tempdir};
+
+fn profile_file(contents: &str) -> (TempDir, PathBuf) {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("skyline.csv");
+    write_text_file(&path, contents);
+    (dir, path)
+}
+
+fn profile_arg(path: &std::path::Path) -> String {
+    format!("--horizon-profile=@{}", path.display())
+}
+
+fn csv_rows(args: &[&str]) -> Vec<HashMap<String, String>> {
+    let mut cmd_args = vec!["--format=csv", "--timezone=UTC"];
+    cmd_args.extend_from_slice(args);
+    let output = SunceTest::new().args(&cmd_args).get_output();
+    assert!(
+        output.status.success(),
+        "sunce failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    parse_csv_output_maps(&String::from_utf8(output.stdout).unwrap())
+}
+
+#[test]
+fn flat_profile_matches_standard_sunrise() {
+    let (_dir, path) = profile_file("0 0\n");
+    let skyline = csv_rows(&["47.3", "11.4", "2024-06-21", "sunrise", &profile_arg(&path)]);
+    let standard = csv_rows(&["47.3", "11.4", "2024-06-21", "sunrise"]);
+
+    assert_eq!(skyline.len(), 1);
+    assert_eq!(skyline[0]["type"], "NORMAL");
+    assert_eq!(skyline[0]["transit"], standard[0]["transit"]);
+    assert_time_close(&skyline[0]["sunrise"], &standard[0]["sunrise"], 2);
+    assert_time_close(&skyline[0]["sunset"], &standard[0]["sunset"], 2);
+}
+
+#[test]
+fn valley_profile_delays_sunrise_and_advances_sunset() {
+    let (_dir, path) =
+        profile_file("# azimuth elevation\n0,5\n90,15\n135,25\n180,8\n225,2\n270,12\n");
+    let skyline = csv_rows(&["47.3", "11.4", "2024-12-21", "sunrise", &profile_arg(&path)]);
+    let standard = csv_rows(&["47.3", "11.4", "2024-12-21", "sunrise"]);
+
+    assert_eq!(skyline.len(), 1);
+    assert!(skyline[0]["sunrise"] > standard[0]["sunrise"]);
+    assert!(skyline[0]["sunset"] < standard[0]["sunset"]);
+}
+
+#[test]
+fn narrow_obstruction_splits_the_day_into_periods() {
+    let (_dir, path) = profile_file("0 2\n190 2\n195 40\n200 2\n");
+    let rows = csv_rows(&["47.3", "11.4", "2024-12-21", "sunrise", &profile_arg(&path)]);
+
+    assert_eq!(rows.len(), 2, "expected two visible periods: {rows:?}");
+    assert!(rows.iter().all(|row| row["type"] == "NORMAL"));
+    assert!(rows[0]["sunset"] < rows[1]["sunrise"]);
+    assert_eq!(rows[0]["transit"], rows[1]["transit"]);
+}
+
+#[test]
+fn polar_days_report_all_day_and_all_night() {
+    let (_dir, path) = profile_file("0 0\n");
+    let summer = csv_rows(&["78", "15", "2024-06-21", "sunrise", &profile_arg(&path)]);
+    let winter = csv_rows(&["78", "15", "2024-12-21", "sunrise", &profile_arg(&path)]);
+    assert_eq!(summer[0]["type"], "ALL_DAY");
+    assert_eq!(winter[0]["type"], "ALL_NIGHT");
+    assert_eq!(winter[0]["sunrise"], "");
+
+    // A ridge to the north hides the midnight sun.
+    let (_dir, path) = profile_file("0 20\n180 2\n");
+    let rows = csv_rows(&["78", "15", "2024-06-21", "sunrise", &profile_arg(&path)]);
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0]["type"], "NORMAL");
+    assert!(rows[0]["sunrise"] < rows[0]["transit"]);
+}
+
+#[test]
+fn position_reports_obstruction() {
+    let (_dir, path) = profile_file("0 30\n");
+    let output = SunceTest::new()
+        .args([
+            "--format=json",
+            "47.3",
+            "11.4",
+            "2024-06-21T05:00:00Z",
+            "position",
+            &profile_arg(&path),
+        ])
+        .get_output();
+    let json = parse_json_output(&String::from_utf8(output.stdout).unwrap());
+    assert_eq!(json["obstructed"], true);
+
+    let rows = csv_rows(&[
+        "47.3",
+        "11.4",
+        "2024-06-21T11:00:00",
+        "position",
+        &profile_arg(&path),
+    ]);
+    assert_eq!(rows[0]["obstructed"], "false");
+
+    let rows = csv_rows(&["47.3", "11.4", "2024-06-21T11:00:00", "position"]);
+    assert!(!rows[0].contains_key("obstructed"));
+}
+
+#[test]
+fn position_is_clear_from_the_reported_sunrise() {
+    let (_dir, path) = profile_file("# azimuth elevation\n0,5\n90,15\n135,25\n180,8\n");
+    let sunrise = csv_rows(&["47.3", "11.4", "2024-12-21", "sunrise", &profile_arg(&path)]);
+    let sunrise = parse_rfc3339(&sunrise[0]["sunrise"]);
+
+    let obstructed_at = |offset: i64| {
+        let time = (sunrise + chrono::Duration::seconds(offset)).to_rfc3339();
+        let rows = csv_rows(&["47.3", "11.4", &time, "position", &profile_arg(&path)]);
+        rows[0]["obstructed"].clone()
+    };
+    assert_eq!(obstructed_at(0), "false");
+    assert_eq!(obstructed_at(-2), "true");
+}
+
+#[test]
+fn invalid_profiles_and_combinations_are_rejected() {
+    let (_dir, path) = profile_file("0 5\n90 abc\n");
+    SunceTest::new()
+        .args(["52", "13", "2024-06-21", "sunrise", &profile_arg(&path)])
+        .assert_failure()
+        .stderr(predicates::str::contains(":2: invalid elevation 'abc'"));
+
+    let (_dir, path) = profile_file("400 5\n");
+    SunceTest::new()
+        .args(["52", "13", "2024-06-21", "sunrise", &profile_arg(&path)])
+        .assert_failure()
+        .stderr(predicates::str::contains(
+            "azimuth must be between 0 and 360",
+        ));
+
+    SunceTest::new()
+        .args([
+            "52",
+            "13",
+            "2024-06-21",
+            "sunrise",
+            "--horizon-profile=skyline.csv",
+        ])
+        .assert_failure()
+        .stderr(predicates::str::contains("requires a file argument"));
+
+    let (_dir, path) = profile_file("0 5\n");
+    SunceTest::new()
+        .args([
+            "52",
+            "13",
+            "2024-06-21",
+            "sunrise",
+            "--twilight",
+            &profile_arg(&path),
+        ])
+        .assert_failure()
+        .stderr(predicates::str::contains(
+            "Option --horizon-profile cannot be used with --twilight",
+        ));
+    SunceTest::new()
+        .args(["52", "13", "2024-06-21", "events", &profile_arg(&path)])
+        .assert_failure()
+        .stderr(predicates::str::contains(
+            "Option --horizon-profile not valid for events command",
+        ));
+}
@@ -0,0 +1,111 @@
+mod common;
+use common::*;
+
+fn ics_output(args: &[&str]) -> String {
+    let output = sunce_command()
+        .args(args)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    String::from_utf8(output).unwrap()
+}
+
+fn events(ics: &str) -> Vec<&str> {
+    ics.split("BEGIN:VEVENT\r\n").skip(1).collect()
+}
+
+#[test]
+fn test_ics_sunrise_events_with_timezone() {
+    let ics = ics_output(&[
+        "--format=ics",
+        "--timezone=Europe/Berlin",
+        "52.0",
+        "13.4",
+        "2024-06-21",
+        "sunrise",
+    ]);
+    assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+    assert!(ics.ends_with("END:VCALENDAR\r\n"));
+    assert!(ics.contains("BEGIN:VTIMEZONE\r\nTZID:Europe/Berlin\r\n"));
+
+    let events = events(&ics);
+    let summaries: Vec<&str> = events
+        .iter()
+        .filter_map(|event| event.lines().find_map(|line| line.strip_prefix("SUMMARY:")))
+        .collect();
+    assert_eq!(summaries, ["Sunrise", "Solar noon", "Sunset"]);
+    assert!(events[0].contains("DTSTART;TZID=Europe/Berlin:20240621T044"));
+    assert!(events[0].contains("LOCATION:52\\, 13.4\r\n"));
+    assert!(events[0].contains("GEO:52;13.4\r\n"));
+    assert!(events[0].contains("UID:sunrise-20240621T024"));
+}
+
+#[test]
+fn test_ics_vtimezone_covers_every_year() {
+    let mut cmd = sunce_command();
+    cmd.args(["--format=ics", "--timezone=Africa/Casablanca"])
+        .args(["33.6", "-7.6", "@-", "sunrise"])
+        .write_stdin("2023-06-01\n2025-06-01\n");
+    let ics = String::from_utf8(cmd.assert().success().get_output().stdout.clone()).unwrap();
+    let start = ics.find("BEGIN:VTIMEZONE").unwrap();
+    assert!(start > ics.rfind("END:VEVENT").unwrap());
+    let vtimezone = &ics[start..];
+
+    // Ramadan moves Morocco's transitions every year, so each one is listed.
+    assert!(vtimezone.contains(
+        "DTSTART:20230319T030000\r\nRDATE:20240310T030000\r\nRDATE:20250223T030000\r\nTZOFFSETFROM:+0100\r\nTZOFFSETTO:+0000\r\n"
+    ));
+    assert!(vtimezone.contains(
+        "DTSTART:20230423T020000\r\nRDATE:20240414T020000\r\nRDATE:20250406T020000\r\nTZOFFSETFROM:+0000\r\nTZOFFSETTO:+0100\r\n"
+    ));
+    assert!(!vtimezone.contains("RRULE"));
+    assert_eq!(vtimezone.matches("BEGIN:VTIMEZONE").count(), 1);
+}
+
+#[test]
+fn test_ics_twilight_and_utc_times() {
+    let ics = ics_output(&[
+        "--format=ICS",
+        "--timezone=+02:00",
+        "52.0",
+        "13.4",
+        "2024-03-20",
+        "sunrise",
+        "--twilight",
+    ]);
+    assert!(!ics.contains("VTIMEZONE"));
+    let events = events(&ics);
+    assert_eq!(events.len(), 9);
+    assert!(events[0].contains("SUMMARY:Astronomical dawn"));
+    assert!(events[8].contains("SUMMARY:Astronomical dusk"));
+    assert!(
+        events
+            .iter()
+            .all(|event| event.contains("DTSTART:20240320T") && event.contains("Z\r\n"))
+    );
+}
+
+#[test]
+fn test_ics_polar_days_are_all_day_events() {
+    let ics = ics_output(&["--format=ics", "78.0", "15.0", "2024-06-21", "sunrise"]);
+    let events = events(&ics);
+    assert_eq!(events.len(), 2);
+    assert!(events[0].contains("DTSTART;VALUE=DATE:20240621\r\nDTEND;VALUE=DATE:20240622\r\n"));
+    assert!(events[0].contains("SUMMARY:Sun up all day"));
+    assert!(events[1].contains("SUMMARY:Solar noon"));
+
+    let ics = ics_output(&["--format=ics", "78.0", "15.0", "2024-12-21", "sunrise"]);
+    assert!(ics.contains("SUMMARY:Sun down all day"));
+}
+
+#[test]
+fn test_ics_requires_sunrise_command() {
+    SunceTest::new()
+        .args(["--format=ics", "52.0", "13.4", "2024-06-21", "position"])
+        .assert_failure()
+        .stderr(predicates::str::contains(
+            "Option --format=ics not valid for position command",
+        ));
+}
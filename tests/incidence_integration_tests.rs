@@ -0,0 +1,113 @@
+mod common;
+use common::*;
+
+#[test]
+fn horizontal_surface_incidence_equals_zenith() {
+    let row = csv_row(&[
+        "47.3",
+        "11.4",
+        "2024-06-21T08:00:00Z",
+        "position",
+        "--surface-tilt=0",
+    ]);
+    let zenith = numeric_field(&row, "zenith");
+    assert!((numeric_field(&row, "incidence") - zenith).abs() < 1e-3);
+    assert!((numeric_field(&row, "cos_incidence") - zenith.to_radians().cos()).abs() < 1e-5);
+}
+
+#[test]
+fn south_facing_panel_beats_east_facing_at_noon() {
+    let south = csv_row(&[
+        "47.3",
+        "11.4",
+        "2024-06-21T11:00:00Z",
+        "position",
+        "--surface-tilt=30",
+        "--surface-azimuth=180",
+    ]);
+    let east = csv_row(&[
+        "47.3",
+        "11.4",
+        "2024-06-21T11:00:00Z",
+        "position",
+        "--surface-tilt=30",
+        "--surface-azimuth=90",
+    ]);
+    assert!(numeric_field(&south, "cos_incidence") > numeric_field(&east, "cos_incidence"));
+}
+
+#[test]
+fn trackers_follow_the_sun_through_the_day() {
+    let output = SunceTest::new()
+        .args([
+            "--format=csv",
+            "47.3",
+            "11.4",
+            "2024-06-21",
+            "position",
+            "--step=3h",
+            "--tracker=single-axis",
+        ])
+        .get_output();
+    let rows = parse_csv_output_maps(&String::from_utf8(output.stdout).unwrap());
+    let daytime: Vec<_> = rows
+        .iter()
+        .filter(|row| !row["tracker_rotation"].is_empty())
+        .collect();
+    assert!(daytime.len() >= 4);
+    assert!(
+        numeric_field(daytime[0], "tracker_rotation") < 0.0,
+        "morning faces east"
+    );
+    assert!(
+        numeric_field(daytime[daytime.len() - 1], "tracker_rotation") > 0.0,
+        "evening faces west"
+    );
+    assert!(rows.iter().any(|row| row["incidence"].is_empty()));
+
+    let row = csv_row(&[
+        "47.3",
+        "11.4",
+        "2024-06-21T09:00:00Z",
+        "position",
+        "--tracker=dual-axis",
+    ]);
+    assert_eq!(row["tracker_tilt"], row["zenith"]);
+    assert_eq!(row["tracker_azimuth"], row["azimuth"]);
+    assert_eq!(row["cos_incidence"], "1.000000");
+}
+
+#[test]
+fn surface_options_are_validated() {
+    SunceTest::new()
+        .args([
+            "52",
+            "13",
+            "2024-06-21T12:00:00Z",
+            "position",
+            "--tracker=dual-axis",
+            "--surface-tilt=20",
+        ])
+        .assert_failure()
+        .stderr(predicates::str::contains(
+            "Option --surface-tilt not valid with --tracker=dual-axis",
+        ));
+    SunceTest::new()
+        .args([
+            "52",
+            "13",
+            "2024-06-21T12:00:00Z",
+            "position",
+            "--surface-azimuth=400",
+        ])
+        .assert_failure()
+        .stderr(predicates::str::contains(
+            "Invalid surface azimuth value: 400",
+        ));
+    SunceTest::new()
+        .args(["52", "13", "2024-06-21", "sunrise", "--tracker=single-axis"])
+        .assert_failure()
+        .stderr(predicates::str::contains(
+            "Option --tracker not valid for sunrise command",
+        ));
+}
@@ -0,0 +1,115 @@
+mod common;
+use common::*;
+
+#[test]
+fn irradiance_columns_follow_position() {
+    let (headers, _) = parse_csv_single_record(
+        &String::from_utf8(
+            SunceTest::new()
+                .args([
+                    "--format=csv",
+                    "47.3",
+                    "11.4",
+                    "2024-06-21T10:00:00Z",
+                    "position",
+                    "--irradiance",
+                ])
+                .get_output()
+                .stdout,
+        )
+        .unwrap(),
+    );
+    assert_eq!(
+        headers,
+        fields(&[
+            "dateTime",
+            "azimuth",
+            "zenith",
+            "extraterrestrial",
+            "air_mass",
+            "ghi",
+            "dni",
+            "dhi"
+        ])
+    );
+}
+
+#[test]
+fn midday_clear_sky_is_plausible_and_consistent() {
+    let row = csv_row(&[
+        "47.3",
+        "11.4",
+        "2024-06-21T11:00:00Z",
+        "position",
+        "--irradiance",
+    ]);
+    let zenith = numeric_field(&row, "zenith").to_radians();
+    let (ghi, dni, dhi) = (
+        numeric_field(&row, "ghi"),
+        numeric_field(&row, "dni"),
+        numeric_field(&row, "dhi"),
+    );
+
+    assert!((1315.0..1330.0).contains(&numeric_field(&row, "extraterrestrial")));
+    assert!((800.0..1000.0).contains(&ghi), "ghi {ghi}");
+    assert!((ghi - dni * zenith.cos() - dhi).abs() < 0.05);
+}
+
+#[test]
+fn turbidity_and_altitude_shift_direct_and_diffuse() {
+    let base = csv_row(&[
+        "47.3",
+        "11.4",
+        "2024-06-21T11:00:00Z",
+        "position",
+        "--irradiance",
+    ]);
+    let hazy = csv_row(&[
+        "47.3",
+        "11.4",
+        "2024-06-21T11:00:00Z",
+        "--linke-turbidity=6",
+        "position",
+        "--irradiance",
+    ]);
+    let alpine = csv_row(&[
+        "47.3",
+        "11.4",
+        "2024-06-21T11:00:00Z",
+        "--elevation=2500",
+        "--pressure=750",
+        "position",
+        "--irradiance",
+    ]);
+
+    assert!(numeric_field(&hazy, "dni") < numeric_field(&base, "dni"));
+    assert!(numeric_field(&hazy, "dhi") > numeric_field(&base, "dhi"));
+    assert!(numeric_field(&alpine, "ghi") > numeric_field(&base, "ghi"));
+}
+
+#[test]
+fn night_has_zero_irradiance_and_no_air_mass() {
+    let row = csv_row(&[
+        "47.3",
+        "11.4",
+        "2024-06-21T00:00:00Z",
+        "position",
+        "--irradiance",
+    ]);
+    assert_eq!(row["air_mass"], "");
+    assert_eq!(numeric_field(&row, "ghi"), 0.0);
+
+    let output = SunceTest::new()
+        .args([
+            "--format=json",
+            "47.3",
+            "11.4",
+            "2024-06-21T00:00:00Z",
+            "position",
+            "--irradiance",
+        ])
+        .get_output();
+    let json = parse_json_output(&String::from_utf8(output.stdout).unwrap());
+    assert!(json["air_mass"].is_null());
+    assert_eq!(json["dni"], 0.0);
+}
@@ -0,0 +1,144 @@
+use chrono::{DateTime, FixedOffset};
+use sunce::{
+    CalculationResult, Command, CustomHorizon, DataSource, ErrorKind, LocationSource, OutputFormat,
+    Parameters, TimeSource,
+};
+
+fn instant(value: &str) -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339(value).unwrap()
+}
+
+#[test]
+fn calculate_position_matches_cli_output() {
+    let params = Parameters::default();
+    let result =
+        sunce::calculate_position(52.0, 13.4, instant("2024-06-21T12:00:00+02:00"), &params)
+            .unwrap();
+
+    let CalculationResult::Position { position, .. } = result else {
+        panic!("expected position result");
+    };
+    assert!((position.azimuth() - 148.8808).abs() < 1e-4);
+    assert!((position.zenith_angle() - 31.4083).abs() < 1e-4);
+}
+
+#[test]
+fn stream_pipeline_writes_csv() {
+    let mut params = Parameters::default();
+    params.output.format = OutputFormat::Csv;
+    let source = DataSource::Separate(
+        LocationSource::Range {
+            lat: (50.0, 51.0, 1.0),
+            lon: (10.0, 10.0, 1.0),
+        },
+        TimeSource::Single(instant("2024-06-21T12:00:00Z")),
+    );
+
+    let data = sunce::expand_data_source(&source, &params, Command::Sunrise).unwrap();
+    let results = sunce::calculate_stream(data, Command::Sunrise, params.clone(), true);
+    let mut out = Vec::new();
+    let count = sunce::write_output(results, Command::Sunrise, &params, &mut out, false).unwrap();
+
+    let text = String::from_utf8(out).unwrap();
+    assert_eq!(count, 2);
+    assert_eq!(text.lines().count(), 3);
+    assert!(text.starts_with("dateTime,type,sunrise,transit,sunset"));
+}
+
+#[test]
+fn custom_horizons_from_owned_labels() {
+    let mut params = Parameters::default();
+    params.output.format = OutputFormat::Csv;
+    let label = String::from("hunting");
+    params.calculation.horizons = vec![CustomHorizon::new(label, -6.0)].into();
+    let source = DataSource::Separate(
+        LocationSource::Single(52.0, 13.4),
+        TimeSource::Single(instant("2024-06-21T00:00:00+02:00")),
+    );
+
+    let data = sunce::expand_data_source(&source, &params, Command::Sunrise).unwrap();
+    let results = sunce::calculate_stream(data, Command::Sunrise, params.clone(), true);
+    let mut out = Vec::new();
+    sunce::write_output(results, Command::Sunrise, &params, &mut out, false).unwrap();
+
+    let text = String::from_utf8(out).unwrap();
+    let header = text.lines().next().unwrap();
+    assert!(header.ends_with(",hunting_start,hunting_end"), "{header}");
+}
+
+#[test]
+fn errors_are_typed() {
+    let source = DataSource::Paired(sunce::InputPath::File("/nonexistent/input.csv".into()));
+    let err = match sunce::expand_data_source(&source, &Parameters::default(), Command::Position) {
+        Ok(_) => panic!("expected missing file error"),
+        Err(err) => err,
+    };
+
+    let boxed: Box<dyn std::error::Error> = Box::new(err.clone());
+    assert_eq!(boxed.to_string(), err.message());
+    assert!(err.message().contains("/nonexistent/input.csv"));
+    assert_eq!(err.kind(), ErrorKind::Io);
+
+    let dt = instant("2024-06-21T12:00:00Z");
+    let mut params = Parameters::default();
+    params.environment.pressure = -1.0;
+    let err = sunce::calculate_position(52.0, 13.4, dt, &params).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Input);
+
+    let err = sunce::calculate_position(95.0, 13.4, dt, &Parameters::default()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Calculation);
+
+    // Stream errors keep their kind through the Parquet writer.
+    #[cfg(feature = "parquet")]
+    {
+        let mut params = Parameters::default();
+        params.output.format = OutputFormat::Parquet;
+        let source =
+            DataSource::Separate(LocationSource::Single(95.0, 13.4), TimeSource::Single(dt));
+        let data = sunce::expand_data_source(&source, &params, Command::Position).unwrap();
+        let results = sunce::calculate_stream(data, Command::Position, params.clone(), true);
+        let err = sunce::write_output(results, Command::Position, &params, Vec::new(), false)
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Calculation);
+    }
+}
+
+#[test]
+fn horizon_profile_file_is_loaded_by_the_stream() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("skyline.csv");
+    std::fs::write(&path, "0 2\n190 2\n195 40\n200 2\n").unwrap();
+    let mut params = Parameters::default();
+    params.calculation.horizon_profile_file = Some(sunce::InputPath::File(path));
+    let source = DataSource::Separate(
+        LocationSource::Single(47.3, 11.4),
+        TimeSource::Single(instant("2024-12-21T00:00:00Z")),
+    );
+
+    for threads in [1, 2] {
+        let data = sunce::expand_data_source(&source, &params, Command::Sunrise).unwrap();
+        let results: Vec<_> =
+            sunce::calculate_stream_parallel(data, Command::Sunrise, params.clone(), true, threads)
+                .collect::<Result<_, _>>()
+                .unwrap();
+        assert_eq!(results.len(), 2, "expected two visible periods");
+        assert!(
+            results
+                .iter()
+                .all(|result| matches!(result, CalculationResult::SkylineSunrise { .. }))
+        );
+    }
+
+    let source = DataSource::Separate(
+        LocationSource::Single(47.3, 11.4),
+        TimeSource::Single(instant("2024-12-21T12:15:00Z")),
+    );
+    let data = sunce::expand_data_source(&source, &params, Command::Position).unwrap();
+    let results: Vec<_> = sunce::calculate_stream(data, Command::Position, params, true)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let CalculationResult::Position { obstructed, .. } = results[0] else {
+        panic!("expected position result");
+    };
+    assert_eq!(obstructed, Some(true));
+}
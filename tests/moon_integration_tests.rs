@@ -0,0 +1,195 @@
+mod common;
+use common::*;
+use std::collections::HashMap;
+
+fn moon_csv(args: &[&str]) -> Vec<HashMap<String, String>> {
+    let mut cmd_args = vec!["--format=csv", "--timezone=UTC"];
+    cmd_args.extend_from_slice(args);
+    let output = SunceTest::new().args(&cmd_args).get_output();
+    assert!(
+        output.status.success(),
+        "moon command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    parse_csv_output_maps(&String::from_utf8(output.stdout).unwrap())
+}
+
+fn moon_altitude(datetime: &str) -> f64 {
+    moon_csv(&["52.52", "13.41", datetime, "moon-position"])[0]["altitude"]
+        .parse()
+        .unwrap()
+}
+
+#[test]
+fn moon_position_csv_columns() {
+    let output = SunceTest::new()
+        .args([
+            "--format=csv",
+            "--show-inputs",
+            "52.52",
+            "13.41",
+            "2024-06-21T22:00:00+02:00",
+            "moon-position",
+        ])
+        .get_output();
+    assert!(output.status.success());
+    let (headers, record) = parse_csv_single_record(&String::from_utf8(output.stdout).unwrap());
+    assert_eq!(
+        headers,
+        fields(&[
+            "latitude",
+            "longitude",
+            "elevation",
+            "pressure",
+            "temperature",
+            "dateTime",
+            "deltaT",
+            "azimuth",
+            "altitude",
+            "distance",
+            "illuminated_fraction",
+            "phase_angle",
+            "phase",
+        ])
+    );
+    let row = csv_row_map(&headers, &record);
+    // Full moon on 2024-06-22 01:08 UTC, rising in the south-east.
+    assert_eq!(row["phase"], "FULL_MOON");
+    assert!(row["illuminated_fraction"].parse::<f64>().unwrap() > 0.99);
+    let azimuth: f64 = row["azimuth"].parse().unwrap();
+    assert!((120.0..160.0).contains(&azimuth), "azimuth {azimuth}");
+    let distance: f64 = row["distance"].parse().unwrap();
+    assert!((356_000.0..407_000.0).contains(&distance));
+}
+
+#[test]
+fn moon_position_json_and_no_refraction() {
+    let refracted = parse_json_output(
+        &String::from_utf8(
+            SunceTest::new()
+                .args([
+                    "--format=json",
+                    "52.52",
+                    "13.41",
+                    "2024-06-21T20:00:00Z",
+                    "moon-position",
+                ])
+                .get_output()
+                .stdout,
+        )
+        .unwrap(),
+    );
+    let geometric = parse_json_output(
+        &String::from_utf8(
+            SunceTest::new()
+                .args([
+                    "--format=json",
+                    "52.52",
+                    "13.41",
+                    "2024-06-21T20:00:00Z",
+                    "moon-position",
+                    "--no-refraction",
+                ])
+                .get_output()
+                .stdout,
+        )
+        .unwrap(),
+    );
+    assert_eq!(refracted["phase"], "FULL_MOON");
+    let lift = refracted["altitude"].as_f64().unwrap() - geometric["altitude"].as_f64().unwrap();
+    assert!(lift > 0.1 && lift < 0.6, "refraction lift {lift}");
+    assert_eq!(refracted["azimuth"], geometric["azimuth"]);
+}
+
+#[test]
+fn moonrise_brackets_horizon_crossing() {
+    let rows = moon_csv(&["52.52", "13.41", "2024-06-21", "moonrise"]);
+    assert_eq!(rows.len(), 1);
+    let row = &rows[0];
+    assert_eq!(row["type"], "NORMAL");
+    assert_eq!(row["phase"], "FULL_MOON");
+
+    let moonrise = parse_rfc3339(&row["moonrise"]);
+    let moonset = parse_rfc3339(&row["moonset"]);
+    for (time, rising) in [(moonrise, true), (moonset, false)] {
+        let before = moon_altitude(&(time - chrono::Duration::minutes(30)).to_rfc3339());
+        let after = moon_altitude(&(time + chrono::Duration::minutes(30)).to_rfc3339());
+        if rising {
+            assert!(before < 0.0 && after > 0.0, "{before} -> {after}");
+        } else {
+            assert!(before > 0.0 && after < 0.0, "{before} -> {after}");
+        }
+    }
+}
+
+#[test]
+fn moonrise_reports_circumpolar_days() {
+    let rows = moon_csv(&["78.2", "15.6", "2024-06", "moonrise"]);
+    assert_eq!(rows.len(), 30);
+    let types: Vec<&str> = rows.iter().map(|row| row["type"].as_str()).collect();
+    assert!(types.contains(&"ALWAYS_UP"));
+    assert!(types.contains(&"ALWAYS_DOWN"));
+    for row in &rows {
+        if row["type"] != "NORMAL" {
+            assert!(row["moonrise"].is_empty() && row["moonset"].is_empty());
+        }
+    }
+}
+
+#[test]
+fn moon_below_predicate_exit_codes() {
+    SunceTest::new()
+        .args([
+            "52.52",
+            "13.41",
+            "2024-06-21T12:00:00Z",
+            "moon-position",
+            "--moon-below=0",
+        ])
+        .assert_success();
+    SunceTest::new()
+        .args([
+            "52.52",
+            "13.41",
+            "2024-06-21T22:00:00Z",
+            "moon-position",
+            "--moon-below=0",
+        ])
+        .command()
+        .assert()
+        .code(1);
+}
+
+#[test]
+fn moon_commands_reject_solar_options() {
+    let cases: [(&[&str], &str); 5] = [
+        (
+            &["--algorithm=grena3", "moon-position"],
+            "Option --algorithm not valid for moon-position command",
+        ),
+        (
+            &["moonrise", "--horizon=1"],
+            "Option --horizon not valid for moonrise command",
+        ),
+        (
+            &["moonrise", "--twilight"],
+            "Option --twilight not valid for moonrise command",
+        ),
+        (
+            &["moonrise", "--is-daylight"],
+            "Predicate options are not valid for moonrise command",
+        ),
+        (
+            &["position", "--moon-below=0"],
+            "Moon predicates require the moon-position command",
+        ),
+    ];
+    for (case, message) in cases {
+        let mut args = vec!["52.52", "13.41", "2024-06-21T12:00:00Z"];
+        args.extend_from_slice(case);
+        SunceTest::new()
+            .args(&args)
+            .assert_failure()
+            .stderr(predicates::str::contains(message));
+    }
+}
@@ -0,0 +1,92 @@
+mod common;
+use common::{sunce_command, write_text_file};
+
+fn stdout_for(args: &[&str]) -> String {
+    let output = sunce_command().args(args).output().unwrap();
+    assert!(
+        output.status.success(),
+        "command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn assert_threads_match_sequential(args: &[&str]) {
+    let sequential = stdout_for(args);
+    for threads in ["--threads=2", "--threads=5"] {
+        let parallel = stdout_for(&[&[threads], args].concat());
+        assert_eq!(parallel, sequential, "output differs with {threads}");
+    }
+}
+
+#[test]
+fn parallel_position_grid_matches_sequential_order() {
+    assert_threads_match_sequential(&[
+        "--format=csv",
+        "50:52:0.1",
+        "10:11:0.5",
+        "2024-03-01",
+        "position",
+        "--step=1h",
+    ]);
+}
+
+#[test]
+fn parallel_position_without_time_cache_matches_sequential() {
+    assert_threads_match_sequential(&[
+        "--format=json",
+        "50:51:0.5",
+        "10:11:0.5",
+        "2024-06-21",
+        "position",
+        "--algorithm=grena3",
+        "--step=10m",
+    ]);
+}
+
+#[test]
+fn parallel_sunrise_series_matches_sequential() {
+    assert_threads_match_sequential(&[
+        "--format=csv",
+        "40:60:5",
+        "0:10:5",
+        "2024",
+        "sunrise",
+        "--twilight",
+    ]);
+}
+
+#[test]
+fn parallel_file_input_reports_errors_in_order() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("paired.txt");
+    let mut contents = String::new();
+    for second in 0..1500 {
+        contents.push_str(&format!(
+            "52.0 13.4 2024-01-01T12:{:02}:{:02}Z\n",
+            second / 60,
+            second % 60
+        ));
+    }
+    contents.push_str("95.0 13.4 2024-01-02T00:00:00Z\n");
+    write_text_file(&path, &contents);
+    let arg = format!("@{}", path.display());
+
+    let output = sunce_command()
+        .args([
+            "--threads=3",
+            "--format=csv",
+            "--no-headers",
+            &arg,
+            "position",
+        ])
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).lines().count(),
+        1500
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains(":1501:"));
+}
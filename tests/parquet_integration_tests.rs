@@ -1,12 +1,17 @@
 #![cfg(feature = "parquet")]
 
 mod common;
-use arrow::array::{Array, Float64Array, StringArray};
-use arrow::datatypes::DataType;
+use arrow::array::{
+    Array, BooleanArray, Float64Array, Int32Array, Int64Array, StringArray, TimestampSecondArray,
+};
+use arrow::datatypes::{DataType, TimeUnit};
+use arrow::ipc::reader::StreamReader;
 use arrow::record_batch::RecordBatch;
 use bytes::Bytes;
 use common::*;
 use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::basic::{Compression, Encoding};
+use parquet::file::metadata::ParquetMetaData;
 
 fn parquet_batches(args: &[&str], envs: &[(&str, &str)]) -> Vec<RecordBatch> {
     let mut cmd = sunce_command();
@@ -29,6 +34,31 @@ fn parquet_batches(args: &[&str], envs: &[(&str, &str)]) -> Vec<RecordBatch> {
         .expect("Failed to read batches")
 }
 
+fn parquet_metadata(args: &[&str]) -> ParquetMetaData {
+    let output = sunce_command()
+        .args(args)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    ParquetRecordBatchReaderBuilder::try_new(Bytes::from(output))
+        .expect("Failed to create Parquet reader")
+        .metadata()
+        .as_ref()
+        .clone()
+}
+
+fn metadata_value<'a>(metadata: &'a ParquetMetaData, key: &str) -> Option<&'a str> {
+    metadata
+        .file_metadata()
+        .key_value_metadata()?
+        .iter()
+        .find(|entry| entry.key == key)?
+        .value
+        .as_deref()
+}
+
 fn parquet_single_batch(args: &[&str], envs: &[(&str, &str)]) -> RecordBatch {
     let batches = parquet_batches(args, envs);
     assert_eq!(batches.len(), 1, "Should have exactly one batch");
@@ -57,6 +87,15 @@ fn string_array<'a>(batch: &'a RecordBatch, name: &str) -> &'a StringArray {
         .unwrap_or_else(|| panic!("{name} should be StringArray"))
 }
 
+fn timestamp_array<'a>(batch: &'a RecordBatch, name: &str) -> &'a TimestampSecondArray {
+    batch
+        .column_by_name(name)
+        .unwrap_or_else(|| panic!("missing {name} column"))
+        .as_any()
+        .downcast_ref::<TimestampSecondArray>()
+        .unwrap_or_else(|| panic!("{name} should be TimestampSecondArray"))
+}
+
 fn float_array<'a>(batch: &'a RecordBatch, name: &str) -> &'a Float64Array {
     batch
         .column_by_name(name)
@@ -80,7 +119,7 @@ fn test_parquet_position_basic() {
     );
     assert_eq!(
         schema_field_names(&batch),
-        vec!["dateTime", "azimuth", "zenith"]
+        vec!["dateTime", "utc_offset", "azimuth", "zenith"]
     );
     assert_eq!(batch.num_rows(), 1);
 }
@@ -107,6 +146,7 @@ fn test_parquet_position_with_inputs() {
             "pressure",
             "temperature",
             "dateTime",
+            "utc_offset",
             "deltaT",
             "azimuth",
             "zenith",
@@ -124,7 +164,17 @@ fn test_parquet_sunrise_basic() {
     let schema = batch.schema();
     assert_eq!(
         schema_field_names(&batch),
-        vec!["dateTime", "type", "sunrise", "transit", "sunset"]
+        vec![
+            "dateTime",
+            "utc_offset",
+            "type",
+            "sunrise",
+            "sunrise_utc_offset",
+            "transit",
+            "transit_utc_offset",
+            "sunset",
+            "sunset_utc_offset",
+        ]
     );
     assert!(schema.field_with_name("sunrise").unwrap().is_nullable());
     assert!(schema.field_with_name("sunset").unwrap().is_nullable());
@@ -153,17 +203,27 @@ fn test_parquet_sunrise_with_twilight() {
             "latitude",
             "longitude",
             "dateTime",
+            "utc_offset",
             "deltaT",
             "type",
             "sunrise",
+            "sunrise_utc_offset",
             "transit",
+            "transit_utc_offset",
             "sunset",
+            "sunset_utc_offset",
             "civil_start",
+            "civil_start_utc_offset",
             "civil_end",
+            "civil_end_utc_offset",
             "nautical_start",
+            "nautical_start_utc_offset",
             "nautical_end",
+            "nautical_end_utc_offset",
             "astronomical_start",
+            "astronomical_start_utc_offset",
             "astronomical_end",
+            "astronomical_end_utc_offset",
         ]
     );
     for field in [
@@ -247,11 +307,11 @@ fn test_parquet_consistency_with_csv() {
         ],
         &[],
     );
-    assert_eq!(batch.num_columns(), 3);
+    assert_eq!(batch.num_columns(), 4);
     assert_eq!(batch.num_rows(), 1);
     assert_eq!(
-        string_array(&batch, "dateTime").value(0),
-        csv_record["dateTime"]
+        timestamp_array(&batch, "dateTime").value(0),
+        parse_rfc3339(&csv_record["dateTime"]).timestamp()
     );
 
     let csv_azimuth = csv_record["azimuth"].parse::<f64>().unwrap();
@@ -273,11 +333,125 @@ fn test_parquet_timezone_preservation() {
         &[("TZ", "Europe/Berlin")],
     );
     let datetime_col = batch.column_by_name("dateTime").unwrap();
-    assert!(matches!(datetime_col.data_type(), DataType::Utf8));
+    assert_eq!(
+        datetime_col.data_type(),
+        &DataType::Timestamp(TimeUnit::Second, Some("UTC".into()))
+    );
+    assert_eq!(
+        timestamp_array(&batch, "dateTime").value(0),
+        parse_rfc3339("2024-06-21T10:00:00Z").timestamp()
+    );
+    let offsets = batch
+        .column_by_name("utc_offset")
+        .unwrap()
+        .as_any()
+        .downcast_ref::<Int32Array>()
+        .unwrap();
+    assert_eq!(offsets.value(0), 7200);
+}
+
+#[test]
+fn test_parquet_dst_day_offsets_per_timestamp() {
+    // Berlin switches to summer time at 02:00 on this date, after midnight but before sunrise.
+    let batch = parquet_single_batch(
+        &[
+            "--format=PARQUET",
+            "--timezone=Europe/Berlin",
+            "52.0",
+            "13.4",
+            "2024-03-31",
+            "sunrise",
+        ],
+        &[],
+    );
+    let offset = |name: &str| {
+        batch
+            .column_by_name(name)
+            .unwrap()
+            .as_any()
+            .downcast_ref::<Int32Array>()
+            .unwrap()
+            .value(0)
+    };
+    assert_eq!(offset("utc_offset"), 3600);
+    assert_eq!(offset("sunrise_utc_offset"), 7200);
+    assert_eq!(offset("transit_utc_offset"), 7200);
+    assert_eq!(offset("sunset_utc_offset"), 7200);
+    assert_eq!(
+        timestamp_array(&batch, "sunrise").value(0),
+        parse_rfc3339("2024-03-31T06:42:44+02:00").timestamp()
+    );
+}
+
+#[test]
+fn test_parquet_string_timestamps() {
+    let batch = parquet_single_batch(
+        &[
+            "--format=PARQUET",
+            "--parquet-timestamps=string",
+            "52.0",
+            "13.4",
+            "2024-06-21",
+            "sunrise",
+            "--twilight",
+        ],
+        &[("TZ", "Europe/Berlin")],
+    );
+    assert!(!schema_field_names(&batch).contains(&"utc_offset".to_string()));
+    for name in ["dateTime", "sunrise", "transit", "civil_end"] {
+        let column = batch.column_by_name(name).unwrap();
+        assert!(matches!(column.data_type(), DataType::Utf8), "{name}");
+    }
     assert_eq!(
         string_array(&batch, "dateTime").value(0),
-        "2024-06-21T12:00:00+02:00"
+        "2024-06-21T00:00:00+02:00"
+    );
+    assert!(string_array(&batch, "sunrise").value(0).ends_with("+02:00"));
+}
+
+#[test]
+fn test_parquet_native_event_timestamps() {
+    let batch = parquet_single_batch(
+        &[
+            "--format=PARQUET",
+            "--timezone=UTC",
+            "52.0",
+            "13.4",
+            "2024-06-21",
+            "sunrise",
+            "--twilight",
+        ],
+        &[],
     );
+    let csv = String::from_utf8(
+        SunceTest::new()
+            .args([
+                "--format=CSV",
+                "--timezone=UTC",
+                "52.0",
+                "13.4",
+                "2024-06-21",
+                "sunrise",
+                "--twilight",
+            ])
+            .get_output()
+            .stdout,
+    )
+    .unwrap();
+    let record = parse_csv_single_record_map(&csv);
+    for name in [
+        "sunrise",
+        "transit",
+        "sunset",
+        "civil_start",
+        "nautical_end",
+    ] {
+        assert_eq!(
+            timestamp_array(&batch, name).value(0),
+            parse_rfc3339(&record[name]).timestamp(),
+            "{name}"
+        );
+    }
 }
 
 #[test]
@@ -296,9 +470,9 @@ fn test_parquet_sunrise_null_handling() {
         &[],
     );
     assert_eq!(string_array(&batch, "type").value(0), "ALL_DAY");
-    assert!(string_array(&batch, "sunrise").is_null(0));
-    assert!(string_array(&batch, "sunset").is_null(0));
-    assert!(!string_array(&batch, "transit").is_null(0));
+    assert!(timestamp_array(&batch, "sunrise").is_null(0));
+    assert!(timestamp_array(&batch, "sunset").is_null(0));
+    assert!(!timestamp_array(&batch, "transit").is_null(0));
 }
 
 #[test]
@@ -323,8 +497,703 @@ fn test_parquet_sunrise_twilight_null_handling() {
         "astronomical_end",
     ] {
         assert!(
-            string_array(&batch, field).is_null(0),
+            timestamp_array(&batch, field).is_null(0),
             "{field} should be null"
         );
     }
 }
+
+#[test]
+fn test_parquet_events_schema() {
+    let batch = parquet_single_batch(
+        &[
+            "--format=PARQUET",
+            "--timezone=UTC",
+            "52.0",
+            "13.4",
+            "2024-03-20",
+            "events",
+        ],
+        &[],
+    );
+    assert_eq!(
+        schema_field_names(&batch),
+        vec![
+            "dateTime",
+            "utc_offset",
+            "state_before",
+            "state_after",
+            "horizon"
+        ]
+    );
+    assert_eq!(batch.num_rows(), 8);
+    assert_eq!(string_array(&batch, "state_before").value(0), "NIGHT");
+    assert_eq!(float_array(&batch, "horizon").value(0), -18.0);
+}
+
+#[test]
+fn test_parquet_horizon_profile_columns() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("skyline.csv");
+    write_text_file(&path, "0 2\n190 2\n195 40\n200 2\n");
+    let profile = format!("--horizon-profile=@{}", path.display());
+
+    let batch = parquet_single_batch(
+        &[
+            "--format=PARQUET",
+            "52.0",
+            "13.4",
+            "2024-06-21T02:00:00Z",
+            "position",
+            &profile,
+        ],
+        &[],
+    );
+    assert_eq!(
+        schema_field_names(&batch),
+        vec!["dateTime", "utc_offset", "azimuth", "zenith", "obstructed"]
+    );
+    let obstructed = batch
+        .column_by_name("obstructed")
+        .unwrap()
+        .as_any()
+        .downcast_ref::<BooleanArray>()
+        .expect("obstructed should be BooleanArray");
+    assert!(obstructed.value(0));
+
+    let batch = parquet_single_batch(
+        &[
+            "--format=PARQUET",
+            "--timezone=UTC",
+            "47.3",
+            "11.4",
+            "2024-12-21",
+            "sunrise",
+            &profile,
+        ],
+        &[],
+    );
+    assert_eq!(batch.num_rows(), 2);
+    assert_eq!(string_array(&batch, "type").value(1), "NORMAL");
+}
+
+#[test]
+fn test_parquet_irradiance_columns() {
+    let batch = parquet_single_batch(
+        &[
+            "--format=PARQUET",
+            "52.0",
+            "13.4",
+            "2024-06-21T00:00:00Z",
+            "position",
+            "--irradiance",
+        ],
+        &[],
+    );
+    assert_eq!(
+        schema_field_names(&batch),
+        vec![
+            "dateTime",
+            "utc_offset",
+            "azimuth",
+            "zenith",
+            "extraterrestrial",
+            "air_mass",
+            "ghi",
+            "dni",
+            "dhi"
+        ]
+    );
+    assert!(float_array(&batch, "air_mass").is_null(0));
+    assert_eq!(float_array(&batch, "ghi").value(0), 0.0);
+}
+
+#[test]
+fn test_parquet_tracker_columns() {
+    let batch = parquet_single_batch(
+        &[
+            "--format=PARQUET",
+            "52.0",
+            "13.4",
+            "2024-06-21T00:00:00Z",
+            "position",
+            "--tracker=single-axis",
+        ],
+        &[],
+    );
+    assert_eq!(
+        schema_field_names(&batch),
+        vec![
+            "dateTime",
+            "utc_offset",
+            "azimuth",
+            "zenith",
+            "tracker_rotation",
+            "incidence",
+            "cos_incidence"
+        ]
+    );
+    assert!(float_array(&batch, "tracker_rotation").is_null(0));
+}
+
+#[test]
+fn test_parquet_day_stats_columns() {
+    let batch = parquet_single_batch(
+        &[
+            "--format=PARQUET",
+            "78.2",
+            "15.6",
+            "2024-06-21",
+            "sunrise",
+            "--day-stats",
+        ],
+        &[],
+    );
+    let day_length = batch
+        .column_by_name("day_length")
+        .expect("missing day_length column")
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .expect("day_length should be Int64Array");
+    assert_eq!(day_length.value(0), 86_400);
+    assert!(float_array(&batch, "noon_elevation").value(0) > 30.0);
+    assert_eq!(
+        batch
+            .schema()
+            .field_with_name("day_length_change")
+            .unwrap()
+            .data_type(),
+        &DataType::Int64
+    );
+}
+
+#[test]
+fn test_parquet_custom_horizon_columns() {
+    let batch = parquet_single_batch(
+        &[
+            "--format=PARQUET",
+            "78.2",
+            "15.6",
+            "2024-06-21",
+            "sunrise",
+            "--horizons=low=-6,high=40",
+        ],
+        &[],
+    );
+    let schema = batch.schema();
+    for name in ["low_start", "low_end", "high_start", "high_end"] {
+        let field = schema.field_with_name(name).unwrap();
+        assert!(field.is_nullable(), "{name} should be nullable");
+        assert!(matches!(field.data_type(), DataType::Timestamp(..)));
+    }
+    assert!(timestamp_array(&batch, "low_start").is_null(0));
+    assert!(timestamp_array(&batch, "high_end").is_null(0));
+}
+
+#[test]
+fn test_parquet_shadow_columns() {
+    let batch = parquet_single_batch(
+        &[
+            "--format=PARQUET",
+            "52.0",
+            "13.4",
+            "2024-06-21T00:00:00Z",
+            "position",
+            "--shadow-height=10",
+        ],
+        &[],
+    );
+    assert_eq!(
+        schema_field_names(&batch),
+        vec![
+            "dateTime",
+            "utc_offset",
+            "azimuth",
+            "zenith",
+            "shadow_length",
+            "shadow_azimuth",
+            "shadow_east",
+            "shadow_north"
+        ]
+    );
+    assert!(float_array(&batch, "shadow_length").is_null(0));
+}
+
+#[test]
+fn test_parquet_geometry_columns() {
+    let batch = parquet_single_batch(
+        &[
+            "--format=PARQUET",
+            "52.0",
+            "13.4",
+            "2024-06-21T11:00:00Z",
+            "position",
+            "--geometry",
+        ],
+        &[],
+    );
+    assert_eq!(
+        schema_field_names(&batch)[4..],
+        [
+            "declination",
+            "right_ascension",
+            "hour_angle",
+            "equation_of_time",
+            "earth_sun_distance",
+            "julian_day",
+            "sidereal_time"
+        ]
+    );
+    assert!((float_array(&batch, "declination").value(0) - 23.44).abs() < 0.01);
+    assert!((float_array(&batch, "julian_day").value(0) - 2460482.958333).abs() < 1e-6);
+}
+
+#[test]
+fn test_parquet_site_columns() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("sites.geojson");
+    write_text_file(
+        &path,
+        r#"{"type": "Feature", "id": "A", "properties": {},
+            "geometry": {"type": "Point", "coordinates": [13.4, 52.0]}}"#,
+    );
+    let batch = parquet_single_batch(
+        &[
+            "--format=PARQUET",
+            &format!("@{}", path.display()),
+            "2024-06-21T12:00:00Z",
+            "position",
+        ],
+        &[],
+    );
+    assert_eq!(schema_field_names(&batch)[..3], ["id", "name", "latitude"]);
+    let ids = batch
+        .column_by_name("id")
+        .unwrap()
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    assert_eq!(ids.value(0), "A");
+    assert!(batch.column_by_name("name").unwrap().is_null(0));
+}
+
+#[test]
+fn test_parquet_writer_options() {
+    let defaults = parquet_metadata(&[
+        "--format=parquet",
+        "52.0",
+        "13.4",
+        "2024-06-21T12:00:00",
+        "position",
+    ]);
+    assert_eq!(defaults.num_row_groups(), 1);
+    let column = defaults.row_group(0).column(0);
+    assert_eq!(column.compression(), Compression::SNAPPY);
+
+    let tuned = parquet_metadata(&[
+        "--format=parquet",
+        "--parquet-compression=zstd:9",
+        "--parquet-row-group-size=10",
+        "--no-parquet-dictionary",
+        "52.0",
+        "13.4",
+        "2024-06-21",
+        "position",
+        "--step=1h",
+    ]);
+    assert_eq!(tuned.file_metadata().num_rows(), 24);
+    assert_eq!(tuned.num_row_groups(), 3);
+    for row_group in tuned.row_groups() {
+        for column in row_group.columns() {
+            assert!(matches!(column.compression(), Compression::ZSTD(_)));
+            assert!(column.dictionary_page_offset().is_none());
+            assert!(
+                !column
+                    .encodings()
+                    .any(|encoding| encoding == Encoding::RLE_DICTIONARY)
+            );
+        }
+    }
+
+    let gzip = parquet_metadata(&[
+        "--format=parquet",
+        "--parquet-compression=gzip",
+        "52.0",
+        "13.4",
+        "2024-06-21",
+        "sunrise",
+    ]);
+    assert!(matches!(
+        gzip.row_group(0).column(0).compression(),
+        Compression::GZIP(_)
+    ));
+}
+
+#[test]
+fn test_parquet_run_metadata() {
+    let position = parquet_metadata(&[
+        "--format=parquet",
+        "--deltat=69.2",
+        "--algorithm=grena3",
+        "--timezone=UTC",
+        "52.0",
+        "13.4",
+        "2024-06-21T12:00:00",
+        "position",
+        "--no-refraction",
+    ]);
+    assert_eq!(
+        metadata_value(&position, "sunce.version"),
+        Some(env!("CARGO_PKG_VERSION"))
+    );
+    assert_eq!(metadata_value(&position, "sunce.command"), Some("position"));
+    assert_eq!(metadata_value(&position, "sunce.algorithm"), Some("grena3"));
+    assert_eq!(metadata_value(&position, "sunce.deltat"), Some("69.2"));
+    assert_eq!(metadata_value(&position, "sunce.refraction"), Some("false"));
+    assert_eq!(metadata_value(&position, "sunce.pressure"), None);
+    assert_eq!(metadata_value(&position, "sunce.timezone"), Some("UTC"));
+
+    let sunrise = parquet_metadata(&[
+        "--format=parquet",
+        "--deltat",
+        "52.0",
+        "13.4",
+        "2024-06-21",
+        "sunrise",
+        "--horizon=-0.5",
+    ]);
+    assert_eq!(metadata_value(&sunrise, "sunce.command"), Some("sunrise"));
+    assert_eq!(metadata_value(&sunrise, "sunce.deltat"), Some("estimated"));
+    assert_eq!(metadata_value(&sunrise, "sunce.horizon"), Some("-0.5"));
+    assert_eq!(metadata_value(&sunrise, "sunce.algorithm"), None);
+    assert_eq!(metadata_value(&sunrise, "sunce.twilight"), None);
+
+    let sunrise = parquet_metadata(&[
+        "--format=parquet",
+        "--dst-policy=later",
+        "52.0",
+        "13.4",
+        "2024-06-21",
+        "sunrise",
+        "--twilight",
+        "--horizons=dusk=-3,-1.5",
+    ]);
+    assert_eq!(metadata_value(&sunrise, "sunce.dst_policy"), Some("later"));
+    assert_eq!(metadata_value(&sunrise, "sunce.twilight"), Some("true"));
+    assert_eq!(
+        metadata_value(&sunrise, "sunce.horizons"),
+        Some("dusk=-3,horizon_m1_5=-1.5")
+    );
+
+    let dir = tempfile::tempdir().unwrap();
+    let skyline = dir.path().join("skyline.csv");
+    std::fs::write(&skyline, "0 5\n").unwrap();
+    let profile = format!("--horizon-profile=@{}", skyline.display());
+    let position = parquet_metadata(&[
+        "--format=parquet",
+        "52.0",
+        "13.4",
+        "2024-06-21T12:00:00",
+        "position",
+        "--tracker=dual-axis",
+        &profile,
+    ]);
+    assert_eq!(
+        metadata_value(&position, "sunce.tracker"),
+        Some("dual-axis")
+    );
+    assert_eq!(
+        metadata_value(&position, "sunce.horizon_profile"),
+        Some(skyline.display().to_string().as_str())
+    );
+}
+
+fn dataset_files(dir: &std::path::Path) -> Vec<String> {
+    fn walk(dir: &std::path::Path, root: &std::path::Path, files: &mut Vec<String>) {
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                walk(&path, root, files);
+            } else {
+                let relative = path.strip_prefix(root).unwrap();
+                files.push(relative.to_string_lossy().replace('\\', "/"));
+            }
+        }
+    }
+    let mut files = Vec::new();
+    walk(dir, dir, &mut files);
+    files.sort();
+    files
+}
+
+fn read_parquet_file(path: &std::path::Path) -> Vec<RecordBatch> {
+    let file = std::fs::File::open(path).unwrap();
+    ParquetRecordBatchReaderBuilder::try_new(file)
+        .expect("Failed to create Parquet reader")
+        .build()
+        .expect("Failed to build Parquet reader")
+        .collect::<Result<Vec<_>, _>>()
+        .expect("Failed to read batches")
+}
+
+#[test]
+fn test_parquet_dataset_partitions_by_month() {
+    let dir = tempfile::tempdir().unwrap();
+    let out = dir.path().join("dataset");
+    sunce_command()
+        .args([
+            "--format=parquet",
+            &format!("--output-dir={}", out.display()),
+            "52.0",
+            "13.4",
+            "2024",
+            "sunrise",
+        ])
+        .assert()
+        .success()
+        .stdout("");
+
+    let files = dataset_files(&out);
+    let expected: Vec<String> = (1..=12)
+        .map(|month| format!("year=2024/month={month:02}/part-0000.parquet"))
+        .collect();
+    assert_eq!(files, expected);
+    let rows: Vec<usize> = files
+        .iter()
+        .map(|file| total_rows(&read_parquet_file(&out.join(file))))
+        .collect();
+    assert_eq!(rows[..3], [31, 29, 31]);
+    assert_eq!(rows.iter().sum::<usize>(), 366);
+
+    sunce_command()
+        .args([
+            "--format=parquet",
+            &format!("--output-dir={}", out.display()),
+            "52.0",
+            "13.4",
+            "2024-01-15",
+            "sunrise",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("File exists"));
+}
+
+#[test]
+fn test_parquet_dataset_location_major_order() {
+    // A timezone column orders records by location, so each site runs through every month.
+    let dir = tempfile::tempdir().unwrap();
+    let coords = dir.path().join("sites.txt");
+    write_text_file(
+        &coords,
+        "52.52 13.40 Europe/Berlin\n40.71 -74.01 America/New_York\n35.68 139.69 Asia/Tokyo\n",
+    );
+    let out = dir.path().join("dataset");
+    sunce_command()
+        .args([
+            "--format=parquet",
+            &format!("--output-dir={}", out.display()),
+            &format!("@{}", coords.display()),
+            "2024",
+            "sunrise",
+        ])
+        .assert()
+        .success()
+        .stdout("");
+
+    let files = dataset_files(&out);
+    let expected: Vec<String> = (1..=12)
+        .map(|month| format!("year=2024/month={month:02}/part-0000.parquet"))
+        .collect();
+    assert_eq!(files, expected);
+    let january = read_parquet_file(&out.join(&files[0]));
+    assert_eq!(total_rows(&january), 3 * 31);
+}
+
+#[test]
+fn test_parquet_dataset_lat_bands_and_rolling() {
+    let dir = tempfile::tempdir().unwrap();
+    sunce_command()
+        .args([
+            "--format=parquet",
+            &format!("--output-dir={}", dir.path().display()),
+            "--partition-lat-band=5",
+            "--max-file-rows=10",
+            "-2:3:5",
+            "13.4",
+            "2024-06-21",
+            "position",
+            "--step=1h",
+        ])
+        .assert()
+        .success();
+
+    let files = dataset_files(dir.path());
+    assert_eq!(
+        files,
+        [
+            "year=2024/month=06/lat_band=-5/part-0000.parquet",
+            "year=2024/month=06/lat_band=-5/part-0001.parquet",
+            "year=2024/month=06/lat_band=-5/part-0002.parquet",
+            "year=2024/month=06/lat_band=0/part-0000.parquet",
+            "year=2024/month=06/lat_band=0/part-0001.parquet",
+            "year=2024/month=06/lat_band=0/part-0002.parquet",
+        ]
+    );
+    let rows: Vec<usize> = files
+        .iter()
+        .map(|file| total_rows(&read_parquet_file(&dir.path().join(file))))
+        .collect();
+    assert_eq!(rows, [10, 10, 4, 10, 10, 4]);
+    let batches = read_parquet_file(&dir.path().join(&files[3]));
+    assert_eq!(float_array(&batches[0], "latitude").value(0), 3.0);
+}
+
+fn arrow_batches(args: &[&str]) -> Vec<RecordBatch> {
+    let output = sunce_command()
+        .args(args)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    StreamReader::try_new(output.as_slice(), None)
+        .expect("Failed to create Arrow stream reader")
+        .collect::<Result<Vec<_>, _>>()
+        .expect("Failed to read batches")
+}
+
+#[test]
+fn test_arrow_stream_matches_parquet_schema() {
+    let args = ["52.0", "13.4", "2024-06-21", "sunrise"];
+    let arrow = arrow_batches(&[&["--format=arrow"], &args[..]].concat());
+    let parquet = parquet_batches(&[&["--format=parquet"], &args[..]].concat(), &[]);
+    assert_eq!(arrow.len(), 1);
+    assert_eq!(arrow[0].schema(), parquet[0].schema());
+    assert_eq!(arrow[0], parquet[0]);
+
+    let position = arrow_batches(&[
+        "--format=ARROW",
+        "52.0",
+        "13.4",
+        "2024-06-21",
+        "position",
+        "--step=1h",
+    ]);
+    assert_eq!(total_rows(&position), 24);
+    assert_eq!(
+        timestamp_array(&position[0], "dateTime").value(1)
+            - timestamp_array(&position[0], "dateTime").value(0),
+        3600
+    );
+}
+
+#[test]
+fn test_parquet_column_selection() {
+    let batch = parquet_single_batch(
+        &[
+            "--format=parquet",
+            "--columns=sunset,latitude,dateTime",
+            "52.0",
+            "13.4",
+            "2024-06-21",
+            "sunrise",
+        ],
+        &[],
+    );
+    assert_eq!(
+        schema_field_names(&batch),
+        vec![
+            "sunset",
+            "sunset_utc_offset",
+            "latitude",
+            "dateTime",
+            "utc_offset"
+        ]
+    );
+    assert_eq!(float_array(&batch, "latitude").value(0), 52.0);
+
+    let arrow = arrow_batches(&[
+        "--format=arrow",
+        "--columns=azimuth",
+        "52.0",
+        "13.4",
+        "2024-06-21T12:00:00",
+        "position",
+    ]);
+    assert_eq!(schema_field_names(&arrow[0]), vec!["azimuth"]);
+}
+
+#[test]
+fn test_parquet_string_timestamps_use_time_format() {
+    let batch = parquet_single_batch(
+        &[
+            "--format=parquet",
+            "--parquet-timestamps=string",
+            "--time-format=unix",
+            "52.0",
+            "13.4",
+            "2024-06-21T12:00:00+02:00",
+            "position",
+        ],
+        &[],
+    );
+    assert_eq!(string_array(&batch, "dateTime").value(0), "1718964000");
+
+    sunce_command()
+        .args([
+            "--format=parquet",
+            "--time-format=unix",
+            "52.0",
+            "13.4",
+            "2024-06-21",
+            "position",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "Option --time-format requires --parquet-timestamps=string",
+        ));
+}
+
+#[test]
+fn test_arrow_stream_batch_per_record_from_stdin() {
+    let output = sunce_command()
+        .args(["--format=arrow", "52.0", "13.4", "@-", "position"])
+        .write_stdin("2024-06-21T10:00:00Z\n2024-06-21T11:00:00Z\n2024-06-21T12:00:00Z\n")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let batches = StreamReader::try_new(output.as_slice(), None)
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(batches.len(), 3);
+    assert!(batches.iter().all(|batch| batch.num_rows() == 1));
+}
+
+#[test]
+fn test_parquet_dst_policy_input_column() {
+    let batch = parquet_single_batch(
+        &[
+            "--format=parquet",
+            "--show-inputs",
+            "--dst-policy=later",
+            "--timezone=Europe/Berlin",
+            "52.0",
+            "13.4",
+            "2024-10-27T02:30:00",
+            "position",
+        ],
+        &[],
+    );
+    let names = schema_field_names(&batch);
+    let date_time = names.iter().position(|name| name == "dateTime").unwrap();
+    assert_eq!(
+        names[date_time + 1..date_time + 4],
+        ["utc_offset", "dstPolicy", "deltaT"]
+    );
+    assert_eq!(string_array(&batch, "dstPolicy").value(0), "later");
+}
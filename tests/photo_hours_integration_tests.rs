@@ -0,0 +1,146 @@
+mod common;
+use common::*;
+
+fn photo_hours_row(
+    lat: &str,
+    lon: &str,
+    date: &str,
+    extra: &[&str],
+) -> std::collections::HashMap<String, String> {
+    let mut args = vec![
+        "--format=csv",
+        "--timezone=UTC",
+        lat,
+        lon,
+        date,
+        "sunrise",
+        "--photo-hours",
+    ];
+    args.extend_from_slice(extra);
+    let output = SunceTest::new().args(&args).get_output();
+    assert!(
+        output.status.success(),
+        "sunrise failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    parse_csv_single_record_map(&String::from_utf8(output.stdout).unwrap())
+}
+
+#[test]
+fn photo_hours_columns_follow_twilight() {
+    let output = SunceTest::new()
+        .args([
+            "--format=csv",
+            "52.0",
+            "13.4",
+            "2024-06-21",
+            "sunrise",
+            "--twilight",
+            "--photo-hours",
+            "--day-stats",
+        ])
+        .get_output();
+    let (headers, _) = parse_csv_single_record(&String::from_utf8(output.stdout).unwrap());
+    let start = headers
+        .iter()
+        .position(|name| name == "astronomical_end")
+        .unwrap();
+    assert_eq!(
+        headers[start..start + 10],
+        fields(&[
+            "astronomical_end",
+            "golden_morning_start",
+            "golden_morning_end",
+            "golden_evening_start",
+            "golden_evening_end",
+            "blue_morning_start",
+            "blue_morning_end",
+            "blue_evening_start",
+            "blue_evening_end",
+            "day_length",
+        ])
+    );
+}
+
+#[test]
+fn photo_hours_bracket_sunrise_and_sunset() {
+    let row = photo_hours_row("52.0", "13.4", "2024-03-21", &["--twilight"]);
+
+    // Blue hour starts at civil dawn and hands over to the golden hour.
+    assert_eq!(row["blue_morning_start"], row["civil_start"]);
+    assert_eq!(row["blue_morning_end"], row["golden_morning_start"]);
+    assert_eq!(row["golden_evening_end"], row["blue_evening_start"]);
+    assert_eq!(row["blue_evening_end"], row["civil_end"]);
+
+    let order = [
+        "blue_morning_start",
+        "golden_morning_start",
+        "sunrise",
+        "golden_morning_end",
+        "golden_evening_start",
+        "sunset",
+        "golden_evening_end",
+        "blue_evening_end",
+    ]
+    .map(|name| parse_rfc3339(&row[name]));
+    assert!(order.windows(2).all(|pair| pair[0] < pair[1]));
+
+    // The sun climbs about 10 degrees in roughly an hour at the equinox in Berlin.
+    let golden =
+        parse_rfc3339(&row["golden_morning_end"]) - parse_rfc3339(&row["golden_morning_start"]);
+    assert!((55..=75).contains(&golden.num_minutes()), "{golden}");
+}
+
+#[test]
+fn photo_hours_are_empty_where_the_sun_does_not_cross() {
+    let row = photo_hours_row("69.65", "18.96", "2024-12-01", &[]);
+    assert_eq!(row["type"], "ALL_NIGHT");
+    assert!(!row["golden_morning_start"].is_empty());
+    assert!(row["golden_morning_end"].is_empty());
+    assert!(row["golden_evening_start"].is_empty());
+    assert!(!row["golden_evening_end"].is_empty());
+
+    let row = photo_hours_row("78.2", "15.6", "2024-06-21", &[]);
+    assert_eq!(row["type"], "ALL_DAY");
+    assert!(row["blue_morning_start"].is_empty() && row["golden_evening_end"].is_empty());
+}
+
+#[test]
+fn photo_hours_json_and_option_conflicts() {
+    let json = parse_json_output(
+        &String::from_utf8(
+            SunceTest::new()
+                .args([
+                    "--format=json",
+                    "--time-format=unix",
+                    "52.0",
+                    "13.4",
+                    "2024-06-21",
+                    "sunrise",
+                    "--photo-hours",
+                ])
+                .get_output()
+                .stdout,
+        )
+        .unwrap(),
+    );
+    assert!(json["golden_morning_start"].is_i64());
+    assert!(json["blue_evening_end"].is_i64());
+
+    for (args, message) in [
+        (
+            ["sunrise", "--photo-hours", "--horizon=2"],
+            "Option --photo-hours cannot be used with --horizon",
+        ),
+        (
+            ["position", "--photo-hours", "--headers"],
+            "Option --photo-hours not valid for position command",
+        ),
+    ] {
+        SunceTest::new()
+            .args(["52.0", "13.4", "2024-06-21"])
+            .args(args)
+            .assert_failure()
+            .stderr(predicates::str::contains(message));
+    }
+}
@@ -104,6 +104,23 @@ fn test_twilight_state_boundaries_are_half_open() {
     }
 }
 
+#[test]
+fn test_photo_hour_predicates_follow_band_boundaries() {
+    for (datetime, predicate, code) in [
+        ("2024-03-21T04:32:14", "--is-blue-hour", 0),
+        ("2024-03-21T04:45:18", "--is-blue-hour", 1),
+        ("2024-03-21T04:45:18", "--is-golden-hour", 0),
+        ("2024-03-21T05:50:18", "--is-golden-hour", 1),
+        ("2024-03-21T12:00:00", "--is-golden-hour", 1),
+        ("2024-03-21T16:37:28", "--is-golden-hour", 0),
+        ("2024-03-21T17:42:39", "--is-golden-hour", 1),
+        ("2024-03-21T17:42:39", "--is-blue-hour", 0),
+        ("2024-03-21T17:55:46", "--is-blue-hour", 1),
+    ] {
+        assert_berlin_state(datetime, predicate, code);
+    }
+}
+
 #[test]
 fn test_angle_predicates_for_known_thresholds() {
     for (predicate, code) in [
@@ -0,0 +1,76 @@
+mod common;
+use common::*;
+
+#[test]
+fn shadow_points_away_from_the_sun() {
+    let row = csv_row(&[
+        "52.0",
+        "13.4",
+        "2024-12-21T11:00:00Z",
+        "position",
+        "--shadow-height=10",
+    ]);
+    let zenith = numeric_field(&row, "zenith");
+    let length = numeric_field(&row, "shadow_length");
+    let azimuth = numeric_field(&row, "shadow_azimuth");
+
+    assert!((length - 10.0 * zenith.to_radians().tan()).abs() < 0.01);
+    assert!((azimuth - (numeric_field(&row, "azimuth") + 180.0) % 360.0).abs() < 1e-3);
+    assert!(
+        numeric_field(&row, "shadow_north") > 0.0,
+        "winter noon shadow points north"
+    );
+    let offset = numeric_field(&row, "shadow_east").hypot(numeric_field(&row, "shadow_north"));
+    assert!((offset - length).abs() < 0.01);
+}
+
+#[test]
+fn shadows_shorten_towards_summer() {
+    let winter = csv_row(&[
+        "52.0",
+        "13.4",
+        "2024-12-21T11:00:00Z",
+        "position",
+        "--shadow-height=10",
+    ]);
+    let summer = csv_row(&[
+        "52.0",
+        "13.4",
+        "2024-06-21T11:00:00Z",
+        "position",
+        "--shadow-height=10",
+    ]);
+    assert!(numeric_field(&summer, "shadow_length") < numeric_field(&winter, "shadow_length"));
+}
+
+#[test]
+fn no_shadow_at_night() {
+    let row = csv_row(&[
+        "52.0",
+        "13.4",
+        "2024-12-21T00:00:00Z",
+        "position",
+        "--shadow-height=10",
+    ]);
+    for name in [
+        "shadow_length",
+        "shadow_azimuth",
+        "shadow_east",
+        "shadow_north",
+    ] {
+        assert_eq!(row[name], "", "{name}");
+    }
+
+    let output = SunceTest::new()
+        .args([
+            "--format=json",
+            "52.0",
+            "13.4",
+            "2024-12-21T00:00:00Z",
+            "position",
+            "--shadow-height=10",
+        ])
+        .get_output();
+    let json = parse_json_output(&String::from_utf8(output.stdout).unwrap());
+    assert!(json["shadow_length"].is_null());
+}
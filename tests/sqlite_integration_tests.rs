@@ -0,0 +1,284 @@
+#![cfg(feature = "sqlite")]
+
+mod common;
+use common::*;
+use rusqlite::Connection;
+
+fn run_sqlite(args: &[&str]) {
+    sunce_command().args(args).assert().success().stdout("");
+}
+
+fn column_types(connection: &Connection, table: &str) -> Vec<(String, String)> {
+    let mut statement = connection
+        .prepare(&format!("PRAGMA table_info(\"{table}\")"))
+        .unwrap();
+    statement
+        .query_map([], |row| Ok((row.get(1)?, row.get(2)?)))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+fn row_count(connection: &Connection, table: &str) -> i64 {
+    connection
+        .query_row(&format!("SELECT COUNT(*) FROM \"{table}\""), [], |row| {
+            row.get(0)
+        })
+        .unwrap()
+}
+
+#[test]
+fn test_sqlite_position_table() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("solar.db");
+    let output = format!("--output={}", db.display());
+    run_sqlite(&[
+        "--format=sqlite",
+        &output,
+        "52.0",
+        "13.4",
+        "2024-06-21T12:00:00+02:00",
+        "position",
+    ]);
+
+    let connection = Connection::open(&db).unwrap();
+    let types = column_types(&connection, "position");
+    assert_eq!(types[0], ("latitude".to_string(), "REAL".to_string()));
+    assert!(types.contains(&("dateTime".to_string(), "TEXT".to_string())));
+    assert!(types.contains(&("zenith".to_string(), "REAL".to_string())));
+
+    let (lat, date_time, zenith): (f64, String, f64) = connection
+        .query_row(
+            "SELECT latitude, dateTime, zenith FROM position",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .unwrap();
+    assert_eq!(lat, 52.0);
+    assert_eq!(date_time, "2024-06-21T12:00:00+02:00");
+    assert!((zenith - 31.408).abs() < 0.01);
+}
+
+#[test]
+fn test_sqlite_upsert_refreshes_rows() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("solar.db");
+    let output = format!("--output={}", db.display());
+    let args = [
+        "--format=sqlite",
+        output.as_str(),
+        "--table=berlin",
+        "--upsert",
+        "52.0",
+        "13.4",
+        "2024-06",
+        "sunrise",
+        "--day-stats",
+    ];
+    run_sqlite(&args);
+    run_sqlite(&args);
+    let connection = Connection::open(&db).unwrap();
+    assert_eq!(row_count(&connection, "berlin"), 30);
+    let day_length: i64 = connection
+        .query_row(
+            "SELECT day_length FROM berlin WHERE dateTime LIKE '2024-06-21%'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert!(day_length > 16 * 3600);
+
+    sunce_command()
+        .args([
+            "--format=sqlite",
+            &output,
+            "--table=berlin",
+            "52.0",
+            "13.4",
+            "2024-06",
+            "sunrise",
+            "--day-stats",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("NOT NULL constraint failed"));
+}
+
+#[test]
+fn test_sqlite_keeps_every_skyline_period() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("solar.db");
+    let output = format!("--output={}", db.display());
+    let skyline = dir.path().join("skyline.csv");
+    write_text_file(&skyline, "0 2\n190 2\n195 40\n200 2\n");
+    let profile = format!("--horizon-profile=@{}", skyline.display());
+    // A narrow obstruction splits this day into two visible periods with the same row key.
+    let args = [
+        "--format=sqlite",
+        output.as_str(),
+        "47.3",
+        "11.4",
+        "2024-12-21",
+        "sunrise",
+        profile.as_str(),
+    ];
+    run_sqlite(&args);
+    let connection = Connection::open(&db).unwrap();
+    assert_eq!(row_count(&connection, "sunrise"), 2);
+
+    sunce_command()
+        .args(args)
+        .arg("--upsert")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "Option --upsert cannot be used with --horizon-profile for sunrise",
+        ));
+    assert_eq!(row_count(&connection, "sunrise"), 2);
+}
+
+#[test]
+fn test_sqlite_upsert_key_ignores_time_rendering() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("solar.db");
+    let output = format!("--output={}", db.display());
+    let times = dir.path().join("times.txt");
+    write_text_file(
+        &times,
+        "2024-06-21T00:00:00Z\n2024-06-21T06:00:00Z\n2024-06-21T12:00:00Z\n2024-06-21T18:00:00Z\n",
+    );
+    let times = format!("@{}", times.display());
+    let run = |extra: &[&str]| {
+        let mut args = vec![
+            "--format=sqlite",
+            output.as_str(),
+            "--upsert",
+            "52.0",
+            "13.4",
+            times.as_str(),
+            "position",
+        ];
+        args.extend_from_slice(extra);
+        run_sqlite(&args);
+    };
+    run(&[]);
+    run(&["--timezone=+05:00"]);
+    run(&["--time-format=unix"]);
+
+    let connection = Connection::open(&db).unwrap();
+    assert_eq!(row_count(&connection, "position"), 4);
+    // The last run refreshed the rendered dateTime of the existing rows.
+    let (date_time, epoch): (String, i64) = connection
+        .query_row(
+            "SELECT dateTime, dateTime_epoch FROM position ORDER BY dateTime_epoch LIMIT 1",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap();
+    assert_eq!(date_time, "1718928000");
+    assert_eq!(epoch, 1_718_928_000);
+}
+
+#[test]
+fn test_sqlite_upsert_with_only_key_columns() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("solar.db");
+    let output = format!("--output={}", db.display());
+    for columns in [
+        "--columns=latitude,longitude,dateTime",
+        "--columns=latitude,longitude",
+    ] {
+        let args = [
+            "--format=sqlite",
+            output.as_str(),
+            "--upsert",
+            columns,
+            "52.0",
+            "13.4",
+            "2024-06-21T12:00:00Z",
+            "position",
+        ];
+        run_sqlite(&args);
+        run_sqlite(&args);
+    }
+
+    let connection = Connection::open(&db).unwrap();
+    assert_eq!(row_count(&connection, "position"), 1);
+}
+
+#[test]
+fn test_sqlite_events_with_no_show_inputs() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("solar.db");
+    run_sqlite(&[
+        "--format=SQLITE",
+        &format!("--output={}", db.display()),
+        "--no-show-inputs",
+        "52.0",
+        "13.4",
+        "2024-06-21",
+        "events",
+    ]);
+
+    let connection = Connection::open(&db).unwrap();
+    let names: Vec<String> = column_types(&connection, "events")
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect();
+    assert_eq!(
+        names,
+        ["dateTime", "state_before", "state_after", "horizon"]
+    );
+    assert!(row_count(&connection, "events") > 0);
+}
+
+#[test]
+fn test_sqlite_column_selection() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("solar.db");
+    run_sqlite(&[
+        "--format=sqlite",
+        &format!("--output={}", db.display()),
+        "--columns=sunrise,day_length,dateTime",
+        "--day-stats",
+        "52.0",
+        "13.4",
+        "2024-06-21",
+        "sunrise",
+    ]);
+
+    let connection = Connection::open(&db).unwrap();
+    assert_eq!(
+        column_types(&connection, "sunrise"),
+        [
+            ("sunrise".to_string(), "TEXT".to_string()),
+            ("day_length".to_string(), "INTEGER".to_string()),
+            ("dateTime".to_string(), "TEXT".to_string()),
+        ]
+    );
+    assert_eq!(row_count(&connection, "sunrise"), 1);
+}
+
+#[test]
+fn test_sqlite_epoch_time_format_uses_integer_columns() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("solar.db");
+    run_sqlite(&[
+        "--format=sqlite",
+        &format!("--output={}", db.display()),
+        "--time-format=unix-ms",
+        "52.0",
+        "13.4",
+        "2024-06-21",
+        "sunrise",
+    ]);
+
+    let connection = Connection::open(&db).unwrap();
+    let types = column_types(&connection, "sunrise");
+    assert!(types.contains(&("dateTime".to_string(), "INTEGER".to_string())));
+    assert!(types.contains(&("sunset".to_string(), "INTEGER".to_string())));
+    let date_time: i64 = connection
+        .query_row("SELECT dateTime FROM sunrise", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(date_time, 1_718_928_000_000);
+}
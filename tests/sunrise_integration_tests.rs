@@ -194,6 +194,22 @@ fn test_sunrise_dst_and_accuracy_regressions() {
         assert_time_close(&row["transit"], transit, 0);
         assert_time_close(&row["sunset"], sunset, 0);
     }
+
+    // Result times use the offset at each instant, not the one at the start of the day.
+    let row = csv_row(
+        &[
+            "--format=CSV",
+            "--timezone=Europe/Berlin",
+            "52.0",
+            "13.4",
+            "2024-03-31",
+            "sunrise",
+        ],
+        &[],
+    );
+    assert_eq!(row["dateTime"], "2024-03-31T00:00:00+01:00");
+    assert_eq!(row["sunrise"], "2024-03-31T06:42:44+02:00");
+    assert_eq!(row["sunset"], "2024-03-31T19:39:08+02:00");
 }
 
 #[test]
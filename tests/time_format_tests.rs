@@ -0,0 +1,91 @@
+mod common;
+use common::{parse_csv_single_record_map, parse_json_output, sunce_command};
+
+fn stdout(args: &[&str]) -> String {
+    let output = sunce_command().args(args).output().unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn csv_with_time_format(
+    time_format: &str,
+    datetime: &str,
+) -> std::collections::HashMap<String, String> {
+    parse_csv_single_record_map(&stdout(&[
+        "--format=csv",
+        &format!("--time-format={time_format}"),
+        "52.0",
+        "13.4",
+        datetime,
+        "position",
+    ]))
+}
+
+#[test]
+fn test_time_formats_in_csv() {
+    let datetime = "2024-06-21T12:00:00.250+02:00";
+    let cases = [
+        ("rfc3339", "2024-06-21T12:00:00+02:00"),
+        ("RFC3339-MILLIS", "2024-06-21T12:00:00.250+02:00"),
+        ("unix", "1718964000"),
+        ("unix-ms", "1718964000250"),
+        ("utc", "2024-06-21T10:00:00Z"),
+        ("%d.%m.%Y %H:%M %z", "21.06.2024 12:00 +0200"),
+    ];
+    for (time_format, expected) in cases {
+        assert_eq!(
+            csv_with_time_format(time_format, datetime)["dateTime"],
+            expected,
+            "--time-format={time_format}"
+        );
+    }
+}
+
+#[test]
+fn test_epoch_time_formats_are_json_numbers() {
+    let json = parse_json_output(&stdout(&[
+        "--format=json",
+        "--time-format=unix",
+        "52.0",
+        "13.4",
+        "2024-06-21",
+        "sunrise",
+    ]));
+    assert_eq!(json["dateTime"], 1718928000);
+    assert!(json["sunrise"].is_i64());
+    assert!(json["sunset"].is_i64());
+
+    let json = parse_json_output(&stdout(&[
+        "--format=json",
+        "--time-format=utc",
+        "--timezone=+02:00",
+        "52.0",
+        "13.4",
+        "2024-06-21T12:00:00",
+        "position",
+    ]));
+    assert_eq!(json["dateTime"], "2024-06-21T10:00:00Z");
+}
+
+#[test]
+fn test_time_format_applies_to_event_columns() {
+    let output = stdout(&[
+        "--format=csv",
+        "--no-headers",
+        "--time-format=%H:%M",
+        "52.0",
+        "13.4",
+        "2024-06-21",
+        "events",
+    ]);
+    let times: Vec<&str> = output
+        .lines()
+        .map(|line| line.split(',').next().unwrap())
+        .collect();
+    assert!(!times.is_empty());
+    assert!(
+        times
+            .iter()
+            .all(|time| time.len() == 5 && time.as_bytes()[2] == b':')
+    );
+}
@@ -0,0 +1,85 @@
+#![cfg(feature = "tz-lookup")]
+
+mod common;
+use common::*;
+use tempfile::tempdir;
+
+fn csv_rows(args: &[&str], stdin: &str) -> Vec<std::collections::HashMap<String, String>> {
+    let mut cmd = sunce_command();
+    cmd.arg("--format=csv").args(args).write_stdin(stdin);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    parse_csv_output_maps(&String::from_utf8(output).unwrap())
+}
+
+#[test]
+fn test_auto_timezone_for_a_single_location() {
+    let auto = csv_row(&["--timezone=auto", "52.0", "13.4", "2024-06-21", "sunrise"]);
+    let named = csv_row(&[
+        "--timezone=Europe/Berlin",
+        "52.0",
+        "13.4",
+        "2024-06-21",
+        "sunrise",
+    ]);
+    assert_eq!(auto, named);
+    assert_eq!(auto["sunrise"], "2024-06-21T04:46:15+02:00");
+}
+
+#[test]
+fn test_auto_timezone_per_location() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("sites.txt");
+    // The first row's column takes precedence over the lookup; the others are looked up.
+    write_text_file(
+        &path,
+        "52.52 13.40 Asia/Kolkata\n40.71,-74.01\n35.68 139.69\n",
+    );
+    let coords = format!("@{}", path.display());
+    let rows = csv_rows(&["--timezone=auto", &coords, "2024-06-21", "sunrise"], "");
+    let dates: Vec<&str> = rows.iter().map(|row| row["dateTime"].as_str()).collect();
+    assert_eq!(
+        dates,
+        [
+            "2024-06-21T00:00:00+05:30",
+            "2024-06-21T00:00:00-04:00",
+            "2024-06-21T00:00:00+09:00",
+        ]
+    );
+    assert!(rows[1]["sunrise"].starts_with("2024-06-21T05:25"));
+
+    // Any row may give its own zone; only the others are looked up.
+    write_text_file(&path, "40.71,-74.01\n52.52 13.40 Asia/Kolkata\n");
+    let rows = csv_rows(&["--timezone=auto", &coords, "2024-06-21", "sunrise"], "");
+    let dates: Vec<&str> = rows.iter().map(|row| row["dateTime"].as_str()).collect();
+    assert_eq!(
+        dates,
+        ["2024-06-21T00:00:00-04:00", "2024-06-21T00:00:00+05:30"]
+    );
+
+    // Ranges at sea get the nautical zone of their longitude band.
+    let rows = csv_rows(
+        &["--timezone=auto", "0:1:1", "-30", "2024-06", "sunrise"],
+        "",
+    );
+    assert_eq!(rows.len(), 60);
+    assert_eq!(rows[0]["dateTime"], "2024-06-01T00:00:00-02:00");
+    assert_eq!(rows[30]["latitude"], "1.00000");
+}
+
+#[test]
+fn test_auto_timezone_for_paired_input() {
+    // Times with an offset keep their instant and are shown in the location's zone.
+    let rows = csv_rows(
+        &["--timezone=auto", "@-", "position"],
+        "52.52 13.40 2024-06-21T12:00:00\n40.71 -74.01 2024-06-21 12:00\n-33.87 151.21 2024-06-21T12:00:00Z\n",
+    );
+    let dates: Vec<&str> = rows.iter().map(|row| row["dateTime"].as_str()).collect();
+    assert_eq!(
+        dates,
+        [
+            "2024-06-21T12:00:00+02:00",
+            "2024-06-21T12:00:00-04:00",
+            "2024-06-21T22:00:00+10:00",
+        ]
+    );
+}
@@ -292,7 +292,7 @@ fn test_twilight_polar_json_behavior() {
 #[cfg(feature = "parquet")]
 #[test]
 fn test_twilight_parquet_output() {
-    use arrow::array::StringArray;
+    use arrow::array::TimestampSecondArray;
     use bytes::Bytes;
     use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
 
@@ -321,16 +321,26 @@ fn test_twilight_parquet_output() {
             .collect::<Vec<_>>(),
         vec![
             "dateTime",
+            "utc_offset",
             "type",
             "sunrise",
+            "sunrise_utc_offset",
             "transit",
+            "transit_utc_offset",
             "sunset",
+            "sunset_utc_offset",
             "civil_start",
+            "civil_start_utc_offset",
             "civil_end",
+            "civil_end_utc_offset",
             "nautical_start",
+            "nautical_start_utc_offset",
             "nautical_end",
+            "nautical_end_utc_offset",
             "astronomical_start",
+            "astronomical_start_utc_offset",
             "astronomical_end",
+            "astronomical_end_utc_offset",
         ]
     );
 
@@ -341,18 +351,18 @@ fn test_twilight_parquet_output() {
         .unwrap()
         .unwrap();
     assert_eq!(batch.num_rows(), 1);
-    for (field, expected_prefix) in [
-        ("sunrise", "2024-06-21T02:46:15"),
-        ("civil_start", "2024-06-21T01:57:19"),
-        ("nautical_start", "2024-06-21T00:38:45"),
+    for (field, expected) in [
+        ("sunrise", "2024-06-21T02:46:15Z"),
+        ("civil_start", "2024-06-21T01:57:19Z"),
+        ("nautical_start", "2024-06-21T00:38:45Z"),
     ] {
         let value = batch
             .column_by_name(field)
             .unwrap()
             .as_any()
-            .downcast_ref::<StringArray>()
+            .downcast_ref::<TimestampSecondArray>()
             .unwrap()
             .value(0);
-        assert!(value.contains(expected_prefix));
+        assert_eq!(value, parse_rfc3339(expected).timestamp(), "{field}");
     }
 }